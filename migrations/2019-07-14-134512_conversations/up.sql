@@ -0,0 +1,25 @@
+CREATE TABLE conversations (
+	id BIGSERIAL PRIMARY KEY,
+	context VARCHAR NOT NULL,
+	last_activity_id BIGINT NOT NULL REFERENCES activities(id) ON DELETE CASCADE,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	UNIQUE(context)
+);
+
+CREATE TABLE conversation_participants (
+	id BIGSERIAL PRIMARY KEY,
+	conversation_id BIGINT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+	actor_id BIGINT NOT NULL REFERENCES actors(id) ON DELETE CASCADE,
+	unread BOOLEAN NOT NULL DEFAULT TRUE,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	UNIQUE(conversation_id, actor_id)
+);
+
+CREATE INDEX conversation_participants_actor ON conversation_participants (actor_id);
+
+CREATE TRIGGER set_conversation_updated BEFORE UPDATE ON conversations FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
+CREATE TRIGGER set_conversation_participant_updated BEFORE UPDATE ON conversation_participants FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
@@ -0,0 +1,14 @@
+CREATE TABLE poll_votes (
+	id BIGSERIAL PRIMARY KEY,
+	activity_id BIGINT NOT NULL REFERENCES activities(id) ON DELETE CASCADE,
+	actor_id BIGINT NOT NULL REFERENCES actors(id) ON DELETE CASCADE,
+	choice VARCHAR NOT NULL,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	UNIQUE(activity_id, actor_id, choice)
+);
+
+CREATE INDEX poll_votes_activity ON poll_votes (activity_id);
+
+CREATE TRIGGER set_poll_vote_updated BEFORE UPDATE ON poll_votes FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
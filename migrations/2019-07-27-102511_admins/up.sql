@@ -0,0 +1 @@
+ALTER TABLE actors ADD COLUMN admin BOOLEAN NOT NULL DEFAULT FALSE;
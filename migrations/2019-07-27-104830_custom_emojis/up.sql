@@ -0,0 +1,13 @@
+CREATE TABLE emojis (
+	id BIGSERIAL PRIMARY KEY,
+	shortcode VARCHAR NOT NULL,
+	file VARCHAR NOT NULL,
+	category VARCHAR,
+	visible_in_picker BOOLEAN NOT NULL DEFAULT TRUE,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	UNIQUE(shortcode)
+);
+
+CREATE TRIGGER set_emoji_updated BEFORE UPDATE ON emojis FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
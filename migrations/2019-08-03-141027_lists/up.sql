@@ -0,0 +1,23 @@
+CREATE TABLE lists (
+	id BIGSERIAL PRIMARY KEY,
+	actor_id BIGINT NOT NULL REFERENCES actors(id) ON DELETE CASCADE,
+	title VARCHAR NOT NULL,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE list_members (
+	id BIGSERIAL PRIMARY KEY,
+	list_id BIGINT NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+	actor_id BIGINT NOT NULL REFERENCES actors(id) ON DELETE CASCADE,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	UNIQUE(list_id, actor_id)
+);
+
+CREATE INDEX lists_actor ON lists (actor_id);
+CREATE INDEX list_members_list ON list_members (list_id);
+
+CREATE TRIGGER set_list_updated BEFORE UPDATE ON lists FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
+CREATE TRIGGER set_list_member_updated BEFORE UPDATE ON list_members FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS filter_statuses;
+DROP TABLE IF EXISTS filter_keywords;
+DROP TABLE IF EXISTS filters;
@@ -0,0 +1,37 @@
+CREATE TABLE filters (
+	id BIGSERIAL PRIMARY KEY,
+	actor_id BIGINT NOT NULL REFERENCES actors(id) ON DELETE CASCADE,
+	title VARCHAR NOT NULL,
+	context VARCHAR[] NOT NULL,
+	expires_at TIMESTAMP,
+	irreversible BOOLEAN NOT NULL DEFAULT FALSE,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE filter_keywords (
+	id BIGSERIAL PRIMARY KEY,
+	filter_id BIGINT NOT NULL REFERENCES filters(id) ON DELETE CASCADE,
+	keyword VARCHAR NOT NULL,
+	whole_word BOOLEAN NOT NULL DEFAULT TRUE,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE filter_statuses (
+	id BIGSERIAL PRIMARY KEY,
+	filter_id BIGINT NOT NULL REFERENCES filters(id) ON DELETE CASCADE,
+	activity_id BIGINT NOT NULL REFERENCES activities(id) ON DELETE CASCADE,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	UNIQUE(filter_id, activity_id)
+);
+
+CREATE INDEX filters_actor ON filters (actor_id);
+CREATE INDEX filter_keywords_filter ON filter_keywords (filter_id);
+CREATE INDEX filter_statuses_filter ON filter_statuses (filter_id);
+
+CREATE TRIGGER set_filter_updated BEFORE UPDATE ON filters FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
+CREATE TRIGGER set_filter_keyword_updated BEFORE UPDATE ON filter_keywords FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
+CREATE TRIGGER set_filter_status_updated BEFORE UPDATE ON filter_statuses FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
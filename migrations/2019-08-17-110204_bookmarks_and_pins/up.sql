@@ -0,0 +1,25 @@
+CREATE TABLE bookmarks (
+	id BIGSERIAL PRIMARY KEY,
+	actor_id BIGINT NOT NULL REFERENCES actors(id) ON DELETE CASCADE,
+	activity_id BIGINT NOT NULL REFERENCES activities(id) ON DELETE CASCADE,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	UNIQUE(actor_id, activity_id)
+);
+
+CREATE TABLE pins (
+	id BIGSERIAL PRIMARY KEY,
+	actor_id BIGINT NOT NULL REFERENCES actors(id) ON DELETE CASCADE,
+	activity_id BIGINT NOT NULL REFERENCES activities(id) ON DELETE CASCADE,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	UNIQUE(actor_id, activity_id)
+);
+
+CREATE INDEX bookmarks_actor ON bookmarks (actor_id);
+CREATE INDEX pins_actor ON pins (actor_id);
+
+CREATE TRIGGER set_bookmark_updated BEFORE UPDATE ON bookmarks FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
+CREATE TRIGGER set_pin_updated BEFORE UPDATE ON pins FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS activities_ap_type_actor;
+DROP INDEX IF EXISTS activities_ap_type_object;
@@ -0,0 +1,2 @@
+CREATE INDEX activities_ap_type_object ON activities ((data->>'type'), (data->>'object'));
+CREATE INDEX activities_ap_type_actor ON activities ((data->>'type'), actor_uri);
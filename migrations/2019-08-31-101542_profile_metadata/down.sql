@@ -0,0 +1,5 @@
+ALTER TABLE actors DROP COLUMN fields;
+ALTER TABLE actors DROP COLUMN discoverable;
+ALTER TABLE actors DROP COLUMN bot;
+ALTER TABLE actors DROP COLUMN locked;
+ALTER TABLE actors DROP COLUMN header;
@@ -0,0 +1,5 @@
+ALTER TABLE actors ADD COLUMN header VARCHAR;
+ALTER TABLE actors ADD COLUMN locked BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE actors ADD COLUMN bot BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE actors ADD COLUMN discoverable BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE actors ADD COLUMN fields JSONB NOT NULL DEFAULT '[]';
@@ -0,0 +1,2 @@
+ALTER TABLE actors DROP COLUMN moved_to;
+ALTER TABLE actors DROP COLUMN also_known_as;
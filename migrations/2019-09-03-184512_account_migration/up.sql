@@ -0,0 +1,2 @@
+ALTER TABLE actors ADD COLUMN also_known_as JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE actors ADD COLUMN moved_to VARCHAR;
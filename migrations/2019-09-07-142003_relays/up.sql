@@ -0,0 +1,9 @@
+CREATE TABLE relays (
+	id BIGSERIAL PRIMARY KEY,
+	actor_uri VARCHAR NOT NULL UNIQUE,
+	follow_activity_id VARCHAR NOT NULL,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TRIGGER set_relay_updated BEFORE UPDATE ON relays FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
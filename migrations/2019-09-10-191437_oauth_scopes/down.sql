@@ -0,0 +1,2 @@
+ALTER TABLE oauth_authorizations DROP COLUMN scopes;
+ALTER TABLE oauth_tokens DROP COLUMN scopes;
@@ -0,0 +1,2 @@
+ALTER TABLE oauth_authorizations ADD COLUMN scopes VARCHAR NOT NULL DEFAULT 'read write follow';
+ALTER TABLE oauth_tokens ADD COLUMN scopes VARCHAR NOT NULL DEFAULT 'read write follow';
@@ -0,0 +1 @@
+ALTER TABLE oauth_authorizations DROP COLUMN code_challenge;
@@ -0,0 +1 @@
+ALTER TABLE oauth_authorizations ADD COLUMN code_challenge VARCHAR;
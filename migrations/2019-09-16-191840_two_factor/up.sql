@@ -0,0 +1,12 @@
+CREATE TABLE two_factor_credentials (
+	id BIGSERIAL PRIMARY KEY,
+	actor_id BIGINT NOT NULL UNIQUE REFERENCES actors(id) ON DELETE CASCADE,
+	secret VARCHAR NOT NULL,
+	enabled BOOLEAN NOT NULL DEFAULT FALSE,
+	recovery_codes JSONB NOT NULL DEFAULT '[]',
+	last_step BIGINT NOT NULL DEFAULT 0,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TRIGGER set_two_factor_credential_updated BEFORE UPDATE ON two_factor_credentials FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
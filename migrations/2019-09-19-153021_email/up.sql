@@ -0,0 +1,23 @@
+CREATE TABLE email_preferences (
+	id BIGSERIAL PRIMARY KEY,
+	actor_id BIGINT NOT NULL UNIQUE REFERENCES actors(id) ON DELETE CASCADE,
+	mentions BOOLEAN NOT NULL DEFAULT FALSE,
+	follows BOOLEAN NOT NULL DEFAULT FALSE,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE email_tokens (
+	id BIGSERIAL PRIMARY KEY,
+	actor_id BIGINT NOT NULL REFERENCES actors(id) ON DELETE CASCADE,
+	purpose VARCHAR NOT NULL,
+	token VARCHAR NOT NULL UNIQUE,
+	valid_until TIMESTAMP NOT NULL,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX email_tokens_actor ON email_tokens (actor_id);
+
+CREATE TRIGGER set_email_preference_updated BEFORE UPDATE ON email_preferences FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
+CREATE TRIGGER set_email_token_updated BEFORE UPDATE ON email_tokens FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
@@ -0,0 +1,2 @@
+DROP TABLE pending_registrations;
+DROP TABLE invites;
@@ -0,0 +1,23 @@
+CREATE TABLE invites (
+	id BIGSERIAL PRIMARY KEY,
+	actor_id BIGINT NOT NULL REFERENCES actors(id) ON DELETE CASCADE,
+	code VARCHAR NOT NULL UNIQUE,
+	max_uses INTEGER,
+	uses INTEGER NOT NULL DEFAULT 0,
+	valid_until TIMESTAMP,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX invites_actor ON invites (actor_id);
+
+CREATE TABLE pending_registrations (
+	id BIGSERIAL PRIMARY KEY,
+	actor_id BIGINT NOT NULL UNIQUE REFERENCES actors(id) ON DELETE CASCADE,
+	reason TEXT NOT NULL,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TRIGGER set_invite_updated BEFORE UPDATE ON invites FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
+CREATE TRIGGER set_pending_registration_updated BEFORE UPDATE ON pending_registrations FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
@@ -0,0 +1,3 @@
+DROP TABLE markers;
+DROP INDEX notifications_actor_activity;
+ALTER TABLE notifications DROP COLUMN notification_type;
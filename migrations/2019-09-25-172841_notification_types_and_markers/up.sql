@@ -0,0 +1,28 @@
+ALTER TABLE notifications ADD COLUMN notification_type VARCHAR NOT NULL DEFAULT 'mention';
+
+UPDATE notifications SET notification_type = CASE activities.data->>'type'
+	WHEN 'Follow' THEN 'follow'
+	WHEN 'Announce' THEN 'reblog'
+	WHEN 'Like' THEN 'favourite'
+	WHEN 'EmojiReact' THEN 'pleroma:emoji_reaction'
+	ELSE 'mention'
+END
+FROM activities
+WHERE activities.id = notifications.activity_id;
+
+ALTER TABLE notifications ALTER COLUMN notification_type DROP DEFAULT;
+
+CREATE INDEX notifications_actor_activity ON notifications (actor_id, activity_id);
+
+CREATE TABLE markers (
+	id BIGSERIAL PRIMARY KEY,
+	actor_id BIGINT NOT NULL REFERENCES actors(id) ON DELETE CASCADE,
+	timeline VARCHAR NOT NULL,
+	last_read_id BIGINT NOT NULL,
+	version INTEGER NOT NULL DEFAULT 0,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	UNIQUE (actor_id, timeline)
+);
+
+CREATE TRIGGER set_marker_updated BEFORE UPDATE ON markers FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
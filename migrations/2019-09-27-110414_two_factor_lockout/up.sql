@@ -0,0 +1,2 @@
+ALTER TABLE two_factor_credentials ADD COLUMN failed_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE two_factor_credentials ADD COLUMN locked_until TIMESTAMP;
@@ -0,0 +1,4 @@
+CREATE TABLE poll_refreshes (
+	activity_id BIGINT PRIMARY KEY REFERENCES activities(id) ON DELETE CASCADE,
+	refreshed TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
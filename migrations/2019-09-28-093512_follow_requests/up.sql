@@ -0,0 +1,12 @@
+CREATE TABLE follow_requests (
+	id BIGSERIAL PRIMARY KEY,
+	actor_id BIGINT NOT NULL REFERENCES actors(id) ON DELETE CASCADE,
+	follower_id BIGINT NOT NULL REFERENCES actors(id) ON DELETE CASCADE,
+	activity_id BIGINT NOT NULL REFERENCES activities(id) ON DELETE CASCADE,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	modified TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+	UNIQUE(actor_id, follower_id)
+);
+
+CREATE TRIGGER set_follow_request_updated BEFORE UPDATE ON follow_requests FOR EACH ROW EXECUTE PROCEDURE set_updated_timestamp();
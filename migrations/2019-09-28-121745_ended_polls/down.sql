@@ -0,0 +1 @@
+DROP TABLE ended_polls;
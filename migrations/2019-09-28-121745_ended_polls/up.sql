@@ -0,0 +1,4 @@
+CREATE TABLE ended_polls (
+	activity_id BIGINT PRIMARY KEY REFERENCES activities(id) ON DELETE CASCADE,
+	created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1 @@
+ALTER TABLE actors ADD COLUMN followers_uri VARCHAR;
@@ -9,6 +9,7 @@ use diesel::sql_query;
 use diesel::ExpressionMethods;
 use env;
 use serde_json;
+use timeline::prepare_order_query;
 #[derive(Clone)]
 pub struct Activity {
     pub id: i64,
@@ -45,8 +46,8 @@ pub fn count_ap_object_reactions_by_id(
     reaction: &str,
 ) -> Result<usize, diesel::result::Error> {
     match sql_query(format!(
-        "SELECT id FROM activities WHERE data @> '{{\"type\": \"{reaction_type}\"}}' \
-         AND data @> '{{\"object\": \"{id}\"}}';",
+        "SELECT id FROM activities WHERE data->>'type' = '{reaction_type}' \
+         AND data->>'object' = '{id}';",
         reaction_type = runtime_escape(reaction),
         id = runtime_escape(object_id)
     ))
@@ -194,6 +195,186 @@ pub fn get_ap_object_replies_by_id(
     }
 }
 
+/// Returns the reactions of a certain type (e.g. `Like` or `Announce`) to an object
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `object_id`     -          &str | ActivityPub ID of the reacted object
+/// * `reaction`      -          &str | ActivityPub type of the reaction
+/// * `max_id`        -   Option<i64> | Return reactions older than this ID
+/// * `since_id`      -   Option<i64> | Return reactions newer than this ID
+/// * `min_id`        -   Option<i64> | Return reactions immediately newer than this ID
+/// * `limit`         -           i64 | Maximum number of reactions to return
+///
+pub fn get_ap_object_reactions_by_id(
+    db_connection: &PgConnection,
+    object_id: &str,
+    reaction: &str,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: i64,
+) -> Result<Vec<Activity>, diesel::result::Error> {
+    match sql_query(format!(
+        "SELECT * FROM activities WHERE data->>'type' = '{reaction_type}' \
+         AND data->>'object' = '{object_id}' \
+         {id} \
+         LIMIT {limit};",
+        reaction_type = runtime_escape(reaction),
+        object_id = runtime_escape(object_id),
+        id = prepare_order_query(max_id, since_id, min_id),
+        limit = limit
+    ))
+    .load::<QueryActivity>(db_connection)
+    {
+        Ok(activity_arr) => Ok(activity_arr
+            .iter()
+            .map(|activity| serialize_activity(activity.clone()))
+            .collect()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns the reactions of a certain type (e.g. `Like` or `Announce`) made by an actor
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -          &str | ActivityPub ID of the reacting actor
+/// * `reaction`      -          &str | ActivityPub type of the reaction
+/// * `max_id`        -   Option<i64> | Return reactions older than this ID
+/// * `since_id`      -   Option<i64> | Return reactions newer than this ID
+/// * `min_id`        -   Option<i64> | Return reactions immediately newer than this ID
+/// * `limit`         -           i64 | Maximum number of reactions to return
+///
+pub fn get_ap_reactions_by_actor(
+    db_connection: &PgConnection,
+    actor: &str,
+    reaction: &str,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: i64,
+) -> Result<Vec<Activity>, diesel::result::Error> {
+    match sql_query(format!(
+        "SELECT * FROM activities WHERE data->>'type' = '{reaction_type}' \
+         AND actor_uri = '{actor}' \
+         {id} \
+         LIMIT {limit};",
+        reaction_type = runtime_escape(reaction),
+        actor = runtime_escape(actor),
+        id = prepare_order_query(max_id, since_id, min_id),
+        limit = limit
+    ))
+    .load::<QueryActivity>(db_connection)
+    {
+        Ok(activity_arr) => Ok(activity_arr
+            .iter()
+            .map(|activity| serialize_activity(activity.clone()))
+            .collect()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns all `Create` and `Announce` activities of an actor, oldest first
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -          &str | ActivityPub ID of the actor
+///
+pub fn get_ap_activities_by_actor(
+    db_connection: &PgConnection,
+    actor: &str,
+) -> Result<Vec<Activity>, diesel::result::Error> {
+    match sql_query(format!(
+        "SELECT * FROM activities WHERE data->>'type' IN ('Create', 'Announce') \
+         AND actor_uri = '{actor}' \
+         ORDER BY id ASC;",
+        actor = runtime_escape(actor)
+    ))
+    .load::<QueryActivity>(db_connection)
+    {
+        Ok(activity_arr) => Ok(activity_arr
+            .iter()
+            .map(|activity| serialize_activity(activity.clone()))
+            .collect()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns polls which ended within the last hours and weren't announced yet
+///
+/// # Description
+///
+/// Only polls of local actors and polls local actors voted on are of interest, as nobody else
+/// is notified about their end.
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `hours`         -           i64 | How long ago the polls may have ended
+///
+pub fn get_unannounced_ended_polls(
+    db_connection: &PgConnection,
+    hours: i64,
+) -> Result<Vec<Activity>, diesel::result::Error> {
+    match sql_query(format!(
+        "SELECT * FROM activities WHERE data->>'type' = 'Create' \
+         AND data->'object'->>'type' = 'Question' \
+         AND (data->'object'->>'endTime')::timestamptz <= NOW() \
+         AND (data->'object'->>'endTime')::timestamptz > NOW() - INTERVAL '{hours} hours' \
+         AND id NOT IN (SELECT activity_id FROM ended_polls) \
+         AND (actor_uri IN (SELECT actor_uri FROM actors WHERE local) \
+         OR id IN (SELECT poll_votes.activity_id FROM poll_votes \
+         JOIN actors ON actors.id = poll_votes.actor_id WHERE actors.local));",
+        hours = hours
+    ))
+    .load::<QueryActivity>(db_connection)
+    {
+        Ok(activity_arr) => Ok(activity_arr
+            .iter()
+            .map(|activity| serialize_activity(activity.clone()))
+            .collect()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Checks whether an actor sent a `Follow` activity to another actor which has neither been undone
+/// nor rejected yet
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -          &str | ActivityPub ID of the following actor
+/// * `object`        -          &str | ActivityPub ID of the followed actor
+///
+pub fn follow_exists(
+    db_connection: &PgConnection,
+    actor: &str,
+    object: &str,
+) -> Result<bool, diesel::result::Error> {
+    match sql_query(format!(
+        "SELECT id FROM activities AS follow \
+         WHERE data->>'type' = 'Follow' \
+         AND actor_uri = '{actor}' \
+         AND data->>'object' = '{object}' \
+         AND NOT EXISTS (SELECT id FROM activities \
+         WHERE data->>'type' IN ('Undo', 'Reject') \
+         AND (data->'object'->>'id' = follow.data->>'id' \
+         OR data->>'object' = follow.data->>'id')) \
+         LIMIT 1;",
+        actor = runtime_escape(actor),
+        object = runtime_escape(object)
+    ))
+    .load::<QueryActivityId>(db_connection)
+    {
+        Ok(activity_arr) => Ok(!activity_arr.is_empty()),
+        Err(e) => Err(e),
+    }
+}
+
 pub fn type_exists_for_object_id(
     db_connection: &PgConnection,
     _type: &str,
@@ -243,6 +424,20 @@ pub fn insert_activity(db_connection: &PgConnection, activity: Activity) -> Acti
     )
 }
 
+pub fn update_activity_data(
+    db_connection: &PgConnection,
+    activity_id: i64,
+    new_data: &serde_json::Value,
+) -> Result<Activity, diesel::result::Error> {
+    match diesel::update(activities.filter(id.eq(activity_id)))
+        .set(data.eq(new_data))
+        .get_result::<QueryActivity>(db_connection)
+    {
+        Ok(activity) => Ok(serialize_activity(activity)),
+        Err(e) => Err(e),
+    }
+}
+
 pub fn delete_ap_activity_by_id(db_connection: &PgConnection, activity_id: String) {
     sql_query(format!(
         "DELETE FROM activities WHERE data->>'id' = '{}';",
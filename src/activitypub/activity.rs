@@ -1,4 +1,6 @@
 use activity;
+use api_error::ApiError;
+use conversation;
 use database;
 use env;
 use serde::{Deserialize, Serialize};
@@ -21,6 +23,14 @@ pub struct Activity {
     pub published: String,
     pub to: Vec<String>,
     pub cc: Vec<String>,
+    // Only used by `EmojiReact` activities, which carry the reaction as their content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<Vec<serde_json::Value>>,
+    // Only used by `Move` activities, which point to the new account of an actor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
 }
 
 #[allow(non_snake_case)]
@@ -31,11 +41,15 @@ pub struct Object {
     #[serde(rename = "type")]
     pub _type: String,
     pub id: String,
+    // Mastodon doesn't date votes on polls
+    #[serde(default)]
     pub published: String,
     pub attributedTo: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub inReplyTo: Option<String>,
     pub summary: Option<String>,
+    // Poll votes are notes which only carry a `name`
+    #[serde(default)]
     pub content: String,
     pub to: Vec<String>,
     pub cc: Vec<String>,
@@ -44,6 +58,29 @@ pub struct Object {
     pub attachment: Option<Vec<serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sensitive: Option<bool>,
+    // Properties of `Question` objects and votes on them according to
+    // - https://www.w3.org/TR/activitystreams-vocabulary/#dfn-question
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oneOf: Option<Vec<serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anyOf: Option<Vec<serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endTime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub votersCount: Option<i64>,
+    // The conversation an object belongs to, `context` is used by Pleroma and `conversation` by
+    // Mastodon
+    #[serde(rename = "context", default, skip_serializing_if = "Option::is_none")]
+    pub conversation_context: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation: Option<serde_json::Value>,
+    // Either the ID of a collection of replies or the collection itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replies: Option<serde_json::Value>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -54,7 +91,7 @@ pub struct Tag {
     pub name: String,
 }
 
-pub fn get_activity_json_by_id(id: &str) -> serde_json::Value {
+pub fn get_activity_json_by_id(id: &str) -> Result<serde_json::Value, ApiError> {
     let database = database::establish_connection();
     let activity_id = format!(
         "{}://{}/activities/{}",
@@ -63,13 +100,57 @@ pub fn get_activity_json_by_id(id: &str) -> serde_json::Value {
         id
     );
 
+    // Objects are fetched without authentication, so direct messages are never served
     match activity::get_ap_activity_by_id(&database, &activity_id) {
-        Ok(activity) => json!(serialize_from_internal_activity(activity).object),
-        Err(_) => json!({"error": "Object not found."}),
+        Ok(activity)
+            if conversation::is_visible_to(
+                &activity.data,
+                &conversation::followers_uri_of(&database, &activity.data),
+                None,
+            ) =>
+        {
+            Ok(json!(serialize_from_internal_activity(activity).object))
+        }
+        _ => Err(ApiError::not_found("Object not found.")),
+    }
+}
+
+pub fn get_object_json_by_id(id: &str) -> Result<serde_json::Value, ApiError> {
+    let database = database::establish_connection();
+    let object_id = format!(
+        "{}://{}/objects/{}",
+        env::get_value(String::from("endpoint.base_scheme")),
+        env::get_value(String::from("endpoint.base_domain")),
+        id
+    );
+
+    match activity::get_ap_object_by_id(&database, &object_id) {
+        Ok(activity)
+            if conversation::is_visible_to(
+                &activity.data,
+                &conversation::followers_uri_of(&database, &activity.data),
+                None,
+            ) =>
+        {
+            let mut object = serialize_from_internal_activity(activity).object;
+
+            // Notes created before replies collections were introduced lack the reference
+            if object.get("replies").is_none() {
+                object["replies"] = json!(format!("{}/replies", object_id));
+            }
+            Ok(object)
+        }
+        _ => Err(ApiError::not_found("Object not found.")),
     }
 }
 
-pub fn get_object_json_by_id(id: &str) -> serde_json::Value {
+/// Returns the public replies to a local object as an ordered collection
+///
+/// # Description
+///
+/// Replies which aren't addressed to the public are left out, as the collection can be fetched
+/// by anyone.
+pub fn get_replies_json_by_id(id: &str) -> Result<serde_json::Value, ApiError> {
     let database = database::establish_connection();
     let object_id = format!(
         "{}://{}/objects/{}",
@@ -79,8 +160,35 @@ pub fn get_object_json_by_id(id: &str) -> serde_json::Value {
     );
 
     match activity::get_ap_object_by_id(&database, &object_id) {
-        Ok(activity) => json!(serialize_from_internal_activity(activity).object),
-        Err(_) => json!({"error": "Object not found."}),
+        Ok(_) => {
+            let mut replies =
+                activity::get_ap_object_replies_by_id(&database, &object_id).unwrap_or_default();
+            replies.sort_by_key(|reply| reply.id);
+
+            let items: Vec<serde_json::Value> = replies
+                .into_iter()
+                .filter(|reply| {
+                    reply.data["to"]
+                        .as_array()
+                        .into_iter()
+                        .chain(reply.data["cc"].as_array())
+                        .flatten()
+                        .any(|receipient| {
+                            receipient == "https://www.w3.org/ns/activitystreams#Public"
+                        })
+                })
+                .map(|reply| reply.data["object"]["id"].clone())
+                .collect();
+
+            Ok(json!({
+                "@context": "https://www.w3.org/ns/activitystreams",
+                "type": "OrderedCollection",
+                "id": format!("{}/replies", object_id),
+                "totalItems": items.len(),
+                "orderedItems": items
+            }))
+        }
+        Err(_) => Err(ApiError::not_found("Object not found.")),
     }
 }
 
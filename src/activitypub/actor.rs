@@ -1,10 +1,17 @@
+use activity::{get_activities_by_id, get_ap_object_by_id};
 use activitypub::activity::Activity;
+use activitypub::controller;
 use activitypub::validator;
 use actor;
+use api_error::ApiError;
 use chrono::Duration;
 use chrono::Utc;
 use database;
+use emoji;
 use env;
+use html;
+use pin;
+use registration;
 use serde::{Deserialize, Serialize};
 use serde_json::{self, json};
 use web;
@@ -33,6 +40,26 @@ pub struct Actor {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub icon: Option<serde_json::Value>,
     pub endpoints: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<Vec<serde_json::Value>>,
+    // Collection of pinned objects, as introduced by Mastodon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub featured: Option<String>,
+    // Profile header, as introduced by Mastodon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manuallyApprovesFollowers: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discoverable: Option<bool>,
+    // Profile metadata as `PropertyValue` attachments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<Vec<serde_json::Value>>,
+    // Account migrations, as introduced by Mastodon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alsoKnownAs: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub movedTo: Option<String>,
 }
 
 // ActivityStreams2/AcitivityPub properties are expressed in CamelCase
@@ -87,7 +114,7 @@ pub fn remove_follow(account: &str, source: &str) {
     }
 }
 
-// Refetches remote actor and detects changes to icon, username, keys and summary
+// Refetches remote actor and detects changes to their profile and keys
 pub fn refresh(uri: String) {
     std::thread::spawn(move || {
         let expiration_time: chrono::DateTime<Utc> = Utc::now() - Duration::days(2);
@@ -105,8 +132,13 @@ pub fn refresh(uri: String) {
                     match validator::validate_actor(parsed_object) {
                         Ok(actor) => {
                             let serialized_actor: Actor = serde_json::from_value(actor).unwrap();
+                            let featured = serialized_actor.featured.clone();
 
                             actor::update(&database, create_internal_actor(serialized_actor));
+
+                            if let Some(featured) = featured {
+                                fetch_featured(&uri, &featured);
+                            }
                         }
                         Err(_) => {
                             eprintln!("Unable to refresh actor, remote object is invalid: {}", uri)
@@ -119,27 +151,219 @@ pub fn refresh(uri: String) {
     });
 }
 
-pub fn get_json_by_preferred_username(preferred_username: &str) -> serde_json::Value {
+/// Fetches a remote actor regardless of whether it's already known and stores the result
+///
+/// # Description
+///
+/// Unlike `refresh`, this function blocks until the actor was fetched, which is needed whenever a
+/// decision depends on the current state of a remote actor, e.g. verifying an account migration.
+///
+/// # Parameters
+///
+/// * `uri` - &str | The URI of a remote actor
+pub fn refetch(uri: &str) -> Result<actor::Actor, &'static str> {
+    let database = database::establish_connection();
+
+    if let Ok(actor) = actor::get_actor_by_uri(&database, uri) {
+        if actor.local {
+            return Ok(actor);
+        }
+    }
+
+    let object: serde_json::Value = match web::fetch_remote_object(uri) {
+        Ok(object) => serde_json::from_str(&object).map_err(|_| "Remote actor is invalid")?,
+        Err(_) => return Err("Unable to fetch remote actor"),
+    };
+
+    match validator::validate_actor(object) {
+        Ok(object) => {
+            let serialized_actor: Actor =
+                serde_json::from_value(object).map_err(|_| "Remote actor is invalid")?;
+            let mut internal_actor = create_internal_actor(serialized_actor);
+
+            // The fetched document has to describe the actor it was fetched for
+            if internal_actor.actor_uri != uri {
+                return Err("Remote actor is invalid");
+            }
+
+            if controller::actor_exists(uri) {
+                actor::update(&database, internal_actor);
+            } else {
+                actor::create_actor(&database, &mut internal_actor);
+            }
+            actor::get_actor_by_uri(&database, uri).map_err(|_| "Actor not found")
+        }
+        Err(_) => Err("Remote actor is invalid"),
+    }
+}
+
+/// Fetches the `featured` collection of a remote actor in the background and replaces their
+/// pinned statuses
+///
+/// # Description
+///
+/// Objects of the collection which aren't known yet are being fetched as well, items of a
+/// collection can either be embedded objects or links to them. Only the first `pin::MAX_PINS`
+/// items are considered, as local actors can't pin more statuses either.
+///
+/// # Parameters
+///
+/// * `actor_uri` - &str | The URI of a remote actor
+/// * `featured`  - &str | The URI of the actor's `featured` collection
+pub fn fetch_featured(actor_uri: &str, featured: &str) {
+    let actor_uri = actor_uri.to_string();
+    let featured = featured.to_string();
+
+    std::thread::spawn(move || {
+        let database = database::establish_connection();
+
+        let collection: serde_json::Value = match web::fetch_remote_object(&featured) {
+            Ok(collection) => serde_json::from_str(&collection).unwrap_or_default(),
+            Err(_) => {
+                eprintln!("Unable to fetch featured collection: {}", featured);
+                return;
+            }
+        };
+        let items = match collection["orderedItems"].as_array() {
+            Some(items) => items.clone(),
+            None => collection["items"].as_array().cloned().unwrap_or_default(),
+        };
+
+        let mut activity_ids: Vec<i64> = Vec::new();
+        for item in items.into_iter().take(pin::MAX_PINS) {
+            let object_id = match item.as_str() {
+                Some(object_id) => object_id.to_string(),
+                None => item["id"].as_str().unwrap_or_default().to_string(),
+            };
+
+            if !controller::object_exists(&object_id) {
+                controller::fetch_object_by_id(object_id.clone());
+            }
+
+            // Only objects attributed to the actor may be pinned by them
+            match get_ap_object_by_id(&database, &object_id) {
+                Ok(activity) => {
+                    if activity.actor == actor_uri {
+                        activity_ids.push(activity.id)
+                    }
+                }
+                Err(_) => (),
+            }
+        }
+
+        match actor::get_actor_by_uri(&database, &actor_uri) {
+            Ok(actor) => pin::replace_pins(&database, &actor, activity_ids),
+            Err(_) => eprintln!("Unable to store pins of unknown actor: {}", actor_uri),
+        }
+    });
+}
+
+/// Returns the pinned objects of a local actor as an ordered collection
+pub fn get_featured_json_by_preferred_username(
+    preferred_username: &str,
+) -> Result<serde_json::Value, ApiError> {
     let database = database::establish_connection();
 
     match actor::get_local_actor_by_preferred_username(&database, preferred_username) {
-        Ok(actor) => json!(serialize_from_internal_actor(&actor)),
-        Err(_) => json!({"error": "User not found."}),
+        Ok(ref actor) if registration::is_pending(&database, actor) => {
+            Err(ApiError::not_found("User not found."))
+        }
+        Ok(actor) => {
+            let pinned_activities =
+                pin::pinned_activities(&database, &actor).unwrap_or_else(|_| Vec::new());
+            let mut activities = get_activities_by_id(&database, pinned_activities.clone())
+                .unwrap_or_else(|_| Vec::new());
+            activities.sort_by_key(|activity| {
+                pinned_activities
+                    .iter()
+                    .position(|activity_id| activity_id == &activity.id)
+            });
+
+            let objects: Vec<serde_json::Value> = activities
+                .into_iter()
+                .map(|activity| activity.data["object"].clone())
+                .collect();
+
+            Ok(json!({
+                "@context": "https://www.w3.org/ns/activitystreams",
+                "type": "OrderedCollection",
+                "id": format!("{}/collections/featured", actor.actor_uri),
+                "totalItems": objects.len(),
+                "orderedItems": objects
+            }))
+        }
+        Err(_) => Err(ApiError::not_found("User not found.")),
+    }
+}
+
+pub fn get_json_by_preferred_username(
+    preferred_username: &str,
+) -> Result<serde_json::Value, ApiError> {
+    let database = database::establish_connection();
+
+    match actor::get_local_actor_by_preferred_username(&database, preferred_username) {
+        // Accounts aren't federated until they're approved
+        Ok(ref actor) if registration::is_pending(&database, actor) => {
+            Err(ApiError::not_found("User not found."))
+        }
+        Ok(actor) => Ok(json!(serialize_from_internal_actor(&actor))),
+        Err(_) => Err(ApiError::not_found("User not found.")),
     }
 }
 
 pub fn serialize_from_internal_actor(actor: &actor::Actor) -> Actor {
+    let database = database::establish_connection();
     let icon = match &actor.icon {
         Some(url) => Some(serde_json::json!({"url": url, "type": "Image"})),
         None => None,
     };
+    let image = match &actor.header {
+        Some(url) => Some(serde_json::json!({"url": url, "type": "Image"})),
+        None => None,
+    };
+    let attachment: Vec<serde_json::Value> = actor
+        .fields
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|field| {
+            serde_json::json!({
+                "type": "PropertyValue",
+                "name": field["name"],
+                "value": field["value"]
+            })
+        })
+        .collect();
+    let emojis = emoji::tags_for_text(
+        &database,
+        &format!(
+            "{} {}",
+            actor.username.clone().unwrap_or_default(),
+            actor.summary.clone().unwrap_or_default()
+        ),
+    );
 
     Actor {
         context: Some(serde_json::json!([
             String::from("https://www.w3.org/ns/activitystreams"),
             String::from("https://w3id.org/security/v1"),
+            serde_json::json!({
+                "manuallyApprovesFollowers": "as:manuallyApprovesFollowers",
+                "toot": "http://joinmastodon.org/ns#",
+                "discoverable": "toot:discoverable",
+                "schema": "http://schema.org#",
+                "PropertyValue": "schema:PropertyValue",
+                "value": "schema:value",
+                "alsoKnownAs": {"@id": "as:alsoKnownAs", "@type": "@id"},
+                "movedTo": {"@id": "as:movedTo", "@type": "@id"}
+            }),
         ])),
-        _type: String::from("Person"),
+        _type: if actor.bot {
+            String::from("Service")
+        } else {
+            String::from("Person")
+        },
         id: actor.actor_uri.clone(),
         summary: actor.summary.clone(),
         following: format!("{}/following", actor.actor_uri.clone()),
@@ -163,6 +387,16 @@ pub fn serialize_from_internal_actor(actor: &actor::Actor) -> Actor {
                     env::get_value(String::from("endpoint.base_domain"))
                 )
         })),
+        tag: Some(emojis),
+        featured: Some(format!("{}/collections/featured", actor.actor_uri.clone())),
+        image: image,
+        manuallyApprovesFollowers: Some(actor.locked),
+        discoverable: Some(actor.discoverable),
+        attachment: Some(attachment),
+        alsoKnownAs: Some(
+            serde_json::from_value(actor.also_known_as.clone()).unwrap_or_else(|_| vec![]),
+        ),
+        movedTo: actor.moved_to.clone(),
     }
 }
 
@@ -199,6 +433,25 @@ pub fn create_internal_actor(ap_actor: Actor) -> actor::Actor {
         None => None,
     };
 
+    let actor_header = match &ap_actor.image {
+        Some(image) => image["url"].as_str().map(String::from),
+        None => None,
+    };
+
+    // Only `PropertyValue` attachments are profile metadata
+    let actor_fields: Vec<serde_json::Value> = ap_actor
+        .attachment
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|attachment| attachment["type"].as_str() == Some("PropertyValue"))
+        .map(|attachment| {
+            json!({
+                "name": html::escape(&html::to_raw_text(attachment["name"].as_str().unwrap_or_default())),
+                "value": html::strip_tags(attachment["value"].as_str().unwrap_or_default())
+            })
+        })
+        .collect();
+
     actor::Actor {
         id: 0, // Fill with placeholder value, this property will get ignored
         email: None,
@@ -209,10 +462,18 @@ pub fn create_internal_actor(ap_actor: Actor) -> actor::Actor {
         summary: ap_actor.summary,
         inbox: actor_inbox,
         icon: actor_icon,
+        header: actor_header,
         local: false,
         keys: serde_json::json!({"public" : ap_actor.publicKey["publicKeyPem"]}),
         followers: serde_json::json!({"activitypub": []}),
         created: Utc::now().naive_utc(),
         modified: Utc::now().naive_utc(),
+        locked: ap_actor.manuallyApprovesFollowers.unwrap_or(false),
+        bot: ap_actor._type == "Service",
+        discoverable: ap_actor.discoverable.unwrap_or(false),
+        fields: json!(actor_fields),
+        also_known_as: json!(ap_actor.alsoKnownAs.unwrap_or_default()),
+        moved_to: ap_actor.movedTo,
+        followers_uri: Some(ap_actor.followers),
     }
 }
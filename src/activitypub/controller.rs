@@ -1,3 +1,4 @@
+use activity::delete_ap_activity_by_id;
 use activity::get_ap_activity_by_id;
 use activity::get_ap_object_by_id;
 use activity::insert_activity;
@@ -6,22 +7,40 @@ use activitypub::activity::Activity;
 use activitypub::activity::Object;
 use activitypub::actor::add_follow;
 use activitypub::actor::create_internal_actor;
+use activitypub::actor::fetch_featured;
+use activitypub::actor::refetch;
 use activitypub::actor::remove_follow;
+use activitypub::actor::serialize_from_internal_actor;
 use activitypub::actor::Actor;
 use activitypub::validator;
+use actor;
 use actor::create_actor;
 use actor::get_actor_by_uri;
 use actor::is_actor_followed_by;
 use chrono::Utc;
+use conversation;
 use database;
+use diesel::PgConnection;
+use email;
 use env;
+use follow_request;
+use html;
+use kibou_api;
 use notification::{self, Notification};
+use poll;
+use reaction;
+use relay;
 use std::thread;
 use url::Url;
 use uuid::Uuid;
 use web;
 use web::http_signatures::Signature;
 
+// Limits of the backfill of remote threads
+const MAX_BACKFILL_DEPTH: u8 = 3;
+const MAX_BACKFILL_OBJECTS: usize = 50;
+const MAX_BACKFILL_PAGES: usize = 5;
+
 /// Creates a new `Accept` activity, inserts it into the database and returns the newly created activity
 ///
 /// # Parameters
@@ -86,6 +105,32 @@ pub fn create(
     activity_build("Create", actor, object, to, cc)
 }
 
+/// Creates a new `EmojiReact` activity, inserts it into the database and returns the newly created activity
+///
+/// # Parameters
+///
+/// * `actor`   -                          &str | Reference to an ActivityPub actor
+/// * `object`  -                          &str | Reference to an ActivityStreams object
+/// * `content` -                          &str | A unicode emoji or the shortcode of a custom emoji
+/// * `tag`     - Option<Vec<serde_json::Value>> | The `Emoji` tag of a custom emoji
+/// * `to`      -                   Vec<String> | A vector of strings that provides direct receipients
+/// * `cc`      -                   Vec<String> | A vector of strings that provides passive receipients
+///
+pub fn emoji_react(
+    actor: &str,
+    object: &str,
+    content: &str,
+    tag: Option<Vec<serde_json::Value>>,
+    to: Vec<String>,
+    cc: Vec<String>,
+) -> Activity {
+    let mut new_activity = activity_new("EmojiReact", actor, serde_json::json!(object), to, cc);
+    new_activity.content = Some(content.to_string());
+    new_activity.tag = tag;
+
+    activity_insert(new_activity)
+}
+
 /// Creates a new `Follow` activity, inserts it into the database and returns the newly created activity
 ///
 /// # Parameters
@@ -116,52 +161,197 @@ pub fn like(actor: &str, object: &str, to: Vec<String>, cc: Vec<String>) -> Acti
     activity_build("Like", actor, serde_json::json!(object), to, cc)
 }
 
+/// Creates a new `Move` activity, inserts it into the database and returns the newly created
+/// activity
+///
+/// # Parameters
+///
+/// * `actor`  - &actor::Actor | The local actor who's moving
+/// * `target` -          &str | Reference to the new ActivityPub actor
+///
+pub fn move_actor(actor: &actor::Actor, target: &str) -> Activity {
+    let mut new_activity = activity_new(
+        "Move",
+        &actor.actor_uri,
+        serde_json::json!(actor.actor_uri),
+        vec![format!("{}/followers", actor.actor_uri)],
+        vec![],
+    );
+    new_activity.target = Some(target.to_string());
+    activity_insert(new_activity)
+}
+
+/// Creates a new `Reject` activity, inserts it into the database and returns the newly created activity
+///
+/// # Parameters
+///
+/// * `actor`  -              &str | Reference to an ActivityPub actor
+/// * `object` -              &str | Reference to an ActivityStreams object
+/// * `to`     -       Vec<String> | A vector of strings that provides direct receipients
+/// * `cc`     -       Vec<String> | A vector of strings that provides passive receipients
+///
+pub fn reject(actor: &str, object: &str, to: Vec<String>, cc: Vec<String>) -> Activity {
+    activity_build("Reject", actor, serde_json::json!(object), to, cc)
+}
+
 pub fn undo(actor: &str, object: serde_json::Value, to: Vec<String>, cc: Vec<String>) -> Activity {
     activity_build("Undo", actor, object, to, cc)
 }
 
+/// Creates a new `Update` activity of a local actor and returns it
+///
+/// # Description
+///
+/// Unlike other activities, updates aren't inserted into the database, as the actor itself
+/// already reflects the update.
+///
+/// # Parameters
+///
+/// * `actor` - &actor::Actor | The updated local actor
+///
+pub fn update_actor(actor: &actor::Actor) -> Activity {
+    activity_new(
+        "Update",
+        &actor.actor_uri,
+        serde_json::json!(serialize_from_internal_actor(actor)),
+        vec![String::from("https://www.w3.org/ns/activitystreams#Public")],
+        vec![format!("{}/followers", actor.actor_uri)],
+    )
+}
+
 /// Returns a new ActivityStreams object of the type `Note`
 ///
 /// # Parameters
 ///
-/// * `actor`    -                   &str | Reference to an ActivityPub actor
-/// * `reply_to` -         Option<String> | An optional reference to another ActivityStreams object this object is a reply to
-/// * `content`  -                 String | The content of this note
-/// * `to`       -            Vec<String> | A vector of strings that provides direct receipients
-/// * `cc`       -            Vec<String> | A vector of strings that provides passive receipients
-/// * `tag`      - Vec<serde_json::Value> | A vector of tags to ActivityStreams objects wrapped in JSON
+/// * `actor`     -                   &str | Reference to an ActivityPub actor
+/// * `reply_to`  -         Option<String> | An optional reference to another ActivityStreams object this object is a reply to
+/// * `content`   -                 String | The content of this note
+/// * `summary`   -         Option<String> | An optional content warning shown in place of the content
+/// * `sensitive` -                   bool | Whether the content or attachments of this note are sensitive
+/// * `to`        -            Vec<String> | A vector of strings that provides direct receipients
+/// * `cc`        -            Vec<String> | A vector of strings that provides passive receipients
+/// * `tag`       - Vec<serde_json::Value> | A vector of tags to ActivityStreams objects wrapped in JSON
 ///
 pub fn note(
     actor: &str,
     reply_to: Option<String>,
     content: String,
+    summary: Option<String>,
+    sensitive: bool,
     to: Vec<String>,
     cc: Vec<String>,
     tag: Vec<serde_json::Value>,
 ) -> Object {
+    let id = format!(
+        "{base_scheme}://{base_domain}/objects/{uuid}",
+        base_scheme = env::get_value(String::from("endpoint.base_scheme")),
+        base_domain = env::get_value(String::from("endpoint.base_domain")),
+        uuid = Uuid::new_v4()
+    );
+
     Object {
         context: Some(serde_json::json!(vec![
             String::from("https://www.w3.org/ns/activitystreams"),
             String::from("https://w3id.org/security/v1"),
         ])),
         _type: String::from("Note"),
-        id: format!(
-            "{base_scheme}://{base_domain}/objects/{uuid}",
-            base_scheme = env::get_value(String::from("endpoint.base_scheme")),
-            base_domain = env::get_value(String::from("endpoint.base_domain")),
-            uuid = Uuid::new_v4()
-        ),
+        id: id.clone(),
         attributedTo: actor.to_string(),
         inReplyTo: reply_to,
-        summary: None, // [TODO]
+        summary: summary,
         content: content,
         published: Utc::now().to_rfc3339().to_string(),
         to: to,
         cc: cc,
         tag: Some(tag),
         attachment: None,
-        sensitive: Some(false),
+        sensitive: Some(sensitive),
+        name: None,
+        oneOf: None,
+        anyOf: None,
+        endTime: None,
+        closed: None,
+        votersCount: None,
+        conversation_context: None,
+        conversation: None,
+        replies: Some(serde_json::json!(format!("{}/replies", id))),
+    }
+}
+
+/// Turns an ActivityStreams object of the type `Note` into a `Question`
+///
+/// # Parameters
+///
+/// * `note`     -      Object | An ActivityStreams object of the type `Note`
+/// * `options`  - Vec<String> | The names of the poll's options
+/// * `end_time` -      String | The point in time the poll ends, formatted according to RFC 3339
+/// * `multiple` -        bool | Whether more than one option may be chosen
+///
+pub fn question(
+    mut note: Object,
+    options: Vec<String>,
+    end_time: String,
+    multiple: bool,
+) -> Object {
+    let options: Vec<serde_json::Value> = options
+        .iter()
+        .map(|option| {
+            serde_json::json!({
+                "type": "Note",
+                "name": option,
+                "replies": {"type": "Collection", "totalItems": 0}
+            })
+        })
+        .collect();
+
+    note._type = String::from("Question");
+    note.endTime = Some(end_time);
+    note.votersCount = Some(0);
+    if multiple {
+        note.anyOf = Some(options);
+    } else {
+        note.oneOf = Some(options);
     }
+    note
+}
+
+/// Returns a new `Create` activity wrapping a vote on a poll, without inserting it into the
+/// database
+///
+/// # Description
+///
+/// Votes are only addressed to the poll's author and never show up as statuses, so there's no
+/// need to store them. Local votes are tracked by `poll::vote` instead.
+///
+/// # Parameters
+///
+/// * `actor`    - &str | Reference to an ActivityPub actor
+/// * `question` - &str | Reference to an ActivityStreams `Question`
+/// * `owner`    - &str | Reference to the ActivityPub actor who created the poll
+/// * `choice`   - &str | The name of the chosen option
+///
+pub fn vote(actor: &str, question: &str, owner: &str, choice: &str) -> Activity {
+    let mut vote = note(
+        actor,
+        Some(question.to_string()),
+        String::new(),
+        None,
+        false,
+        vec![owner.to_string()],
+        vec![],
+        vec![],
+    );
+    vote.name = Some(choice.to_string());
+    vote.sensitive = None;
+    vote.tag = None;
+
+    activity_new(
+        "Create",
+        actor,
+        serde_json::to_value(vote).unwrap(),
+        vec![owner.to_string()],
+        vec![],
+    )
 }
 
 /// Trys to fetch a remote object based on the ActivityStreams id
@@ -216,6 +406,47 @@ pub fn fetch_object_by_id(url: String) {
     }
 }
 
+/// Backfills the replies of remote objects by walking their `replies` collections
+///
+/// # Description
+///
+/// Replies are fetched depth-first, up to `MAX_BACKFILL_DEPTH` levels below each of the given
+/// objects. No more than `MAX_BACKFILL_OBJECTS` replies and `MAX_BACKFILL_PAGES` pages per
+/// collection are visited in total, so that large threads can't keep the job crawling.
+///
+/// # Parameters
+///
+/// * `object_ids` - Vec<String> | IDs of the objects whose replies are backfilled, the most
+///                                relevant first
+pub fn backfill_replies(object_ids: Vec<String>) {
+    let mut remaining_objects = MAX_BACKFILL_OBJECTS;
+
+    for object_id in object_ids {
+        backfill_replies_for(&object_id, MAX_BACKFILL_DEPTH, &mut remaining_objects);
+    }
+}
+
+/// Returns the IDs of the items of a collection or a collection page
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/activitypub_controller.rs`
+/// - collection_item_ids()
+pub fn collection_item_ids(collection: &serde_json::Value) -> Vec<String> {
+    let items = match collection["orderedItems"].as_array() {
+        Some(items) => items.clone(),
+        None => collection["items"].as_array().cloned().unwrap_or_default(),
+    };
+
+    items
+        .iter()
+        .filter_map(|item| match item.as_str() {
+            Some(item_id) => Some(item_id.to_string()),
+            None => item["id"].as_str().map(String::from),
+        })
+        .collect()
+}
+
 /// Determines whether an ActivityStreams object exists in the database
 ///
 /// # Parameters
@@ -260,8 +491,27 @@ fn activity_build(
     to: Vec<String>,
     cc: Vec<String>,
 ) -> Activity {
+    activity_insert(activity_new(_type, actor, object, to, cc))
+}
+
+fn activity_insert(new_activity: Activity) -> Activity {
     let database = database::establish_connection();
-    let new_activity = Activity {
+
+    insert_activity(
+        &database,
+        create_internal_activity(&serde_json::json!(&new_activity), &new_activity.actor),
+    );
+    new_activity
+}
+
+fn activity_new(
+    _type: &str,
+    actor: &str,
+    object: serde_json::Value,
+    to: Vec<String>,
+    cc: Vec<String>,
+) -> Activity {
+    Activity {
         context: Some(serde_json::json!(vec![
             String::from("https://www.w3.org/ns/activitystreams"),
             String::from("https://w3id.org/security/v1"),
@@ -278,13 +528,79 @@ fn activity_build(
         published: Utc::now().to_rfc3339().to_string(),
         to: to,
         cc: cc,
+        content: None,
+        tag: None,
+        target: None,
+    }
+}
+
+fn backfill_replies_for(object_id: &str, depth: u8, remaining_objects: &mut usize) {
+    if depth == 0 || *remaining_objects == 0 {
+        return;
+    }
+
+    // Replies to local objects are already known
+    let local_host = env::get_value(String::from("endpoint.base_domain"));
+    match Url::parse(object_id) {
+        Ok(url) => {
+            if url.host_str() == Some(local_host.as_str()) {
+                return;
+            }
+        }
+        Err(_) => return,
+    }
+
+    let database = database::establish_connection();
+    let replies = match get_ap_object_by_id(&database, object_id) {
+        Ok(activity) => activity.data["object"]["replies"].clone(),
+        Err(_) => return,
     };
 
-    insert_activity(
-        &database,
-        create_internal_activity(&serde_json::json!(&new_activity), &new_activity.actor),
-    );
-    new_activity
+    for reply_id in fetch_collection_item_ids(replies) {
+        if *remaining_objects == 0 {
+            return;
+        }
+        *remaining_objects -= 1;
+
+        if !object_exists(&reply_id) {
+            fetch_object_by_id(reply_id.clone());
+        }
+        if object_exists(&reply_id) {
+            backfill_replies_for(&reply_id, depth - 1, remaining_objects);
+        }
+    }
+}
+
+// Collections may be referenced by their ID or embedded, with their items spread across pages
+fn fetch_collection_item_ids(collection: serde_json::Value) -> Vec<String> {
+    let mut item_ids: Vec<String> = Vec::new();
+    let mut page = Some(collection);
+
+    for _ in 0..MAX_BACKFILL_PAGES {
+        let mut current_page = match page.take() {
+            Some(serde_json::Value::String(page_id)) => match web::fetch_remote_object(&page_id) {
+                Ok(fetched_page) => serde_json::from_str(&fetched_page).unwrap_or_default(),
+                Err(_) => {
+                    eprintln!("Unable to fetch collection: {}", &page_id);
+                    break;
+                }
+            },
+            Some(serde_json::Value::Object(embedded_page)) => {
+                serde_json::Value::Object(embedded_page)
+            }
+            _ => break,
+        };
+
+        item_ids.extend(collection_item_ids(&current_page));
+
+        // Collections refer to their first page, pages to the next one
+        page = if current_page.get("first").is_some() {
+            Some(current_page["first"].take())
+        } else {
+            Some(current_page["next"].take())
+        };
+    }
+    item_ids
 }
 
 /// Handles a newly fetched object and wraps it into it's own internal `Create` activity
@@ -328,8 +644,14 @@ fn handle_object(object: serde_json::Value) {
 fn handle_actor(actor: serde_json::Value) {
     let database = database::establish_connection();
     let serialized_actor: Actor = serde_json::from_value(actor).unwrap();
+    let actor_uri = serialized_actor.id.clone();
+    let featured = serialized_actor.featured.clone();
 
     create_actor(&database, &mut create_internal_actor(serialized_actor));
+
+    if let Some(featured) = featured {
+        fetch_featured(&actor_uri, &featured);
+    }
 }
 
 /// Final handling of incoming ActivityStreams activities which have already been validated
@@ -382,26 +704,55 @@ fn handle_activity(activity: serde_json::Value) {
 
             insert_activity(&database, create_internal_activity(&activity, &actor));
         }
+        // Relays announce public statuses of other instances, only the statuses themselves are of
+        // interest
+        Some("Announce") if relay::is_relay(&database, &actor) => {
+            let public = activity["to"]
+                .as_array()
+                .into_iter()
+                .chain(activity["cc"].as_array())
+                .flatten()
+                .any(|receipient| receipient == "https://www.w3.org/ns/activitystreams#Public");
+            let object_id = match activity["object"].as_str() {
+                Some(object_id) => object_id.to_string(),
+                None => activity["object"]["id"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            };
+
+            if public && !object_id.is_empty() {
+                thread::spawn(move || {
+                    fetch_object_by_id(object_id);
+                });
+            }
+        }
         Some("Announce") => {
             let object_id = activity["object"].as_str().unwrap().to_string();
+            let id = insert_activity(&database, create_internal_activity(&activity, &actor)).id;
+            notify_author(&database, &object_id, id, "reblog");
+
             thread::spawn(move || {
                 fetch_object_by_id(object_id);
             });
-
-            let id = insert_activity(&database, create_internal_activity(&activity, &actor)).id;
-
-            match get_actor_by_uri(&database, &actor) {
-                Ok(actor) => {
-                    if actor.local {
-                        let notification = Notification::new(id, actor.id);
-                        notification::insert(&database, notification);
-                    }
-                }
-
-                Err(_) => (),
-            }
         }
+        // Votes on polls are only counted, they're not statuses on their own
+        Some("Create") if poll::is_vote(&activity["object"]) => {
+            poll::handle_vote(&database, &activity);
+        }
+        // Of the delivered statuses only replies and direct messages are stored
+        Some("Create")
+            if !activity["object"]["inReplyTo"].is_string()
+                && !conversation::is_direct(
+                    &activity,
+                    &conversation::followers_uri_of(&database, &activity),
+                ) => {}
         Some("Create") => {
+            let internal_activity =
+                insert_activity(&database, create_internal_activity(&activity, &actor));
+            let id = internal_activity.id;
+            let mut notified_actors: Vec<i64> = Vec::new();
+
             if activity["object"].get("inReplyTo").is_some() {
                 if activity["object"]["inReplyTo"] != serde_json::Value::Null {
                     let reply_id = activity["object"]["inReplyTo"]
@@ -409,15 +760,14 @@ fn handle_activity(activity: serde_json::Value) {
                         .unwrap()
                         .to_string();
 
-                    let id =
-                        insert_activity(&database, create_internal_activity(&activity, &actor)).id;
-
                     match get_ap_object_by_id(&database, &reply_id) {
                         Ok(object) => match get_actor_by_uri(&database, &object.actor) {
                             Ok(actor) => {
                                 if actor.local {
-                                    let notification = Notification::new(id, actor.id);
+                                    let notification = Notification::new(id, actor.id, "mention");
                                     notification::insert(&database, notification);
+                                    notify_by_email(&database, &actor, "mention", &activity);
+                                    notified_actors.push(actor.id);
                                 }
                             }
                             Err(_) => eprintln!(
@@ -433,6 +783,69 @@ fn handle_activity(activity: serde_json::Value) {
                     }
                 }
             }
+
+            // Local receipients of direct messages are notified even if the message does not
+            // reply to one of their statuses
+            if conversation::is_direct(
+                &activity,
+                &conversation::followers_uri_of(&database, &activity),
+            ) {
+                let receipients: Vec<String> =
+                    serde_json::from_value(activity["to"].to_owned()).unwrap_or_else(|_| vec![]);
+
+                for receipient in receipients {
+                    match get_actor_by_uri(&database, &receipient) {
+                        Ok(receipient_actor) => {
+                            if receipient_actor.local
+                                && !notified_actors.contains(&receipient_actor.id)
+                            {
+                                let notification =
+                                    Notification::new(id, receipient_actor.id, "mention");
+                                notification::insert(&database, notification);
+                                notify_by_email(&database, &receipient_actor, "mention", &activity);
+                            }
+                        }
+                        Err(_) => (),
+                    }
+                }
+
+                conversation::handle_activity(&database, &internal_activity);
+            }
+        }
+        Some("EmojiReact") => {
+            let object_id = activity["object"].as_str().unwrap_or_default().to_string();
+            let content = activity["content"].as_str().unwrap_or_default();
+
+            match reaction::parse_reaction(content) {
+                Some(name) => {
+                    if reaction::reaction_for_actor(&database, &actor, &object_id, &name).is_some()
+                    {
+                        return;
+                    }
+
+                    let id =
+                        insert_activity(&database, create_internal_activity(&activity, &actor)).id;
+
+                    match get_ap_object_by_id(&database, &object_id) {
+                        Ok(object) => match get_actor_by_uri(&database, &object.actor) {
+                            Ok(author) => {
+                                if author.local {
+                                    let notification =
+                                        Notification::new(id, author.id, "pleroma:emoji_reaction");
+                                    notification::insert(&database, notification);
+                                }
+                            }
+                            Err(_) => (),
+                        },
+                        Err(_) => {
+                            thread::spawn(move || {
+                                fetch_object_by_id(object_id);
+                            });
+                        }
+                    }
+                }
+                None => eprintln!("Invalid emoji reaction: {}", content),
+            }
         }
         Some("Follow") => {
             let remote_account = get_actor_by_uri(&database, &actor).unwrap();
@@ -441,11 +854,18 @@ fn handle_activity(activity: serde_json::Value) {
 
             match get_actor_by_uri(&database, activity["object"].as_str().unwrap()) {
                 Ok(actor) => {
-                    if actor.local {
-                        let notification = Notification::new(id, actor.id);
+                    let followed =
+                        is_actor_followed_by(&database, &actor, &remote_account.actor_uri);
+
+                    // Locked accounts hold new followers back until they authorize them
+                    if actor.local && actor.locked && followed.as_ref().ok() == Some(&false) {
+                        follow_request::request(&database, &actor, &remote_account, id);
+                    } else if actor.local {
+                        let notification = Notification::new(id, actor.id, "follow");
                         notification::insert(&database, notification);
+                        notify_by_email(&database, &actor, "follow", &activity);
 
-                        match is_actor_followed_by(&database, &actor, &remote_account.actor_uri) {
+                        match followed {
                             Ok(false) => {
                                 let accept_activity = serde_json::to_value(accept(
                                     &actor.actor_uri,
@@ -495,21 +915,57 @@ fn handle_activity(activity: serde_json::Value) {
         }
         Some("Like") => {
             let object_id = activity["object"].as_str().unwrap().to_string();
+            let id = insert_activity(&database, create_internal_activity(&activity, &actor)).id;
+            notify_author(&database, &object_id, id, "favourite");
+
             thread::spawn(move || {
                 fetch_object_by_id(object_id);
             });
+        }
+        Some("Move") => {
+            // Actors can only move themselves, and only to accounts which list them as an alias
+            if activity["object"].as_str() == Some(actor.as_str()) {
+                match activity["target"].as_str() {
+                    Some(target) => match refetch(target) {
+                        Ok(new_actor) => {
+                            if new_actor.also_known_as.as_array().map_or(false, |aliases| {
+                                aliases.contains(&serde_json::json!(actor))
+                            }) {
+                                insert_activity(
+                                    &database,
+                                    create_internal_activity(&activity, &actor),
+                                );
 
-            let id = insert_activity(&database, create_internal_activity(&activity, &actor)).id;
+                                let mut old_actor = get_actor_by_uri(&database, &actor).unwrap();
+                                old_actor.moved_to = Some(new_actor.actor_uri.clone());
+                                actor::update(&database, old_actor.clone());
 
-            match get_actor_by_uri(&database, &actor) {
-                Ok(actor) => {
-                    if actor.local {
-                        let notification = Notification::new(id, actor.id);
-                        notification::insert(&database, notification);
+                                thread::spawn(move || {
+                                    kibou_api::move_followers(&old_actor, &new_actor)
+                                });
+                            } else {
+                                eprintln!(
+                                    "Rejected move of {}, target doesn't list it as an alias",
+                                    actor
+                                )
+                            }
+                        }
+                        Err(e) => eprintln!("Unable to verify move of {}: {}", actor, e),
+                    },
+                    None => eprintln!("Move of {} is missing a target", actor),
+                }
+            }
+        }
+        Some("Update") => {
+            // Only updates of actors by the actors themselves are supported so far
+            if activity["object"]["id"].as_str() == Some(actor.as_str()) {
+                match validator::validate_actor(activity["object"].clone()) {
+                    Ok(object) => {
+                        let serialized_actor: Actor = serde_json::from_value(object).unwrap();
+                        actor::update(&database, create_internal_actor(serialized_actor));
                     }
+                    Err(_) => eprintln!("Unable to update actor, object is invalid: {}", actor),
                 }
-
-                Err(_) => (),
             }
         }
         Some("Undo") => {
@@ -524,17 +980,73 @@ fn handle_activity(activity: serde_json::Value) {
                         get_actor_by_uri(&database, object.data["object"].as_str().unwrap())
                             .unwrap();
 
+                    // Follows which weren't authorized yet are withdrawn with the same `Undo`
                     match is_actor_followed_by(&database, &account, &actor) {
                         Ok(true) => remove_follow(&account.actor_uri, &remote_account.actor_uri),
+                        Ok(false) if account.local => {
+                            let _ = follow_request::remove(&database, &account, remote_account.id);
+                        }
                         Ok(false) => (),
                         Err(_) => (),
                     }
 
                     insert_activity(&database, create_internal_activity(&activity, &actor));
                 }
+                "EmojiReact" => {
+                    if object.actor == actor {
+                        delete_ap_activity_by_id(
+                            &database,
+                            object.data["id"].as_str().unwrap_or_default().to_string(),
+                        );
+                    }
+                }
                 &_ => (),
             }
         }
         _ => (),
     }
 }
+
+// Reblogs and favourites concern the author of the object, if they're a local actor
+fn notify_author(
+    db_connection: &PgConnection,
+    object_id: &str,
+    activity_id: i64,
+    notification_type: &str,
+) {
+    if let Ok(object) = get_ap_object_by_id(db_connection, object_id) {
+        if let Ok(author) = get_actor_by_uri(db_connection, &object.actor) {
+            if author.local {
+                let notification = Notification::new(activity_id, author.id, notification_type);
+                notification::insert(db_connection, notification);
+            }
+        }
+    }
+}
+
+// Local actors may opt in to e-mails about mentions and follows, see `email::notify`
+fn notify_by_email(
+    db_connection: &PgConnection,
+    local_actor: &actor::Actor,
+    notification_type: &str,
+    activity: &serde_json::Value,
+) {
+    let sender = activity["actor"].as_str().unwrap_or_default();
+    let account = match get_actor_by_uri(db_connection, sender) {
+        Ok(mut sender_actor) => sender_actor.get_acct(),
+        Err(_) => sender.to_string(),
+    };
+
+    let context = match notification_type {
+        "mention" => serde_json::json!({
+            "account": account,
+            "content": html::to_raw_text(activity["object"]["content"].as_str().unwrap_or_default()),
+            "link": activity["object"]["url"]
+                .as_str()
+                .or(activity["object"]["id"].as_str())
+                .unwrap_or_default()
+        }),
+        _ => serde_json::json!({ "account": account, "link": sender }),
+    };
+    email::notify(db_connection, local_actor, notification_type, context);
+}
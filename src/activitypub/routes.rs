@@ -5,15 +5,32 @@ use activitypub::ActivitypubMediatype;
 use activitypub::ActivitystreamsResponse;
 use activitypub::Payload;
 use activitypub::Signature;
+use api_error::ApiError;
 
 #[get("/activities/<id>")]
-pub fn activity(_media_type: ActivitypubMediatype, id: String) -> ActivitystreamsResponse {
-    ActivitystreamsResponse(ap_activity::get_activity_json_by_id(&id).to_string())
+pub fn activity(
+    _media_type: ActivitypubMediatype,
+    id: String,
+) -> Result<ActivitystreamsResponse, ApiError> {
+    ap_activity::get_activity_json_by_id(&id).map(|json| ActivitystreamsResponse(json.to_string()))
 }
 
 #[get("/actors/<handle>")]
-pub fn actor(_media_type: ActivitypubMediatype, handle: String) -> ActivitystreamsResponse {
-    ActivitystreamsResponse(ap_actor::get_json_by_preferred_username(&handle).to_string())
+pub fn actor(
+    _media_type: ActivitypubMediatype,
+    handle: String,
+) -> Result<ActivitystreamsResponse, ApiError> {
+    ap_actor::get_json_by_preferred_username(&handle)
+        .map(|json| ActivitystreamsResponse(json.to_string()))
+}
+
+#[get("/actors/<handle>/collections/featured")]
+pub fn actor_featured(
+    _media_type: ActivitypubMediatype,
+    handle: String,
+) -> Result<ActivitystreamsResponse, ApiError> {
+    ap_actor::get_featured_json_by_preferred_username(&handle)
+        .map(|json| ActivitystreamsResponse(json.to_string()))
 }
 
 #[post("/actors/<_id>/inbox", data = "<activity>")]
@@ -27,6 +44,17 @@ pub fn inbox(activity: Payload, _signature: Signature) {
 }
 
 #[get("/objects/<id>")]
-pub fn object(_media_type: ActivitypubMediatype, id: String) -> ActivitystreamsResponse {
-    ActivitystreamsResponse(ap_activity::get_object_json_by_id(&id).to_string())
+pub fn object(
+    _media_type: ActivitypubMediatype,
+    id: String,
+) -> Result<ActivitystreamsResponse, ApiError> {
+    ap_activity::get_object_json_by_id(&id).map(|json| ActivitystreamsResponse(json.to_string()))
+}
+
+#[get("/objects/<id>/replies")]
+pub fn object_replies(
+    _media_type: ActivitypubMediatype,
+    id: String,
+) -> Result<ActivitystreamsResponse, ApiError> {
+    ap_activity::get_replies_json_by_id(&id).map(|json| ActivitystreamsResponse(json.to_string()))
 }
@@ -7,6 +7,7 @@ use activitypub::controller::object_exists;
 use actor;
 use database;
 use html;
+use reaction;
 use regex::Regex;
 use url::Url;
 use web;
@@ -17,14 +18,19 @@ pub fn validate_activity(
     signature: Signature,
 ) -> Result<serde_json::Value, &'static str> {
     let database = database::establish_connection();
+    activity = reaction::normalize_misskey_like(activity);
+
     let known_type = if activity.get("type").is_some() {
         match activity["type"].as_str() {
             Some("Accept") => true,
             Some("Announce") => true,
             Some("Create") => true,
+            Some("EmojiReact") => true,
             Some("Follow") => true,
             Some("Like") => true,
+            Some("Move") => true,
             Some("Undo") => true,
+            Some("Update") => true,
             _ => false,
         }
     } else {
@@ -78,6 +84,9 @@ pub fn validate_object(
         match object["type"].as_str() {
             Some("Note") => true,
             Some("Article") => true,
+            Some("Question") => true,
+            // Votes on polls as sent by Pleroma
+            Some("Answer") => true,
             _ => false,
         }
     } else {
@@ -119,7 +128,10 @@ pub fn validate_object(
 pub fn validate_actor(actor: serde_json::Value) -> Result<serde_json::Value, &'static str> {
     let known_type = if actor.get("type").is_some() {
         match actor["type"].as_str() {
+            // Relays are usually represented as applications
+            Some("Application") => true,
             Some("Person") => true,
+            Some("Service") => true,
             _ => false,
         }
     } else {
@@ -178,6 +190,12 @@ fn normalize_activity(mut activity: serde_json::Value) -> serde_json::Value {
 
     let mut new_activity: Activity;
 
+    // Mastodon doesn't address `Move` activities at all
+    if activity.get("to").is_none() {
+        let new_to_tag: Vec<String> = vec![];
+        activity["to"] = serde_json::json!(new_to_tag);
+    }
+
     if activity.get("cc").is_none() {
         let new_cc_tag: Vec<String> = vec![];
         activity["cc"] = serde_json::json!(new_cc_tag);
@@ -209,6 +227,10 @@ fn normalize_object(mut object: serde_json::Value) -> serde_json::Value {
 
     new_object = serde_json::from_value(object.clone()).unwrap();
     new_object.content = html::strip_tags(&new_object.content);
+    new_object.summary = match new_object.summary {
+        Some(summary) => Some(html::strip_tags(&summary)),
+        None => None,
+    };
     new_object.context = None;
     new_object.to = normalize_public_addressing(new_object.to);
     new_object.cc = normalize_public_addressing(new_object.cc);
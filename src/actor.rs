@@ -15,8 +15,16 @@ use openssl::rsa::Rsa;
 use openssl::sign::Signer;
 use pem::Pem;
 use serde_json;
+use timeline::prepare_order_query;
 use url::Url;
 
+// Limits of the profiles of local actors, which are the same as Mastodon's
+pub const MAX_DISPLAY_NAME_CHARS: usize = 30;
+pub const MAX_NOTE_CHARS: usize = 500;
+pub const MAX_PROFILE_FIELDS: usize = 4;
+pub const MAX_PROFILE_FIELD_CHARS: usize = 255;
+
+#[derive(Clone)]
 pub struct Actor {
     pub id: i64,
     pub email: Option<String>,
@@ -28,10 +36,23 @@ pub struct Actor {
     pub followers: serde_json::Value,
     pub inbox: Option<String>,
     pub icon: Option<String>,
+    pub header: Option<String>,
     pub local: bool,
     pub keys: serde_json::Value,
     pub created: NaiveDateTime,
     pub modified: NaiveDateTime,
+    pub locked: bool,
+    pub bot: bool,
+    pub discoverable: bool,
+    // Profile metadata as an array of `{"name": ..., "value": ...}` objects, values are rendered
+    // as HTML
+    pub fields: serde_json::Value,
+    // URIs of other accounts of the same person, a migration to one of them requires it to be
+    // listed here as well
+    pub also_known_as: serde_json::Value,
+    pub moved_to: Option<String>,
+    // URI of the actor's followers collection, which is only known for remote actors
+    pub followers_uri: Option<String>,
 }
 
 impl Actor {
@@ -72,6 +93,24 @@ impl Actor {
         }
     }
 
+    /// Returns the URI of the actor's followers collection
+    ///
+    /// # Description
+    ///
+    /// Local actors and remote actors which were stored before their collection was known use
+    /// Kibou's URL layout, `<actor>/followers`.
+    ///
+    /// # Tests
+    ///
+    /// Tests for this function are in `tests/actor.rs`
+    /// - followers_collection()
+    pub fn followers_collection(&self) -> String {
+        match &self.followers_uri {
+            Some(followers_uri) => followers_uri.to_string(),
+            None => format!("{}/followers", self.actor_uri),
+        }
+    }
+
     pub fn get_public_key(&mut self) -> String {
         let parsed_public_key = self.keys["public"].as_str();
         parsed_public_key.unwrap().to_string()
@@ -127,11 +166,19 @@ fn serialize_actor(sql_actor: QueryActor) -> Actor {
         summary: sql_actor.summary,
         inbox: sql_actor.inbox,
         icon: sql_actor.icon,
+        header: sql_actor.header,
         keys: sql_actor.keys,
         local: sql_actor.local,
         followers: sql_actor.followers,
         created: sql_actor.created,
         modified: sql_actor.modified,
+        locked: sql_actor.locked,
+        bot: sql_actor.bot,
+        discoverable: sql_actor.discoverable,
+        fields: sql_actor.fields,
+        also_known_as: sql_actor.also_known_as,
+        moved_to: sql_actor.moved_to,
+        followers_uri: sql_actor.followers_uri,
     }
 }
 
@@ -215,8 +262,16 @@ pub fn create_actor(db_connection: &PgConnection, actor: &mut Actor) {
         summary.eq(&actor.summary),
         inbox.eq(&actor.inbox),
         icon.eq(&actor.icon),
+        header.eq(&actor.header),
         local.eq(&actor.local),
         keys.eq(&actor.keys),
+        locked.eq(&actor.locked),
+        bot.eq(&actor.bot),
+        discoverable.eq(&actor.discoverable),
+        fields.eq(&actor.fields),
+        also_known_as.eq(&actor.also_known_as),
+        moved_to.eq(&actor.moved_to),
+        followers_uri.eq(&actor.followers_uri),
     );
 
     diesel::insert_into(actors::table)
@@ -249,12 +304,29 @@ pub fn update(db_connection: &PgConnection, actor: Actor) {
             username.eq(&actor.username),
             summary.eq(&actor.summary),
             icon.eq(&actor.icon),
+            header.eq(&actor.header),
             keys.eq(&actor.keys),
+            locked.eq(&actor.locked),
+            bot.eq(&actor.bot),
+            discoverable.eq(&actor.discoverable),
+            fields.eq(&actor.fields),
+            also_known_as.eq(&actor.also_known_as),
+            moved_to.eq(&actor.moved_to),
+            followers_uri.eq(&actor.followers_uri),
         ))
         .execute(db_connection)
         .expect("Could not update actor");
 }
 
+pub fn update_password(db_connection: &PgConnection, actor: &Actor, new_password: &str) {
+    let hashed_password = bcrypt::hash(new_password, bcrypt::DEFAULT_COST).unwrap();
+
+    diesel::update(actors.filter(id.eq(actor.id)))
+        .set(password.eq(&hashed_password))
+        .execute(db_connection)
+        .expect("Could not update actor");
+}
+
 pub fn update_followers(db_connection: &PgConnection, actor: Actor) {
     diesel::update(actors.filter(actor_uri.eq(&actor.actor_uri)))
         .set(followers.eq(&actor.followers))
@@ -322,7 +394,7 @@ pub fn get_actor_followees(
 ) -> Result<Vec<Actor>, diesel::result::Error> {
     match sql_query(format!(
         "WITH actor \
-        AS ( SELECT id, email, password, actor_uri, username, preferred_username, summary, inbox, icon, keys, created, modified, local, jsonb_array_elements(followers->'activitypub') \
+        AS ( SELECT id, email, password, actor_uri, username, preferred_username, summary, inbox, icon, keys, created, modified, local, admin, header, locked, bot, discoverable, fields, also_known_as, moved_to, followers_uri, jsonb_array_elements(followers->'activitypub') \
         AS followers FROM actors) \
         SELECT * FROM actor \
         WHERE (followers->>'href') = '{uri}';",
@@ -335,6 +407,83 @@ pub fn get_actor_followees(
         }
 }
 
+/// Returns a page of the actors that an actor follows, ordered by their IDs
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -        &Actor | The following actor
+/// * `max_id`        -   Option<i64> | Return actors with an ID lower than this ID
+/// * `since_id`      -   Option<i64> | Return actors with an ID higher than this ID
+/// * `min_id`        -   Option<i64> | Return actors immediately following this ID
+/// * `limit`         -           i64 | Maximum number of actors to return
+///
+pub fn followees_for_actor(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: i64,
+) -> Result<Vec<Actor>, diesel::result::Error> {
+    match sql_query(format!(
+        "SELECT * FROM actors \
+         WHERE followers->'activitypub' @> '[{{\"href\": \"{uri}\"}}]' \
+         {id} \
+         LIMIT {limit};",
+        uri = runtime_escape(&actor.actor_uri),
+        id = prepare_order_query(max_id, since_id, min_id),
+        limit = limit
+    ))
+    .load::<QueryActor>(db_connection)
+    {
+        Ok(actor_vec) => Ok(actor_vec
+            .iter()
+            .map(|actor| serialize_actor(actor.to_owned()))
+            .collect()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns a page of the actors following an actor, ordered by their IDs
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -        &Actor | The followed actor
+/// * `max_id`        -   Option<i64> | Return actors with an ID lower than this ID
+/// * `since_id`      -   Option<i64> | Return actors with an ID higher than this ID
+/// * `min_id`        -   Option<i64> | Return actors immediately following this ID
+/// * `limit`         -           i64 | Maximum number of actors to return
+///
+pub fn followers_for_actor(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: i64,
+) -> Result<Vec<Actor>, diesel::result::Error> {
+    match sql_query(format!(
+        "SELECT * FROM actors \
+         WHERE actor_uri IN (SELECT jsonb_array_elements(followers->'activitypub')->>'href' \
+         FROM actors WHERE id = {actor_id}) \
+         {id} \
+         LIMIT {limit};",
+        actor_id = actor.id,
+        id = prepare_order_query(max_id, since_id, min_id),
+        limit = limit
+    ))
+    .load::<QueryActor>(db_connection)
+    {
+        Ok(actor_vec) => Ok(actor_vec
+            .iter()
+            .map(|actor| serialize_actor(actor.to_owned()))
+            .collect()),
+        Err(e) => Err(e),
+    }
+}
+
 /// Runs a database query based on a local actor's preferred_username, returns either
 /// an actor::Actor or a diesel::result::Error
 ///
@@ -362,6 +511,43 @@ pub fn get_local_actor_by_preferred_username(
     }
 }
 
+pub fn get_local_actor_by_email(
+    db_connection: &PgConnection,
+    address: &str,
+) -> Result<Actor, diesel::result::Error> {
+    match actors
+        .filter(email.eq(address))
+        .filter(local.eq(true))
+        .limit(1)
+        .first::<QueryActor>(db_connection)
+    {
+        Ok(actor) => Ok(serialize_actor(actor)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Determines whether an actor may use the administrative parts of the API
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -        &Actor | An actor serialized in an actor::Actor struct
+pub fn is_admin(db_connection: &PgConnection, actor: &Actor) -> bool {
+    actors
+        .filter(id.eq(actor.id))
+        .filter(local.eq(true))
+        .select(admin)
+        .first::<bool>(db_connection)
+        .unwrap_or_else(|_| false)
+}
+
+pub fn set_admin(db_connection: &PgConnection, actor: &Actor, value: bool) {
+    diesel::update(actors.filter(id.eq(actor.id)))
+        .set(admin.eq(value))
+        .execute(db_connection)
+        .expect("Could not update actor");
+}
+
 pub fn is_actor_followed_by(
     db_connection: &PgConnection,
     actor: &Actor,
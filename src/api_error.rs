@@ -0,0 +1,97 @@
+//! Errors of the Mastodon API, Kibou API and ActivityPub routes
+//!
+//! Errors are answered with the matching HTTP status code and a body like Mastodon's, e.g.
+//! `{"error": "Record not found"}`, so clients can tell them apart from successful responses.
+
+use diesel::result::Error as DieselError;
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
+use rocket_contrib::json;
+use rocket_contrib::json::Json;
+
+pub type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Debug, PartialEq)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    Unprocessable(String),
+    TooManyRequests(String),
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn bad_request(message: &str) -> ApiError {
+        ApiError::BadRequest(message.to_string())
+    }
+
+    pub fn unauthorized(message: &str) -> ApiError {
+        ApiError::Unauthorized(message.to_string())
+    }
+
+    pub fn forbidden(message: &str) -> ApiError {
+        ApiError::Forbidden(message.to_string())
+    }
+
+    pub fn not_found(message: &str) -> ApiError {
+        ApiError::NotFound(message.to_string())
+    }
+
+    pub fn unprocessable(message: &str) -> ApiError {
+        ApiError::Unprocessable(message.to_string())
+    }
+
+    pub fn too_many_requests(message: &str) -> ApiError {
+        ApiError::TooManyRequests(message.to_string())
+    }
+
+    pub fn internal(message: &str) -> ApiError {
+        ApiError::Internal(message.to_string())
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::BadRequest(message)
+            | ApiError::Unauthorized(message)
+            | ApiError::Forbidden(message)
+            | ApiError::NotFound(message)
+            | ApiError::Unprocessable(message)
+            | ApiError::TooManyRequests(message)
+            | ApiError::Internal(message) => message,
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        match self {
+            ApiError::BadRequest(_) => Status::BadRequest,
+            ApiError::Unauthorized(_) => Status::Unauthorized,
+            ApiError::Forbidden(_) => Status::Forbidden,
+            ApiError::NotFound(_) => Status::NotFound,
+            ApiError::Unprocessable(_) => Status::UnprocessableEntity,
+            ApiError::TooManyRequests(_) => Status::TooManyRequests,
+            ApiError::Internal(_) => Status::InternalServerError,
+        }
+    }
+}
+
+// Queries for a single record fail with `NotFound` if it doesn't exist, every other database
+// error is unexpected
+impl From<DieselError> for ApiError {
+    fn from(error: DieselError) -> ApiError {
+        match error {
+            DieselError::NotFound => ApiError::not_found("Record not found"),
+            _ => ApiError::internal("An error occured while querying the database"),
+        }
+    }
+}
+
+impl<'r> Responder<'r> for ApiError {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+        let status = self.status();
+        let body = json!({ "error": self.message() }).respond_to(request)?;
+        Response::build_from(body).status(status).ok()
+    }
+}
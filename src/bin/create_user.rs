@@ -33,6 +33,11 @@ fn main() {
         "a summary (bio) for the new user",
         "I am a Kibou Test-Actor, how are you doing?",
     );
+    options.optflag(
+        "a",
+        "admin",
+        "grant administrative privileges to the new user",
+    );
 
     let matches = match options.parse(&args[1..]) {
         Ok(m) => m,
@@ -55,11 +60,27 @@ fn main() {
         followers: serde_json::json!({"activitypub": []}),
         inbox: None,
         icon: None,
+        header: None,
         local: true,
         keys: serde_json::json!({}),
         created: Utc::now().naive_utc(),
         modified: Utc::now().naive_utc(),
+        locked: false,
+        bot: false,
+        discoverable: false,
+        fields: serde_json::json!([]),
+        also_known_as: serde_json::json!([]),
+        moved_to: None,
+        followers_uri: None,
     };
 
-    actor::create_actor(&database, &mut new_actor)
+    actor::create_actor(&database, &mut new_actor);
+
+    if matches.opt_present("admin") {
+        match actor::get_local_actor_by_preferred_username(&database, &new_actor.preferred_username)
+        {
+            Ok(created_actor) => actor::set_admin(&database, &created_actor, true),
+            Err(_) => eprintln!("Unable to grant administrative privileges"),
+        }
+    }
 }
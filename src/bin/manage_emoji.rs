@@ -0,0 +1,81 @@
+extern crate getopts;
+extern crate kibou;
+
+use getopts::Options;
+use kibou::database;
+use kibou::emoji;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+fn main() {
+    let database = database::establish_connection();
+    let args: Vec<String> = std::env::args().collect();
+
+    let mut options = Options::new();
+    options.optopt("a", "add", "shortcode of a new emoji", "blobcat");
+    options.optopt(
+        "f",
+        "file",
+        "image of the new emoji (png, gif, jpg or webp)",
+        "blobcat.png",
+    );
+    options.optopt("c", "category", "category of the new emoji", "Blobs");
+    options.optopt("r", "remove", "shortcode of the emoji to remove", "blobcat");
+    options.optflag("l", "list", "list all emojis of this instance");
+
+    let matches = match options.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(e) => panic!(e.to_string()),
+    };
+
+    if let Some(shortcode) = matches.opt_str("add") {
+        let file = matches
+            .opt_str("file")
+            .expect("An image has to be provided with --file");
+        let extension = Path::new(&file)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase()
+            .replace("jpeg", "jpg");
+        let mut image: Vec<u8> = Vec::new();
+
+        // Like the API, only read one byte more than `emoji::add` accepts
+        if let Err(e) = File::open(&file).and_then(|image_file| {
+            image_file
+                .take(emoji::MAX_IMAGE_SIZE + 1)
+                .read_to_end(&mut image)
+        }) {
+            eprintln!("Unable to read image: {}", e);
+            return;
+        }
+
+        match emoji::add(
+            &database,
+            &shortcode,
+            &extension,
+            &image,
+            matches.opt_str("category"),
+        ) {
+            Ok(new_emoji) => println!("Added :{}: ({})", new_emoji.shortcode, new_emoji.url()),
+            Err(e) => eprintln!("{}", e),
+        }
+    } else if let Some(shortcode) = matches.opt_str("remove") {
+        match emoji::remove(&database, &shortcode) {
+            Ok(_) => println!("Removed :{}:", shortcode),
+            Err(e) => eprintln!("{}", e),
+        }
+    } else if matches.opt_present("list") {
+        for listed_emoji in emoji::get_emojis(&database).unwrap_or_else(|_| vec![]) {
+            println!(
+                ":{}: {} {}",
+                listed_emoji.shortcode,
+                listed_emoji.url(),
+                listed_emoji.category.unwrap_or_default()
+            );
+        }
+    } else {
+        print!("{}", options.usage("Usage: manage_emoji [options]"));
+    }
+}
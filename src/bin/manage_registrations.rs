@@ -0,0 +1,76 @@
+extern crate getopts;
+extern crate kibou;
+
+use getopts::Options;
+use kibou::actor;
+use kibou::database;
+use kibou::registration;
+
+fn main() {
+    let database = database::establish_connection();
+    let args: Vec<String> = std::env::args().collect();
+
+    let mut options = Options::new();
+    options.optflag(
+        "p",
+        "pending",
+        "list accounts which are waiting for approval",
+    );
+    options.optopt("a", "approve", "approve a pending account", "alyssatest");
+    options.optopt(
+        "r",
+        "reject",
+        "reject and delete a pending account",
+        "alyssatest",
+    );
+    options.optopt(
+        "i",
+        "invite",
+        "create an unlimited invite on behalf of a local user",
+        "alyssatest",
+    );
+
+    let matches = match options.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(e) => panic!(e.to_string()),
+    };
+
+    if matches.opt_present("pending") {
+        match registration::pending(&database) {
+            Ok(pending_registrations) => {
+                for pending_registration in pending_registrations {
+                    println!(
+                        "{} <{}> ({}): {}",
+                        pending_registration.username,
+                        pending_registration.email.unwrap_or_default(),
+                        pending_registration.created_at,
+                        pending_registration.reason
+                    );
+                }
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    } else if let Some(username) = matches.opt_str("approve") {
+        match registration::approve(&database, &username) {
+            Ok(()) => println!("Approved {}", username),
+            Err(e) => eprintln!("{}", e),
+        }
+    } else if let Some(username) = matches.opt_str("reject") {
+        match registration::reject(&database, &username) {
+            Ok(()) => println!("Rejected {}", username),
+            Err(e) => eprintln!("{}", e),
+        }
+    } else if let Some(username) = matches.opt_str("invite") {
+        match actor::get_local_actor_by_preferred_username(&database, &username) {
+            Ok(local_actor) => {
+                match registration::create_invite(&database, &local_actor, None, None) {
+                    Ok(invite) => println!("{}", invite.serialize().url),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            Err(_) => eprintln!("User {} not found", username),
+        }
+    } else {
+        print!("{}", options.usage("Usage: manage_registrations [options]"));
+    }
+}
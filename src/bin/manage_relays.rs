@@ -0,0 +1,54 @@
+extern crate getopts;
+extern crate kibou;
+
+use getopts::Options;
+use kibou::database;
+use kibou::relay;
+
+fn main() {
+    let database = database::establish_connection();
+    let args: Vec<String> = std::env::args().collect();
+
+    let mut options = Options::new();
+    options.optopt(
+        "f",
+        "follow",
+        "subscribe to a relay",
+        "https://relay.example.tld/actor",
+    );
+    options.optopt(
+        "u",
+        "unfollow",
+        "unsubscribe from a relay",
+        "https://relay.example.tld/actor",
+    );
+    options.optflag("l", "list", "list all relays of this instance");
+
+    let matches = match options.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(e) => panic!(e.to_string()),
+    };
+
+    if let Some(relay_uri) = matches.opt_str("follow") {
+        match relay::subscribe(&database, &relay_uri) {
+            Ok(new_relay) => println!("Subscribed to {}", new_relay.actor_uri),
+            Err(e) => eprintln!("{}", e),
+        }
+    } else if let Some(relay_uri) = matches.opt_str("unfollow") {
+        match relay::unsubscribe(&database, &relay_uri) {
+            Ok(_) => println!("Unsubscribed from {}", relay_uri),
+            Err(e) => eprintln!("{}", e),
+        }
+    } else if matches.opt_present("list") {
+        for listed_relay in relay::get_relays(&database).unwrap_or_else(|_| vec![]) {
+            let state = if relay::is_accepted(&database, &listed_relay) {
+                "accepted"
+            } else {
+                "pending"
+            };
+            println!("{} ({})", listed_relay.actor_uri, state);
+        }
+    } else {
+        print!("{}", options.usage("Usage: manage_relays [options]"));
+    }
+}
@@ -0,0 +1,55 @@
+extern crate getopts;
+extern crate kibou;
+
+use getopts::Options;
+use kibou::actor;
+use kibou::database;
+use kibou::two_factor;
+
+fn main() {
+    let database = database::establish_connection();
+    let args: Vec<String> = std::env::args().collect();
+
+    let mut options = Options::new();
+    options.optopt(
+        "r",
+        "reset",
+        "disable two-factor authentication of a local user who lost access to it",
+        "alyssatest",
+    );
+    options.optopt(
+        "s",
+        "status",
+        "show whether a local user enabled two-factor authentication",
+        "alyssatest",
+    );
+
+    let matches = match options.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(e) => panic!(e.to_string()),
+    };
+
+    if let Some(username) = matches.opt_str("reset") {
+        match actor::get_local_actor_by_preferred_username(&database, &username) {
+            Ok(local_actor) => {
+                two_factor::reset(&database, &local_actor);
+                println!("Reset two-factor authentication of {}", username);
+            }
+            Err(_) => eprintln!("User {} not found", username),
+        }
+    } else if let Some(username) = matches.opt_str("status") {
+        match actor::get_local_actor_by_preferred_username(&database, &username) {
+            Ok(local_actor) => {
+                let state = if two_factor::is_enabled(&database, &local_actor) {
+                    "enabled"
+                } else {
+                    "disabled"
+                };
+                println!("Two-factor authentication of {} is {}", username, state);
+            }
+            Err(_) => eprintln!("User {} not found", username),
+        }
+    } else {
+        print!("{}", options.usage("Usage: manage_two_factor [options]"));
+    }
+}
@@ -0,0 +1,88 @@
+use actor::Actor;
+use database::models::InsertBookmark;
+use database::schema::bookmarks;
+use diesel::pg::PgConnection;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+pub fn bookmark(db_connection: &PgConnection, actor: &Actor, activity_id: i64) {
+    diesel::insert_into(bookmarks::table)
+        .values(InsertBookmark {
+            actor_id: actor.id,
+            activity_id: activity_id,
+        })
+        .on_conflict_do_nothing()
+        .execute(db_connection)
+        .expect("Error adding bookmark");
+}
+
+pub fn unbookmark(db_connection: &PgConnection, actor: &Actor, activity_id: i64) {
+    diesel::delete(
+        bookmarks::table
+            .filter(bookmarks::actor_id.eq(actor.id))
+            .filter(bookmarks::activity_id.eq(activity_id)),
+    )
+    .execute(db_connection)
+    .expect("Error removing bookmark");
+}
+
+/// Returns the ids of an actor's bookmarks and their activities, starting with the latest bookmark
+///
+/// # Description
+///
+/// Bookmarks are paginated by their own ids, as they're listed in the order they were added.
+///
+/// # Parameters
+///
+/// * `db_connection` -  &PgConnection | Reference to a database connection
+/// * `actor`         -        &Actor | The local actor the bookmarks belong to
+/// * `max_id`        -   Option<i64> | Only bookmarks older than this bookmark
+/// * `since_id`      -   Option<i64> | Only bookmarks newer than this bookmark
+/// * `min_id`        -   Option<i64> | The bookmarks directly following this bookmark
+/// * `limit`         -   Option<i64> | The maximum amount of bookmarks, 20 by default
+pub fn bookmarked_activities(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<(i64, i64)>, diesel::result::Error> {
+    let mut query = bookmarks::table
+        .filter(bookmarks::actor_id.eq(actor.id))
+        .select((bookmarks::id, bookmarks::activity_id))
+        .into_boxed();
+
+    query = if max_id.is_some() {
+        query
+            .filter(bookmarks::id.lt(max_id.unwrap()))
+            .order(bookmarks::id.desc())
+    } else if since_id.is_some() {
+        query
+            .filter(bookmarks::id.gt(since_id.unwrap()))
+            .order(bookmarks::id.desc())
+    } else if min_id.is_some() {
+        query
+            .filter(bookmarks::id.gt(min_id.unwrap()))
+            .order(bookmarks::id.asc())
+    } else {
+        query.order(bookmarks::id.desc())
+    };
+
+    query
+        .limit(limit.unwrap_or(20))
+        .load::<(i64, i64)>(db_connection)
+}
+
+/// Returns which of the given activities an actor has bookmarked
+pub fn bookmarked_among(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    activity_ids: Vec<i64>,
+) -> Vec<i64> {
+    bookmarks::table
+        .filter(bookmarks::actor_id.eq(actor.id))
+        .filter(bookmarks::activity_id.eq_any(activity_ids))
+        .select(bookmarks::activity_id)
+        .load::<i64>(db_connection)
+        .unwrap_or_else(|_| Vec::new())
+}
@@ -0,0 +1,355 @@
+use activity::{get_ap_object_by_id, Activity};
+use actor::{get_actor_by_uri, Actor};
+use chrono::NaiveDateTime;
+use database::models::{InsertConversation, InsertConversationParticipant};
+use database::schema::conversation_participants;
+use database::schema::conversations;
+use diesel::pg::PgConnection;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+// Limit of the objects followed up a thread while determining its context
+const MAX_THREAD_DEPTH: usize = 100;
+
+#[derive(Queryable, PartialEq, QueryableByName, Clone)]
+#[table_name = "conversations"]
+pub struct Conversation {
+    pub id: i64,
+    pub context: String,
+    pub last_activity_id: i64,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+/// A conversation as seen by one of its participants
+pub struct ParticipantConversation {
+    pub id: i64,
+    pub last_activity_id: i64,
+    pub unread: bool,
+}
+
+/// Determines whether an ActivityStreams activity is only addressed to the actors it mentions
+///
+/// # Description
+///
+/// An activity counts as direct if neither the public collection nor the followers collection of
+/// its actor have been addressed.
+///
+/// # Parameters
+///
+/// * `activity`      - &serde_json::Value | An ActivityStreams activity serialized in JSON
+/// * `followers_uri` -               &str | The followers collection of the activity's actor, see
+///                                          `followers_uri_of`
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/conversation.rs`
+/// - is_direct()
+pub fn is_direct(activity: &serde_json::Value, followers_uri: &str) -> bool {
+    let public = "https://www.w3.org/ns/activitystreams#Public";
+
+    for audience in &[&activity["to"], &activity["cc"]] {
+        match audience.as_array() {
+            Some(receipients) => {
+                for receipient in receipients {
+                    if receipient == public || receipient.as_str() == Some(followers_uri) {
+                        return false;
+                    }
+                }
+            }
+            None => (),
+        }
+    }
+    true
+}
+
+/// Determines whether an actor belongs to the audience of an ActivityStreams activity
+///
+/// # Description
+///
+/// Activities which aren't direct are visible to everyone. Direct ones are only visible to their
+/// actor and the actors they're addressed to, anonymous viewers never see them.
+///
+/// # Parameters
+///
+/// * `activity`      - &serde_json::Value | An ActivityStreams activity serialized in JSON
+/// * `followers_uri` -               &str | The followers collection of the activity's actor
+/// * `viewer`        -       Option<&str> | The URI of the viewing actor
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/conversation.rs`
+/// - is_visible_to()
+pub fn is_visible_to(
+    activity: &serde_json::Value,
+    followers_uri: &str,
+    viewer: Option<&str>,
+) -> bool {
+    if !is_direct(activity, followers_uri) {
+        return true;
+    }
+
+    match viewer {
+        Some(viewer) => {
+            activity["actor"].as_str() == Some(viewer)
+                || [&activity["to"], &activity["cc"]].iter().any(|audience| {
+                    audience.as_array().map_or(false, |receipients| {
+                        receipients
+                            .iter()
+                            .any(|receipient| receipient.as_str() == Some(viewer))
+                    })
+                })
+        }
+        None => false,
+    }
+}
+
+/// Returns the URI of the followers collection of an activity's actor
+///
+/// # Description
+///
+/// Remote instances don't necessarily follow Kibou's URL layout, so the collection of the stored
+/// actor is used. Unknown actors are assumed to follow Kibou's layout.
+pub fn followers_uri_of(db_connection: &PgConnection, activity: &serde_json::Value) -> String {
+    let actor_uri = activity["actor"].as_str().unwrap_or_default();
+
+    match get_actor_by_uri(db_connection, actor_uri) {
+        Ok(actor) => actor.followers_collection(),
+        Err(_) => format!("{}/followers", actor_uri),
+    }
+}
+
+/// Determines the context which groups an ActivityStreams object into a conversation
+///
+/// # Description
+///
+/// Replies inherit the context of the first object of their thread which is known to this
+/// instance, so that remote replies to local statuses stay in the conversation they were started
+/// in. Otherwise an object is grouped by its `context` (Pleroma) or `conversation` (Mastodon)
+/// property, by the object it's replying to, or starts a new context identified by its own id.
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection      | Reference to a database connection
+/// * `object`        - &serde_json::Value | An ActivityStreams object serialized in JSON
+pub fn context_for_object(db_connection: &PgConnection, object: &serde_json::Value) -> String {
+    let mut object = object.clone();
+
+    // Bounded, as remote objects could reply to each other in circles
+    for _ in 0..MAX_THREAD_DEPTH {
+        match object["inReplyTo"]
+            .as_str()
+            .map(|parent_id| get_ap_object_by_id(db_connection, parent_id))
+        {
+            Some(Ok(parent)) => object = parent.data["object"].clone(),
+            _ => break,
+        }
+    }
+
+    if let Some(context) = object["context"].as_str() {
+        return context.to_string();
+    }
+
+    if let Some(context) = object["conversation"].as_str() {
+        return context.to_string();
+    }
+
+    match object["inReplyTo"].as_str() {
+        Some(parent_id) => parent_id.to_string(),
+        None => object["id"].as_str().unwrap_or_default().to_string(),
+    }
+}
+
+/// Adds a direct `Create` activity to its conversation and marks the conversation as unread for
+/// every local participant except the author
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `activity`      -     &Activity | An internal activity which has already been stored
+pub fn handle_activity(db_connection: &PgConnection, activity: &Activity) {
+    if activity.data["type"].as_str() != Some("Create")
+        || !is_direct(
+            &activity.data,
+            &followers_uri_of(db_connection, &activity.data),
+        )
+    {
+        return;
+    }
+
+    let context = context_for_object(db_connection, &activity.data["object"]);
+    let conversation = match get_conversation_by_context(db_connection, &context) {
+        Ok(conversation) => {
+            diesel::update(conversations::table.filter(conversations::id.eq(conversation.id)))
+                .set(conversations::last_activity_id.eq(activity.id))
+                .execute(db_connection)
+                .expect("Error updating conversation");
+            conversation
+        }
+        Err(_) => diesel::insert_into(conversations::table)
+            .values(InsertConversation {
+                context: &context,
+                last_activity_id: activity.id,
+            })
+            .get_result::<Conversation>(db_connection)
+            .expect("Error creating conversation"),
+    };
+
+    let mut participants: Vec<String> = vec![activity.actor.clone()];
+    for audience in &[&activity.data["to"], &activity.data["cc"]] {
+        match audience.as_array() {
+            Some(receipients) => {
+                for receipient in receipients {
+                    match receipient.as_str() {
+                        Some(uri) => participants.push(uri.to_string()),
+                        None => (),
+                    }
+                }
+            }
+            None => (),
+        }
+    }
+    participants.sort();
+    participants.dedup();
+
+    for uri in participants {
+        match get_actor_by_uri(db_connection, &uri) {
+            Ok(participant) => {
+                if participant.local {
+                    add_participant(
+                        db_connection,
+                        conversation.id,
+                        participant.id,
+                        participant.actor_uri != activity.actor,
+                    );
+                }
+            }
+            Err(_) => (),
+        }
+    }
+}
+
+pub fn get_conversation_by_context(
+    db_connection: &PgConnection,
+    context: &str,
+) -> Result<Conversation, diesel::result::Error> {
+    conversations::table
+        .filter(conversations::context.eq(context))
+        .first::<Conversation>(db_connection)
+}
+
+pub fn get_conversation_for_actor(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    conversation_id: i64,
+) -> Result<ParticipantConversation, diesel::result::Error> {
+    match conversations::table
+        .inner_join(conversation_participants::table)
+        .filter(conversations::id.eq(conversation_id))
+        .filter(conversation_participants::actor_id.eq(actor.id))
+        .select((
+            conversations::id,
+            conversations::last_activity_id,
+            conversation_participants::unread,
+        ))
+        .first::<(i64, i64, bool)>(db_connection)
+    {
+        Ok(conversation) => Ok(serialize_participant_conversation(conversation)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns the conversations of an actor, ordered by their most recent activity
+pub fn conversations_for_actor(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<ParticipantConversation>, diesel::result::Error> {
+    let mut query = conversations::table
+        .inner_join(conversation_participants::table)
+        .filter(conversation_participants::actor_id.eq(actor.id))
+        .select((
+            conversations::id,
+            conversations::last_activity_id,
+            conversation_participants::unread,
+        ))
+        .into_boxed();
+
+    query = if max_id.is_some() {
+        query
+            .filter(conversations::last_activity_id.lt(max_id.unwrap()))
+            .order(conversations::last_activity_id.desc())
+    } else if since_id.is_some() {
+        query
+            .filter(conversations::last_activity_id.gt(since_id.unwrap()))
+            .order(conversations::last_activity_id.desc())
+    } else if min_id.is_some() {
+        query
+            .filter(conversations::last_activity_id.gt(min_id.unwrap()))
+            .order(conversations::last_activity_id.asc())
+    } else {
+        query.order(conversations::last_activity_id.desc())
+    };
+
+    match query
+        .limit(limit.unwrap_or_else(|| 20))
+        .load::<(i64, i64, bool)>(db_connection)
+    {
+        Ok(conversation_vec) => Ok(conversation_vec
+            .into_iter()
+            .map(serialize_participant_conversation)
+            .collect()),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn mark_as_read(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    conversation_id: i64,
+) -> Result<usize, diesel::result::Error> {
+    diesel::update(
+        conversation_participants::table
+            .filter(conversation_participants::conversation_id.eq(conversation_id))
+            .filter(conversation_participants::actor_id.eq(actor.id)),
+    )
+    .set(conversation_participants::unread.eq(false))
+    .execute(db_connection)
+}
+
+fn add_participant(
+    db_connection: &PgConnection,
+    conversation_id: i64,
+    actor_id: i64,
+    unread: bool,
+) {
+    let existing_participant = diesel::update(
+        conversation_participants::table
+            .filter(conversation_participants::conversation_id.eq(conversation_id))
+            .filter(conversation_participants::actor_id.eq(actor_id)),
+    )
+    .set(conversation_participants::unread.eq(unread))
+    .execute(db_connection)
+    .unwrap_or_else(|_| 0);
+
+    if existing_participant == 0 {
+        diesel::insert_into(conversation_participants::table)
+            .values(InsertConversationParticipant {
+                conversation_id: conversation_id,
+                actor_id: actor_id,
+                unread: unread,
+            })
+            .execute(db_connection)
+            .expect("Error adding conversation participant");
+    }
+}
+
+fn serialize_participant_conversation(row: (i64, i64, bool)) -> ParticipantConversation {
+    ParticipantConversation {
+        id: row.0,
+        last_activity_id: row.1,
+        unread: row.2,
+    }
+}
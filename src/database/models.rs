@@ -1,7 +1,21 @@
 use chrono::NaiveDateTime;
 use database::schema::activities;
 use database::schema::actors;
+use database::schema::bookmarks;
+use database::schema::conversation_participants;
+use database::schema::conversations;
+use database::schema::emojis;
+use database::schema::filter_keywords;
+use database::schema::filter_statuses;
+use database::schema::filters;
+use database::schema::follow_requests;
+use database::schema::list_members;
+use database::schema::lists;
+use database::schema::markers;
 use database::schema::notifications;
+use database::schema::pins;
+use database::schema::poll_votes;
+use database::schema::relays;
 
 #[derive(Queryable, PartialEq, QueryableByName, Clone)]
 #[table_name = "activities"]
@@ -26,6 +40,91 @@ pub struct InsertActivity<'a> {
     pub actor_uri: &'a String,
 }
 
+#[derive(Insertable)]
+#[table_name = "bookmarks"]
+pub struct InsertBookmark {
+    pub actor_id: i64,
+    pub activity_id: i64,
+}
+
+#[derive(Insertable)]
+#[table_name = "conversations"]
+pub struct InsertConversation<'a> {
+    pub context: &'a String,
+    pub last_activity_id: i64,
+}
+
+#[derive(Insertable)]
+#[table_name = "conversation_participants"]
+pub struct InsertConversationParticipant {
+    pub conversation_id: i64,
+    pub actor_id: i64,
+    pub unread: bool,
+}
+
+#[derive(Insertable)]
+#[table_name = "emojis"]
+pub struct InsertEmoji<'a> {
+    pub shortcode: &'a String,
+    pub file: &'a String,
+    pub category: Option<&'a String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "filters"]
+pub struct InsertFilter<'a> {
+    pub actor_id: i64,
+    pub title: &'a String,
+    pub context: &'a Vec<String>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub irreversible: bool,
+}
+
+#[derive(Insertable)]
+#[table_name = "filter_keywords"]
+pub struct InsertFilterKeyword<'a> {
+    pub filter_id: i64,
+    pub keyword: &'a String,
+    pub whole_word: bool,
+}
+
+#[derive(Insertable)]
+#[table_name = "filter_statuses"]
+pub struct InsertFilterStatus {
+    pub filter_id: i64,
+    pub activity_id: i64,
+}
+
+#[derive(Insertable)]
+#[table_name = "follow_requests"]
+pub struct InsertFollowRequest {
+    pub actor_id: i64,
+    pub follower_id: i64,
+    pub activity_id: i64,
+}
+
+#[derive(Insertable)]
+#[table_name = "lists"]
+pub struct InsertList<'a> {
+    pub actor_id: i64,
+    pub title: &'a String,
+}
+
+#[derive(Insertable)]
+#[table_name = "list_members"]
+pub struct InsertListMember {
+    pub list_id: i64,
+    pub actor_id: i64,
+}
+
+#[derive(Insertable)]
+#[table_name = "markers"]
+pub struct InsertMarker<'a> {
+    pub actor_id: i64,
+    pub timeline: &'a str,
+    pub last_read_id: i64,
+}
+
 #[derive(Insertable)]
 #[table_name = "notifications"]
 pub struct InsertNotification {
@@ -33,6 +132,29 @@ pub struct InsertNotification {
     pub actor_id: i64,
     pub created: NaiveDateTime,
     pub modified: NaiveDateTime,
+    pub notification_type: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "pins"]
+pub struct InsertPin {
+    pub actor_id: i64,
+    pub activity_id: i64,
+}
+
+#[derive(Insertable)]
+#[table_name = "poll_votes"]
+pub struct InsertPollVote<'a> {
+    pub activity_id: i64,
+    pub actor_id: i64,
+    pub choice: &'a String,
+}
+
+#[derive(Insertable)]
+#[table_name = "relays"]
+pub struct InsertRelay<'a> {
+    pub actor_uri: &'a String,
+    pub follow_activity_id: &'a String,
 }
 
 #[derive(Queryable, PartialEq, QueryableByName, Clone)]
@@ -52,6 +174,15 @@ pub struct QueryActor {
     pub modified: NaiveDateTime,
     pub local: bool,
     pub followers: serde_json::Value,
+    pub admin: bool,
+    pub header: Option<String>,
+    pub locked: bool,
+    pub bot: bool,
+    pub discoverable: bool,
+    pub fields: serde_json::Value,
+    pub also_known_as: serde_json::Value,
+    pub moved_to: Option<String>,
+    pub followers_uri: Option<String>,
 }
 
 #[derive(Queryable, Debug)]
@@ -76,6 +207,8 @@ pub struct QueryOAuthAuthorization {
     pub created: NaiveDateTime,
     pub modified: NaiveDateTime,
     pub valid_until: NaiveDateTime,
+    pub scopes: String,
+    pub code_challenge: Option<String>,
 }
 
 #[derive(Queryable, Debug)]
@@ -88,4 +221,6 @@ pub struct QueryOauthToken {
     pub created: NaiveDateTime,
     pub modified: NaiveDateTime,
     pub valid_until: NaiveDateTime,
+    pub scopes: String,
+    pub last_used: Option<NaiveDateTime>,
 }
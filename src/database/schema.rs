@@ -24,6 +24,178 @@ table! {
         modified -> Timestamp,
         local -> Bool,
         followers -> Jsonb,
+        admin -> Bool,
+        header -> Nullable<Varchar>,
+        locked -> Bool,
+        bot -> Bool,
+        discoverable -> Bool,
+        fields -> Jsonb,
+        also_known_as -> Jsonb,
+        moved_to -> Nullable<Varchar>,
+        followers_uri -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    bookmarks (id) {
+        id -> Int8,
+        actor_id -> Int8,
+        activity_id -> Int8,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    conversation_participants (id) {
+        id -> Int8,
+        conversation_id -> Int8,
+        actor_id -> Int8,
+        unread -> Bool,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    conversations (id) {
+        id -> Int8,
+        context -> Varchar,
+        last_activity_id -> Int8,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    email_preferences (id) {
+        id -> Int8,
+        actor_id -> Int8,
+        mentions -> Bool,
+        follows -> Bool,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    email_tokens (id) {
+        id -> Int8,
+        actor_id -> Int8,
+        purpose -> Varchar,
+        token -> Varchar,
+        valid_until -> Timestamp,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    emojis (id) {
+        id -> Int8,
+        shortcode -> Varchar,
+        file -> Varchar,
+        category -> Nullable<Varchar>,
+        visible_in_picker -> Bool,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    ended_polls (activity_id) {
+        activity_id -> Int8,
+        created -> Timestamp,
+    }
+}
+
+table! {
+    filter_keywords (id) {
+        id -> Int8,
+        filter_id -> Int8,
+        keyword -> Varchar,
+        whole_word -> Bool,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    filter_statuses (id) {
+        id -> Int8,
+        filter_id -> Int8,
+        activity_id -> Int8,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    filters (id) {
+        id -> Int8,
+        actor_id -> Int8,
+        title -> Varchar,
+        context -> Array<Varchar>,
+        expires_at -> Nullable<Timestamp>,
+        irreversible -> Bool,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    follow_requests (id) {
+        id -> Int8,
+        actor_id -> Int8,
+        follower_id -> Int8,
+        activity_id -> Int8,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    invites (id) {
+        id -> Int8,
+        actor_id -> Int8,
+        code -> Varchar,
+        max_uses -> Nullable<Int4>,
+        uses -> Int4,
+        valid_until -> Nullable<Timestamp>,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    list_members (id) {
+        id -> Int8,
+        list_id -> Int8,
+        actor_id -> Int8,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    lists (id) {
+        id -> Int8,
+        actor_id -> Int8,
+        title -> Varchar,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    markers (id) {
+        id -> Int8,
+        actor_id -> Int8,
+        timeline -> Varchar,
+        last_read_id -> Int8,
+        version -> Int4,
+        created -> Timestamp,
+        modified -> Timestamp,
     }
 }
 
@@ -34,6 +206,7 @@ table! {
         actor_id -> Int8,
         created -> Timestamp,
         modified -> Timestamp,
+        notification_type -> Varchar,
     }
 }
 
@@ -60,6 +233,8 @@ table! {
         created -> Timestamp,
         modified -> Timestamp,
         valid_until -> Timestamp,
+        scopes -> Varchar,
+        code_challenge -> Nullable<Varchar>,
     }
 }
 
@@ -73,17 +248,129 @@ table! {
         created -> Timestamp,
         modified -> Timestamp,
         valid_until -> Timestamp,
+        scopes -> Varchar,
+        last_used -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    pending_registrations (id) {
+        id -> Int8,
+        actor_id -> Int8,
+        reason -> Text,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    pins (id) {
+        id -> Int8,
+        actor_id -> Int8,
+        activity_id -> Int8,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    poll_refreshes (activity_id) {
+        activity_id -> Int8,
+        refreshed -> Timestamp,
+    }
+}
+
+table! {
+    poll_votes (id) {
+        id -> Int8,
+        activity_id -> Int8,
+        actor_id -> Int8,
+        choice -> Varchar,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    relays (id) {
+        id -> Int8,
+        actor_uri -> Varchar,
+        follow_activity_id -> Varchar,
+        created -> Timestamp,
+        modified -> Timestamp,
+    }
+}
+
+table! {
+    two_factor_credentials (id) {
+        id -> Int8,
+        actor_id -> Int8,
+        secret -> Varchar,
+        enabled -> Bool,
+        recovery_codes -> Jsonb,
+        last_step -> Int8,
+        created -> Timestamp,
+        modified -> Timestamp,
+        failed_attempts -> Int4,
+        locked_until -> Nullable<Timestamp>,
     }
 }
 
+joinable!(bookmarks -> activities (activity_id));
+joinable!(bookmarks -> actors (actor_id));
+joinable!(conversation_participants -> actors (actor_id));
+joinable!(conversation_participants -> conversations (conversation_id));
+joinable!(conversations -> activities (last_activity_id));
+joinable!(email_preferences -> actors (actor_id));
+joinable!(email_tokens -> actors (actor_id));
+joinable!(ended_polls -> activities (activity_id));
+joinable!(filter_keywords -> filters (filter_id));
+joinable!(filter_statuses -> activities (activity_id));
+joinable!(filter_statuses -> filters (filter_id));
+joinable!(filters -> actors (actor_id));
+joinable!(follow_requests -> activities (activity_id));
+joinable!(follow_requests -> actors (actor_id));
+joinable!(invites -> actors (actor_id));
+joinable!(list_members -> actors (actor_id));
+joinable!(list_members -> lists (list_id));
+joinable!(lists -> actors (actor_id));
+joinable!(markers -> actors (actor_id));
 joinable!(notifications -> activities (activity_id));
 joinable!(notifications -> actors (actor_id));
+joinable!(pending_registrations -> actors (actor_id));
+joinable!(pins -> activities (activity_id));
+joinable!(pins -> actors (actor_id));
+joinable!(poll_refreshes -> activities (activity_id));
+joinable!(poll_votes -> activities (activity_id));
+joinable!(poll_votes -> actors (actor_id));
+joinable!(two_factor_credentials -> actors (actor_id));
 
 allow_tables_to_appear_in_same_query!(
     activities,
     actors,
+    bookmarks,
+    conversation_participants,
+    conversations,
+    email_preferences,
+    email_tokens,
+    emojis,
+    ended_polls,
+    filter_keywords,
+    filter_statuses,
+    filters,
+    follow_requests,
+    invites,
+    list_members,
+    lists,
+    markers,
     notifications,
     oauth_applications,
     oauth_authorizations,
     oauth_tokens,
+    pending_registrations,
+    pins,
+    poll_refreshes,
+    poll_votes,
+    relays,
+    two_factor_credentials,
 );
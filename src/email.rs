@@ -0,0 +1,274 @@
+//! E-mail addresses of local actors
+//!
+//! If `mail.confirm_registrations` is enabled, new actors have to confirm their address before
+//! they can log in. Actors who forgot their password can reset it with a link sent to their
+//! address, and may opt in to e-mails about mentions and follows.
+//!
+
+use actor::{self, get_local_actor_by_email, get_local_actor_by_preferred_username, Actor};
+use chrono::{Duration, NaiveDateTime, Utc};
+use database::schema::email_preferences;
+use database::schema::email_tokens;
+use diesel::pg::PgConnection;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use env;
+use mailer;
+use oauth;
+use openssl::bn::BigNum;
+use openssl::bn::MsbOption;
+use serde_json;
+
+const CONFIRMATION: &str = "confirmation";
+const PASSWORD_RESET: &str = "password_reset";
+
+// Confirmation links are valid longer, as nobody is waiting for them
+const CONFIRMATION_HOURS: i64 = 168;
+const PASSWORD_RESET_HOURS: i64 = 2;
+
+pub const MIN_PASSWORD_CHARS: usize = 8;
+
+#[derive(Queryable, Clone)]
+pub struct EmailToken {
+    pub id: i64,
+    pub actor_id: i64,
+    pub purpose: String,
+    pub token: String,
+    pub valid_until: NaiveDateTime,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+#[derive(Queryable, Clone)]
+pub struct EmailPreferences {
+    pub id: i64,
+    pub actor_id: i64,
+    pub mentions: bool,
+    pub follows: bool,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+/// Determines whether new actors have to confirm their e-mail address
+pub fn confirmation_required() -> bool {
+    mailer::is_enabled() && env::get_value(String::from("mail.confirm_registrations")) == "true"
+}
+
+/// Determines whether a local actor confirmed their e-mail address
+///
+/// # Description
+///
+/// Actors are considered confirmed unless a confirmation is outstanding, which includes every
+/// actor registered before confirmations were required.
+pub fn is_confirmed(db_connection: &PgConnection, username: &str) -> bool {
+    match get_local_actor_by_preferred_username(db_connection, username) {
+        Ok(actor) => email_tokens::table
+            .filter(email_tokens::actor_id.eq(actor.id))
+            .filter(email_tokens::purpose.eq(CONFIRMATION))
+            .first::<EmailToken>(db_connection)
+            .is_err(),
+        Err(_) => true,
+    }
+}
+
+/// Sends a link to a new local actor, which confirms their e-mail address
+pub fn request_confirmation(db_connection: &PgConnection, actor: &Actor) {
+    if let Some(address) = &actor.email {
+        let confirmation_token =
+            create_token(db_connection, actor, CONFIRMATION, CONFIRMATION_HOURS);
+
+        mailer::send(
+            "confirmation",
+            address,
+            "Confirm your e-mail address",
+            serde_json::json!({
+                "username": actor.preferred_username,
+                "link": format!("{}/auth/confirmation?token={}", mailer::base_url(), confirmation_token)
+            }),
+        );
+    }
+}
+
+/// Confirms the e-mail address of a local actor
+///
+/// # Description
+///
+/// If the link has expired already, a new one is sent to the actor.
+pub fn confirm(db_connection: &PgConnection, confirmation_token: &str) -> Result<(), &'static str> {
+    let email_token = get_token(db_connection, CONFIRMATION, confirmation_token)?;
+    delete_tokens(db_connection, email_token.actor_id, CONFIRMATION);
+
+    if email_token.valid_until < Utc::now().naive_utc() {
+        if let Ok(actor) = actor::get_actor_by_id(db_connection, &email_token.actor_id) {
+            request_confirmation(db_connection, &actor);
+        }
+        return Err("This link has expired, a new one was sent to your e-mail address");
+    }
+    Ok(())
+}
+
+/// Sends a link for resetting the password to a local actor
+///
+/// # Description
+///
+/// Whether an actor with this address exists isn't disclosed, so nothing happens if there's none.
+pub fn request_password_reset(db_connection: &PgConnection, address: &str) {
+    if let Ok(actor) = get_local_actor_by_email(db_connection, address) {
+        delete_tokens(db_connection, actor.id, PASSWORD_RESET);
+        let reset_token = create_token(db_connection, &actor, PASSWORD_RESET, PASSWORD_RESET_HOURS);
+
+        mailer::send(
+            "password_reset",
+            address,
+            "Reset your password",
+            serde_json::json!({
+                "username": actor.preferred_username,
+                "link": format!("{}/auth/password/edit?token={}", mailer::base_url(), reset_token),
+                "valid_hours": PASSWORD_RESET_HOURS
+            }),
+        );
+    }
+}
+
+/// Sets a new password with a password reset link
+///
+/// # Description
+///
+/// Every token of the actor is revoked afterwards, as the old password might've been compromised.
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `reset_token`   -          &str | The token of the password reset link
+/// * `new_password`  -          &str | The new password in plain text
+///
+pub fn reset_password(
+    db_connection: &PgConnection,
+    reset_token: &str,
+    new_password: &str,
+) -> Result<(), &'static str> {
+    let email_token = get_token(db_connection, PASSWORD_RESET, reset_token)?;
+
+    if email_token.valid_until < Utc::now().naive_utc() {
+        return Err("This link has expired");
+    }
+
+    if new_password.chars().count() < MIN_PASSWORD_CHARS {
+        return Err("The password is too short");
+    }
+
+    match actor::get_actor_by_id(db_connection, &email_token.actor_id) {
+        Ok(actor) => {
+            actor::update_password(db_connection, &actor, new_password);
+            delete_tokens(db_connection, actor.id, PASSWORD_RESET);
+            oauth::token::revoke_actor(db_connection, &actor.preferred_username)
+                .expect("Error deleting oauth tokens");
+            Ok(())
+        }
+        Err(_) => Err("Account not found"),
+    }
+}
+
+/// Returns whether a local actor receives e-mails about mentions and follows
+pub fn preferences(db_connection: &PgConnection, actor: &Actor) -> (bool, bool) {
+    match email_preferences::table
+        .filter(email_preferences::actor_id.eq(actor.id))
+        .first::<EmailPreferences>(db_connection)
+    {
+        Ok(email_preference) => (email_preference.mentions, email_preference.follows),
+        Err(_) => (false, false),
+    }
+}
+
+pub fn set_preferences(db_connection: &PgConnection, actor: &Actor, mentions: bool, follows: bool) {
+    diesel::insert_into(email_preferences::table)
+        .values((
+            email_preferences::actor_id.eq(actor.id),
+            email_preferences::mentions.eq(mentions),
+            email_preferences::follows.eq(follows),
+        ))
+        .on_conflict(email_preferences::actor_id)
+        .do_update()
+        .set((
+            email_preferences::mentions.eq(mentions),
+            email_preferences::follows.eq(follows),
+        ))
+        .execute(db_connection)
+        .expect("Error updating e-mail preferences");
+}
+
+/// Sends an e-mail about a notification, if the local actor opted in to it
+///
+/// # Parameters
+///
+/// * `db_connection`     -      &PgConnection | Reference to a database connection
+/// * `actor`             -             &Actor | The local actor who is notified
+/// * `notification_type` -               &str | Either `mention` or `follow`
+/// * `context`           - serde_json::Value | The context of the `mail/<notification_type>`
+///                                             template
+///
+pub fn notify(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    notification_type: &str,
+    mut context: serde_json::Value,
+) {
+    let (mentions, follows) = preferences(db_connection, actor);
+    let subject = match notification_type {
+        "mention" if mentions => "You were mentioned",
+        "follow" if follows => "You have a new follower",
+        _ => return,
+    };
+
+    if let Some(address) = &actor.email {
+        if is_confirmed(db_connection, &actor.preferred_username) {
+            context["username"] = serde_json::json!(actor.preferred_username);
+            mailer::send(notification_type, address, subject, context);
+        }
+    }
+}
+
+fn create_token(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    purpose: &str,
+    valid_hours: i64,
+) -> String {
+    let mut token_num: BigNum = BigNum::new().unwrap();
+    token_num
+        .rand(256, MsbOption::MAYBE_ZERO, true)
+        .expect("Error generating e-mail token");
+    let new_token = token_num.to_hex_str().unwrap().to_string();
+
+    diesel::insert_into(email_tokens::table)
+        .values((
+            email_tokens::actor_id.eq(actor.id),
+            email_tokens::purpose.eq(purpose),
+            email_tokens::token.eq(&new_token),
+            email_tokens::valid_until.eq(Utc::now().naive_utc() + Duration::hours(valid_hours)),
+        ))
+        .execute(db_connection)
+        .expect("Error creating e-mail token");
+    new_token
+}
+
+fn delete_tokens(db_connection: &PgConnection, actor_id: i64, purpose: &str) {
+    diesel::delete(
+        email_tokens::table
+            .filter(email_tokens::actor_id.eq(actor_id))
+            .filter(email_tokens::purpose.eq(purpose)),
+    )
+    .execute(db_connection)
+    .expect("Error deleting e-mail tokens");
+}
+
+fn get_token(
+    db_connection: &PgConnection,
+    purpose: &str,
+    value: &str,
+) -> Result<EmailToken, &'static str> {
+    email_tokens::table
+        .filter(email_tokens::purpose.eq(purpose))
+        .filter(email_tokens::token.eq(value))
+        .first::<EmailToken>(db_connection)
+        .map_err(|_| "This link is invalid")
+}
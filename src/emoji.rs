@@ -0,0 +1,229 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use database::models::InsertEmoji;
+use database::schema::emojis;
+use diesel::pg::PgConnection;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use env;
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+// Emoji files are served as static files, see `rocket_app` in lib.rs
+pub const EMOJI_DIRECTORY: &str = "static/emoji";
+
+// Maximum size of an emoji image in bytes
+pub const MAX_IMAGE_SIZE: u64 = 524288;
+
+#[derive(Queryable, PartialEq, Clone)]
+pub struct Emoji {
+    pub id: i64,
+    pub shortcode: String,
+    pub file: String,
+    pub category: Option<String>,
+    pub visible_in_picker: bool,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+impl Emoji {
+    pub fn url(&self) -> String {
+        format!(
+            "{}://{}/static/emoji/{}",
+            env::get_value(String::from("endpoint.base_scheme")),
+            env::get_value(String::from("endpoint.base_domain")),
+            self.file
+        )
+    }
+
+    /// Returns the emoji as an ActivityStreams `Emoji` tag
+    pub fn to_tag(&self) -> serde_json::Value {
+        let media_type = match Path::new(&self.file)
+            .extension()
+            .and_then(|extension| extension.to_str())
+        {
+            Some("gif") => "image/gif",
+            Some("jpg") => "image/jpeg",
+            Some("webp") => "image/webp",
+            _ => "image/png",
+        };
+
+        serde_json::json!({
+            "id": self.url(),
+            "type": "Emoji",
+            "name": format!(":{}:", self.shortcode),
+            "updated": DateTime::<Utc>::from_utc(self.modified, Utc).to_rfc3339(),
+            "icon": {
+                "type": "Image",
+                "mediaType": media_type,
+                "url": self.url()
+            }
+        })
+    }
+}
+
+/// Adds a new emoji to the instance's registry and stores its image, which may be at most
+/// `MAX_IMAGE_SIZE` bytes large
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection   | Reference to a database connection
+/// * `shortcode`     - &str            | The shortcode of the emoji, without colons
+/// * `extension`     - &str            | File extension of the image, one of png, gif, jpg or webp
+/// * `image`         - &[u8]           | The image itself
+/// * `category`      - Option<String>  | An optional category the emoji is listed under
+pub fn add(
+    db_connection: &PgConnection,
+    shortcode: &str,
+    extension: &str,
+    image: &[u8],
+    category: Option<String>,
+) -> Result<Emoji, &'static str> {
+    if !valid_shortcode(shortcode) {
+        return Err("Shortcodes may only contain letters, numbers and underscores");
+    }
+
+    if !["png", "gif", "jpg", "webp"].contains(&extension) {
+        return Err("Unsupported image format");
+    }
+
+    if image.len() as u64 > MAX_IMAGE_SIZE {
+        return Err("Image is larger than 512KB");
+    }
+
+    if get_emoji_by_shortcode(db_connection, shortcode).is_ok() {
+        return Err("An emoji with this shortcode already exists");
+    }
+
+    let file = format!("{}.{}", shortcode, extension);
+    let new_emoji = match diesel::insert_into(emojis::table)
+        .values(InsertEmoji {
+            shortcode: &shortcode.to_string(),
+            file: &file,
+            category: category.as_ref(),
+        })
+        .get_result::<Emoji>(db_connection)
+    {
+        Ok(new_emoji) => new_emoji,
+        Err(_) => return Err("Unable to store emoji"),
+    };
+
+    // The image is only stored once the shortcode is taken, so that no files are left behind by
+    // emojis which couldn't be added
+    if fs::create_dir_all(EMOJI_DIRECTORY).is_err()
+        || fs::write(Path::new(EMOJI_DIRECTORY).join(&file), image).is_err()
+    {
+        diesel::delete(emojis::table.filter(emojis::id.eq(new_emoji.id)))
+            .execute(db_connection)
+            .expect("Could not delete emoji");
+        return Err("Unable to store emoji");
+    }
+
+    Ok(new_emoji)
+}
+
+/// Removes an emoji from the instance's registry and deletes its image
+pub fn remove(db_connection: &PgConnection, shortcode: &str) -> Result<(), &'static str> {
+    match get_emoji_by_shortcode(db_connection, shortcode) {
+        Ok(emoji) => {
+            diesel::delete(emojis::table.filter(emojis::id.eq(emoji.id)))
+                .execute(db_connection)
+                .expect("Could not delete emoji");
+
+            if fs::remove_file(Path::new(EMOJI_DIRECTORY).join(&emoji.file)).is_err() {
+                eprintln!("Unable to delete the image of emoji :{}:", shortcode);
+            }
+            Ok(())
+        }
+        Err(_) => Err("Emoji not found"),
+    }
+}
+
+pub fn get_emoji_by_shortcode(
+    db_connection: &PgConnection,
+    shortcode: &str,
+) -> Result<Emoji, diesel::result::Error> {
+    emojis::table
+        .filter(emojis::shortcode.eq(shortcode))
+        .first::<Emoji>(db_connection)
+}
+
+pub fn get_emojis(db_connection: &PgConnection) -> Result<Vec<Emoji>, diesel::result::Error> {
+    emojis::table
+        .order(emojis::shortcode.asc())
+        .load::<Emoji>(db_connection)
+}
+
+/// Returns every `:shortcode:` used in a text, without colons and without duplicates
+///
+/// # Parameters
+///
+/// * `text` - &str | Any text which may contain shortcodes
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/emoji.rs`
+/// - parse_shortcodes()
+pub fn parse_shortcodes(text: &str) -> Vec<String> {
+    let shortcode_regex = Regex::new(r":([a-zA-Z0-9_]+):").unwrap();
+    let mut shortcodes: Vec<String> = Vec::new();
+
+    for shortcode in shortcode_regex.captures_iter(text) {
+        let shortcode = shortcode.get(1).unwrap().as_str().to_string();
+        if !shortcodes.contains(&shortcode) {
+            shortcodes.push(shortcode);
+        }
+    }
+    shortcodes
+}
+
+/// Returns the shortcodes and image URLs of all `Emoji` tags of an ActivityStreams object
+///
+/// # Parameters
+///
+/// * `tags` - &serde_json::Value | The `tag` property of an ActivityStreams object or actor
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/emoji.rs`
+/// - parse_tags()
+pub fn parse_tags(tags: &serde_json::Value) -> Vec<(String, String)> {
+    let mut emojis: Vec<(String, String)> = Vec::new();
+
+    match tags.as_array() {
+        Some(tag_vec) => {
+            for tag in tag_vec {
+                if tag["type"].as_str() == Some("Emoji") {
+                    let shortcode = tag["name"].as_str().unwrap_or_default().trim_matches(':');
+                    let url = match tag["icon"]["url"].as_str() {
+                        Some(url) => url,
+                        None => continue,
+                    };
+
+                    if valid_shortcode(shortcode) {
+                        emojis.push((shortcode.to_string(), url.to_string()));
+                    }
+                }
+            }
+        }
+        None => (),
+    }
+    emojis
+}
+
+/// Returns `Emoji` tags for every emoji of the instance's registry used in a text
+pub fn tags_for_text(db_connection: &PgConnection, text: &str) -> Vec<serde_json::Value> {
+    let mut tags: Vec<serde_json::Value> = Vec::new();
+
+    for shortcode in parse_shortcodes(text) {
+        match get_emoji_by_shortcode(db_connection, &shortcode) {
+            Ok(emoji) => tags.push(emoji.to_tag()),
+            Err(_) => (),
+        }
+    }
+    tags
+}
+
+fn valid_shortcode(shortcode: &str) -> bool {
+    let shortcode_regex = Regex::new(r"^[a-zA-Z0-9_]+$").unwrap();
+    shortcode_regex.is_match(shortcode)
+}
@@ -31,4 +31,26 @@ fn set_default_config_values(config: &mut config::Config) {
     // Serve nodeinfo by default, but provide admins with a way
     // to disable it in the config file.
     config.set_default("nodeinfo.enabled", true).unwrap();
+
+    // Falls back to `node.registrations_enabled` if it's empty, see `registration`
+    config.set_default("node.registrations_mode", "").unwrap();
+    config.set_default("node.reserved_usernames", "").unwrap();
+    config.set_default("captcha.provider", "none").unwrap();
+    config.set_default("captcha.question", "").unwrap();
+    config.set_default("captcha.answer", "").unwrap();
+
+    // E-mails aren't sent unless a backend is configured, see `mailer`
+    config.set_default("mail.backend", "none").unwrap();
+    config.set_default("mail.from", "").unwrap();
+    config
+        .set_default("mail.confirm_registrations", false)
+        .unwrap();
+    config.set_default("mail.smtp_host", "localhost").unwrap();
+    config.set_default("mail.smtp_port", 587).unwrap();
+    config
+        .set_default("mail.smtp_security", "starttls")
+        .unwrap();
+    config.set_default("mail.smtp_username", "").unwrap();
+    config.set_default("mail.smtp_password", "").unwrap();
+    config.set_default("mail.file_path", "mail").unwrap();
 }
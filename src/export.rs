@@ -0,0 +1,262 @@
+//! Exports and imports of account data
+//!
+//! CSV files use the same formats as Mastodon's exports, so that follows and lists can be taken
+//! from Kibou to Mastodon and vice versa.
+//!
+
+use activity::get_ap_activities_by_actor;
+use activitypub::activity::serialize_from_internal_activity;
+use activitypub::actor::serialize_from_internal_actor;
+use actor::{get_actor_followees, Actor};
+use chrono::Utc;
+use diesel::PgConnection;
+use env;
+use list;
+use media::MEDIA_DIRECTORY;
+use serde_json;
+use std::fs;
+use std::path::Path;
+
+/// Returns the accounts an actor follows, in the format of Mastodon's `following_accounts.csv`
+pub fn following_csv(db_connection: &PgConnection, actor: &Actor) -> String {
+    let mut csv = String::from("Account address,Show boosts\n");
+
+    for followee in get_actor_followees(db_connection, &actor.actor_uri).unwrap_or_default() {
+        csv.push_str(&format!("{},true\n", full_acct(followee)));
+    }
+    csv
+}
+
+/// Returns the lists of an actor, in the format of Mastodon's `lists.csv`
+pub fn lists_csv(db_connection: &PgConnection, actor: &Actor) -> String {
+    let mut csv = String::new();
+
+    for list in list::lists_for_actor(db_connection, actor).unwrap_or_default() {
+        for member in list::members(db_connection, &list) {
+            csv.push_str(&format!(
+                "{},{}\n",
+                csv_field(&list.title),
+                full_acct(member)
+            ));
+        }
+    }
+    csv
+}
+
+/// Returns a tar archive of an actor's profile, their statuses and the media they uploaded
+///
+/// # Description
+///
+/// The archive contains `actor.json` and `outbox.json` in their ActivityStreams form, media
+/// files are stored in the `media` directory of the archive.
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -        &Actor | The local actor whose data is exported
+pub fn archive(db_connection: &PgConnection, actor: &Actor) -> Vec<u8> {
+    let activities =
+        get_ap_activities_by_actor(db_connection, &actor.actor_uri).unwrap_or_default();
+
+    let mut media_urls: Vec<String> = vec![actor.icon.clone(), actor.header.clone()]
+        .into_iter()
+        .flatten()
+        .collect();
+    for activity in &activities {
+        if let Some(attachments) = activity.data["object"]["attachment"].as_array() {
+            for attachment in attachments {
+                if let Some(url) = attachment["url"].as_str() {
+                    media_urls.push(url.to_string());
+                }
+            }
+        }
+    }
+    media_urls.sort();
+    media_urls.dedup();
+
+    let ordered_items: Vec<serde_json::Value> = activities
+        .into_iter()
+        .map(|activity| serde_json::json!(serialize_from_internal_activity(activity)))
+        .collect();
+    let outbox = serde_json::json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "type": "OrderedCollection",
+        "id": format!("{}/outbox", actor.actor_uri),
+        "totalItems": ordered_items.len(),
+        "orderedItems": ordered_items
+    });
+
+    let mut files: Vec<(String, Vec<u8>)> = vec![
+        (
+            String::from("actor.json"),
+            serde_json::to_vec_pretty(&serialize_from_internal_actor(actor)).unwrap_or_default(),
+        ),
+        (
+            String::from("outbox.json"),
+            serde_json::to_vec_pretty(&outbox).unwrap_or_default(),
+        ),
+    ];
+
+    // Only media stored by this instance can be exported
+    let media_prefix = format!(
+        "{}://{}/static/media/",
+        env::get_value(String::from("endpoint.base_scheme")),
+        env::get_value(String::from("endpoint.base_domain"))
+    );
+    for url in media_urls {
+        if url.starts_with(&media_prefix) {
+            let file = &url[media_prefix.len()..];
+
+            if !file.contains('/') && !file.contains("..") {
+                if let Ok(data) = fs::read(Path::new(MEDIA_DIRECTORY).join(file)) {
+                    files.push((format!("media/{}", file), data));
+                }
+            }
+        }
+    }
+    tar(files)
+}
+
+/// Quotes a CSV field, if it contains characters which would otherwise split it
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/export.rs`
+/// - csv_field()
+pub fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Parses CSV into rows of fields, empty rows are skipped
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/export.rs`
+/// - parse_csv()
+pub fn parse_csv(input: &str) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut characters = input.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if quoted => {
+                // Quotes within quoted fields are escaped by doubling them
+                if characters.peek() == Some(&'"') {
+                    field.push('"');
+                    characters.next();
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::replace(&mut field, String::new())),
+            '\n' if !quoted => {
+                row.push(std::mem::replace(&mut field, String::new()));
+                rows.push(std::mem::replace(&mut row, Vec::new()));
+            }
+            '\r' if !quoted => (),
+            _ => field.push(character),
+        }
+    }
+    row.push(field);
+    rows.push(row);
+
+    rows.into_iter()
+        .filter(|row| row.iter().any(|field| !field.trim().is_empty()))
+        .collect()
+}
+
+/// Returns the accts of a CSV file of accounts, such as `following_accounts.csv`
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/export.rs`
+/// - parse_accounts()
+pub fn parse_accounts(input: &str) -> Vec<String> {
+    parse_csv(input)
+        .into_iter()
+        .filter_map(|row| row.into_iter().next())
+        .map(|acct| acct.trim().trim_start_matches('@').to_string())
+        // Mastodon's exports start with a header
+        .filter(|acct| !acct.is_empty() && acct != "Account address")
+        .collect()
+}
+
+/// Returns the list titles and accts of a `lists.csv` file
+pub fn parse_lists(input: &str) -> Vec<(String, String)> {
+    parse_csv(input)
+        .into_iter()
+        .filter(|row| row.len() >= 2)
+        .map(|row| {
+            (
+                row[0].trim().to_string(),
+                row[1].trim().trim_start_matches('@').to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Packs files into an uncompressed tar archive
+///
+/// # Parameters
+///
+/// * `files` - Vec<(String, Vec<u8>)> | Names and contents of the files, names may not be longer
+///                                      than 100 bytes
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/export.rs`
+/// - tar()
+pub fn tar(files: Vec<(String, Vec<u8>)>) -> Vec<u8> {
+    let modified = format!("{:011o}", Utc::now().timestamp());
+    let mut archive: Vec<u8> = Vec::new();
+
+    for (name, data) in files {
+        let mut header = [0u8; 512];
+        let name = name.as_bytes();
+        let name_length = name.len().min(100);
+
+        header[..name_length].copy_from_slice(&name[..name_length]);
+        header[100..107].copy_from_slice(b"0000644");
+        header[108..115].copy_from_slice(b"0000000");
+        header[116..123].copy_from_slice(b"0000000");
+        header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+        header[136..147].copy_from_slice(modified.as_bytes());
+        header[156] = b'0';
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+
+        // The checksum is calculated while its own field consists of spaces
+        header[148..156].copy_from_slice(b"        ");
+        let checksum: u32 = header.iter().map(|byte| *byte as u32).sum();
+        header[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+
+        archive.extend_from_slice(&header);
+        archive.extend_from_slice(&data);
+        archive.extend(vec![0u8; (512 - data.len() % 512) % 512]);
+    }
+
+    // An archive ends with two empty blocks
+    archive.extend(vec![0u8; 1024]);
+    archive
+}
+
+// Mastodon's exports contain the domain of local accounts as well
+fn full_acct(mut actor: Actor) -> String {
+    if actor.local {
+        format!(
+            "{}@{}",
+            actor.preferred_username,
+            env::get_value(String::from("endpoint.base_domain"))
+        )
+    } else {
+        actor.get_acct()
+    }
+}
@@ -0,0 +1,431 @@
+use actor::Actor;
+use chrono::{Duration, NaiveDateTime, Utc};
+use database::models::{InsertFilter, InsertFilterKeyword, InsertFilterStatus};
+use database::schema::filter_keywords;
+use database::schema::filter_statuses;
+use database::schema::filters;
+use diesel::pg::PgConnection;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use regex::Regex;
+
+// The contexts a filter can be applied in, as used by the Mastodon API
+pub const CONTEXTS: [&str; 5] = ["home", "notifications", "public", "thread", "account"];
+pub const MAX_KEYWORD_CHARS: usize = 100;
+// Filters can expire at most one year after they've been created or updated
+pub const MAX_EXPIRES_IN: i64 = 31_536_000;
+
+#[derive(Queryable, PartialEq, Clone)]
+pub struct Filter {
+    pub id: i64,
+    pub actor_id: i64,
+    pub title: String,
+    pub context: Vec<String>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub irreversible: bool,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+#[derive(Queryable, PartialEq, Clone)]
+pub struct FilterKeyword {
+    pub id: i64,
+    pub filter_id: i64,
+    pub keyword: String,
+    pub whole_word: bool,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+#[derive(Queryable, PartialEq, Clone)]
+pub struct FilterStatus {
+    pub id: i64,
+    pub filter_id: i64,
+    pub activity_id: i64,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+/// A filter which currently applies, together with everything it matches
+pub struct ActiveFilter {
+    pub filter: Filter,
+    pub keywords: Vec<FilterKeyword>,
+    pub statuses: Vec<FilterStatus>,
+}
+
+/// A filter which is about to be created or updated by a local actor
+pub struct NewFilter {
+    pub title: String,
+    pub context: Vec<String>,
+    pub expires_in: Option<i64>,
+    pub irreversible: bool,
+}
+
+impl ActiveFilter {
+    /// Returns the keywords of the filter found in a status' content
+    ///
+    /// # Parameters
+    ///
+    /// * `content` - &str | The HTML content and the content warning of a status
+    pub fn keyword_matches(&self, content: &str) -> Vec<String> {
+        let text = plain_text(content);
+
+        self.keywords
+            .iter()
+            .filter(|keyword| matches_keyword(&text, &keyword.keyword, keyword.whole_word))
+            .map(|keyword| keyword.keyword.clone())
+            .collect()
+    }
+
+    pub fn status_matches(&self, activity_id: i64) -> bool {
+        self.statuses
+            .iter()
+            .any(|status| status.activity_id == activity_id)
+    }
+}
+
+/// Determines whether a filter doesn't apply anymore
+pub fn is_expired(filter: &Filter) -> bool {
+    match filter.expires_at {
+        Some(expires_at) => expires_at < Utc::now().naive_utc(),
+        None => false,
+    }
+}
+
+/// Determines whether a text contains a keyword, ignoring its case
+///
+/// # Description
+///
+/// Whole word keywords only match if they aren't part of another word, e.g. `cat` would match
+/// "my cat" but not "concatenate".
+///
+/// # Parameters
+///
+/// * `text`       - &str | Any plain text
+/// * `keyword`    - &str | The keyword of a filter
+/// * `whole_word` - bool | Whether the keyword has to be a whole word
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/filter.rs`
+/// - matches_keyword()
+pub fn matches_keyword(text: &str, keyword: &str, whole_word: bool) -> bool {
+    let keyword = keyword.trim();
+    if keyword.is_empty() {
+        return false;
+    }
+
+    let is_word_char = |character: Option<char>| match character {
+        Some(character) => character.is_alphanumeric() || character == '_',
+        None => false,
+    };
+    let pattern = if whole_word {
+        format!(
+            "(?i){start}{keyword}{end}",
+            start = if is_word_char(keyword.chars().next()) {
+                r"\b"
+            } else {
+                ""
+            },
+            keyword = regex::escape(keyword),
+            end = if is_word_char(keyword.chars().last()) {
+                r"\b"
+            } else {
+                ""
+            }
+        )
+    } else {
+        format!("(?i){}", regex::escape(keyword))
+    };
+
+    match Regex::new(&pattern) {
+        Ok(keyword_regex) => keyword_regex.is_match(text),
+        Err(_) => false,
+    }
+}
+
+/// Returns the given contexts without duplicates, if all of them are known
+///
+/// # Parameters
+///
+/// * `context` - Vec<String> | The contexts a filter should be applied in
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/filter.rs`
+/// - validate_context()
+pub fn validate_context(context: Vec<String>) -> Result<Vec<String>, &'static str> {
+    let mut valid_context: Vec<String> = Vec::new();
+
+    for value in context {
+        if !CONTEXTS.contains(&value.as_str()) {
+            return Err("Unknown filter context");
+        }
+
+        if !valid_context.contains(&value) {
+            valid_context.push(value);
+        }
+    }
+
+    if valid_context.is_empty() {
+        Err("Filters need at least one context")
+    } else {
+        Ok(valid_context)
+    }
+}
+
+pub fn create(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    new_filter: NewFilter,
+) -> Result<Filter, &'static str> {
+    let title = validate_text(&new_filter.title)?;
+    let context = validate_context(new_filter.context)?;
+    let expires_at = expires_at(new_filter.expires_in)?;
+
+    match diesel::insert_into(filters::table)
+        .values(InsertFilter {
+            actor_id: actor.id,
+            title: &title,
+            context: &context,
+            expires_at: expires_at,
+            irreversible: new_filter.irreversible,
+        })
+        .get_result::<Filter>(db_connection)
+    {
+        Ok(filter) => Ok(filter),
+        Err(_) => Err("Unable to create filter"),
+    }
+}
+
+pub fn update(
+    db_connection: &PgConnection,
+    filter: &Filter,
+    new_filter: NewFilter,
+) -> Result<Filter, &'static str> {
+    let title = validate_text(&new_filter.title)?;
+    let context = validate_context(new_filter.context)?;
+    let expires_at = expires_at(new_filter.expires_in)?;
+
+    match diesel::update(filters::table.filter(filters::id.eq(filter.id)))
+        .set((
+            filters::title.eq(title),
+            filters::context.eq(context),
+            filters::expires_at.eq(expires_at),
+            filters::irreversible.eq(new_filter.irreversible),
+        ))
+        .get_result::<Filter>(db_connection)
+    {
+        Ok(filter) => Ok(filter),
+        Err(_) => Err("Unable to update filter"),
+    }
+}
+
+pub fn delete(db_connection: &PgConnection, filter: &Filter) {
+    diesel::delete(filters::table.filter(filters::id.eq(filter.id)))
+        .execute(db_connection)
+        .expect("Could not delete filter");
+}
+
+/// Returns a filter, but only if it's owned by the given actor
+pub fn get_filter_for_actor(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    id: i64,
+) -> Result<Filter, diesel::result::Error> {
+    filters::table
+        .filter(filters::id.eq(id))
+        .filter(filters::actor_id.eq(actor.id))
+        .first::<Filter>(db_connection)
+}
+
+pub fn filters_for_actor(
+    db_connection: &PgConnection,
+    actor: &Actor,
+) -> Result<Vec<Filter>, diesel::result::Error> {
+    filters::table
+        .filter(filters::actor_id.eq(actor.id))
+        .order(filters::id.asc())
+        .load::<Filter>(db_connection)
+}
+
+/// Returns all filters of an actor which apply in a certain context and haven't expired yet
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -       &Actor | The local actor the filters belong to
+/// * `context`       -         &str | One of `CONTEXTS`
+pub fn active_filters(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    context: &str,
+) -> Vec<ActiveFilter> {
+    filters_for_actor(db_connection, actor)
+        .unwrap_or_else(|_| Vec::new())
+        .into_iter()
+        .filter(|filter| filter.context.iter().any(|value| value == context) && !is_expired(filter))
+        .map(|filter| ActiveFilter {
+            keywords: keywords_for_filter(db_connection, &filter).unwrap_or_else(|_| Vec::new()),
+            statuses: statuses_for_filter(db_connection, &filter).unwrap_or_else(|_| Vec::new()),
+            filter: filter,
+        })
+        .collect()
+}
+
+pub fn add_keyword(
+    db_connection: &PgConnection,
+    filter: &Filter,
+    keyword: &str,
+    whole_word: bool,
+) -> Result<FilterKeyword, &'static str> {
+    let keyword = validate_text(keyword)?;
+
+    match diesel::insert_into(filter_keywords::table)
+        .values(InsertFilterKeyword {
+            filter_id: filter.id,
+            keyword: &keyword,
+            whole_word: whole_word,
+        })
+        .get_result::<FilterKeyword>(db_connection)
+    {
+        Ok(filter_keyword) => Ok(filter_keyword),
+        Err(_) => Err("Unable to add keyword"),
+    }
+}
+
+pub fn update_keyword(
+    db_connection: &PgConnection,
+    filter_keyword: &FilterKeyword,
+    keyword: &str,
+    whole_word: bool,
+) -> Result<FilterKeyword, &'static str> {
+    let keyword = validate_text(keyword)?;
+
+    match diesel::update(filter_keywords::table.filter(filter_keywords::id.eq(filter_keyword.id)))
+        .set((
+            filter_keywords::keyword.eq(keyword),
+            filter_keywords::whole_word.eq(whole_word),
+        ))
+        .get_result::<FilterKeyword>(db_connection)
+    {
+        Ok(filter_keyword) => Ok(filter_keyword),
+        Err(_) => Err("Unable to update keyword"),
+    }
+}
+
+pub fn remove_keyword(db_connection: &PgConnection, filter_keyword: &FilterKeyword) {
+    diesel::delete(filter_keywords::table.filter(filter_keywords::id.eq(filter_keyword.id)))
+        .execute(db_connection)
+        .expect("Could not delete filter keyword");
+}
+
+/// Returns a keyword, but only if its filter is owned by the given actor
+pub fn get_keyword_for_actor(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    id: i64,
+) -> Result<FilterKeyword, diesel::result::Error> {
+    filter_keywords::table
+        .inner_join(filters::table)
+        .filter(filter_keywords::id.eq(id))
+        .filter(filters::actor_id.eq(actor.id))
+        .select(filter_keywords::all_columns)
+        .first::<FilterKeyword>(db_connection)
+}
+
+pub fn keywords_for_filter(
+    db_connection: &PgConnection,
+    filter: &Filter,
+) -> Result<Vec<FilterKeyword>, diesel::result::Error> {
+    filter_keywords::table
+        .filter(filter_keywords::filter_id.eq(filter.id))
+        .order(filter_keywords::id.asc())
+        .load::<FilterKeyword>(db_connection)
+}
+
+pub fn add_status(
+    db_connection: &PgConnection,
+    filter: &Filter,
+    activity_id: i64,
+) -> Result<FilterStatus, &'static str> {
+    match diesel::insert_into(filter_statuses::table)
+        .values(InsertFilterStatus {
+            filter_id: filter.id,
+            activity_id: activity_id,
+        })
+        .get_result::<FilterStatus>(db_connection)
+    {
+        Ok(filter_status) => Ok(filter_status),
+        Err(_) => Err("Unable to add status to filter"),
+    }
+}
+
+pub fn remove_status(db_connection: &PgConnection, filter_status: &FilterStatus) {
+    diesel::delete(filter_statuses::table.filter(filter_statuses::id.eq(filter_status.id)))
+        .execute(db_connection)
+        .expect("Could not delete filter status");
+}
+
+/// Returns a filtered status, but only if its filter is owned by the given actor
+pub fn get_status_for_actor(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    id: i64,
+) -> Result<FilterStatus, diesel::result::Error> {
+    filter_statuses::table
+        .inner_join(filters::table)
+        .filter(filter_statuses::id.eq(id))
+        .filter(filters::actor_id.eq(actor.id))
+        .select(filter_statuses::all_columns)
+        .first::<FilterStatus>(db_connection)
+}
+
+pub fn statuses_for_filter(
+    db_connection: &PgConnection,
+    filter: &Filter,
+) -> Result<Vec<FilterStatus>, diesel::result::Error> {
+    filter_statuses::table
+        .filter(filter_statuses::filter_id.eq(filter.id))
+        .order(filter_statuses::id.asc())
+        .load::<FilterStatus>(db_connection)
+}
+
+/// Determines when a filter expires, filters without `expires_in` never expire
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/filter.rs`
+/// - expires_at()
+pub fn expires_at(expires_in: Option<i64>) -> Result<Option<NaiveDateTime>, &'static str> {
+    match expires_in {
+        Some(seconds) if seconds <= 0 || seconds > MAX_EXPIRES_IN => {
+            Err("Filters have to expire within a year")
+        }
+        Some(seconds) => match Utc::now()
+            .naive_utc()
+            .checked_add_signed(Duration::seconds(seconds))
+        {
+            Some(expires_at) => Ok(Some(expires_at)),
+            None => Err("Filters have to expire within a year"),
+        },
+        None => Ok(None),
+    }
+}
+
+// Keywords are matched against the text of a status, not against its markup
+fn plain_text(content: &str) -> String {
+    let tag_regex = Regex::new("<[^>]*>").unwrap();
+    tag_regex.replace_all(content, " ").to_string()
+}
+
+fn validate_text(text: &str) -> Result<String, &'static str> {
+    let text = text.trim();
+
+    if text.is_empty() {
+        Err("Filters and keywords can't be empty")
+    } else if text.chars().count() > MAX_KEYWORD_CHARS {
+        Err("Filters and keywords can't be longer than 100 characters")
+    } else {
+        Ok(text.to_string())
+    }
+}
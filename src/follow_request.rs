@@ -0,0 +1,123 @@
+use actor::Actor;
+use database::models::InsertFollowRequest;
+use database::schema::follow_requests;
+use diesel::pg::PgConnection;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use notification::{self, Notification};
+
+#[derive(Queryable, PartialEq, Clone)]
+pub struct FollowRequest {
+    pub id: i64,
+    pub actor_id: i64,
+    pub follower_id: i64,
+    pub activity_id: i64,
+    pub created: chrono::NaiveDateTime,
+    pub modified: chrono::NaiveDateTime,
+}
+
+/// Holds the follow of a locked account until the account authorizes or rejects it
+///
+/// # Description
+///
+/// The account is notified about new requests only, repeated `Follow` activities of the same
+/// follower are ignored while their first request is pending.
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -        &Actor | The locked local actor who's being followed
+/// * `follower`      -        &Actor | The actor who wants to follow
+/// * `activity_id`   -           i64 | The ID of the `Follow` activity
+pub fn request(db_connection: &PgConnection, actor: &Actor, follower: &Actor, activity_id: i64) {
+    let inserted = diesel::insert_into(follow_requests::table)
+        .values(InsertFollowRequest {
+            actor_id: actor.id,
+            follower_id: follower.id,
+            activity_id: activity_id,
+        })
+        .on_conflict_do_nothing()
+        .execute(db_connection)
+        .expect("Error adding follow request");
+
+    if inserted > 0 {
+        notification::insert(
+            db_connection,
+            Notification::new(activity_id, actor.id, "follow_request"),
+        );
+    }
+}
+
+/// Removes a pending follow request together with its notification and returns it
+pub fn remove(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    follower_id: i64,
+) -> Result<FollowRequest, diesel::result::Error> {
+    let follow_request = diesel::delete(
+        follow_requests::table
+            .filter(follow_requests::actor_id.eq(actor.id))
+            .filter(follow_requests::follower_id.eq(follower_id)),
+    )
+    .get_result::<FollowRequest>(db_connection)?;
+
+    notification::dismiss(db_connection, actor, follow_request.activity_id)?;
+    Ok(follow_request)
+}
+
+/// Determines whether an actor has requested to follow a locked actor
+pub fn is_requested(db_connection: &PgConnection, actor: &Actor, follower_id: i64) -> bool {
+    match follow_requests::table
+        .filter(follow_requests::actor_id.eq(actor.id))
+        .filter(follow_requests::follower_id.eq(follower_id))
+        .count()
+        .get_result::<i64>(db_connection)
+    {
+        Ok(count) => count > 0,
+        Err(_) => false,
+    }
+}
+
+/// Returns the ids of an actor's pending follow requests and their followers, starting with the
+/// latest request
+///
+/// # Parameters
+///
+/// * `db_connection` -  &PgConnection | Reference to a database connection
+/// * `actor`         -        &Actor | The locked local actor the requests belong to
+/// * `max_id`        -   Option<i64> | Only requests older than this request
+/// * `since_id`      -   Option<i64> | Only requests newer than this request
+/// * `min_id`        -   Option<i64> | The requests directly following this request
+/// * `limit`         -   Option<i64> | The maximum amount of requests, 40 by default
+pub fn requests_for_actor(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<(i64, i64)>, diesel::result::Error> {
+    let mut query = follow_requests::table
+        .filter(follow_requests::actor_id.eq(actor.id))
+        .select((follow_requests::id, follow_requests::follower_id))
+        .into_boxed();
+
+    query = if max_id.is_some() {
+        query
+            .filter(follow_requests::id.lt(max_id.unwrap()))
+            .order(follow_requests::id.desc())
+    } else if since_id.is_some() {
+        query
+            .filter(follow_requests::id.gt(since_id.unwrap()))
+            .order(follow_requests::id.desc())
+    } else if min_id.is_some() {
+        query
+            .filter(follow_requests::id.gt(min_id.unwrap()))
+            .order(follow_requests::id.asc())
+    } else {
+        query.order(follow_requests::id.desc())
+    };
+
+    query
+        .limit(limit.unwrap_or(40))
+        .load::<(i64, i64)>(db_connection)
+}
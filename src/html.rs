@@ -1,5 +1,47 @@
 use regex::Regex;
 
+pub fn escape(input: &str) -> String {
+    input
+        .replace("&", "&amp;")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+        .replace("\"", "&quot;")
+        .replace("'", "&#39;")
+}
+
+/// Wraps the http(s) URLs of an already escaped text in links
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/html.rs`
+/// - linkify()
+pub fn linkify(input: &str) -> String {
+    let url_regex: Regex = Regex::new(r"(^|\s)(https?://[^\s<]*[^\s<.,:;!?)])").unwrap();
+
+    url_regex
+        .replace_all(input, "$1<a href=\"$2\" rel=\"nofollow noopener\">$2</a>")
+        .to_string()
+}
+
+/// Reverts rendered text, such as a profile note, to the text it was written as
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/html.rs`
+/// - to_raw_text()
+pub fn to_raw_text(input: &str) -> String {
+    let tag_regex: Regex = Regex::new("<[^>]*>").unwrap();
+    let output = input.replace("<br>", "\n");
+
+    tag_regex
+        .replace_all(&output, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
 pub fn to_plain_text(input: &str) -> String {
     let output = str::replace(&input, "\n", "<br>");
 
@@ -7,92 +7,919 @@
 pub mod routes;
 
 use activity::{
-    get_activity_by_id, get_ap_activity_by_id, get_ap_object_by_id, type_exists_for_object_id,
+    delete_ap_activity_by_id, get_activity_by_id, get_ap_activity_by_id, get_ap_object_by_id,
+    type_exists_for_object_id, Activity,
 };
 use activitypub::activity::{serialize_from_internal_activity, Tag};
-use activitypub::actor::{add_follow, remove_follow};
+use activitypub::actor::{add_follow, refetch, remove_follow};
 use activitypub::controller as ap_controller;
-use actor::{get_actor_by_acct, get_actor_by_id, get_actor_by_uri, is_actor_followed_by, Actor};
+use actor::{
+    self, authorize, get_actor_by_acct, get_actor_by_id, get_actor_by_uri,
+    get_local_actor_by_preferred_username, is_actor_followed_by, is_admin, Actor,
+};
+use api_error::{ApiError, ApiResult};
+use chrono::{Duration, Utc};
+use conversation;
 use database;
 use database::PooledConnection;
 use diesel::PgConnection;
+use email;
+use emoji;
+use export;
+use follow_request;
 use html;
+use list;
 use mastodon_api;
+use oauth::authorized_application;
+use oauth::token::{revoke_application, verify_token};
+use poll::{self, NewPoll};
+use reaction;
 use regex::Regex;
-use rocket_contrib::json;
-use rocket_contrib::json::JsonValue;
+use registration;
+use relay;
+use rocket::data::{self, Data, FromDataSimple};
+use rocket::request::Request;
+use rocket::Outcome;
+use rocket_contrib::json::Json;
 use std::thread;
 use timeline;
+use two_factor;
+use url::Url;
 use web::federator;
+use web::multipart;
+use well_known::webfinger;
 
-pub fn follow(sender: &str, receipient: &str) {
-    let database = database::establish_connection();
-    let serialized_actor: Actor = get_actor_by_uri(&database, &sender).unwrap();
+#[derive(FromForm)]
+pub struct AliasForm {
+    pub alias: String,
+}
 
-    if sender != receipient {
-        match get_actor_by_uri(&database, &receipient) {
-            Ok(followee) => {
-                if !is_actor_followed_by(&database, &followee, &sender).unwrap() {
-                    let activitypub_activity_follow = ap_controller::follow(sender, receipient);
+// CSV files which are larger than 2MB are rejected
+pub const MAX_IMPORT_SIZE: u64 = 2097152;
 
-                    if !followee.local {
-                        federator::enqueue(
-                            serialized_actor,
-                            serde_json::json!(&activitypub_activity_follow),
-                            vec![followee.inbox.unwrap()],
-                        );
-                    } else {
-                        add_follow(receipient, sender, &activitypub_activity_follow.id);
+#[derive(FromForm)]
+pub struct RelayForm {
+    pub relay_url: String,
+}
+
+#[derive(FromForm)]
+pub struct InviteForm {
+    pub max_uses: Option<i32>,
+    // Hours after which the invite expires
+    pub expires_in: Option<i64>,
+}
+
+#[derive(FromForm)]
+pub struct MoveAccountForm {
+    pub target_account: String,
+    pub password: String,
+}
+
+#[derive(FromForm)]
+pub struct EmailPreferencesForm {
+    pub mentions: bool,
+    pub follows: bool,
+}
+
+#[derive(FromForm)]
+pub struct TwoFactorCodeForm {
+    pub code: String,
+}
+
+pub struct ImportForm {
+    // One of `following`, `lists`, `blocks` or `mutes`
+    pub _type: String,
+    pub data: String,
+}
+
+impl FromDataSimple for ImportForm {
+    type Error = String;
+
+    fn from_data(request: &Request, data: Data) -> data::Outcome<Self, String> {
+        match multipart::from_data(request, data, MAX_IMPORT_SIZE + 65536) {
+            Ok(parts) => {
+                let mut form = ImportForm {
+                    _type: String::new(),
+                    data: String::new(),
+                };
+
+                for part in parts {
+                    match part.name.as_str() {
+                        "type" => form._type = String::from_utf8_lossy(&part.data).to_string(),
+                        "data" => form.data = String::from_utf8_lossy(&part.data).to_string(),
+                        _ => (),
                     }
                 }
+                Outcome::Success(form)
             }
-            Err(_) => (),
+            Err(e) => Outcome::Failure(e),
         }
     }
 }
 
-pub fn react(actor: &i64, _type: &str, object_id: &str) {
+pub fn admin_emoji_create(
+    pooled_connection: &PooledConnection,
+    token: String,
+    shortcode: &str,
+    extension: &str,
+    image: &[u8],
+    category: Option<String>,
+) -> ApiResult<mastodon_api::Emoji> {
+    verify_admin(pooled_connection, token)?;
+
+    match emoji::add(pooled_connection, shortcode, extension, image, category) {
+        Ok(new_emoji) => Ok(Json(mastodon_api::Emoji::from_emoji(new_emoji))),
+        Err(e) => Err(ApiError::unprocessable(e)),
+    }
+}
+
+pub fn admin_emoji_delete(
+    pooled_connection: &PooledConnection,
+    token: String,
+    shortcode: &str,
+) -> ApiResult<serde_json::Value> {
+    verify_admin(pooled_connection, token)?;
+
+    match emoji::remove(pooled_connection, shortcode) {
+        Ok(_) => Ok(Json(serde_json::json!({}))),
+        Err("Emoji not found") => Err(ApiError::not_found("Emoji not found")),
+        Err(e) => Err(ApiError::internal(e)),
+    }
+}
+
+// Unlike `/api/v1/custom_emojis` this also lists emojis which are hidden from the picker
+pub fn admin_emojis(
+    pooled_connection: &PooledConnection,
+    token: String,
+) -> ApiResult<Vec<mastodon_api::Emoji>> {
+    verify_admin(pooled_connection, token)?;
+
+    match emoji::get_emojis(pooled_connection) {
+        Ok(emojis) => Ok(Json(
+            emojis
+                .into_iter()
+                .map(mastodon_api::Emoji::from_emoji)
+                .collect(),
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while querying custom emojis",
+        )),
+    }
+}
+
+/// Subscribes the instance to a relay
+///
+/// # Parameters
+///
+/// * `pooled_connection` - &PooledConnection | Reference to a database connection
+/// * `token`             -            String | The OAuth token of an administrator
+/// * `relay_url`         -              &str | The URI of the relay's actor
+pub fn admin_relay_follow(
+    pooled_connection: &PooledConnection,
+    token: String,
+    relay_url: &str,
+) -> ApiResult<serde_json::Value> {
+    verify_admin(pooled_connection, token)?;
+
+    match relay::subscribe(pooled_connection, relay_url) {
+        Ok(new_relay) => Ok(Json(relay::to_json(pooled_connection, &new_relay))),
+        Err(e) => Err(ApiError::unprocessable(e)),
+    }
+}
+
+pub fn admin_relay_unfollow(
+    pooled_connection: &PooledConnection,
+    token: String,
+    relay_url: &str,
+) -> ApiResult<serde_json::Value> {
+    verify_admin(pooled_connection, token)?;
+
+    match relay::unsubscribe(pooled_connection, relay_url) {
+        Ok(_) => Ok(Json(serde_json::json!({}))),
+        Err(e) => Err(ApiError::not_found(e)),
+    }
+}
+
+pub fn admin_relays(
+    pooled_connection: &PooledConnection,
+    token: String,
+) -> ApiResult<Vec<serde_json::Value>> {
+    verify_admin(pooled_connection, token)?;
+
+    match relay::get_relays(pooled_connection) {
+        Ok(relays) => Ok(Json(
+            relays
+                .iter()
+                .map(|listed_relay| relay::to_json(pooled_connection, listed_relay))
+                .collect(),
+        )),
+        Err(_) => Err(ApiError::internal("An error occured while querying relays")),
+    }
+}
+
+pub fn admin_registrations(
+    pooled_connection: &PooledConnection,
+    token: String,
+) -> ApiResult<Vec<registration::SerializedPendingRegistration>> {
+    verify_admin(pooled_connection, token)?;
+
+    match registration::pending(pooled_connection) {
+        Ok(pending_registrations) => Ok(Json(pending_registrations)),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while querying pending registrations",
+        )),
+    }
+}
+
+/// Approves or rejects an account which is waiting for approval, rejected accounts are deleted
+///
+/// # Parameters
+///
+/// * `pooled_connection` - &PooledConnection | Reference to a database connection
+/// * `token`             -            String | The OAuth token of an admin
+/// * `username`          -              &str | The username of the pending local actor
+/// * `approved`          -              bool | Whether the account is approved
+pub fn admin_registration_review(
+    pooled_connection: &PooledConnection,
+    token: String,
+    username: &str,
+    approved: bool,
+) -> ApiResult<serde_json::Value> {
+    verify_admin(pooled_connection, token)?;
+
+    let result = if approved {
+        registration::approve(pooled_connection, username)
+    } else {
+        registration::reject(pooled_connection, username)
+    };
+
+    match result {
+        Ok(()) => Ok(Json(serde_json::json!({}))),
+        Err(e) => Err(ApiError::not_found(e)),
+    }
+}
+
+/// Disables two-factor authentication of a local actor who lost access to it
+///
+/// # Parameters
+///
+/// * `pooled_connection` - &PooledConnection | Reference to a database connection
+/// * `token`             -            String | The OAuth token of an admin
+/// * `username`          -              &str | The username of the local actor
+pub fn admin_two_factor_reset(
+    pooled_connection: &PooledConnection,
+    token: String,
+    username: &str,
+) -> ApiResult<serde_json::Value> {
+    verify_admin(pooled_connection, token)?;
+
+    match get_local_actor_by_preferred_username(pooled_connection, username) {
+        Ok(actor) => {
+            two_factor::reset(pooled_connection, &actor);
+            Ok(Json(serde_json::json!({})))
+        }
+        Err(_) => Err(ApiError::not_found("Account not found")),
+    }
+}
+
+/// Adds an alias to a local actor, which allows the aliased account to move to them
+///
+/// # Parameters
+///
+/// * `pooled_connection` - &PooledConnection | Reference to a database connection
+/// * `token`             -            String | The OAuth token of the local actor
+/// * `alias`             -              &str | The URI or the acct of another account
+pub fn alias_add(
+    pooled_connection: &PooledConnection,
+    token: String,
+    alias: &str,
+) -> ApiResult<serde_json::Value> {
+    let mut actor = verify_actor(pooled_connection, token)?;
+    let aliased_actor = resolve_account(pooled_connection, alias).map_err(ApiError::not_found)?;
+
+    if aliased_actor.actor_uri == actor.actor_uri {
+        return Err(ApiError::unprocessable(
+            "Accounts can't be aliases of themselves",
+        ));
+    }
+
+    let mut aliases: Vec<String> =
+        serde_json::from_value(actor.also_known_as.clone()).unwrap_or_default();
+
+    if !aliases.contains(&aliased_actor.actor_uri) {
+        aliases.push(aliased_actor.actor_uri);
+        actor.also_known_as = serde_json::json!(aliases);
+        actor::update(pooled_connection, actor.clone());
+        update_actor(&actor);
+    }
+    Ok(Json(serde_json::json!({ "aliases": actor.also_known_as })))
+}
+
+/// Removes an alias from a local actor
+///
+/// # Parameters
+///
+/// * `pooled_connection` - &PooledConnection | Reference to a database connection
+/// * `token`             -            String | The OAuth token of the local actor
+/// * `alias`             -              &str | The URI or the acct of another account
+pub fn alias_remove(
+    pooled_connection: &PooledConnection,
+    token: String,
+    alias: &str,
+) -> ApiResult<serde_json::Value> {
+    let mut actor = verify_actor(pooled_connection, token)?;
+
+    // Aliases might not be resolvable anymore, e.g. if their instance went down
+    let aliased_actor_uri =
+        match get_actor_by_acct(pooled_connection, alias.trim_start_matches('@')) {
+            Ok(aliased_actor) => aliased_actor.actor_uri,
+            Err(_) => alias.to_string(),
+        };
+    let mut aliases: Vec<String> =
+        serde_json::from_value(actor.also_known_as.clone()).unwrap_or_default();
+
+    if aliases.contains(&aliased_actor_uri) {
+        aliases.retain(|uri| uri != &aliased_actor_uri);
+        actor.also_known_as = serde_json::json!(aliases);
+        actor::update(pooled_connection, actor.clone());
+        update_actor(&actor);
+    }
+    Ok(Json(serde_json::json!({ "aliases": actor.also_known_as })))
+}
+
+pub fn aliases(
+    pooled_connection: &PooledConnection,
+    token: String,
+) -> ApiResult<serde_json::Value> {
+    let actor = verify_actor(pooled_connection, token)?;
+    Ok(Json(serde_json::json!({ "aliases": actor.also_known_as })))
+}
+
+pub fn authorized_applications(
+    pooled_connection: &PooledConnection,
+    token: String,
+) -> ApiResult<Vec<authorized_application::AuthorizedApplication>> {
+    let actor = verify_actor(pooled_connection, token)?;
+
+    match authorized_application::authorized_applications(
+        pooled_connection,
+        &actor.preferred_username,
+    ) {
+        Ok(applications) => Ok(Json(applications)),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while querying authorized applications",
+        )),
+    }
+}
+
+/// Revokes all tokens a local actor has issued to an application
+///
+/// # Parameters
+///
+/// * `pooled_connection` - &PooledConnection | Reference to a database connection
+/// * `token`             -            String | The OAuth token of the local actor
+/// * `application_id`    -               i64 | The ID of the OAuth application
+pub fn authorized_application_revoke(
+    pooled_connection: &PooledConnection,
+    token: String,
+    application_id: i64,
+) -> ApiResult<serde_json::Value> {
+    let actor = verify_actor(pooled_connection, token)?;
+
+    match revoke_application(pooled_connection, &actor.preferred_username, application_id) {
+        Ok(0) => Err(ApiError::not_found("Application not found")),
+        Ok(_) => Ok(Json(serde_json::json!({}))),
+        Err(_) => Err(ApiError::internal("An error occured while revoking tokens")),
+    }
+}
+
+pub fn email_preferences(
+    pooled_connection: &PooledConnection,
+    token: String,
+) -> ApiResult<serde_json::Value> {
+    let actor = verify_actor(pooled_connection, token)?;
+    let (mentions, follows) = email::preferences(pooled_connection, &actor);
+    Ok(Json(
+        serde_json::json!({ "mentions": mentions, "follows": follows }),
+    ))
+}
+
+/// Chooses about which notifications a local actor receives e-mails
+///
+/// # Parameters
+///
+/// * `pooled_connection` - &PooledConnection | Reference to a database connection
+/// * `token`             -            String | The OAuth token of the local actor
+/// * `mentions`          -              bool | Whether e-mails are sent about mentions
+/// * `follows`           -              bool | Whether e-mails are sent about new followers
+pub fn email_preferences_update(
+    pooled_connection: &PooledConnection,
+    token: String,
+    mentions: bool,
+    follows: bool,
+) -> ApiResult<serde_json::Value> {
+    let actor = verify_actor(pooled_connection, token)?;
+    email::set_preferences(pooled_connection, &actor, mentions, follows);
+    Ok(Json(
+        serde_json::json!({ "mentions": mentions, "follows": follows }),
+    ))
+}
+
+/// Reacts to a status with an emoji and sends the reaction to the status' author and the
+/// actor's followers
+///
+/// # Parameters
+///
+/// * `actor`     - &Actor | The local actor who's reacting
+/// * `object_id` -   &str | Reference to an ActivityStreams object
+/// * `emoji`     -   &str | A unicode emoji or the shortcode of one of the instance's custom emojis
+pub fn emoji_react(actor: &Actor, object_id: &str, emoji: &str) -> Result<(), &'static str> {
     let database = database::establish_connection();
-    let serialized_actor: Actor = get_actor_by_id(&database, actor).expect("Actor should exist!");
 
-    if !type_exists_for_object_id(&database, _type, &serialized_actor.actor_uri, object_id)
-        .unwrap_or_else(|_| true)
-    {
-        match get_ap_object_by_id(&database, object_id) {
+    let name = match reaction::parse_reaction(emoji) {
+        Some(name) => name,
+        None => return Err("Invalid emoji"),
+    };
+
+    // Only the instance's own custom emojis can be used for reactions
+    let tag = if name.starts_with(':') {
+        match emoji::get_emoji_by_shortcode(&database, name.trim_matches(':')) {
+            Ok(custom_emoji) => Some(vec![custom_emoji.to_tag()]),
+            Err(_) => return Err("Unknown emoji"),
+        }
+    } else {
+        None
+    };
+
+    if reaction::reaction_for_actor(&database, &actor.actor_uri, object_id, &name).is_some() {
+        return Ok(());
+    }
+
+    match get_ap_object_by_id(&database, object_id) {
+        Ok(activity) => {
+            let (to, cc, inboxes) = reaction_receipients(&database, actor, activity);
+            let new_activity =
+                ap_controller::emoji_react(&actor.actor_uri, object_id, &name, tag, to, cc);
+
+            match get_actor_by_id(&database, &actor.id) {
+                Ok(serialized_actor) => {
+                    federator::enqueue(serialized_actor, serde_json::json!(&new_activity), inboxes)
+                }
+                Err(_) => (),
+            }
+            Ok(())
+        }
+        Err(_) => Err("Status not found"),
+    }
+}
+
+/// Withdraws an emoji reaction of an actor and sends an `Undo` activity for it
+///
+/// # Parameters
+///
+/// * `actor`     - &Actor | The local actor who reacted
+/// * `object_id` -   &str | Reference to an ActivityStreams object
+/// * `emoji`     -   &str | The emoji of the reaction
+pub fn emoji_unreact(actor: &Actor, object_id: &str, emoji: &str) -> Result<(), &'static str> {
+    let database = database::establish_connection();
+
+    let name = match reaction::parse_reaction(emoji) {
+        Some(name) => name,
+        None => return Err("Invalid emoji"),
+    };
+
+    match reaction::reaction_for_actor(&database, &actor.actor_uri, object_id, &name) {
+        Some(reaction) => match get_ap_object_by_id(&database, object_id) {
             Ok(activity) => {
-                let ap_activity = serialize_from_internal_activity(activity);
+                let (to, cc, inboxes) = reaction_receipients(&database, actor, activity);
+                let undo_activity =
+                    ap_controller::undo(&actor.actor_uri, reaction.data.clone(), to, cc);
+
+                delete_ap_activity_by_id(
+                    &database,
+                    reaction.data["id"].as_str().unwrap_or_default().to_string(),
+                );
+
+                match get_actor_by_id(&database, &actor.id) {
+                    Ok(serialized_actor) => federator::enqueue(
+                        serialized_actor,
+                        serde_json::json!(&undo_activity),
+                        inboxes,
+                    ),
+                    Err(_) => (),
+                }
+                Ok(())
+            }
+            Err(_) => Err("Status not found"),
+        },
+        None => Err("Reaction not found"),
+    }
+}
+
+/// Exports the data of a local actor
+///
+/// # Parameters
+///
+/// * `pooled_connection` - &PooledConnection | Reference to a database connection
+/// * `token`             -            String | The OAuth token of the local actor
+/// * `file`              -              &str | One of `following_accounts.csv`, `lists.csv`,
+///                                             `blocked_accounts.csv`, `muted_accounts.csv` or
+///                                             `archive.tar`
+pub fn export(
+    pooled_connection: &PooledConnection,
+    token: String,
+    file: &str,
+) -> Result<Vec<u8>, ApiError> {
+    let actor = verify_actor(pooled_connection, token)?;
+
+    match file {
+        "following_accounts.csv" => {
+            Ok(export::following_csv(pooled_connection, &actor).into_bytes())
+        }
+        "lists.csv" => Ok(export::lists_csv(pooled_connection, &actor).into_bytes()),
+        // Blocks and mutes aren't supported yet, so there's nothing to export
+        "blocked_accounts.csv" | "muted_accounts.csv" => Ok(Vec::new()),
+        "archive.tar" => Ok(export::archive(pooled_connection, &actor)),
+        _ => Err(ApiError::not_found("Unknown export")),
+    }
+}
+
+pub fn follow(sender: &str, receipient: &str) -> Result<(), ApiError> {
+    let database = database::establish_connection();
+    let serialized_actor: Actor =
+        get_actor_by_uri(&database, &sender).map_err(|_| ApiError::not_found("User not found."))?;
 
-                let mut to: Vec<String> = ap_activity.to.clone();
-                let mut cc: Vec<String> = ap_activity.cc.clone();
-                let mut inboxes: Vec<String> = Vec::new();
+    if sender == receipient {
+        return Ok(());
+    }
 
-                to.retain(|x| x != &format!("{}/followers", &ap_activity.actor));
-                cc.retain(|x| x != &format!("{}/followers", &ap_activity.actor));
+    let followee = get_actor_by_uri(&database, &receipient)
+        .map_err(|_| ApiError::not_found("User not found."))?;
 
-                if to.contains(&"https://www.w3.org/ns/activitystreams#Public".to_string()) {
-                    cc.push(format!("{}/followers", serialized_actor.actor_uri));
-                    inboxes = handle_follower_inboxes(&database, &serialized_actor.followers);
-                } else if cc.contains(&"https://www.w3.org/ns/activitystreams#Public".to_string()) {
-                    to.push(format!("{}/followers", serialized_actor.actor_uri));
-                    inboxes = handle_follower_inboxes(&database, &serialized_actor.followers);
+    match is_actor_followed_by(&database, &followee, &sender) {
+        Ok(false) => {
+            if followee.local && followee.locked {
+                if !follow_request::is_requested(&database, &followee, serialized_actor.id) {
+                    let activitypub_activity_follow = ap_controller::follow(sender, receipient);
+                    let follow = get_ap_activity_by_id(&database, &activitypub_activity_follow.id)
+                        .map_err(|_| ApiError::internal("An error occured while following"))?;
+                    follow_request::request(&database, &followee, &serialized_actor, follow.id);
                 }
+            } else if followee.local {
+                let activitypub_activity_follow = ap_controller::follow(sender, receipient);
+                add_follow(receipient, sender, &activitypub_activity_follow.id);
+            } else {
+                let inbox = followee
+                    .inbox
+                    .ok_or_else(|| ApiError::unprocessable("The account has no inbox"))?;
+                let activitypub_activity_follow = ap_controller::follow(sender, receipient);
+                federator::enqueue(
+                    serialized_actor,
+                    serde_json::json!(&activitypub_activity_follow),
+                    vec![inbox],
+                );
+            }
+            Ok(())
+        }
+        Ok(true) => Ok(()),
+        Err(_) => Err(ApiError::internal("An error occured while following")),
+    }
+}
+
+/// Accepts a pending follow request of a locked account
+///
+/// # Parameters
+///
+/// * `actor`    - &Actor | The locked local actor
+/// * `follower` - &Actor | The actor who requested to follow
+///
+pub fn follow_request_authorize(actor: &Actor, follower: &Actor) -> Result<(), ApiError> {
+    let database = database::establish_connection();
+    let follow_request = follow_request::remove(&database, actor, follower.id)
+        .map_err(|_| ApiError::not_found("Follow request not found"))?;
+    let follow = get_activity_by_id(&database, follow_request.activity_id)?;
+    let follow_id = follow.data["id"].as_str().unwrap_or_default();
 
-                match get_actor_by_uri(&database, &ap_activity.actor) {
-                    Ok(foreign_actor) => {
-                        if !foreign_actor.local {
-                            to.push(foreign_actor.actor_uri);
-                            inboxes.push(foreign_actor.inbox.unwrap());
+    add_follow(&actor.actor_uri, &follower.actor_uri, follow_id);
+
+    match &follower.inbox {
+        Some(inbox) if !follower.local => {
+            let activitypub_activity_accept = ap_controller::accept(
+                &actor.actor_uri,
+                follow_id,
+                vec![follower.actor_uri.clone()],
+                vec![],
+            );
+            federator::enqueue(
+                actor.clone(),
+                serde_json::json!(&activitypub_activity_accept),
+                vec![inbox.to_string()],
+            );
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Declines a pending follow request of a locked account
+///
+/// # Parameters
+///
+/// * `actor`    - &Actor | The locked local actor
+/// * `follower` - &Actor | The actor who requested to follow
+///
+pub fn follow_request_reject(actor: &Actor, follower: &Actor) -> Result<(), ApiError> {
+    let database = database::establish_connection();
+    let follow_request = follow_request::remove(&database, actor, follower.id)
+        .map_err(|_| ApiError::not_found("Follow request not found"))?;
+
+    match &follower.inbox {
+        Some(inbox) if !follower.local => {
+            let follow = get_activity_by_id(&database, follow_request.activity_id)?;
+            let activitypub_activity_reject = ap_controller::reject(
+                &actor.actor_uri,
+                follow.data["id"].as_str().unwrap_or_default(),
+                vec![follower.actor_uri.clone()],
+                vec![],
+            );
+            federator::enqueue(
+                actor.clone(),
+                serde_json::json!(&activitypub_activity_reject),
+                vec![inbox.to_string()],
+            );
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Imports follows or lists of a local actor from a CSV file, as exported by Kibou or Mastodon
+///
+/// # Description
+///
+/// Accounts are resolved and followed in the background. List members are added right away,
+/// even though remote and locked accounts accept follows asynchronously.
+///
+/// # Parameters
+///
+/// * `pooled_connection` - &PooledConnection | Reference to a database connection
+/// * `token`             -            String | The OAuth token of the local actor
+/// * `form`              -        ImportForm | The type of the import and the CSV file
+pub fn import(
+    pooled_connection: &PooledConnection,
+    token: String,
+    form: ImportForm,
+) -> ApiResult<serde_json::Value> {
+    let actor = verify_actor(pooled_connection, token)?;
+
+    match form._type.as_str() {
+        "following" => {
+            let accounts = export::parse_accounts(&form.data);
+            thread::spawn(move || {
+                let database = database::establish_connection();
+
+                for account in accounts {
+                    match resolve_account(&database, &account) {
+                        Ok(followee) => {
+                            if let Err(e) = follow(&actor.actor_uri, &followee.actor_uri) {
+                                eprintln!("Unable to import follow of {}: {}", account, e.message())
+                            }
                         }
+                        Err(e) => eprintln!("Unable to import follow of {}: {}", account, e),
                     }
-                    Err(_) => eprintln!(
-                        "Error: Actor '{}' should exist in order to create a reaction!",
-                        &ap_activity.actor
-                    ),
                 }
+            });
+            Ok(Json(serde_json::json!({})))
+        }
+        "lists" => {
+            let entries = export::parse_lists(&form.data);
+            thread::spawn(move || {
+                let database = database::establish_connection();
 
-                to.dedup();
-                cc.dedup();
-                inboxes.dedup();
+                for (title, account) in entries {
+                    let member = match resolve_account(&database, &account) {
+                        Ok(member) => member,
+                        Err(e) => {
+                            eprintln!("Unable to import list member {}: {}", account, e);
+                            continue;
+                        }
+                    };
+                    if let Err(e) = import_list_member(&database, &actor, &title, member) {
+                        eprintln!("Unable to import list member {}: {}", account, e);
+                    }
+                }
+            });
+            Ok(Json(serde_json::json!({})))
+        }
+        "blocks" | "mutes" => Err(ApiError::unprocessable(
+            "Blocks and mutes aren't supported yet",
+        )),
+        _ => Err(ApiError::unprocessable("Unknown import type")),
+    }
+}
+
+/// Follows an actor and adds them to a list of a local actor, which is created if the local actor
+/// has no list with this title yet
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -        &Actor | The local actor who owns the list
+/// * `title`         -          &str | The title of the list
+/// * `member`        -         Actor | The actor who is added to the list
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/kibou_api.rs`
+/// - import_list_member_with_pending_follow()
+pub fn import_list_member(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    title: &str,
+    member: Actor,
+) -> Result<(), String> {
+    follow(&actor.actor_uri, &member.actor_uri).map_err(|e| e.message().to_string())?;
+
+    let lists = list::lists_for_actor(db_connection, actor).unwrap_or_default();
+    let list = match lists.into_iter().find(|list| list.title == title) {
+        Some(list) => list,
+        None => list::create(db_connection, actor, title)?,
+    };
+
+    list::add_requested_members(db_connection, &list, vec![member]);
+    Ok(())
+}
+
+pub fn invites(
+    pooled_connection: &PooledConnection,
+    token: String,
+) -> ApiResult<Vec<registration::SerializedInvite>> {
+    let actor = verify_actor(pooled_connection, token)?;
+
+    match registration::invites_by_actor(pooled_connection, &actor) {
+        Ok(invites) => Ok(Json(
+            invites.iter().map(|invite| invite.serialize()).collect(),
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while querying invites",
+        )),
+    }
+}
+
+/// Creates an invite, which is unlimited unless `max_uses` or `expires_in` are given
+///
+/// # Parameters
+///
+/// * `pooled_connection` - &PooledConnection | Reference to a database connection
+/// * `token`             -            String | The OAuth token of the local actor
+/// * `max_uses`          -       Option<i32> | How many accounts may be created with the invite
+/// * `expires_in`        -       Option<i64> | Hours after which the invite expires
+pub fn invite_create(
+    pooled_connection: &PooledConnection,
+    token: String,
+    max_uses: Option<i32>,
+    expires_in: Option<i64>,
+) -> ApiResult<registration::SerializedInvite> {
+    let actor = verify_actor(pooled_connection, token)?;
+
+    if registration::mode() == registration::Mode::Closed {
+        return Err(ApiError::forbidden("Registrations are closed"));
+    }
+
+    let valid_until =
+        registration::invite_valid_until(expires_in).map_err(ApiError::unprocessable)?;
+
+    match registration::create_invite(pooled_connection, &actor, max_uses, valid_until) {
+        Ok(invite) => Ok(Json(invite.serialize())),
+        Err(_) => Err(ApiError::internal("Invite could not be created")),
+    }
+}
+
+pub fn invite_delete(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    let actor = verify_actor(pooled_connection, token)?;
+
+    match registration::delete_invite(pooled_connection, &actor, id) {
+        Ok(0) => Err(ApiError::not_found("Invite not found")),
+        Ok(_) => Ok(Json(serde_json::json!({}))),
+        Err(_) => Err(ApiError::internal("Invite could not be deleted")),
+    }
+}
+
+/// Moves a local actor to another account and tells their followers to follow that account
+///
+/// # Description
+///
+/// The new account has to list the local actor as an alias beforehand, the same is required by
+/// other servers before they accept the `Move` activity.
+///
+/// # Parameters
+///
+/// * `pooled_connection` - &PooledConnection | Reference to a database connection
+/// * `token`             -            String | The OAuth token of the local actor
+/// * `target`            -              &str | The URI or the acct of the new account
+/// * `password`          -            String | The password of the local actor
+pub fn move_account(
+    pooled_connection: &PooledConnection,
+    token: String,
+    target: &str,
+    password: String,
+) -> ApiResult<serde_json::Value> {
+    let mut actor = verify_actor(pooled_connection, token)?;
+
+    if !authorize(pooled_connection, &actor.preferred_username, password).unwrap_or(false) {
+        return Err(ApiError::forbidden("Invalid password"));
+    }
+
+    let new_actor = resolve_account(pooled_connection, target).map_err(ApiError::not_found)?;
+
+    if new_actor.actor_uri == actor.actor_uri {
+        return Err(ApiError::unprocessable("Accounts can't move to themselves"));
+    }
+
+    if !new_actor.also_known_as.as_array().map_or(false, |aliases| {
+        aliases.contains(&serde_json::json!(actor.actor_uri))
+    }) {
+        return Err(ApiError::unprocessable(
+            "The target account has to list this account as an alias",
+        ));
+    }
+
+    let new_inbox = match (&new_actor.inbox, new_actor.local) {
+        (_, true) => None,
+        (Some(inbox), false) => Some(inbox.clone()),
+        (None, false) => return Err(ApiError::unprocessable("The target account has no inbox")),
+    };
+
+    actor.moved_to = Some(new_actor.actor_uri.clone());
+    actor::update(pooled_connection, actor.clone());
+
+    let activitypub_activity_move = ap_controller::move_actor(&actor, &new_actor.actor_uri);
+    let mut inboxes = handle_follower_inboxes(pooled_connection, &actor.followers);
+    inboxes.extend(new_inbox);
+    inboxes.sort();
+    inboxes.dedup();
+
+    update_actor(&actor);
+    thread::spawn(move || {
+        federator::enqueue(
+            actor.clone(),
+            serde_json::json!(&activitypub_activity_move),
+            inboxes,
+        );
+        move_followers(&actor, &new_actor);
+    });
+    Ok(Json(serde_json::json!({})))
+}
+
+/// Makes the local followers of an actor follow their new account instead
+///
+/// # Parameters
+///
+/// * `old_actor` - &Actor | The actor who moved
+/// * `new_actor` - &Actor | The account the actor moved to
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/kibou_api.rs`
+/// - move_followers()
+pub fn move_followers(old_actor: &Actor, new_actor: &Actor) {
+    let database = database::establish_connection();
+    let followers: Vec<serde_json::Value> =
+        serde_json::from_value(old_actor.followers["activitypub"].clone()).unwrap_or_default();
+
+    mastodon_api::controller::uncache_account(&old_actor.actor_uri);
+
+    for follower in followers {
+        match get_actor_by_uri(&database, follower["href"].as_str().unwrap_or_default()) {
+            Ok(follower) => {
+                if follower.local {
+                    let moved = unfollow(follower.actor_uri.clone(), old_actor.actor_uri.clone())
+                        .and_then(|_| follow(&follower.actor_uri, &new_actor.actor_uri));
+                    if let Err(e) = moved {
+                        eprintln!("Unable to move {}: {}", follower.actor_uri, e.message());
+                    }
+                }
+            }
+            Err(_) => (),
+        }
+    }
+}
+
+pub fn react(actor: &i64, _type: &str, object_id: &str) {
+    let database = database::establish_connection();
+    let serialized_actor: Actor = get_actor_by_id(&database, actor).expect("Actor should exist!");
+
+    if !type_exists_for_object_id(&database, _type, &serialized_actor.actor_uri, object_id)
+        .unwrap_or_else(|_| true)
+    {
+        match get_ap_object_by_id(&database, object_id) {
+            Ok(activity) => {
+                let (to, cc, inboxes) =
+                    reaction_receipients(&database, &serialized_actor, activity);
 
                 match _type {
                     "Announce" => {
@@ -121,10 +948,88 @@ pub fn react(actor: &i64, _type: &str, object_id: &str) {
     }
 }
 
-pub fn public_activities(pooled_connection: &PooledConnection) -> JsonValue {
+/// Records the votes of a local actor on a poll and sends them to the poll's author
+///
+/// # Parameters
+///
+/// * `actor`   -      &Actor | The local actor who's voting
+/// * `poll`    -   &Activity | The internal `Create` activity of a poll
+/// * `choices` - Vec<String> | The names of the chosen options
+pub fn poll_vote(actor: &Actor, poll: &Activity, choices: Vec<String>) -> Result<(), &'static str> {
+    let database = database::establish_connection();
+
+    match poll::vote(&database, poll, actor, &choices) {
+        Ok(_) => match get_actor_by_uri(&database, &poll.actor) {
+            Ok(owner) => {
+                if owner.local {
+                    poll::tally(&database, poll);
+                } else {
+                    let actor_uri = actor.actor_uri.clone();
+                    let owner_uri = owner.actor_uri;
+                    let question = poll.data["object"]["id"].as_str().unwrap().to_string();
+                    let inbox = owner.inbox.unwrap();
+
+                    thread::spawn(move || {
+                        let database = database::establish_connection();
+                        for choice in choices {
+                            let vote =
+                                ap_controller::vote(&actor_uri, &question, &owner_uri, &choice);
+                            match get_actor_by_uri(&database, &actor_uri) {
+                                Ok(voter) => federator::enqueue(
+                                    voter,
+                                    serde_json::json!(&vote),
+                                    vec![inbox.clone()],
+                                ),
+                                Err(_) => (),
+                            }
+                        }
+                    });
+                }
+                Ok(())
+            }
+            Err(_) => Err("Author of the poll not found"),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+pub fn public_activities(
+    pooled_connection: &PooledConnection,
+) -> ApiResult<Vec<mastodon_api::Status>> {
     match timeline::public_activities(pooled_connection) {
-        Ok(activities) => mastodon_api::controller::cached_statuses(pooled_connection, activities),
-        Err(_) => json!({"error": "An error occured while querying public activities"}),
+        Ok(activities) => Ok(Json(mastodon_api::controller::cached_statuses(
+            pooled_connection,
+            activities,
+            None,
+            "public",
+        ))),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while querying public activities",
+        )),
+    }
+}
+
+/// Renders the text of a profile, such as a note or the value of a metadata field, as HTML with
+/// links and mentions
+pub fn render_profile_text(text: &str) -> String {
+    let (_, _, _, content) = parse_mentions(html::escape(text));
+
+    html::linkify(&content).replace("\n", "<br>")
+}
+
+/// Returns an account by its URI or acct, remote accounts are refetched beforehand
+///
+/// # Description
+///
+/// Remote accounts which aren't known yet are looked up via WebFinger.
+pub fn resolve_account(db_connection: &PgConnection, account: &str) -> Result<Actor, &'static str> {
+    if Url::parse(account).is_ok() {
+        refetch(account)
+    } else {
+        match get_actor_by_acct(db_connection, account.trim_start_matches('@')) {
+            Ok(actor) => refetch(&actor.actor_uri),
+            Err(_) => refetch(&webfinger::resolve(account)?),
+        }
     }
 }
 
@@ -133,9 +1038,13 @@ pub fn status_build(
     mut content: String,
     visibility: &str,
     in_reply_to: Option<String>,
-) -> i64 {
+    spoiler_text: Option<String>,
+    sensitive: bool,
+    new_poll: Option<NewPoll>,
+) -> Result<i64, ApiError> {
     let database = database::establish_connection();
-    let serialized_actor: Actor = get_actor_by_uri(&database, &actor).unwrap();
+    let serialized_actor: Actor =
+        get_actor_by_uri(&database, &actor).map_err(|_| ApiError::not_found("User not found."))?;
 
     let mut direct_receipients: Vec<String> = Vec::new();
     let mut receipients: Vec<String> = Vec::new();
@@ -157,6 +1066,7 @@ pub fn status_build(
                 &database,
                 &serialized_actor.followers,
             ));
+            inboxes.extend(relay::relay_inboxes(&database));
         }
 
         "unlisted" => {
@@ -176,45 +1086,102 @@ pub fn status_build(
             ));
         }
 
+        // Direct statuses are only addressed to the actors mentioned in them, which have already
+        // been added to the receipients above. Unknown scopes are treated the same way, so that
+        // a typo in a client never leaks a status to the public.
         _ => (),
     }
 
-    if in_reply_to.is_some() {
-        match get_activity_by_id(&database, in_reply_to.unwrap().parse::<i64>().unwrap()) {
-            Ok(activity) => {
-                in_reply_to_id = Some(activity.data["object"]["id"].as_str().unwrap().to_string());
+    match in_reply_to {
+        Some(in_reply_to) => {
+            let parent_id = in_reply_to
+                .parse::<i64>()
+                .map_err(|_| ApiError::unprocessable("Invalid in_reply_to_id"))?;
+            let parent = get_activity_by_id(&database, parent_id)
+                .ok()
+                .filter(|parent| {
+                    conversation::is_visible_to(
+                        &parent.data,
+                        &conversation::followers_uri_of(&database, &parent.data),
+                        Some(actor.as_str()),
+                    )
+                })
+                .ok_or_else(|| ApiError::not_found("The status to reply to was not found"))?;
 
-                match get_actor_by_uri(
-                    &database,
-                    activity.data["object"]["attributedTo"].as_str().unwrap(),
-                ) {
-                    Ok(actor) => {
-                        direct_receipients.push(actor.actor_uri);
-                        if !actor.local {
-                            inboxes.push(actor.inbox.unwrap());
-                        }
+            in_reply_to_id = Some(
+                parent.data["object"]["id"]
+                    .as_str()
+                    .ok_or_else(|| ApiError::unprocessable("This status can't be replied to"))?
+                    .to_string(),
+            );
+
+            match get_actor_by_uri(
+                &database,
+                parent.data["object"]["attributedTo"]
+                    .as_str()
+                    .unwrap_or_default(),
+            ) {
+                Ok(parent_actor) => {
+                    direct_receipients.push(parent_actor.actor_uri);
+                    if !parent_actor.local {
+                        inboxes.extend(parent_actor.inbox);
                     }
-                    Err(_) => (),
                 }
+                Err(_) => (),
             }
-            Err(_) => in_reply_to_id = None,
         }
-    } else {
-        in_reply_to_id = None;
+        None => in_reply_to_id = None,
     }
 
     direct_receipients.dedup();
     receipients.dedup();
     inboxes.dedup();
 
-    let activitypub_note = ap_controller::note(
+    // An empty content warning is the same as none at all, Mastodon-API clients tend to always
+    // submit the field
+    let summary = match spoiler_text {
+        Some(text) => {
+            if text.trim().is_empty() {
+                None
+            } else {
+                Some(html::strip_tags(&text))
+            }
+        }
+        None => None,
+    };
+    let sensitive = sensitive || summary.is_some();
+    tags.extend(emoji::tags_for_text(
+        &database,
+        &format!("{} {}", summary.clone().unwrap_or_default(), content),
+    ));
+
+    let mut activitypub_note = ap_controller::note(
         &actor,
         in_reply_to_id,
         content,
+        summary,
+        sensitive,
         direct_receipients.clone(),
         receipients.clone(),
         tags,
     );
+
+    // Remote servers group replies into the conversation of their parent by these properties
+    let context = conversation::context_for_object(
+        &database,
+        &serde_json::to_value(&activitypub_note).unwrap(),
+    );
+    activitypub_note.conversation_context = Some(serde_json::json!(context));
+    activitypub_note.conversation = Some(serde_json::json!(context));
+
+    if let Some(new_poll) = new_poll {
+        activitypub_note = ap_controller::question(
+            activitypub_note,
+            new_poll.options,
+            (Utc::now() + Duration::seconds(new_poll.expires_in)).to_rfc3339(),
+            new_poll.multiple,
+        );
+    }
     let activitypub_activity_create = ap_controller::create(
         &actor,
         serde_json::to_value(&activitypub_note).unwrap(),
@@ -229,50 +1196,214 @@ pub fn status_build(
         );
     });
 
-    return get_ap_object_by_id(&database, &activitypub_note.id)
-        .unwrap()
-        .id;
+    match get_ap_object_by_id(&database, &activitypub_note.id) {
+        Ok(internal_activity) => {
+            conversation::handle_activity(&database, &internal_activity);
+            Ok(internal_activity.id)
+        }
+        Err(_) => Err(ApiError::internal(
+            "An error occured while posting the status",
+        )),
+    }
 }
 
-pub fn unfollow(actor: String, object: String) {
+pub fn two_factor(
+    pooled_connection: &PooledConnection,
+    token: String,
+) -> ApiResult<serde_json::Value> {
+    let actor = verify_actor(pooled_connection, token)?;
+    Ok(Json(
+        serde_json::json!({ "enabled": two_factor::is_enabled(pooled_connection, &actor) }),
+    ))
+}
+
+/// Confirms the secret of a local actor with a first code and enables two-factor authentication
+///
+/// # Description
+///
+/// The response contains the recovery codes, which aren't retrievable afterwards.
+///
+/// # Parameters
+///
+/// * `pooled_connection` - &PooledConnection | Reference to a database connection
+/// * `token`             -            String | The OAuth token of the local actor
+/// * `code`              -              &str | A code generated by the authenticator app
+pub fn two_factor_confirm(
+    pooled_connection: &PooledConnection,
+    token: String,
+    code: &str,
+) -> ApiResult<serde_json::Value> {
+    let actor = verify_actor(pooled_connection, token)?;
+
+    match two_factor::confirm(pooled_connection, &actor, code) {
+        Ok(recovery_codes) => Ok(Json(
+            serde_json::json!({ "recovery_codes": recovery_codes }),
+        )),
+        Err(e) => Err(ApiError::unprocessable(e)),
+    }
+}
+
+/// Disables two-factor authentication of a local actor, which requires a code or a recovery code
+pub fn two_factor_disable(
+    pooled_connection: &PooledConnection,
+    token: String,
+    code: &str,
+) -> ApiResult<serde_json::Value> {
+    let actor = verify_actor(pooled_connection, token)?;
+
+    if two_factor::verify(pooled_connection, &actor, code) {
+        two_factor::reset(pooled_connection, &actor);
+        Ok(Json(serde_json::json!({})))
+    } else {
+        Err(ApiError::unprocessable("The code is invalid"))
+    }
+}
+
+/// Generates a new two-factor secret for a local actor, which has to be confirmed afterwards
+pub fn two_factor_enroll(
+    pooled_connection: &PooledConnection,
+    token: String,
+) -> ApiResult<serde_json::Value> {
+    let actor = verify_actor(pooled_connection, token)?;
+
+    match two_factor::enroll(pooled_connection, &actor) {
+        Ok(secret) => Ok(Json(serde_json::json!({
+            "secret": secret,
+            "otpauth_uri": two_factor::otpauth_uri(&actor.preferred_username, &secret)
+        }))),
+        Err(e) => Err(ApiError::unprocessable(e)),
+    }
+}
+
+pub fn unfollow(actor: String, object: String) -> Result<(), ApiError> {
     let database = database::establish_connection();
-    let serialized_actor: Actor = get_actor_by_uri(&database, &actor).unwrap();
-
-    match get_actor_by_uri(&database, &object) {
-        Ok(followee) => {
-            if is_actor_followed_by(&database, &followee, &actor).unwrap() {
-                let activitypub_followers: Vec<serde_json::Value> =
-                    serde_json::from_value(followee.followers["activitypub"].to_owned()).unwrap();
-
-                let activitypub_follow_index = activitypub_followers
-                    .iter()
-                    .position(|ref follow| follow["href"].as_str().unwrap() == &actor)
-                    .unwrap();
-                let activitypub_follow_id: String = serde_json::from_value(
-                    activitypub_followers[activitypub_follow_index]["activity_id"].to_owned(),
-                )
-                .unwrap();
-
-                let activitypub_activity_unfollow = ap_controller::undo(
-                    &actor,
-                    get_ap_activity_by_id(&database, &activitypub_follow_id)
-                        .unwrap()
-                        .data,
-                    vec![followee.actor_uri],
-                    vec![],
-                );
-                remove_follow(&object, &actor);
+    let serialized_actor: Actor =
+        get_actor_by_uri(&database, &actor).map_err(|_| ApiError::not_found("User not found."))?;
+    let followee =
+        get_actor_by_uri(&database, &object).map_err(|_| ApiError::not_found("User not found."))?;
 
-                if !followee.local {
-                    federator::enqueue(
-                        serialized_actor,
-                        serde_json::json!(&activitypub_activity_unfollow),
-                        vec![followee.inbox.unwrap()],
-                    );
-                }
+    match is_actor_followed_by(&database, &followee, &actor) {
+        Ok(true) => (),
+        // Unfollowing a locked account withdraws a pending follow request
+        Ok(false) => {
+            if followee.local {
+                let _ = follow_request::remove(&database, &followee, serialized_actor.id);
             }
+            return Ok(());
         }
-        Err(_) => (),
+        Err(_) => return Err(ApiError::internal("An error occured while unfollowing")),
+    }
+
+    let activitypub_followers: Vec<serde_json::Value> =
+        serde_json::from_value(followee.followers["activitypub"].to_owned()).unwrap_or_default();
+    let activitypub_follow = activitypub_followers
+        .iter()
+        .find(|follow| follow["href"].as_str() == Some(actor.as_str()))
+        .and_then(|follow| follow["activity_id"].as_str())
+        .and_then(|follow_id| get_ap_activity_by_id(&database, follow_id).ok());
+    remove_follow(&object, &actor);
+
+    // The follow is removed either way, but it can only be undone if the original `Follow`
+    // activity is still known
+    if let Some(activitypub_follow) = activitypub_follow {
+        let activitypub_activity_unfollow = ap_controller::undo(
+            &actor,
+            activitypub_follow.data,
+            vec![followee.actor_uri],
+            vec![],
+        );
+
+        match followee.inbox {
+            Some(inbox) if !followee.local => federator::enqueue(
+                serialized_actor,
+                serde_json::json!(&activitypub_activity_unfollow),
+                vec![inbox],
+            ),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Federates the current profile of a local actor to their followers
+pub fn update_actor(actor: &Actor) {
+    let database = database::establish_connection();
+    let activitypub_activity_update = ap_controller::update_actor(actor);
+    let mut inboxes = handle_follower_inboxes(&database, &actor.followers);
+    inboxes.sort();
+    inboxes.dedup();
+
+    let serialized_actor = actor.clone();
+    thread::spawn(move || {
+        federator::enqueue(
+            serialized_actor,
+            serde_json::json!(&activitypub_activity_update),
+            inboxes,
+        );
+    });
+}
+
+/// Returns the receipients and inboxes of a reaction on an activity
+fn reaction_receipients(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    activity: Activity,
+) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let ap_activity = serialize_from_internal_activity(activity);
+
+    let mut to: Vec<String> = ap_activity.to.clone();
+    let mut cc: Vec<String> = ap_activity.cc.clone();
+    let mut inboxes: Vec<String> = Vec::new();
+
+    to.retain(|x| x != &format!("{}/followers", &ap_activity.actor));
+    cc.retain(|x| x != &format!("{}/followers", &ap_activity.actor));
+
+    if to.contains(&"https://www.w3.org/ns/activitystreams#Public".to_string()) {
+        cc.push(format!("{}/followers", actor.actor_uri));
+        inboxes = handle_follower_inboxes(db_connection, &actor.followers);
+    } else if cc.contains(&"https://www.w3.org/ns/activitystreams#Public".to_string()) {
+        to.push(format!("{}/followers", actor.actor_uri));
+        inboxes = handle_follower_inboxes(db_connection, &actor.followers);
+    }
+
+    match get_actor_by_uri(db_connection, &ap_activity.actor) {
+        Ok(foreign_actor) => {
+            if !foreign_actor.local {
+                to.push(foreign_actor.actor_uri);
+                inboxes.push(foreign_actor.inbox.unwrap());
+            }
+        }
+        Err(_) => eprintln!(
+            "Error: Actor '{}' should exist in order to create a reaction!",
+            &ap_activity.actor
+        ),
+    }
+
+    to.dedup();
+    cc.dedup();
+    inboxes.dedup();
+    (to, cc, inboxes)
+}
+
+fn verify_actor(pooled_connection: &PooledConnection, token: String) -> Result<Actor, ApiError> {
+    match verify_token(pooled_connection, token) {
+        Ok(token) => match get_local_actor_by_preferred_username(pooled_connection, &token.actor) {
+            Ok(actor) => Ok(actor),
+            Err(_) => Err(ApiError::unauthorized("Account not found")),
+        },
+        Err(_) => Err(ApiError::unauthorized("OAuth token invalid")),
+    }
+}
+
+fn verify_admin(pooled_connection: &PooledConnection, token: String) -> Result<Actor, ApiError> {
+    let actor = verify_actor(pooled_connection, token)?;
+
+    if is_admin(pooled_connection, &actor) {
+        Ok(actor)
+    } else {
+        Err(ApiError::forbidden(
+            "This action requires administrative privileges",
+        ))
     }
 }
 
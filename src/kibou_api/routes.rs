@@ -1,8 +1,327 @@
+use api_error::{ApiError, ApiResult};
 use database::PooledConnection;
-use kibou_api;
-use rocket_contrib::json::JsonValue;
+use emoji;
+use kibou_api::{
+    self, AliasForm, EmailPreferencesForm, ImportForm, InviteForm, MoveAccountForm, RelayForm,
+    TwoFactorCodeForm,
+};
+use mastodon_api;
+use oauth::authorized_application;
+use oauth::scope::{self, AdminRead, AdminWrite, Authorized, ReadAccounts, Write, WriteAccounts};
+use registration;
+use rocket::data::Data;
+use rocket::http::ContentType;
+use rocket::request::LenientForm;
+use rocket::response::content::Content;
+use serde_json;
+use std::io::Read;
 
 #[get("/api/kibou/activities")]
-pub fn activities(pooled_connection: PooledConnection) -> JsonValue {
+pub fn activities(pooled_connection: PooledConnection) -> ApiResult<Vec<mastodon_api::Status>> {
     return kibou_api::public_activities(&pooled_connection);
 }
+
+#[get("/api/kibou/admin/emojis")]
+pub fn admin_emojis(
+    pooled_connection: PooledConnection,
+    _token: Authorized<AdminRead>,
+) -> ApiResult<Vec<mastodon_api::Emoji>> {
+    return kibou_api::admin_emojis(&pooled_connection, _token.access_token());
+}
+
+// The image is expected as the raw request body, e.g. `Content-Type: image/png`
+#[post("/api/kibou/admin/emojis/<shortcode>?<category>", data = "<image>")]
+pub fn admin_emoji_create(
+    pooled_connection: PooledConnection,
+    _token: Authorized<AdminWrite>,
+    content_type: &ContentType,
+    shortcode: String,
+    category: Option<String>,
+    image: Data,
+) -> ApiResult<mastodon_api::Emoji> {
+    let extension = match content_type.sub().as_str() {
+        "jpeg" => "jpg",
+        subtype => subtype,
+    };
+    let mut image_data: Vec<u8> = Vec::new();
+
+    // Reading one byte more than allowed lets `emoji::add` tell oversized images apart from ones
+    // at the limit
+    if let Err(_) = image
+        .open()
+        .take(emoji::MAX_IMAGE_SIZE + 1)
+        .read_to_end(&mut image_data)
+    {
+        return Err(ApiError::bad_request("Unable to read image"));
+    }
+
+    return kibou_api::admin_emoji_create(
+        &pooled_connection,
+        _token.access_token(),
+        &shortcode,
+        extension,
+        &image_data,
+        category,
+    );
+}
+
+#[delete("/api/kibou/admin/emojis/<shortcode>")]
+pub fn admin_emoji_delete(
+    pooled_connection: PooledConnection,
+    _token: Authorized<AdminWrite>,
+    shortcode: String,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::admin_emoji_delete(&pooled_connection, _token.access_token(), &shortcode);
+}
+
+#[get("/api/kibou/admin/relays")]
+pub fn admin_relays(
+    pooled_connection: PooledConnection,
+    _token: Authorized<AdminRead>,
+) -> ApiResult<Vec<serde_json::Value>> {
+    return kibou_api::admin_relays(&pooled_connection, _token.access_token());
+}
+
+#[post("/api/kibou/admin/relays", data = "<form>")]
+pub fn admin_relay_follow(
+    pooled_connection: PooledConnection,
+    _token: Authorized<AdminWrite>,
+    form: LenientForm<RelayForm>,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::admin_relay_follow(
+        &pooled_connection,
+        _token.access_token(),
+        &form.relay_url,
+    );
+}
+
+#[delete("/api/kibou/admin/relays?<form..>")]
+pub fn admin_relay_unfollow(
+    pooled_connection: PooledConnection,
+    _token: Authorized<AdminWrite>,
+    form: LenientForm<RelayForm>,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::admin_relay_unfollow(
+        &pooled_connection,
+        _token.access_token(),
+        &form.relay_url,
+    );
+}
+
+#[get("/api/kibou/admin/registrations")]
+pub fn admin_registrations(
+    pooled_connection: PooledConnection,
+    _token: Authorized<AdminRead>,
+) -> ApiResult<Vec<registration::SerializedPendingRegistration>> {
+    return kibou_api::admin_registrations(&pooled_connection, _token.access_token());
+}
+
+#[post("/api/kibou/admin/registrations/<username>/approve")]
+pub fn admin_registration_approve(
+    pooled_connection: PooledConnection,
+    _token: Authorized<AdminWrite>,
+    username: String,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::admin_registration_review(
+        &pooled_connection,
+        _token.access_token(),
+        &username,
+        true,
+    );
+}
+
+#[post("/api/kibou/admin/registrations/<username>/reject")]
+pub fn admin_registration_reject(
+    pooled_connection: PooledConnection,
+    _token: Authorized<AdminWrite>,
+    username: String,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::admin_registration_review(
+        &pooled_connection,
+        _token.access_token(),
+        &username,
+        false,
+    );
+}
+
+#[delete("/api/kibou/admin/accounts/<username>/two_factor")]
+pub fn admin_two_factor_reset(
+    pooled_connection: PooledConnection,
+    _token: Authorized<AdminWrite>,
+    username: String,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::admin_two_factor_reset(&pooled_connection, _token.access_token(), &username);
+}
+
+#[get("/api/kibou/aliases")]
+pub fn aliases(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadAccounts>,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::aliases(&pooled_connection, _token.access_token());
+}
+
+#[post("/api/kibou/aliases", data = "<form>")]
+pub fn alias_add(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    form: LenientForm<AliasForm>,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::alias_add(&pooled_connection, _token.access_token(), &form.alias);
+}
+
+#[delete("/api/kibou/aliases?<form..>")]
+pub fn alias_remove(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    form: LenientForm<AliasForm>,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::alias_remove(&pooled_connection, _token.access_token(), &form.alias);
+}
+
+#[get("/api/kibou/authorized_applications")]
+pub fn authorized_applications(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadAccounts>,
+) -> ApiResult<Vec<authorized_application::AuthorizedApplication>> {
+    return kibou_api::authorized_applications(&pooled_connection, _token.access_token());
+}
+
+#[delete("/api/kibou/authorized_applications/<id>")]
+pub fn authorized_application_revoke(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::authorized_application_revoke(&pooled_connection, _token.access_token(), id);
+}
+
+#[get("/api/kibou/email_preferences")]
+pub fn email_preferences(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadAccounts>,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::email_preferences(&pooled_connection, _token.access_token());
+}
+
+#[put("/api/kibou/email_preferences", data = "<form>")]
+pub fn email_preferences_update(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    form: LenientForm<EmailPreferencesForm>,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::email_preferences_update(
+        &pooled_connection,
+        _token.access_token(),
+        form.mentions,
+        form.follows,
+    );
+}
+
+#[get("/api/kibou/export/<file>")]
+pub fn export(
+    pooled_connection: PooledConnection,
+    _token: Authorized<scope::Read>,
+    file: String,
+) -> Result<Content<Vec<u8>>, ApiError> {
+    let content_type = if file.ends_with(".csv") {
+        ContentType::CSV
+    } else {
+        ContentType::new("application", "x-tar")
+    };
+
+    match kibou_api::export(&pooled_connection, _token.access_token(), &file) {
+        Ok(data) => Ok(Content(content_type, data)),
+        Err(e) => Err(e),
+    }
+}
+
+// The CSV file is expected as the `data` field of a `multipart/form-data` body
+#[post("/api/kibou/import", data = "<form>")]
+pub fn import(
+    pooled_connection: PooledConnection,
+    _token: Authorized<Write>,
+    form: ImportForm,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::import(&pooled_connection, _token.access_token(), form);
+}
+
+#[get("/api/kibou/invites")]
+pub fn invites(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadAccounts>,
+) -> ApiResult<Vec<registration::SerializedInvite>> {
+    return kibou_api::invites(&pooled_connection, _token.access_token());
+}
+
+#[post("/api/kibou/invites", data = "<form>")]
+pub fn invite_create(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    form: LenientForm<InviteForm>,
+) -> ApiResult<registration::SerializedInvite> {
+    return kibou_api::invite_create(
+        &pooled_connection,
+        _token.access_token(),
+        form.max_uses,
+        form.expires_in,
+    );
+}
+
+#[delete("/api/kibou/invites/<id>")]
+pub fn invite_delete(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::invite_delete(&pooled_connection, _token.access_token(), id);
+}
+
+#[post("/api/kibou/move_account", data = "<form>")]
+pub fn move_account(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    form: LenientForm<MoveAccountForm>,
+) -> ApiResult<serde_json::Value> {
+    let form = form.into_inner();
+    return kibou_api::move_account(
+        &pooled_connection,
+        _token.access_token(),
+        &form.target_account,
+        form.password,
+    );
+}
+
+#[get("/api/kibou/two_factor")]
+pub fn two_factor(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadAccounts>,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::two_factor(&pooled_connection, _token.access_token());
+}
+
+#[post("/api/kibou/two_factor")]
+pub fn two_factor_enroll(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::two_factor_enroll(&pooled_connection, _token.access_token());
+}
+
+#[post("/api/kibou/two_factor/confirm", data = "<form>")]
+pub fn two_factor_confirm(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    form: LenientForm<TwoFactorCodeForm>,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::two_factor_confirm(&pooled_connection, _token.access_token(), &form.code);
+}
+
+#[delete("/api/kibou/two_factor?<form..>")]
+pub fn two_factor_disable(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    form: LenientForm<TwoFactorCodeForm>,
+) -> ApiResult<serde_json::Value> {
+    return kibou_api::two_factor_disable(&pooled_connection, _token.access_token(), &form.code);
+}
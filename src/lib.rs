@@ -24,16 +24,34 @@ extern crate uuid;
 mod activity;
 mod activitypub;
 pub mod actor;
+pub mod api_error;
+mod bookmark;
+mod conversation;
 pub mod database;
+pub mod email;
+pub mod emoji;
 pub mod env;
+mod export;
+mod filter;
+mod follow_request;
 mod html;
 mod kibou_api;
+mod list;
+mod mailer;
+mod marker;
 mod mastodon_api;
+mod media;
 mod notification;
 mod oauth;
+mod pin;
+mod poll;
 pub mod raito_fe;
+mod reaction;
+pub mod registration;
+pub mod relay;
 mod tests;
 mod timeline;
+pub mod two_factor;
 mod web;
 mod well_known;
 
@@ -44,32 +62,124 @@ pub fn rocket_app(config: rocket::config::Config) -> rocket::Rocket {
             routes![
                 activitypub::routes::activity,
                 activitypub::routes::actor,
+                activitypub::routes::actor_featured,
                 activitypub::routes::actor_inbox,
                 activitypub::routes::object,
+                activitypub::routes::object_replies,
                 activitypub::routes::inbox
             ],
         )
-        .mount("/", routes![kibou_api::routes::activities])
+        .mount(
+            "/",
+            routes![
+                kibou_api::routes::activities,
+                kibou_api::routes::admin_emoji_create,
+                kibou_api::routes::admin_emoji_delete,
+                kibou_api::routes::admin_emojis,
+                kibou_api::routes::admin_registration_approve,
+                kibou_api::routes::admin_registration_reject,
+                kibou_api::routes::admin_registrations,
+                kibou_api::routes::admin_relay_follow,
+                kibou_api::routes::admin_relay_unfollow,
+                kibou_api::routes::admin_relays,
+                kibou_api::routes::admin_two_factor_reset,
+                kibou_api::routes::alias_add,
+                kibou_api::routes::alias_remove,
+                kibou_api::routes::aliases,
+                kibou_api::routes::authorized_application_revoke,
+                kibou_api::routes::authorized_applications,
+                kibou_api::routes::email_preferences,
+                kibou_api::routes::email_preferences_update,
+                kibou_api::routes::export,
+                kibou_api::routes::import,
+                kibou_api::routes::invite_create,
+                kibou_api::routes::invite_delete,
+                kibou_api::routes::invites,
+                kibou_api::routes::move_account,
+                kibou_api::routes::two_factor,
+                kibou_api::routes::two_factor_confirm,
+                kibou_api::routes::two_factor_disable,
+                kibou_api::routes::two_factor_enroll
+            ],
+        )
         .mount(
             "/",
             routes![
                 mastodon_api::routes::account,
+                mastodon_api::routes::account_create,
                 mastodon_api::routes::account_follow,
+                mastodon_api::routes::account_followers,
+                mastodon_api::routes::account_following,
+                mastodon_api::routes::account_lists,
+                mastodon_api::routes::account_relationships,
                 mastodon_api::routes::account_statuses,
                 mastodon_api::routes::account_unfollow,
+                mastodon_api::routes::account_update_credentials,
                 mastodon_api::routes::account_verify_credentials,
                 mastodon_api::routes::application,
+                mastodon_api::routes::bookmarks,
+                mastodon_api::routes::conversation_read,
+                mastodon_api::routes::conversations,
                 mastodon_api::routes::custom_emojis,
+                mastodon_api::routes::direct_timeline,
+                mastodon_api::routes::favourites,
+                mastodon_api::routes::filter,
+                mastodon_api::routes::filter_create,
+                mastodon_api::routes::filter_delete,
+                mastodon_api::routes::filter_keyword,
+                mastodon_api::routes::filter_keyword_add,
+                mastodon_api::routes::filter_keyword_delete,
+                mastodon_api::routes::filter_keyword_update,
+                mastodon_api::routes::filter_keywords,
+                mastodon_api::routes::filter_status,
+                mastodon_api::routes::filter_status_add,
+                mastodon_api::routes::filter_status_delete,
+                mastodon_api::routes::filter_statuses,
+                mastodon_api::routes::filter_update,
                 mastodon_api::routes::filters,
+                mastodon_api::routes::follow_request_authorize,
+                mastodon_api::routes::follow_request_reject,
+                mastodon_api::routes::follow_requests,
                 mastodon_api::routes::home_timeline,
                 mastodon_api::routes::instance,
+                mastodon_api::routes::list,
+                mastodon_api::routes::list_accounts,
+                mastodon_api::routes::list_accounts_add,
+                mastodon_api::routes::list_accounts_remove,
+                mastodon_api::routes::list_create,
+                mastodon_api::routes::list_delete,
+                mastodon_api::routes::list_timeline,
+                mastodon_api::routes::list_update,
+                mastodon_api::routes::lists,
+                mastodon_api::routes::markers,
+                mastodon_api::routes::markers_update,
+                mastodon_api::routes::notification,
+                mastodon_api::routes::notification_dismiss,
                 mastodon_api::routes::notifications,
+                mastodon_api::routes::notifications_clear,
+                mastodon_api::routes::poll,
+                mastodon_api::routes::poll_vote,
                 mastodon_api::routes::status,
+                mastodon_api::routes::status_bookmark,
                 mastodon_api::routes::status_context,
                 mastodon_api::routes::status_favourite,
+                mastodon_api::routes::status_favourited_by,
+                mastodon_api::routes::status_pin,
                 mastodon_api::routes::status_post,
+                mastodon_api::routes::status_react,
+                mastodon_api::routes::status_reactions,
+                mastodon_api::routes::status_reactions_by_emoji,
                 mastodon_api::routes::status_reblog,
+                mastodon_api::routes::status_reblogged_by,
+                mastodon_api::routes::status_unbookmark,
+                mastodon_api::routes::status_unpin,
+                mastodon_api::routes::status_unreact,
                 mastodon_api::routes::public_timeline,
+                mastodon_api::routes::v1_filter,
+                mastodon_api::routes::v1_filter_create,
+                mastodon_api::routes::v1_filter_delete,
+                mastodon_api::routes::v1_filter_update,
+                mastodon_api::routes::v1_filters,
                 mastodon_api::routes::options_account,
                 mastodon_api::routes::options_account_statuses,
                 mastodon_api::routes::options_account_verify_credentials,
@@ -85,15 +195,26 @@ pub fn rocket_app(config: rocket::config::Config) -> rocket::Rocket {
             routes![
                 oauth::routes::authorize,
                 oauth::routes::authorize_result,
-                oauth::routes::token
+                oauth::routes::token,
+                oauth::routes::token_revoke
             ],
         )
         .mount("/", routes![well_known::webfinger::webfinger])
+        .register(catchers![
+            oauth::routes::forbidden,
+            oauth::routes::unauthorized
+        ])
         .mount(
             "/static",
             rocket_contrib::serve::StaticFiles::from("static"),
         )
-        .attach(rocket_contrib::templates::Template::fairing());
+        .attach(rocket_contrib::templates::Template::fairing())
+        .attach(rocket::fairing::AdHoc::on_launch("OAuth sweeper", |_| {
+            oauth::sweeper::start()
+        }))
+        .attach(rocket::fairing::AdHoc::on_launch("Poll notifier", |_| {
+            poll::start_notifier()
+        }));
 
     // Avoid mounting nodeinfo routes if the admin has disabled
     // nodeinfo in the config file.
@@ -0,0 +1,207 @@
+use actor::{get_actor_by_id, is_actor_followed_by, Actor};
+use chrono::NaiveDateTime;
+use database::models::{InsertList, InsertListMember};
+use database::schema::list_members;
+use database::schema::lists;
+use diesel::pg::PgConnection;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+pub const MAX_TITLE_CHARS: usize = 100;
+
+#[derive(Queryable, PartialEq, QueryableByName, Clone)]
+#[table_name = "lists"]
+pub struct List {
+    pub id: i64,
+    pub actor_id: i64,
+    pub title: String,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+/// Creates a new list for a local actor
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `owner`         -       &Actor | The local actor who owns the list
+/// * `title`         -         &str | The title of the list
+pub fn create(
+    db_connection: &PgConnection,
+    owner: &Actor,
+    title: &str,
+) -> Result<List, &'static str> {
+    let title = validate_title(title)?;
+
+    match diesel::insert_into(lists::table)
+        .values(InsertList {
+            actor_id: owner.id,
+            title: &title,
+        })
+        .get_result::<List>(db_connection)
+    {
+        Ok(list) => Ok(list),
+        Err(_) => Err("Unable to create list"),
+    }
+}
+
+pub fn delete(db_connection: &PgConnection, list: &List) {
+    diesel::delete(lists::table.filter(lists::id.eq(list.id)))
+        .execute(db_connection)
+        .expect("Could not delete list");
+}
+
+pub fn rename(
+    db_connection: &PgConnection,
+    list: &List,
+    title: &str,
+) -> Result<List, &'static str> {
+    let title = validate_title(title)?;
+
+    match diesel::update(lists::table.filter(lists::id.eq(list.id)))
+        .set(lists::title.eq(title))
+        .get_result::<List>(db_connection)
+    {
+        Ok(list) => Ok(list),
+        Err(_) => Err("Unable to rename list"),
+    }
+}
+
+/// Returns a list, but only if it's owned by the given actor
+pub fn get_list_for_actor(
+    db_connection: &PgConnection,
+    owner: &Actor,
+    id: i64,
+) -> Result<List, diesel::result::Error> {
+    lists::table
+        .filter(lists::id.eq(id))
+        .filter(lists::actor_id.eq(owner.id))
+        .first::<List>(db_connection)
+}
+
+pub fn lists_for_actor(
+    db_connection: &PgConnection,
+    owner: &Actor,
+) -> Result<Vec<List>, diesel::result::Error> {
+    lists::table
+        .filter(lists::actor_id.eq(owner.id))
+        .order(lists::id.asc())
+        .load::<List>(db_connection)
+}
+
+/// Returns all lists of an owner which contain a certain actor
+pub fn lists_containing_actor(
+    db_connection: &PgConnection,
+    owner: &Actor,
+    member: &Actor,
+) -> Result<Vec<List>, diesel::result::Error> {
+    lists::table
+        .inner_join(list_members::table)
+        .filter(lists::actor_id.eq(owner.id))
+        .filter(list_members::actor_id.eq(member.id))
+        .select(lists::all_columns)
+        .order(lists::id.asc())
+        .load::<List>(db_connection)
+}
+
+pub fn members(db_connection: &PgConnection, list: &List) -> Vec<Actor> {
+    list_members::table
+        .filter(list_members::list_id.eq(list.id))
+        .select(list_members::actor_id)
+        .order(list_members::id.asc())
+        .load::<i64>(db_connection)
+        .unwrap_or_else(|_| Vec::new())
+        .iter()
+        .filter_map(|actor_id| get_actor_by_id(db_connection, actor_id).ok())
+        .collect()
+}
+
+/// Adds actors to a list
+///
+/// # Description
+///
+/// Only actors followed by the list's owner can become members, as a list timeline is a subset
+/// of the owner's home timeline.
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `owner`         -       &Actor | The local actor who owns the list
+/// * `list`          -        &List | The list the actors are added to
+/// * `new_members`   -   Vec<Actor> | The actors which are added to the list
+pub fn add_members(
+    db_connection: &PgConnection,
+    owner: &Actor,
+    list: &List,
+    new_members: Vec<Actor>,
+) -> Result<(), &'static str> {
+    for member in &new_members {
+        if !is_actor_followed_by(db_connection, member, &owner.actor_uri).unwrap_or(false) {
+            return Err("Only followed accounts can be added to a list");
+        }
+    }
+
+    insert_members(db_connection, list, new_members);
+    Ok(())
+}
+
+/// Adds actors to a list while the owner's follows of them may still be pending
+///
+/// # Description
+///
+/// Imported lists are created right after their members have been followed, before remote and
+/// locked accounts could accept the follows. As list timelines only include members which are
+/// followed, their statuses show up as soon as a follow is accepted.
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `list`          -        &List | The list the actors are added to
+/// * `new_members`   -   Vec<Actor> | The actors which are added to the list
+pub fn add_requested_members(db_connection: &PgConnection, list: &List, new_members: Vec<Actor>) {
+    insert_members(db_connection, list, new_members);
+}
+
+pub fn remove_members(db_connection: &PgConnection, list: &List, member_ids: Vec<i64>) {
+    diesel::delete(
+        list_members::table
+            .filter(list_members::list_id.eq(list.id))
+            .filter(list_members::actor_id.eq_any(member_ids)),
+    )
+    .execute(db_connection)
+    .expect("Error removing list members");
+}
+
+fn insert_members(db_connection: &PgConnection, list: &List, new_members: Vec<Actor>) {
+    for member in new_members {
+        diesel::insert_into(list_members::table)
+            .values(InsertListMember {
+                list_id: list.id,
+                actor_id: member.id,
+            })
+            .on_conflict_do_nothing()
+            .execute(db_connection)
+            .expect("Error adding list member");
+    }
+}
+
+/// Returns a trimmed list title, if it's neither empty nor too long
+///
+/// # Parameters
+///
+/// * `title` - &str | The title of a list
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/list.rs`
+/// - validate_title()
+pub fn validate_title(title: &str) -> Result<String, &'static str> {
+    let title = title.trim();
+
+    if title.is_empty() {
+        Err("Lists need a title")
+    } else if title.chars().count() > MAX_TITLE_CHARS {
+        Err("The title of the list is too long")
+    } else {
+        Ok(title.to_string())
+    }
+}
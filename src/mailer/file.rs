@@ -0,0 +1,22 @@
+//! Writes e-mails to files instead of sending them, which is meant for development and testing
+//!
+
+use chrono::Utc;
+use env;
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+pub fn deliver(to: &str, message: &str) -> Result<(), String> {
+    let directory = env::get_value(String::from("mail.file_path"));
+    let path = Path::new(&directory).join(format!(
+        "{}-{}.eml",
+        Utc::now().format("%Y%m%d%H%M%S"),
+        Uuid::new_v4()
+    ));
+
+    fs::create_dir_all(&directory).map_err(|e| e.to_string())?;
+    fs::write(&path, message).map_err(|e| e.to_string())?;
+    println!("Wrote e-mail to {} into {}", to, path.display());
+    Ok(())
+}
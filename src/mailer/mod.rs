@@ -0,0 +1,202 @@
+//! Outgoing e-mails, which are rendered from the Tera templates in `templates/mail`
+//!
+//! The backend is configured by `mail.backend`: `smtp` delivers e-mails to an SMTP server, `file`
+//! writes them to `mail.file_path` instead, which is meant for development and testing. Without a
+//! backend no e-mails are sent, features relying on them are unavailable then.
+//!
+
+pub mod file;
+pub mod smtp;
+
+use base64;
+use chrono::Utc;
+use env;
+use rocket_contrib::templates::Template;
+use serde_json;
+use std::sync::Mutex;
+use std::thread;
+use uuid::Uuid;
+
+lazy_static! {
+    // Templates are only loaded once, rendering them needs a Rocket instance with the template
+    // fairing attached
+    static ref MAIL_RENDERER: Mutex<rocket::Rocket> =
+        Mutex::new(rocket::ignite().attach(Template::fairing()));
+}
+
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+pub enum Backend {
+    Smtp,
+    File,
+    Disabled,
+}
+
+pub fn backend() -> Backend {
+    match env::get_value(String::from("mail.backend")).as_str() {
+        "smtp" => Backend::Smtp,
+        "file" => Backend::File,
+        _ => Backend::Disabled,
+    }
+}
+
+pub fn is_enabled() -> bool {
+    match backend() {
+        Backend::Disabled => false,
+        _ => true,
+    }
+}
+
+/// Renders an e-mail and delivers it in the background
+///
+/// # Parameters
+///
+/// * `template` -              &str | Name of a template in `templates/mail`, e.g. `confirmation`
+/// * `to`       -              &str | The e-mail address of the receipient
+/// * `subject`  -              &str | The subject of the e-mail
+/// * `context`  - serde_json::Value | The template's context, `instance_name` and `base_url` are
+///                                    added to it
+///
+pub fn send(template: &str, to: &str, subject: &str, mut context: serde_json::Value) {
+    if !is_enabled() {
+        return;
+    }
+
+    context["instance_name"] = serde_json::json!(env::get_value(String::from("node.name")));
+    context["base_url"] = serde_json::json!(base_url());
+
+    let rendered_body = match MAIL_RENDERER.lock() {
+        Ok(renderer) => Template::show(&renderer, format!("mail/{}", template), context),
+        Err(_) => None,
+    };
+    let body = match rendered_body {
+        Some(body) => body,
+        None => {
+            eprintln!("Unable to render e-mail template '{}'", template);
+            return;
+        }
+    };
+    let email = Email {
+        to: to.to_string(),
+        subject: subject.to_string(),
+        body: body,
+    };
+
+    thread::spawn(move || {
+        if let Err(e) = deliver(&email) {
+            eprintln!("Unable to send e-mail to {}: {}", email.to, e);
+        }
+    });
+}
+
+pub fn deliver(email: &Email) -> Result<(), String> {
+    // Line breaks would inject headers or even SMTP commands
+    if [&email.to, &email.subject]
+        .iter()
+        .any(|value| value.contains(|character: char| character == '\r' || character == '\n'))
+    {
+        return Err(String::from("Line breaks aren't allowed in headers"));
+    }
+
+    let from = sender();
+    let message = format_message(
+        &from,
+        email,
+        &Utc::now().to_rfc2822(),
+        &format!(
+            "{}@{}",
+            Uuid::new_v4(),
+            env::get_value(String::from("endpoint.base_domain"))
+        ),
+    );
+
+    match backend() {
+        Backend::Smtp => smtp::deliver(&from, &email.to, &message),
+        Backend::File => file::deliver(&email.to, &message),
+        Backend::Disabled => Ok(()),
+    }
+}
+
+/// Formats an e-mail as an RFC 5322 message with a plain text body
+///
+/// # Description
+///
+/// Line breaks are normalized to CRLF and header values end at their first line break, so that no
+/// headers can be injected. Non-ASCII subjects are encoded according to RFC 2047.
+///
+/// # Parameters
+///
+/// * `from`       -   &str | The e-mail address of the sender
+/// * `email`      - &Email | The e-mail which is formatted
+/// * `date`       -   &str | The date of the e-mail according to RFC 2822
+/// * `message_id` -   &str | A globally unique ID, without angle brackets
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/mailer.rs`
+/// - format_message()
+pub fn format_message(from: &str, email: &Email, date: &str, message_id: &str) -> String {
+    let header = |value: &str| {
+        value
+            .split(|character: char| character == '\r' || character == '\n')
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+    let subject = if email.subject.is_ascii() {
+        header(&email.subject)
+    } else {
+        format!("=?UTF-8?B?{}?=", base64::encode(&header(&email.subject)))
+    };
+    let body = email
+        .body
+        .trim_end()
+        .lines()
+        .collect::<Vec<&str>>()
+        .join("\r\n");
+
+    format!(
+        "From: {from}\r\n\
+         To: {to}\r\n\
+         Subject: {subject}\r\n\
+         Date: {date}\r\n\
+         Message-ID: <{message_id}>\r\n\
+         MIME-Version: 1.0\r\n\
+         Content-Type: text/plain; charset=utf-8\r\n\
+         Content-Transfer-Encoding: 8bit\r\n\
+         \r\n\
+         {body}",
+        from = header(from),
+        to = header(&email.to),
+        subject = subject,
+        date = date,
+        message_id = message_id,
+        body = body
+    )
+}
+
+pub fn base_url() -> String {
+    format!(
+        "{base_scheme}://{base_domain}",
+        base_scheme = env::get_value(String::from("endpoint.base_scheme")),
+        base_domain = env::get_value(String::from("endpoint.base_domain"))
+    )
+}
+
+// The address e-mails are sent from, `noreply@` the instance's domain if none is configured
+fn sender() -> String {
+    let from = env::get_value(String::from("mail.from"));
+
+    if from.is_empty() {
+        format!(
+            "noreply@{}",
+            env::get_value(String::from("endpoint.base_domain"))
+        )
+    } else {
+        from
+    }
+}
@@ -0,0 +1,168 @@
+//! A minimal SMTP client (RFC 5321), which supports STARTTLS, implicit TLS and `AUTH PLAIN`
+//!
+//! `mail.smtp_security` is either `starttls`, `tls` or `none`. Credentials are only sent if
+//! `mail.smtp_username` is set.
+//!
+
+use base64;
+use env;
+use openssl::ssl::{SslConnector, SslMethod};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+// Seconds after which an unresponsive server is given up on
+const TIMEOUT: u64 = 30;
+
+struct Connection<S: Read + Write> {
+    reader: BufReader<S>,
+}
+
+impl<S: Read + Write> Connection<S> {
+    fn new(stream: S) -> Connection<S> {
+        Connection {
+            reader: BufReader::new(stream),
+        }
+    }
+
+    fn command(&mut self, command: &str, expected: &[u16]) -> Result<String, String> {
+        self.reader
+            .get_mut()
+            .write_all(format!("{}\r\n", command).as_bytes())
+            .map_err(|e| e.to_string())?;
+        self.reply(expected)
+    }
+
+    // Reads a reply, the last line of multiline replies has a space after the code instead of a
+    // hyphen
+    fn reply(&mut self, expected: &[u16]) -> Result<String, String> {
+        let mut reply = String::new();
+
+        loop {
+            let mut line = String::new();
+            if self
+                .reader
+                .read_line(&mut line)
+                .map_err(|e| e.to_string())?
+                == 0
+            {
+                return Err(String::from("The SMTP server closed the connection"));
+            }
+            reply.push_str(&line);
+
+            if line.len() < 4 || line.as_bytes()[3] != b'-' {
+                break;
+            }
+        }
+
+        match reply.get(0..3).and_then(|code| code.parse::<u16>().ok()) {
+            Some(code) if expected.contains(&code) => Ok(reply),
+            _ => Err(format!("Unexpected SMTP reply: {}", reply.trim())),
+        }
+    }
+
+    fn into_inner(self) -> S {
+        self.reader.into_inner()
+    }
+}
+
+pub fn deliver(from: &str, to: &str, message: &str) -> Result<(), String> {
+    let host = env::get_value(String::from("mail.smtp_host"));
+    let port = env::get_value(String::from("mail.smtp_port"))
+        .parse::<u16>()
+        .unwrap_or(587);
+    let security = env::get_value(String::from("mail.smtp_security"));
+
+    let stream = TcpStream::connect((host.as_str(), port)).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(Duration::from_secs(TIMEOUT)))
+        .map_err(|e| e.to_string())?;
+    stream
+        .set_write_timeout(Some(Duration::from_secs(TIMEOUT)))
+        .map_err(|e| e.to_string())?;
+    let connector = SslConnector::builder(SslMethod::tls())
+        .map_err(|e| e.to_string())?
+        .build();
+
+    match security.as_str() {
+        "tls" => {
+            let tls_stream = connector
+                .connect(&host, stream)
+                .map_err(|e| e.to_string())?;
+            let mut connection = Connection::new(tls_stream);
+            connection.reply(&[220])?;
+            connection.command(&ehlo(), &[250])?;
+            transaction(connection, from, to, message)
+        }
+        "none" => {
+            let mut connection = Connection::new(stream);
+            connection.reply(&[220])?;
+            connection.command(&ehlo(), &[250])?;
+            transaction(connection, from, to, message)
+        }
+        _ => {
+            let mut connection = Connection::new(stream);
+            connection.reply(&[220])?;
+            connection.command(&ehlo(), &[250])?;
+            connection.command("STARTTLS", &[220])?;
+
+            let tls_stream = connector
+                .connect(&host, connection.into_inner())
+                .map_err(|e| e.to_string())?;
+            let mut connection = Connection::new(tls_stream);
+            connection.command(&ehlo(), &[250])?;
+            transaction(connection, from, to, message)
+        }
+    }
+}
+
+/// Escapes lines starting with a dot, which would otherwise end the message early
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/mailer.rs`
+/// - dot_stuff()
+pub fn dot_stuff(message: &str) -> String {
+    message
+        .split("\r\n")
+        .map(|line| {
+            if line.starts_with('.') {
+                format!(".{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\r\n")
+}
+
+fn ehlo() -> String {
+    format!(
+        "EHLO {}",
+        env::get_value(String::from("endpoint.base_domain"))
+    )
+}
+
+fn transaction<S: Read + Write>(
+    mut connection: Connection<S>,
+    from: &str,
+    to: &str,
+    message: &str,
+) -> Result<(), String> {
+    let username = env::get_value(String::from("mail.smtp_username"));
+
+    if !username.is_empty() {
+        let password = env::get_value(String::from("mail.smtp_password"));
+        let credentials = base64::encode(&format!("\0{}\0{}", username, password));
+        connection.command(&format!("AUTH PLAIN {}", credentials), &[235])?;
+    }
+
+    connection.command(&format!("MAIL FROM:<{}>", from), &[250])?;
+    connection.command(&format!("RCPT TO:<{}>", to), &[250, 251])?;
+    connection.command("DATA", &[354])?;
+    connection.command(&format!("{}\r\n.", dot_stuff(message)), &[250])?;
+
+    // The e-mail was accepted at this point, it doesn't matter how the server says goodbye
+    let _ = connection.command("QUIT", &[221]);
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use actor::Actor;
+use chrono::NaiveDateTime;
+use database::models::InsertMarker;
+use database::schema::markers;
+use diesel::pg::PgConnection;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+// The timelines whose read positions are synchronized between clients
+pub const TIMELINES: [&str; 2] = ["home", "notifications"];
+
+#[derive(Queryable, PartialEq, QueryableByName, Clone)]
+#[table_name = "markers"]
+pub struct Marker {
+    pub id: i64,
+    pub actor_id: i64,
+    pub timeline: String,
+    pub last_read_id: i64,
+    pub version: i32,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+/// Returns the read markers of a local actor
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -        &Actor | The local actor who owns the markers
+/// * `timelines`     -     &[String] | The requested timelines, all of them if empty
+pub fn markers_for_actor(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    timelines: &[String],
+) -> Result<Vec<Marker>, diesel::result::Error> {
+    let mut query = markers::table
+        .filter(markers::actor_id.eq(actor.id))
+        .into_boxed();
+
+    if !timelines.is_empty() {
+        query = query.filter(markers::timeline.eq_any(timelines.to_vec()));
+    }
+
+    query.load::<Marker>(db_connection)
+}
+
+/// Moves the read marker of a timeline, every update increases its version
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -        &Actor | The local actor who owns the marker
+/// * `timeline`      -          &str | Either `home` or `notifications`
+/// * `last_read_id`  -           i64 | The ID of the last read status or notification
+pub fn set(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    timeline: &str,
+    last_read_id: i64,
+) -> Result<Marker, &'static str> {
+    if !TIMELINES.contains(&timeline) {
+        return Err("Unknown timeline");
+    }
+
+    match diesel::insert_into(markers::table)
+        .values(InsertMarker {
+            actor_id: actor.id,
+            timeline: timeline,
+            last_read_id: last_read_id,
+        })
+        .on_conflict((markers::actor_id, markers::timeline))
+        .do_update()
+        .set((
+            markers::last_read_id.eq(last_read_id),
+            markers::version.eq(markers::version + 1),
+        ))
+        .get_result::<Marker>(db_connection)
+    {
+        Ok(marker) => Ok(marker),
+        Err(_) => Err("Error updating marker"),
+    }
+}
@@ -3,53 +3,105 @@ use activity::{
     get_activities_by_id, get_ap_object_by_id, get_ap_object_replies_by_id,
     type_exists_for_object_id,
 };
+use activitypub::controller as ap_controller;
 use actor;
+use api_error::{ApiError, ApiResult};
+use bookmark;
 use chrono;
 use chrono::Utc;
+use conversation;
+use conversation::ParticipantConversation;
 use database;
 use database::PooledConnection;
+use diesel::Connection;
+use email;
+use emoji;
 use env;
+use filter::{self, NewFilter};
+use follow_request;
+use html;
 use kibou_api;
+use list;
+use marker;
 use mastodon_api::{
-    Account, Attachment, HomeTimeline, Instance, Notification, PublicTimeline, RegistrationForm,
-    Relationship, Source, Status, StatusForm, MASTODON_API_ACCOUNT_CACHE,
-    MASTODON_API_NOTIFICATION_CACHE, MASTODON_API_STATUS_CACHE,
+    page_limit, Account, Attachment, Conversation, Emoji, EmojiReaction, Filter, FilterForm,
+    FilterKeyword, FilterKeywordForm, FilterResult, FilterStatus, FilterStatusForm, HomeTimeline,
+    Instance, List, ListAccountsForm, ListForm, Marker, MarkersForm, MarkersUpdateForm,
+    Notification, NotificationsForm, PaginatedJson, Poll, PollForm, PollVoteForm, PublicTimeline,
+    RegistrationForm, Relationship, Source, Status, StatusForm, UpdateCredentialsForm, V1Filter,
+    V1FilterForm, MASTODON_API_ACCOUNT_CACHE, MASTODON_API_NOTIFICATION_CACHE,
+    MASTODON_API_STATUS_CACHE,
 };
-use notification::notifications_for_actor;
+use media;
+use notification;
 use oauth;
-use oauth::application::Application as OAuthApplication;
+use oauth::application::{get_application_by_id, Application as OAuthApplication};
 use oauth::token::verify_token;
+use pin;
+use poll::{self, NewPoll};
+use reaction;
 use regex::Regex;
-use rocket_contrib::json;
-use rocket_contrib::json::JsonValue;
+use registration;
+use rocket_contrib::json::Json;
+use std::collections::{BTreeMap, HashMap};
+use std::thread;
 use timeline;
-use timeline::{home_timeline as get_home_timeline, public_timeline as get_public_timeline};
+use timeline::{
+    direct_timeline as get_direct_timeline, home_timeline as get_home_timeline,
+    list_timeline as get_list_timeline, public_timeline as get_public_timeline,
+};
 
-pub fn account(pooled_connection: &PooledConnection, id: i64) -> JsonValue {
+pub fn account(pooled_connection: &PooledConnection, id: i64) -> ApiResult<Account> {
     match actor::get_actor_by_id(pooled_connection, &id) {
-        Ok(actor) => json!(Account::from_actor(pooled_connection, actor, false)),
-        Err(_) => json!({"error": "User not found."}),
+        Ok(actor) => Ok(Json(Account::from_actor(pooled_connection, actor, false))),
+        Err(_) => Err(ApiError::not_found("User not found.")),
     }
 }
 
-pub fn account_by_oauth_token(pooled_connection: &PooledConnection, token: String) -> JsonValue {
-    match verify_token(pooled_connection, token) {
-        Ok(token) => {
-            match actor::get_local_actor_by_preferred_username(pooled_connection, &token.actor) {
-                Ok(actor) => json!(Account::from_actor(pooled_connection, actor, true)),
-                Err(_) => json!({"error": "No user is associated to this token!"}),
-            }
-        }
-        Err(_) => json!({"error": "Token invalid!"}),
-    }
+pub fn account_by_oauth_token(
+    pooled_connection: &PooledConnection,
+    token: String,
+) -> ApiResult<Account> {
+    let actor = actor_for_token(pooled_connection, token)?;
+    Ok(Json(Account::from_actor(pooled_connection, actor, true)))
 }
 
-pub fn account_create(form: &RegistrationForm) -> JsonValue {
+/// Creates a local account according to the registration mode
+///
+/// # Description
+///
+/// No token is issued if the account has to be approved or its e-mail address confirmed first,
+/// the response tells which of both is the case instead.
+///
+/// # Parameters
+///
+/// * `form`           - &RegistrationForm | The registration data
+/// * `application_id` -               i64 | The application which receives the token, 0 for Raito-FE
+/// * `scopes`         -              &str | The scopes of the token
+///
+pub fn account_create(
+    form: &RegistrationForm,
+    application_id: i64,
+    scopes: &str,
+) -> ApiResult<serde_json::Value> {
     let email_regex = Regex::new(r"^[a-zA-Z0-9_.+-]+@[a-zA-Z0-9-]+\.[a-zA-Z0-9-.]+$").unwrap();
     let username_regex = Regex::new(r"^[A-Za-z0-9_]{1,32}$").unwrap();
 
+    if registration::mode() == registration::Mode::Closed {
+        return Err(ApiError::forbidden("Registrations are closed"));
+    }
+
     if username_regex.is_match(&form.username) && email_regex.is_match(&form.email) {
         let database = database::establish_connection();
+        let invite = registration::validate(
+            &database,
+            &form.username,
+            form.reason.as_ref().map(String::as_str),
+            form.invite_code.as_ref().map(String::as_str),
+            form.captcha_answer.as_ref().map(String::as_str),
+        )
+        .map_err(ApiError::unprocessable)?;
+
         let mut new_actor = actor::Actor {
             id: 0,
             email: Some(form.email.to_string()),
@@ -66,99 +118,245 @@ pub fn account_create(form: &RegistrationForm) -> JsonValue {
             followers: serde_json::json!({"activitypub": []}),
             inbox: None,
             icon: None,
+            header: None,
             local: true,
             keys: serde_json::json!({}),
             created: Utc::now().naive_utc(),
             modified: Utc::now().naive_utc(),
+            locked: false,
+            bot: false,
+            discoverable: false,
+            fields: serde_json::json!([]),
+            also_known_as: serde_json::json!([]),
+            moved_to: None,
+            followers_uri: None,
         };
 
-        actor::create_actor(&database, &mut new_actor);
+        let approval_required = registration::approval_required(&invite);
+
+        // Until the account is pending, it could already be logged into. The invite is used in
+        // the same transaction, so that concurrent sign-ups can't use it more often than allowed
+        // and failed ones don't use it at all.
+        let actor = database.transaction::<_, ApiError, _>(|| {
+            if let Some(invite) = &invite {
+                registration::redeem_invite(&database, invite).map_err(ApiError::unprocessable)?;
+            }
+
+            actor::create_actor(&database, &mut new_actor);
+            let actor = actor::get_local_actor_by_preferred_username(&database, &form.username)
+                .map_err(|_| ApiError::internal("Account could not be created"))?;
+
+            if approval_required {
+                registration::request_approval(
+                    &database,
+                    &actor,
+                    form.reason.as_ref().map(String::as_str).unwrap_or_default(),
+                )?;
+            }
+            Ok(actor)
+        })?;
+
+        let confirmation_required = email::confirmation_required();
+        if confirmation_required {
+            email::request_confirmation(&database, &actor);
+        }
 
-        match actor::get_local_actor_by_preferred_username(&database, &form.username) {
-            Ok(_actor) => json!(oauth::token::create(&form.username)),
-            Err(_) => json!({"error": "Account could not be created"}),
+        if approval_required || confirmation_required {
+            Ok(Json(serde_json::json!({
+                "approval_required": approval_required,
+                "confirmation_required": confirmation_required
+            })))
+        } else {
+            Ok(Json(serde_json::json!(oauth::token::create(
+                &form.username,
+                application_id,
+                scopes
+            ))))
         }
     } else {
-        return json!({"error": "Username or E-mail contains unsupported characters"});
+        Err(ApiError::unprocessable(
+            "Username or E-mail contains unsupported characters",
+        ))
+    }
+}
+
+/// Creates a local account on behalf of an application, e.g. by `POST /api/v1/accounts`
+pub fn account_register(
+    pooled_connection: &PooledConnection,
+    token: String,
+    form: &RegistrationForm,
+) -> ApiResult<serde_json::Value> {
+    match oauth::token::get_application_id(pooled_connection, &token)
+        .and_then(|application_id| get_application_by_id(pooled_connection, application_id))
+    {
+        Ok(application) => account_create(form, application.id, &application.scopes),
+        Err(_) => Err(ApiError::unauthorized("Token invalid!")),
     }
 }
 
+/// Returns the statuses of an account, or only its pinned statuses
 pub fn account_statuses_by_id(
     pooled_connection: &PooledConnection,
     id: i64,
+    pinned: bool,
     max_id: Option<i64>,
     since_id: Option<i64>,
     min_id: Option<i64>,
     limit: Option<i64>,
-) -> JsonValue {
+    token: Option<String>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
     match actor::get_actor_by_id(pooled_connection, &id) {
         Ok(actor) => {
-            match timeline::user_timeline(pooled_connection, actor, max_id, since_id, min_id, limit)
-            {
-                Ok(statuses) => cached_statuses(pooled_connection, statuses),
-                Err(_) => json!({"error": "Error generating user timeline."}),
+            let (statuses, path) = if pinned {
+                (
+                    pin::pinned_activities(pooled_connection, &actor),
+                    format!("/api/v1/accounts/{}/statuses?pinned=true", id),
+                )
+            } else {
+                (
+                    timeline::user_timeline(
+                        pooled_connection,
+                        actor,
+                        max_id,
+                        since_id,
+                        min_id,
+                        Some(page_limit(limit, 20, 40)),
+                    ),
+                    format!("/api/v1/accounts/{}/statuses", id),
+                )
+            };
+
+            match statuses {
+                Ok(statuses) => Ok(PaginatedJson::new(
+                    cached_statuses(
+                        pooled_connection,
+                        statuses.clone(),
+                        viewer_for_token(pooled_connection, token).as_ref(),
+                        "account",
+                    ),
+                    &path,
+                    statuses,
+                )),
+                Err(_) => Err(ApiError::internal("Error generating user timeline.")),
             }
         }
-        Err(_) => json!({"error": "User not found."}),
+        Err(_) => Err(ApiError::not_found("User not found.")),
     }
 }
 
 pub fn application_create(
     pooled_connection: &PooledConnection,
-    application: OAuthApplication,
-) -> JsonValue {
+    mut application: OAuthApplication,
+) -> ApiResult<serde_json::Value> {
+    application.scopes =
+        oauth::scope::normalize(&application.scopes).map_err(ApiError::unprocessable)?;
+
     let oauth_app: OAuthApplication = oauth::application::create(pooled_connection, application);
-    rocket_contrib::json!({
+    Ok(Json(serde_json::json!({
         "name": oauth_app.client_name.unwrap_or_default(),
         "website": oauth_app.website,
         "client_id": oauth_app.client_id,
         "client_secret": oauth_app.client_secret,
         "redirect_uri": oauth_app.redirect_uris,
         "id": oauth_app.id
-    })
+    })))
 }
 
-pub fn cached_account(pooled_connection: &PooledConnection, uri: &str) -> JsonValue {
-    let mut account_cache = MASTODON_API_ACCOUNT_CACHE.lock().unwrap();
+/// Returns the statuses bookmarked by the owner of the token, paginated by their bookmarks
+pub fn bookmarks(
+    pooled_connection: &PooledConnection,
+    token: String,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
+    let actor = actor_for_token(pooled_connection, token)?;
 
-    if account_cache.contains(&uri.to_string()) {
-        return json!(account_cache.get(&uri.to_string()));
-    } else {
-        match actor::get_actor_by_uri(pooled_connection, uri) {
-            Ok(actor) => {
-                let result =
-                    serde_json::json!(Account::from_actor(pooled_connection, actor, false));
-                account_cache.put(uri.to_string(), result.clone());
-                return json!(result);
-            }
-            Err(_) => json!({ "error": format!("Account not found: {}", uri) }),
+    match bookmark::bookmarked_activities(
+        pooled_connection,
+        &actor,
+        max_id,
+        since_id,
+        min_id,
+        Some(page_limit(limit, 20, 40)),
+    ) {
+        Ok(bookmarks) => {
+            let activity_ids: Vec<i64> = bookmarks
+                .iter()
+                .map(|(_, activity_id)| *activity_id)
+                .collect();
+            let statuses = cached_statuses(pooled_connection, activity_ids, None, "thread");
+
+            Ok(PaginatedJson::new(
+                apply_viewer_state(pooled_connection, statuses, Some(&actor)),
+                "/api/v1/bookmarks",
+                bookmarks.iter().map(|(id, _)| *id).collect(),
+            ))
+        }
+        Err(_) => Err(ApiError::internal(
+            "An error occured while fetching bookmarks",
+        )),
+    }
+}
+
+pub fn cached_account(
+    pooled_connection: &PooledConnection,
+    uri: &str,
+) -> Result<Account, ApiError> {
+    let mut account_cache = MASTODON_API_ACCOUNT_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+
+    if let Some(Ok(account)) = account_cache
+        .get(&uri.to_string())
+        .map(|account| serde_json::from_value(account.clone()))
+    {
+        return Ok(account);
+    }
+
+    match actor::get_actor_by_uri(pooled_connection, uri) {
+        Ok(actor) => {
+            let account = Account::from_actor(pooled_connection, actor, false);
+            account_cache.put(uri.to_string(), serde_json::json!(account));
+            Ok(account)
         }
+        Err(_) => Err(ApiError::NotFound(format!("Account not found: {}", uri))),
     }
 }
 
-pub fn cached_notifications(pooled_connection: &PooledConnection, ids: Vec<i64>) -> JsonValue {
-    let mut notification_cache = MASTODON_API_NOTIFICATION_CACHE.lock().unwrap();
+/// Returns notifications, filtered for the given viewer
+pub fn cached_notifications(
+    pooled_connection: &PooledConnection,
+    ids: Vec<i64>,
+    viewer: Option<&actor::Actor>,
+) -> Vec<Notification> {
+    let mut notification_cache = MASTODON_API_NOTIFICATION_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
     let mut notifications: Vec<Notification> = Vec::new();
     let mut uncached_notifications: Vec<i64> = Vec::new();
+    let notification_types = match viewer {
+        Some(viewer) => notification::types_among(pooled_connection, viewer, ids.clone()),
+        None => HashMap::new(),
+    };
     for id in ids {
-        if notification_cache.contains(&id) {
-            notifications.push(
-                serde_json::from_value(notification_cache.get(&id).unwrap().clone()).unwrap(),
-            );
-        } else {
-            uncached_notifications.push(id);
+        match notification_cache
+            .get(&id)
+            .map(|notification| serde_json::from_value(notification.clone()))
+        {
+            Some(Ok(notification)) => notifications.push(notification),
+            _ => uncached_notifications.push(id),
         }
     }
 
     match get_activities_by_id(pooled_connection, uncached_notifications) {
         Ok(activities) => {
             for activity in activities {
+                let id = activity.id;
                 match Notification::try_from(activity) {
                     Ok(notification) => {
-                        notification_cache.put(
-                            notification.id.parse::<i64>().unwrap(),
-                            serde_json::json!(notification),
-                        );
+                        notification_cache.put(id, serde_json::json!(notification));
                         notifications.push(notification);
                     }
                     Err(_) => (),
@@ -168,30 +366,82 @@ pub fn cached_notifications(pooled_connection: &PooledConnection, ids: Vec<i64>)
         Err(_) => (),
     }
     notifications.sort_by(|a, b| b.id.cmp(&a.id));
-    return json!(notifications);
+
+    // The same activity notifies actors for different reasons, e.g. a `Follow` is either a follow
+    // or a follow request, so the cached notification only provides the default type
+    for notification in &mut notifications {
+        if let Some(notification_type) = notification
+            .id
+            .parse::<i64>()
+            .ok()
+            .and_then(|id| notification_types.get(&id))
+        {
+            notification._type = notification_type.to_string();
+        }
+    }
+
+    // Notifications are dropped together with their status if it's hidden by a filter
+    match viewer {
+        Some(viewer) => {
+            let active_filters = filter::active_filters(pooled_connection, viewer, "notifications");
+            notifications
+                .into_iter()
+                .filter_map(|mut notification| match notification.status.take() {
+                    Some(status) => {
+                        filtered_status(&active_filters, viewer, status).map(|status| {
+                            notification.status = Some(status);
+                            notification
+                        })
+                    }
+                    None => Some(notification),
+                })
+                .collect()
+        }
+        None => notifications,
+    }
 }
 
-pub fn cached_statuses(pooled_connection: &PooledConnection, ids: Vec<i64>) -> JsonValue {
+/// Returns statuses, filtered for the given viewer
+///
+/// # Description
+///
+/// Statuses are cached without any viewer-specific properties, which is why filters are applied
+/// after reading them from the cache.
+///
+/// # Parameters
+///
+/// * `pooled_connection` -     &PooledConnection | Reference to a database connection
+/// * `ids`               -              Vec<i64> | The ids of the statuses' activities
+/// * `viewer`            - Option<&actor::Actor> | The local actor whose filters are applied
+/// * `context`           -                  &str | The filter context, e.g. `home` or `public`
+pub fn cached_statuses(
+    pooled_connection: &PooledConnection,
+    ids: Vec<i64>,
+    viewer: Option<&actor::Actor>,
+    context: &str,
+) -> Vec<Status> {
     // Try to ignore mutex poisoning, the mutex might already get poisened once a status fails to
     // serialize.
     let mut status_cache = MASTODON_API_STATUS_CACHE.lock().unwrap_or_else(|e| e.into_inner());
     let mut statuses: Vec<Status> = Vec::new();
     let mut uncached_statuses: Vec<i64> = Vec::new();
     for id in ids {
-        if status_cache.contains(&id) {
-            statuses.push(serde_json::from_value(status_cache.get(&id).unwrap().clone()).unwrap());
-        } else {
-            uncached_statuses.push(id);
+        match status_cache
+            .get(&id)
+            .map(|status| serde_json::from_value(status.clone()))
+        {
+            Some(Ok(status)) => statuses.push(status),
+            _ => uncached_statuses.push(id),
         }
     }
 
     match get_activities_by_id(pooled_connection, uncached_statuses) {
         Ok(activities) => {
             for activity in activities {
+                let id = activity.id;
                 match Status::try_from(activity) {
                     Ok(status) => {
-                        status_cache
-                            .put(status.id.parse::<i64>().unwrap(), serde_json::json!(status));
+                        status_cache.put(id, serde_json::json!(status));
                         statuses.push(status);
                     }
                     Err(_) => (),
@@ -201,14 +451,48 @@ pub fn cached_statuses(pooled_connection: &PooledConnection, ids: Vec<i64>) -> J
         Err(_) => (),
     }
     statuses.sort_by(|a, b| b.id.cmp(&a.id));
-    return json!(statuses);
+    let statuses = apply_filters(pooled_connection, statuses, viewer, context);
+    apply_viewer_state(pooled_connection, statuses, viewer)
 }
 
-pub fn context_json_for_id(pooled_connection: &PooledConnection, id: i64) -> JsonValue {
-    match activity::get_activity_by_id(&pooled_connection, id) {
-        Ok(_activity) => {
-            let mut ancestors = status_parents_for_id(pooled_connection, id, true);
-            let mut descendants = status_children_for_id(pooled_connection, id, true);
+pub fn context_json_for_id(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    token: Option<String>,
+) -> ApiResult<serde_json::Value> {
+    let viewer = viewer_for_token(pooled_connection, token);
+
+    match visible_activity(pooled_connection, id, viewer.as_ref()) {
+        Ok(activity) => {
+            let parents = visible_statuses(
+                pooled_connection,
+                status_parents_for_id(pooled_connection, id, true),
+                viewer.as_ref(),
+            );
+
+            // Replies we didn't receive directly are backfilled for the next time the thread is
+            // opened, starting with the replies to this status and continuing with the rest of the thread
+            let mut backfilled_ids: Vec<String> = vec![activity.data["object"]["id"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()];
+            backfilled_ids.extend(parents.iter().rev().map(|parent| parent.uri.clone()));
+            thread::spawn(move || {
+                ap_controller::backfill_replies(backfilled_ids);
+            });
+
+            let mut ancestors =
+                apply_filters(pooled_connection, parents, viewer.as_ref(), "thread");
+            let mut descendants = apply_filters(
+                pooled_connection,
+                visible_statuses(
+                    pooled_connection,
+                    status_children_for_id(pooled_connection, id, true),
+                    viewer.as_ref(),
+                ),
+                viewer.as_ref(),
+                "thread",
+            );
             ancestors.sort_by(|status_a, status_b| {
                 chrono::DateTime::parse_from_rfc3339(&status_a.created_at)
                     .unwrap_or_else(|_| {
@@ -239,381 +523,1870 @@ pub fn context_json_for_id(pooled_connection: &PooledConnection, id: i64) -> Jso
                             .timestamp(),
                     )
             });
-            json!({"ancestors": ancestors, "descendants": descendants})
+            Ok(Json(
+                serde_json::json!({"ancestors": ancestors, "descendants": descendants}),
+            ))
         }
-        Err(_) => json!({"error": "Status not found"}),
+        Err(e) => Err(e),
     }
 }
 
-pub fn favourite(pooled_connection: &PooledConnection, token: String, id: i64) -> JsonValue {
-    match activity::get_activity_by_id(pooled_connection, id) {
-        Ok(activity) => {
-            let account: Result<Account, serde_json::Error> =
-                serde_json::from_value(account_by_oauth_token(pooled_connection, token).into());
-            match account {
-                Ok(account) => {
-                    kibou_api::react(
-                        &account.id.parse::<i64>().unwrap(),
-                        "Like",
-                        activity.data["object"]["id"].as_str().unwrap(),
-                    );
-                    return status_by_id(pooled_connection, id);
-                }
-                Err(_) => json!({"error": "Token invalid!"}),
-            }
-        }
-        Err(_) => json!({"error": "Status not found"}),
-    }
-}
+pub fn conversation_read(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<Conversation> {
+    let actor = actor_for_token(pooled_connection, token)?;
 
-pub fn follow(token: String, id: i64) -> JsonValue {
-    let database = database::establish_connection();
+    if conversation::mark_as_read(pooled_connection, &actor, id).is_err() {
+        return Err(ApiError::internal("Conversation could not be updated"));
+    }
 
-    match verify_token(&database, token.to_string()) {
-        Ok(token) => match actor::get_local_actor_by_preferred_username(&database, &token.actor) {
-            Ok(actor) => {
-                let followee = actor::get_actor_by_id(&database, &id).unwrap();
-
-                kibou_api::follow(&actor.actor_uri, &followee.actor_uri);
-                return json!(Relationship {
-                    id: followee.id.to_string(),
-                    following: true,
-                    followed_by: false,
-                    blocking: false,
-                    muting: false,
-                    muting_notifications: false,
-                    requested: false,
-                });
-            }
-            Err(_) => json!({"error": "User not found."}),
-        },
-        Err(_) => json!({"error": "Token invalid!"}),
+    match conversation::get_conversation_for_actor(pooled_connection, &actor, id) {
+        Ok(participant_conversation) => Ok(Json(serialize_conversation(
+            pooled_connection,
+            &actor,
+            participant_conversation,
+        ))),
+        Err(_) => Err(ApiError::not_found("Conversation not found")),
     }
 }
 
-pub fn home_timeline(
+/// Returns the conversations of the token's owner, paginated by their latest statuses
+pub fn conversations(
     pooled_connection: &PooledConnection,
     parameters: HomeTimeline,
     token: String,
-) -> JsonValue {
-    match verify_token(pooled_connection, token) {
-        Ok(token) => {
-            match actor::get_local_actor_by_preferred_username(pooled_connection, &token.actor) {
-                Ok(actor) => {
-                    match get_home_timeline(
-                        pooled_connection,
-                        actor,
-                        parameters.max_id,
-                        parameters.since_id,
-                        parameters.min_id,
-                        parameters.limit,
-                    ) {
-                        Ok(statuses) => cached_statuses(pooled_connection, statuses),
-                        Err(_e) => {
-                            json!({"error": "An error occured while generating home timeline"})
-                        }
-                    }
-                }
-                Err(_e) => json!({"error": "User associated to token not found"}),
-            }
+) -> Result<PaginatedJson<Vec<Conversation>>, ApiError> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match conversation::conversations_for_actor(
+        pooled_connection,
+        &actor,
+        parameters.max_id,
+        parameters.since_id,
+        parameters.min_id,
+        Some(page_limit(parameters.limit, 20, 40)),
+    ) {
+        Ok(participant_conversations) => {
+            let ids: Vec<i64> = participant_conversations
+                .iter()
+                .map(|participant_conversation| participant_conversation.last_activity_id)
+                .collect();
+
+            Ok(PaginatedJson::new(
+                participant_conversations
+                    .into_iter()
+                    .map(|participant_conversation| {
+                        serialize_conversation(pooled_connection, &actor, participant_conversation)
+                    })
+                    .collect(),
+                "/api/v1/conversations",
+                ids,
+            ))
         }
-        Err(_e) => json!({"error": "Invalid oauth token"}),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while querying conversations",
+        )),
     }
 }
 
-pub fn instance_info() -> JsonValue {
-    let database = database::establish_connection();
-    json!(Instance {
-        uri: format!(
-            "{base_scheme}://{base_domain}",
-            base_scheme = env::get_value(String::from("endpoint.base_scheme")),
-            base_domain = env::get_value(String::from("endpoint.base_domain"))
-        ),
-        title: env::get_value(String::from("node.name")),
-        description: env::get_value(String::from("node.description")),
-        email: env::get_value(String::from("node.contact_email")),
-        version: String::from("2.3.0 (compatible; Kibou 0.1)"),
-        thumbnail: None,
-        // Kibou does not support Streaming_API yet, but this value is not nullable according to
-        // Mastodon-API's specifications, so that is why it is showing an empty value instead
-        urls: serde_json::json!({"streaming_api": ""}),
-        // `domain_count` always stays 0 as Kibou does not keep data about remote nodes
-        stats: serde_json::json!({"user_count": actor::count_local_actors(&database).unwrap_or_else(|_| 0),
-        "status_count": activity::count_local_ap_notes(&database).unwrap_or_else(|_| 0),
-        "domain_count": 0}),
-        languages: vec![],
-        contact_account: None
-    })
+pub fn custom_emojis(pooled_connection: &PooledConnection) -> ApiResult<Vec<Emoji>> {
+    match emoji::get_emojis(pooled_connection) {
+        Ok(emojis) => Ok(Json(
+            emojis
+                .into_iter()
+                .filter(|emoji| emoji.visible_in_picker)
+                .map(Emoji::from_emoji)
+                .collect(),
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while querying custom emojis",
+        )),
+    }
 }
 
-pub fn notifications(
+pub fn direct_timeline(
     pooled_connection: &PooledConnection,
+    parameters: HomeTimeline,
     token: String,
-    limit: Option<i64>,
-) -> JsonValue {
-    let database = database::establish_connection();
-
-    match verify_token(&database, token) {
-        Ok(token) => {
-            match actor::get_local_actor_by_preferred_username(pooled_connection, &token.actor) {
-                Ok(actor) => {
-                    match notifications_for_actor(
-                        pooled_connection,
-                        &actor,
-                        None,
-                        None,
-                        None,
-                        limit,
-                    ) {
-                        Ok(notifications) => cached_notifications(pooled_connection, notifications),
-                        Err(_) => {
-                            json!({"error": "An error occured while generating notifications"})
-                        }
-                    }
-                }
-                Err(_) => json!({"error": "User associated to token not found"}),
-            }
-        }
-        Err(_) => json!({"error": "Invalid oauth token"}),
-    }
-}
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
+    let actor = actor_for_token(pooled_connection, token)?;
 
-pub fn public_timeline(
-    pooled_connection: &PooledConnection,
-    parameters: PublicTimeline,
-) -> JsonValue {
-    match get_public_timeline(
+    match get_direct_timeline(
         pooled_connection,
-        parameters.local.unwrap_or_else(|| false),
-        parameters.only_media.unwrap_or_else(|| false),
+        actor.clone(),
         parameters.max_id,
         parameters.since_id,
         parameters.min_id,
-        parameters.limit,
+        Some(page_limit(parameters.limit, 20, 40)),
     ) {
-        Ok(statuses) => cached_statuses(pooled_connection, statuses),
-        Err(_e) => json!({"error": "An error occured while generating timeline."}),
+        Ok(statuses) => Ok(PaginatedJson::new(
+            cached_statuses(pooled_connection, statuses.clone(), Some(&actor), "thread"),
+            "/api/v1/timelines/direct",
+            statuses,
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while generating direct timeline",
+        )),
     }
 }
 
-pub fn relationships(token: &str, ids: Vec<i64>) -> JsonValue {
-    let database = database::establish_connection();
+pub fn favourite(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<Status> {
+    status_react_with(pooled_connection, token, id, "Like")
+}
 
-    match verify_token(&database, token.to_string()) {
-        Ok(token) => match actor::get_local_actor_by_preferred_username(&database, &token.actor) {
-            Ok(actor) => {
-                let mut relationships: Vec<Relationship> = Vec::new();
-                let activitypub_followers: Vec<serde_json::Value> =
-                    serde_json::from_value(actor.followers["activitypub"].to_owned()).unwrap();
-                let activitypub_followees =
-                    actor::get_actor_followees(&database, &actor.actor_uri).unwrap();
-
-                for id in ids {
-                    let follower_actor = actor::get_actor_by_id(&database, &id).unwrap();
-
-                    match activitypub_followers.iter().position(|ref follower| {
-                        follower["href"].as_str().unwrap() == follower_actor.actor_uri
-                    }) {
-                        Some(_) => {
-                            relationships.push(Relationship {
-                                id: id.to_string(),
-                                following: false,
-                                followed_by: true,
-                                blocking: false,
-                                muting: false,
-                                muting_notifications: false,
-                                requested: false,
-                            });
-                        }
-                        None => {
-                            relationships.push(Relationship {
-                                id: id.to_string(),
-                                following: false,
-                                followed_by: false,
-                                blocking: false,
-                                muting: false,
-                                muting_notifications: false,
-                                requested: false,
-                            });
-                        }
-                    }
+/// Returns the accounts which favourited a status
+pub fn favourited_by(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    token: Option<String>,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Account>>, ApiError> {
+    status_reactors(
+        pooled_connection,
+        id,
+        token,
+        "Like",
+        &format!("/api/v1/statuses/{}/favourited_by", id),
+        max_id,
+        since_id,
+        min_id,
+        limit,
+    )
+}
 
-                    match activitypub_followees
-                        .iter()
-                        .position(|ref followee| followee.id == id)
-                    {
-                        Some(_) => {
-                            match relationships
-                                .iter()
-                                .position(|ref follower| follower.id == id.to_string())
-                            {
-                                Some(index) => {
-                                    relationships[index].following = true;
-                                }
-                                None => {
-                                    relationships.push(Relationship {
-                                        id: id.to_string(),
-                                        following: true,
-                                        followed_by: false,
-                                        blocking: false,
-                                        muting: false,
-                                        muting_notifications: false,
-                                        requested: false,
-                                    });
-                                }
-                            }
-                        }
-                        None => (),
-                    }
-                }
-                return json!(relationships);
+/// Returns the statuses favourited by the owner of the token, paginated by their favourites
+pub fn favourites(
+    pooled_connection: &PooledConnection,
+    token: String,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match activity::get_ap_reactions_by_actor(
+        pooled_connection,
+        &actor.actor_uri,
+        "Like",
+        max_id,
+        since_id,
+        min_id,
+        page_limit(limit, 20, 40),
+    ) {
+        Ok(likes) => {
+            let activity_ids: Vec<i64> = likes
+                .iter()
+                .filter_map(|like| like.data["object"].as_str())
+                .filter_map(|object_id| get_ap_object_by_id(pooled_connection, object_id).ok())
+                .map(|activity| activity.id)
+                .collect();
+            let mut statuses = cached_statuses(pooled_connection, activity_ids, None, "thread");
+            for status in &mut statuses {
+                status.favourited = Some(true);
             }
-            Err(_) => json!({"error": "User not found."}),
-        },
-        Err(_) => json!({"error": "Access token invalid!"}),
+
+            Ok(PaginatedJson::new(
+                apply_viewer_state(pooled_connection, statuses, Some(&actor)),
+                "/api/v1/favourites",
+                likes.iter().map(|like| like.id).collect(),
+            ))
+        }
+        Err(_) => Err(ApiError::internal(
+            "An error occured while fetching favourites",
+        )),
     }
 }
 
-pub fn reblog(pooled_connection: &PooledConnection, token: String, id: i64) -> JsonValue {
-    match activity::get_activity_by_id(pooled_connection, id) {
-        Ok(activity) => {
-            let account: Result<Account, serde_json::Error> =
-                serde_json::from_value(account_by_oauth_token(pooled_connection, token).into());
-            match account {
-                Ok(account) => {
-                    kibou_api::react(
-                        &account.id.parse::<i64>().unwrap(),
-                        "Announce",
-                        activity.data["object"]["id"].as_str().unwrap(),
-                    );
-                    return status_by_id(pooled_connection, id);
-                }
-                Err(_) => json!({"error": "Token invalid!"}),
-            }
+pub fn filter(pooled_connection: &PooledConnection, token: String, id: i64) -> ApiResult<Filter> {
+    let (_, filter) = filter_for_token(pooled_connection, token, id)?;
+    Ok(Json(serialize_filter(pooled_connection, filter)))
+}
+
+pub fn filter_create(
+    pooled_connection: &PooledConnection,
+    token: String,
+    form: FilterForm,
+) -> ApiResult<Filter> {
+    let actor = actor_for_token(pooled_connection, token)?;
+    let new_filter = NewFilter {
+        title: form.title.unwrap_or_default(),
+        context: form.context,
+        expires_in: form.expires_in,
+        irreversible: form.filter_action.as_ref().map(String::as_str) == Some("hide"),
+    };
+
+    let new_filter =
+        filter::create(pooled_connection, &actor, new_filter).map_err(ApiError::unprocessable)?;
+    for keyword_form in form.keywords_attributes {
+        if keyword_form._destroy == Some(true) {
+            continue;
+        }
+
+        if let Err(e) = filter::add_keyword(
+            pooled_connection,
+            &new_filter,
+            &keyword_form.keyword.unwrap_or_default(),
+            keyword_form.whole_word.unwrap_or(true),
+        ) {
+            filter::delete(pooled_connection, &new_filter);
+            return Err(ApiError::unprocessable(e));
         }
-        Err(_) => json!({"error": "Status not found"}),
+    }
+    Ok(Json(serialize_filter(pooled_connection, new_filter)))
+}
+
+pub fn filter_delete(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    let (_, filter) = filter_for_token(pooled_connection, token, id)?;
+    filter::delete(pooled_connection, &filter);
+    Ok(Json(serde_json::json!({})))
+}
+
+pub fn filter_keyword(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<FilterKeyword> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match filter::get_keyword_for_actor(pooled_connection, &actor, id) {
+        Ok(keyword) => Ok(Json(FilterKeyword::from_keyword(keyword))),
+        Err(_) => Err(ApiError::not_found("Filter keyword not found")),
     }
 }
 
-pub fn status_by_id(pooled_connection: &PooledConnection, id: i64) -> JsonValue {
-    let statuses: Vec<Status> =
-        serde_json::from_value(cached_statuses(pooled_connection, vec![id]).into())
-            .unwrap_or_else(|_| Vec::new());
+pub fn filter_keyword_add(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    form: FilterKeywordForm,
+) -> ApiResult<FilterKeyword> {
+    let (_, filter) = filter_for_token(pooled_connection, token, id)?;
 
-    if statuses.len() > 0 {
-        return json!(statuses[0]);
-    } else {
-        return json!({"error": "Status not found"});
+    match filter::add_keyword(
+        pooled_connection,
+        &filter,
+        &form.keyword.unwrap_or_default(),
+        form.whole_word.unwrap_or(true),
+    ) {
+        Ok(keyword) => Ok(Json(FilterKeyword::from_keyword(keyword))),
+        Err(e) => Err(ApiError::unprocessable(e)),
     }
 }
 
-pub fn status_post(
+pub fn filter_keyword_delete(
     pooled_connection: &PooledConnection,
-    form: StatusForm,
     token: String,
-) -> JsonValue {
-    match verify_token(pooled_connection, token) {
-        Ok(token) => {
-            match actor::get_local_actor_by_preferred_username(pooled_connection, &token.actor) {
-                Ok(actor) => {
-                    let status_id = kibou_api::status_build(
-                        actor.actor_uri,
-                        form.status.unwrap(),
-                        &form.visibility.unwrap(),
-                        form.in_reply_to_id,
-                    );
-
-                    return status_by_id(pooled_connection, status_id);
-                }
-                Err(_) => json!({"error": "Account not found"}),
-            }
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match filter::get_keyword_for_actor(pooled_connection, &actor, id) {
+        Ok(keyword) => {
+            filter::remove_keyword(pooled_connection, &keyword);
+            Ok(Json(serde_json::json!({})))
         }
-        Err(_) => json!({"error": "OAuth token invalid"}),
+        Err(_) => Err(ApiError::not_found("Filter keyword not found")),
     }
 }
 
-pub fn unfollow(token: String, target_id: i64) -> JsonValue {
-    let database = database::establish_connection();
+pub fn filter_keyword_update(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    form: FilterKeywordForm,
+) -> ApiResult<FilterKeyword> {
+    let actor = actor_for_token(pooled_connection, token)?;
 
-    match verify_token(&database, token) {
-        Ok(token) => match actor::get_local_actor_by_preferred_username(&database, &token.actor) {
-            Ok(actor) => {
-                let followee = actor::get_actor_by_id(&database, &target_id).unwrap();
-
-                kibou_api::unfollow(actor.actor_uri, followee.actor_uri);
-                return json!(Relationship {
-                    id: followee.id.to_string(),
-                    following: false,
-                    followed_by: false,
-                    blocking: false,
-                    muting: false,
-                    muting_notifications: false,
-                    requested: false
-                });
-            }
-            Err(_) => json!({"error": "User not found."}),
+    match filter::get_keyword_for_actor(pooled_connection, &actor, id) {
+        Ok(keyword) => match filter::update_keyword(
+            pooled_connection,
+            &keyword,
+            &form.keyword.unwrap_or_else(|| keyword.keyword.clone()),
+            form.whole_word.unwrap_or(keyword.whole_word),
+        ) {
+            Ok(keyword) => Ok(Json(FilterKeyword::from_keyword(keyword))),
+            Err(e) => Err(ApiError::unprocessable(e)),
         },
-        Err(_) => json!({"error": "Token invalid!"}),
+        Err(_) => Err(ApiError::not_found("Filter keyword not found")),
     }
 }
 
-// This function is used to return an empty array on endpoints which are not yet implemented, this
-// happens to prevent breaking Mastodon_API-compatible clients
-pub fn unsupported_endpoint() -> JsonValue {
-    return json!([]);
+pub fn filter_keywords(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<Vec<FilterKeyword>> {
+    let (_, filter) = filter_for_token(pooled_connection, token, id)?;
+
+    match filter::keywords_for_filter(pooled_connection, &filter) {
+        Ok(keywords) => Ok(Json(
+            keywords
+                .into_iter()
+                .map(FilterKeyword::from_keyword)
+                .collect(),
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while fetching filter keywords",
+        )),
+    }
 }
 
-fn status_children_for_id(
+pub fn filter_status(
     pooled_connection: &PooledConnection,
+    token: String,
     id: i64,
-    resolve_children: bool,
-) -> Vec<Status> {
-    let mut statuses: Vec<Status> = vec![];
+) -> ApiResult<FilterStatus> {
+    let actor = actor_for_token(pooled_connection, token)?;
 
-    let head_status: Result<Status, serde_json::Error> =
-        serde_json::from_value(status_by_id(pooled_connection, id).into());
-    match head_status {
-        Ok(status) => match get_ap_object_replies_by_id(pooled_connection, &status.uri) {
-            Ok(replies) => {
-                if !replies.is_empty() {
-                    for reply in replies {
-                        if resolve_children {
-                            let mut child_statuses =
-                                status_children_for_id(pooled_connection, reply.id, true);
-                            statuses.append(&mut child_statuses);
-                        }
+    match filter::get_status_for_actor(pooled_connection, &actor, id) {
+        Ok(status) => Ok(Json(FilterStatus::from_status(status))),
+        Err(_) => Err(ApiError::not_found("Filtered status not found")),
+    }
+}
 
-                        statuses.push(Status::try_from(reply).unwrap());
-                    }
-                }
-            }
-            Err(e) => eprintln!("{}", e),
+pub fn filter_status_add(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    form: FilterStatusForm,
+) -> ApiResult<FilterStatus> {
+    let (_, filter) = filter_for_token(pooled_connection, token, id)?;
+
+    match activity::get_activity_by_id(pooled_connection, form.status_id) {
+        Ok(activity) => match filter::add_status(pooled_connection, &filter, activity.id) {
+            Ok(status) => Ok(Json(FilterStatus::from_status(status))),
+            Err(e) => Err(ApiError::unprocessable(e)),
         },
-        Err(e) => eprintln!("{}", e),
+        Err(_) => Err(ApiError::not_found("Status not found")),
     }
-    return statuses;
 }
 
-fn status_parents_for_id(
+pub fn filter_status_delete(
     pooled_connection: &PooledConnection,
+    token: String,
     id: i64,
-    is_head: bool,
-) -> Vec<Status> {
-    let mut statuses: Vec<Status> = vec![];
+) -> ApiResult<serde_json::Value> {
+    let actor = actor_for_token(pooled_connection, token)?;
 
-    let head_status: Result<Status, serde_json::Error> =
-        serde_json::from_value(status_by_id(pooled_connection, id).into());
-    match head_status {
+    match filter::get_status_for_actor(pooled_connection, &actor, id) {
         Ok(status) => {
-            if status.in_reply_to_id.is_some() {
-                statuses.append(&mut status_parents_for_id(
+            filter::remove_status(pooled_connection, &status);
+            Ok(Json(serde_json::json!({})))
+        }
+        Err(_) => Err(ApiError::not_found("Filtered status not found")),
+    }
+}
+
+pub fn filter_statuses(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<Vec<FilterStatus>> {
+    let (_, filter) = filter_for_token(pooled_connection, token, id)?;
+
+    match filter::statuses_for_filter(pooled_connection, &filter) {
+        Ok(statuses) => Ok(Json(
+            statuses
+                .into_iter()
+                .map(FilterStatus::from_status)
+                .collect(),
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while fetching filtered statuses",
+        )),
+    }
+}
+
+/// Updates a filter and creates, updates or removes its keywords
+pub fn filter_update(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    form: FilterForm,
+) -> ApiResult<Filter> {
+    let (actor, filter) = filter_for_token(pooled_connection, token, id)?;
+    let new_filter = NewFilter {
+        title: form.title.unwrap_or_else(|| filter.title.clone()),
+        context: if form.context.is_empty() {
+            filter.context.clone()
+        } else {
+            form.context
+        },
+        expires_in: form.expires_in,
+        irreversible: match form.filter_action {
+            Some(filter_action) => filter_action == "hide",
+            None => filter.irreversible,
+        },
+    };
+
+    let filter =
+        filter::update(pooled_connection, &filter, new_filter).map_err(ApiError::unprocessable)?;
+    for keyword_form in form.keywords_attributes {
+        let result = match keyword_form.id {
+            Some(keyword_id) => {
+                match filter::get_keyword_for_actor(pooled_connection, &actor, keyword_id) {
+                    Ok(ref keyword) if keyword.filter_id == filter.id => {
+                        if keyword_form._destroy == Some(true) {
+                            filter::remove_keyword(pooled_connection, keyword);
+                            Ok(())
+                        } else {
+                            filter::update_keyword(
+                                pooled_connection,
+                                keyword,
+                                &keyword_form
+                                    .keyword
+                                    .unwrap_or_else(|| keyword.keyword.clone()),
+                                keyword_form.whole_word.unwrap_or(keyword.whole_word),
+                            )
+                            .map(|_| ())
+                            .map_err(ApiError::unprocessable)
+                        }
+                    }
+                    _ => Err(ApiError::not_found("Filter keyword not found")),
+                }
+            }
+            None if keyword_form._destroy == Some(true) => Ok(()),
+            None => filter::add_keyword(
+                pooled_connection,
+                &filter,
+                &keyword_form.keyword.unwrap_or_default(),
+                keyword_form.whole_word.unwrap_or(true),
+            )
+            .map(|_| ())
+            .map_err(ApiError::unprocessable),
+        };
+        result?;
+    }
+    Ok(Json(serialize_filter(pooled_connection, filter)))
+}
+
+pub fn filters(pooled_connection: &PooledConnection, token: String) -> ApiResult<Vec<Filter>> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match filter::filters_for_actor(pooled_connection, &actor) {
+        Ok(filters) => Ok(Json(
+            filters
+                .into_iter()
+                .map(|filter| serialize_filter(pooled_connection, filter))
+                .collect(),
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while fetching filters",
+        )),
+    }
+}
+
+pub fn follow(token: String, id: i64) -> ApiResult<Relationship> {
+    let database = database::establish_connection();
+    let actor = actor_for_token(&database, token)?;
+
+    match actor::get_actor_by_id(&database, &id) {
+        Ok(followee) => {
+            kibou_api::follow(&actor.actor_uri, &followee.actor_uri)?;
+            Ok(Json(relationship(&database, &actor, &followee)))
+        }
+        Err(_) => Err(ApiError::not_found("User not found.")),
+    }
+}
+
+/// Returns the accounts waiting for the owner of the token to authorize their follows, paginated
+/// by their requests
+pub fn follow_requests(
+    pooled_connection: &PooledConnection,
+    token: String,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Account>>, ApiError> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match follow_request::requests_for_actor(
+        pooled_connection,
+        &actor,
+        max_id,
+        since_id,
+        min_id,
+        Some(page_limit(limit, 40, 80)),
+    ) {
+        Ok(follow_requests) => {
+            let accounts: Vec<Account> = follow_requests
+                .iter()
+                .filter_map(|(_, follower_id)| {
+                    actor::get_actor_by_id(pooled_connection, follower_id).ok()
+                })
+                .map(|follower| Account::from_actor(pooled_connection, follower, false))
+                .collect();
+
+            Ok(PaginatedJson::new(
+                accounts,
+                "/api/v1/follow_requests",
+                follow_requests.iter().map(|(id, _)| *id).collect(),
+            ))
+        }
+        Err(_) => Err(ApiError::internal(
+            "An error occured while fetching follow requests",
+        )),
+    }
+}
+
+pub fn follow_request_authorize(token: String, id: i64) -> ApiResult<Relationship> {
+    let database = database::establish_connection();
+    let actor = actor_for_token(&database, token)?;
+
+    match actor::get_actor_by_id(&database, &id) {
+        Ok(follower) => {
+            kibou_api::follow_request_authorize(&actor, &follower)?;
+            Ok(Json(relationship(&database, &actor, &follower)))
+        }
+        Err(_) => Err(ApiError::not_found("User not found.")),
+    }
+}
+
+pub fn follow_request_reject(token: String, id: i64) -> ApiResult<Relationship> {
+    let database = database::establish_connection();
+    let actor = actor_for_token(&database, token)?;
+
+    match actor::get_actor_by_id(&database, &id) {
+        Ok(follower) => {
+            kibou_api::follow_request_reject(&actor, &follower)?;
+            Ok(Json(relationship(&database, &actor, &follower)))
+        }
+        Err(_) => Err(ApiError::not_found("User not found.")),
+    }
+}
+
+/// Returns the accounts following an account, paginated by their IDs
+pub fn followers(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Account>>, ApiError> {
+    match actor::get_actor_by_id(pooled_connection, &id) {
+        Ok(actor) => paginated_accounts(
+            pooled_connection,
+            actor::followers_for_actor(
+                pooled_connection,
+                &actor,
+                max_id,
+                since_id,
+                min_id,
+                page_limit(limit, 40, 80),
+            ),
+            &format!("/api/v1/accounts/{}/followers", id),
+        ),
+        Err(_) => Err(ApiError::not_found("User not found.")),
+    }
+}
+
+/// Returns the accounts followed by an account, paginated by their IDs
+pub fn following(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Account>>, ApiError> {
+    match actor::get_actor_by_id(pooled_connection, &id) {
+        Ok(actor) => paginated_accounts(
+            pooled_connection,
+            actor::followees_for_actor(
+                pooled_connection,
+                &actor,
+                max_id,
+                since_id,
+                min_id,
+                page_limit(limit, 40, 80),
+            ),
+            &format!("/api/v1/accounts/{}/following", id),
+        ),
+        Err(_) => Err(ApiError::not_found("User not found.")),
+    }
+}
+
+pub fn home_timeline(
+    pooled_connection: &PooledConnection,
+    parameters: HomeTimeline,
+    token: String,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match get_home_timeline(
+        pooled_connection,
+        actor.clone(),
+        parameters.max_id,
+        parameters.since_id,
+        parameters.min_id,
+        Some(page_limit(parameters.limit, 20, 40)),
+    ) {
+        Ok(statuses) => Ok(PaginatedJson::new(
+            cached_statuses(pooled_connection, statuses.clone(), Some(&actor), "home"),
+            "/api/v1/timelines/home",
+            statuses,
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while generating home timeline",
+        )),
+    }
+}
+
+pub fn instance_info() -> ApiResult<Instance> {
+    let database = database::establish_connection();
+    Ok(Json(Instance {
+        uri: format!(
+            "{base_scheme}://{base_domain}",
+            base_scheme = env::get_value(String::from("endpoint.base_scheme")),
+            base_domain = env::get_value(String::from("endpoint.base_domain"))
+        ),
+        title: env::get_value(String::from("node.name")),
+        description: env::get_value(String::from("node.description")),
+        email: env::get_value(String::from("node.contact_email")),
+        version: String::from("2.3.0 (compatible; Kibou 0.1)"),
+        thumbnail: None,
+        // Kibou does not support Streaming_API yet, but this value is not nullable according to
+        // Mastodon-API's specifications, so that is why it is showing an empty value instead
+        urls: serde_json::json!({"streaming_api": ""}),
+        // `domain_count` always stays 0 as Kibou does not keep data about remote nodes
+        stats: serde_json::json!({"user_count": actor::count_local_actors(&database).unwrap_or_else(|_| 0),
+        "status_count": activity::count_local_ap_notes(&database).unwrap_or_else(|_| 0),
+        "domain_count": 0}),
+        languages: vec![],
+        contact_account: None,
+        registrations: registration::is_open(),
+        approval_required: registration::mode() == registration::Mode::Approval,
+        invites_enabled: registration::mode() != registration::Mode::Closed,
+    }))
+}
+
+pub fn list(pooled_connection: &PooledConnection, token: String, id: i64) -> ApiResult<List> {
+    let (_, list) = list_for_token(pooled_connection, token, id)?;
+    Ok(Json(List::from_list(list)))
+}
+
+pub fn list_accounts(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<Vec<Account>> {
+    let (_, list) = list_for_token(pooled_connection, token, id)?;
+    Ok(Json(
+        list::members(pooled_connection, &list)
+            .iter()
+            .filter_map(|member| cached_account(pooled_connection, &member.actor_uri).ok())
+            .collect(),
+    ))
+}
+
+pub fn list_accounts_add(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    form: ListAccountsForm,
+) -> ApiResult<serde_json::Value> {
+    let (actor, list) = list_for_token(pooled_connection, token, id)?;
+    let mut new_members: Vec<actor::Actor> = Vec::new();
+    for account_id in form.account_ids {
+        match actor::get_actor_by_id(pooled_connection, &account_id) {
+            Ok(member) => new_members.push(member),
+            Err(_) => return Err(ApiError::not_found("Account not found")),
+        }
+    }
+
+    match list::add_members(pooled_connection, &actor, &list, new_members) {
+        Ok(_) => Ok(Json(serde_json::json!({}))),
+        Err(e) => Err(ApiError::unprocessable(e)),
+    }
+}
+
+pub fn list_accounts_remove(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    form: ListAccountsForm,
+) -> ApiResult<serde_json::Value> {
+    let (_, list) = list_for_token(pooled_connection, token, id)?;
+    list::remove_members(pooled_connection, &list, form.account_ids);
+    Ok(Json(serde_json::json!({})))
+}
+
+pub fn list_create(
+    pooled_connection: &PooledConnection,
+    token: String,
+    form: ListForm,
+) -> ApiResult<List> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match list::create(pooled_connection, &actor, &form.title) {
+        Ok(list) => Ok(Json(List::from_list(list))),
+        Err(e) => Err(ApiError::unprocessable(e)),
+    }
+}
+
+pub fn list_delete(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    let (_, list) = list_for_token(pooled_connection, token, id)?;
+    list::delete(pooled_connection, &list);
+    Ok(Json(serde_json::json!({})))
+}
+
+pub fn list_timeline(
+    pooled_connection: &PooledConnection,
+    parameters: HomeTimeline,
+    token: String,
+    id: i64,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
+    let (actor, list) = list_for_token(pooled_connection, token, id)?;
+    let members = list::members(pooled_connection, &list);
+
+    match get_list_timeline(
+        pooled_connection,
+        actor.clone(),
+        members,
+        parameters.max_id,
+        parameters.since_id,
+        parameters.min_id,
+        Some(page_limit(parameters.limit, 20, 40)),
+    ) {
+        Ok(statuses) => Ok(PaginatedJson::new(
+            cached_statuses(pooled_connection, statuses.clone(), Some(&actor), "home"),
+            &format!("/api/v1/timelines/list/{}", id),
+            statuses,
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while generating list timeline",
+        )),
+    }
+}
+
+pub fn list_update(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    form: ListForm,
+) -> ApiResult<List> {
+    let (_, list) = list_for_token(pooled_connection, token, id)?;
+
+    match list::rename(pooled_connection, &list, &form.title) {
+        Ok(list) => Ok(Json(List::from_list(list))),
+        Err(e) => Err(ApiError::unprocessable(e)),
+    }
+}
+
+/// Returns the lists of the token's owner, optionally only those containing a certain account
+pub fn lists(
+    pooled_connection: &PooledConnection,
+    token: String,
+    account_id: Option<i64>,
+) -> ApiResult<Vec<List>> {
+    let actor = actor_for_token(pooled_connection, token)?;
+    let lists = match account_id {
+        Some(account_id) => match actor::get_actor_by_id(pooled_connection, &account_id) {
+            Ok(member) => list::lists_containing_actor(pooled_connection, &actor, &member),
+            Err(_) => return Err(ApiError::not_found("Account not found")),
+        },
+        None => list::lists_for_actor(pooled_connection, &actor),
+    };
+
+    match lists {
+        Ok(lists) => Ok(Json(lists.into_iter().map(List::from_list).collect())),
+        Err(_) => Err(ApiError::internal("An error occured while fetching lists")),
+    }
+}
+
+/// Returns the read markers of the token's owner, keyed by their timelines
+pub fn markers(
+    pooled_connection: &PooledConnection,
+    token: String,
+    form: MarkersForm,
+) -> ApiResult<BTreeMap<String, Marker>> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match marker::markers_for_actor(pooled_connection, &actor, &form.timelines) {
+        Ok(markers) => Ok(Json(
+            markers
+                .into_iter()
+                .map(|marker| (marker.timeline.clone(), Marker::from_marker(marker)))
+                .collect(),
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while fetching markers",
+        )),
+    }
+}
+
+/// Moves the read markers of the token's owner, returns the updated markers
+pub fn markers_update(
+    pooled_connection: &PooledConnection,
+    token: String,
+    form: MarkersUpdateForm,
+) -> ApiResult<BTreeMap<String, Marker>> {
+    let actor = actor_for_token(pooled_connection, token)?;
+    let positions: Vec<(&str, i64)> =
+        vec![("home", form.home), ("notifications", form.notifications)]
+            .into_iter()
+            .filter_map(|(timeline, last_read_id)| last_read_id.map(|id| (timeline, id)))
+            .collect();
+
+    if positions.is_empty() {
+        return Err(ApiError::bad_request("No markers given"));
+    }
+
+    let mut markers: BTreeMap<String, Marker> = BTreeMap::new();
+    for (timeline, last_read_id) in positions {
+        match marker::set(pooled_connection, &actor, timeline, last_read_id) {
+            Ok(marker) => {
+                markers.insert(marker.timeline.clone(), Marker::from_marker(marker));
+            }
+            Err(e) => return Err(ApiError::internal(e)),
+        }
+    }
+
+    Ok(Json(markers))
+}
+
+/// Returns a single notification of the token's owner, identified by the ID of its activity
+pub fn notification(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<Notification> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    if !notification::is_notified(pooled_connection, &actor, id) {
+        return Err(ApiError::not_found("Notification not found"));
+    }
+
+    match cached_notifications(pooled_connection, vec![id], Some(&actor)).pop() {
+        Some(notification) => Ok(Json(notification)),
+        None => Err(ApiError::not_found("Notification not found")),
+    }
+}
+
+/// Removes a single notification of the token's owner
+pub fn notification_dismiss(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match notification::dismiss(pooled_connection, &actor, id) {
+        Ok(0) => Err(ApiError::not_found("Notification not found")),
+        Ok(_) => Ok(Json(serde_json::json!({}))),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while dismissing the notification",
+        )),
+    }
+}
+
+pub fn notifications(
+    pooled_connection: &PooledConnection,
+    token: String,
+    form: NotificationsForm,
+) -> Result<PaginatedJson<Vec<Notification>>, ApiError> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    // The type filters are kept in the links to the neighbouring pages, unknown types are dropped
+    let type_parameters: Vec<String> = form
+        .types
+        .iter()
+        .map(|_type| ("types[]", _type))
+        .chain(
+            form.exclude_types
+                .iter()
+                .map(|_type| ("exclude_types[]", _type)),
+        )
+        .filter(|(_, _type)| notification::TYPES.contains(&_type.as_str()))
+        .map(|(key, _type)| format!("{}={}", key, _type))
+        .collect();
+    let path = if type_parameters.is_empty() {
+        String::from("/api/v1/notifications")
+    } else {
+        format!("/api/v1/notifications?{}", type_parameters.join("&"))
+    };
+
+    match notification::notifications_for_actor(
+        pooled_connection,
+        &actor,
+        notification::requested_types(&form.types, &form.exclude_types),
+        form.max_id,
+        form.since_id,
+        form.min_id,
+        Some(page_limit(form.limit, 20, 40)),
+    ) {
+        Ok(notifications) => Ok(PaginatedJson::new(
+            cached_notifications(pooled_connection, notifications.clone(), Some(&actor)),
+            &path,
+            notifications,
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while generating notifications",
+        )),
+    }
+}
+
+/// Removes all notifications of the token's owner
+pub fn notifications_clear(
+    pooled_connection: &PooledConnection,
+    token: String,
+) -> ApiResult<serde_json::Value> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match notification::clear(pooled_connection, &actor) {
+        Ok(_) => Ok(Json(serde_json::json!({}))),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while clearing notifications",
+        )),
+    }
+}
+
+pub fn poll(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    token: Option<String>,
+) -> ApiResult<Poll> {
+    let viewer = viewer_for_token(pooled_connection, token);
+
+    match visible_activity(pooled_connection, id, viewer.as_ref()) {
+        Ok(poll_activity) => {
+            if !poll::is_question(&poll_activity.data["object"]) {
+                return Err(ApiError::not_found("Poll not found"));
+            }
+
+            // Results of remote polls are refreshed in the background, the current request is
+            // answered with the results which are already known
+            let local_poll = match actor::get_actor_by_uri(pooled_connection, &poll_activity.actor)
+            {
+                Ok(author) => author.local,
+                Err(_) => false,
+            };
+            if !local_poll && poll::claim_refresh(pooled_connection, &poll_activity) {
+                let stale_poll = poll_activity.clone();
+                thread::spawn(move || {
+                    let database = database::establish_connection();
+                    poll::refresh(&database, &stale_poll);
+                    uncache_status(stale_poll.id);
+                });
+            }
+
+            match serialize_poll(pooled_connection, &poll_activity, viewer.as_ref()) {
+                Some(poll) => Ok(Json(poll)),
+                None => Err(ApiError::not_found("Poll not found")),
+            }
+        }
+        Err(_) => Err(ApiError::not_found("Poll not found")),
+    }
+}
+
+pub fn poll_vote(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    form: PollVoteForm,
+) -> ApiResult<Poll> {
+    let actor = actor_for_token(pooled_connection, token)?;
+    let poll_activity = visible_activity(pooled_connection, id, Some(&actor))
+        .map_err(|_| ApiError::not_found("Poll not found"))?;
+    let options = poll::options(&poll_activity.data["object"]);
+    let mut choices: Vec<String> = Vec::new();
+
+    for index in form.choices {
+        match options.get(index) {
+            Some(option) => {
+                if !choices.contains(option) {
+                    choices.push(option.to_string());
+                }
+            }
+            None => return Err(ApiError::unprocessable("Invalid choice")),
+        }
+    }
+
+    if !poll::votes_for_actor(pooled_connection, id, actor.id)
+        .unwrap_or_default()
+        .is_empty()
+    {
+        return Err(ApiError::unprocessable(
+            "You have already voted on this poll",
+        ));
+    }
+
+    match kibou_api::poll_vote(&actor, &poll_activity, choices) {
+        Ok(_) => {
+            uncache_status(id);
+            let updated_poll =
+                activity::get_activity_by_id(pooled_connection, id).unwrap_or(poll_activity);
+            match serialize_poll(pooled_connection, &updated_poll, Some(&actor)) {
+                Some(poll) => Ok(Json(poll)),
+                None => Err(ApiError::not_found("Poll not found")),
+            }
+        }
+        Err(e) => Err(ApiError::unprocessable(e)),
+    }
+}
+
+pub fn public_timeline(
+    pooled_connection: &PooledConnection,
+    parameters: PublicTimeline,
+    token: Option<String>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
+    let local = parameters.local.unwrap_or_else(|| false);
+
+    match get_public_timeline(
+        pooled_connection,
+        local,
+        parameters.only_media.unwrap_or_else(|| false),
+        parameters.max_id,
+        parameters.since_id,
+        parameters.min_id,
+        Some(page_limit(parameters.limit, 20, 40)),
+    ) {
+        Ok(statuses) => Ok(PaginatedJson::new(
+            cached_statuses(
+                pooled_connection,
+                statuses.clone(),
+                viewer_for_token(pooled_connection, token).as_ref(),
+                "public",
+            ),
+            if local {
+                "/api/v1/timelines/public?local=true"
+            } else {
+                "/api/v1/timelines/public"
+            },
+            statuses,
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while generating timeline.",
+        )),
+    }
+}
+
+pub fn relationships(
+    pooled_connection: &PooledConnection,
+    token: String,
+    ids: Vec<i64>,
+) -> ApiResult<Vec<Relationship>> {
+    let actor = actor_for_token(pooled_connection, token)?;
+    Ok(Json(
+        ids.iter()
+            .filter_map(|id| actor::get_actor_by_id(pooled_connection, id).ok())
+            .map(|target| relationship(pooled_connection, &actor, &target))
+            .collect(),
+    ))
+}
+
+pub fn reblog(pooled_connection: &PooledConnection, token: String, id: i64) -> ApiResult<Status> {
+    status_react_with(pooled_connection, token, id, "Announce")
+}
+
+/// Returns the accounts which reblogged a status
+pub fn reblogged_by(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    token: Option<String>,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Account>>, ApiError> {
+    status_reactors(
+        pooled_connection,
+        id,
+        token,
+        "Announce",
+        &format!("/api/v1/statuses/{}/reblogged_by", id),
+        max_id,
+        since_id,
+        min_id,
+        limit,
+    )
+}
+
+/// Returns the emoji reactions on a status, optionally only those with a certain emoji
+pub fn status_reactions(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    emoji: Option<String>,
+    token: Option<String>,
+) -> ApiResult<Vec<EmojiReaction>> {
+    let viewer = viewer_for_token(pooled_connection, token);
+    let activity = visible_activity(pooled_connection, id, viewer.as_ref())?;
+    let object_id = activity.data["object"]["id"].as_str().unwrap_or_default();
+    let viewer = viewer.map(|actor| actor.actor_uri);
+
+    match reaction::reactions_for_object(pooled_connection, object_id) {
+        Ok(reactions) => Ok(Json(
+            reaction::group(&reactions)
+                .into_iter()
+                .filter(|reaction| match &emoji {
+                    Some(emoji) => &reaction.name == emoji,
+                    None => true,
+                })
+                .map(|reaction| {
+                    EmojiReaction::from_reaction(
+                        pooled_connection,
+                        reaction,
+                        viewer.as_ref().map(String::as_str),
+                        true,
+                    )
+                })
+                .collect(),
+        )),
+        Err(_) => Err(ApiError::internal(
+            "An error occured while fetching reactions",
+        )),
+    }
+}
+
+pub fn status_pin(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<Status> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match visible_activity(pooled_connection, id, Some(&actor)) {
+        Ok(activity) => match pin::pin(pooled_connection, &actor, &activity) {
+            Ok(_) => status_for_viewer(pooled_connection, id, &actor),
+            Err(e) => Err(ApiError::unprocessable(e)),
+        },
+        Err(_) => Err(ApiError::not_found("Status not found")),
+    }
+}
+
+pub fn status_react(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    emoji: String,
+) -> ApiResult<Status> {
+    status_reaction_update(pooled_connection, token, id, emoji, true)
+}
+
+pub fn status_unreact(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    emoji: String,
+) -> ApiResult<Status> {
+    status_reaction_update(pooled_connection, token, id, emoji, false)
+}
+
+pub fn status_bookmark(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<Status> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match visible_activity(pooled_connection, id, Some(&actor)) {
+        Ok(activity) => {
+            bookmark::bookmark(pooled_connection, &actor, activity.id);
+            status_for_viewer(pooled_connection, id, &actor)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub fn status_by_id(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    token: Option<String>,
+) -> ApiResult<Status> {
+    let viewer = viewer_for_token(pooled_connection, token);
+    visible_activity(pooled_connection, id, viewer.as_ref())?;
+    cached_status(pooled_connection, id)
+}
+
+pub fn status_post(
+    pooled_connection: &PooledConnection,
+    form: StatusForm,
+    token: String,
+) -> ApiResult<Status> {
+    let actor = actor_for_token(pooled_connection, token)?;
+    let new_poll = match form.poll {
+        Some(poll_form) => Some(validate_poll(poll_form).map_err(ApiError::unprocessable)?),
+        None => None,
+    };
+    let content = match form.status {
+        Some(content) => content,
+        None => return Err(ApiError::unprocessable("Statuses can't be empty")),
+    };
+
+    let status_id = kibou_api::status_build(
+        actor.actor_uri,
+        content,
+        &form.visibility.unwrap_or_else(|| String::from("public")),
+        form.in_reply_to_id,
+        form.spoiler_text,
+        form.sensitive.unwrap_or_else(|| false),
+        new_poll,
+    )?;
+
+    status_for_viewer(pooled_connection, status_id, &actor)
+}
+
+pub fn status_unbookmark(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<Status> {
+    let actor = actor_for_token(pooled_connection, token)?;
+    bookmark::unbookmark(pooled_connection, &actor, id);
+    status_for_viewer(pooled_connection, id, &actor)
+}
+
+pub fn status_unpin(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<Status> {
+    let actor = actor_for_token(pooled_connection, token)?;
+    pin::unpin(pooled_connection, &actor, id);
+    status_for_viewer(pooled_connection, id, &actor)
+}
+
+pub fn unfollow(token: String, target_id: i64) -> ApiResult<Relationship> {
+    let database = database::establish_connection();
+    let actor = actor_for_token(&database, token)?;
+
+    match actor::get_actor_by_id(&database, &target_id) {
+        Ok(followee) => {
+            kibou_api::unfollow(actor.actor_uri.clone(), followee.actor_uri.clone())?;
+            Ok(Json(relationship(&database, &actor, &followee)))
+        }
+        Err(_) => Err(ApiError::not_found("User not found.")),
+    }
+}
+
+/// Updates the profile of the owner of the token and federates it to their followers
+pub fn update_credentials(
+    pooled_connection: &PooledConnection,
+    token: String,
+    form: UpdateCredentialsForm,
+) -> ApiResult<Account> {
+    let mut actor = actor_for_token(pooled_connection, token)?;
+
+    // Everything is validated before any image gets stored
+    validate_profile(&form).map_err(ApiError::unprocessable)?;
+
+    for (upload, property) in vec![
+        (&form.avatar, &mut actor.icon),
+        (&form.header, &mut actor.header),
+    ] {
+        if let Some(upload) = upload {
+            *property = Some(media::store_image(upload).map_err(ApiError::unprocessable)?);
+        }
+    }
+
+    if let Some(display_name) = form.display_name {
+        actor.username = Some(display_name.trim().to_string());
+    }
+
+    if let Some(note) = form.note {
+        actor.summary = if note.trim().is_empty() {
+            None
+        } else {
+            Some(kibou_api::render_profile_text(note.trim()))
+        };
+    }
+
+    actor.locked = form.locked.unwrap_or(actor.locked);
+    actor.bot = form.bot.unwrap_or(actor.bot);
+    actor.discoverable = form.discoverable.unwrap_or(actor.discoverable);
+
+    if let Some(fields) = form.fields_attributes {
+        let fields: Vec<serde_json::Value> = fields
+            .iter()
+            .filter(|(name, _)| !name.trim().is_empty())
+            .map(|(name, value)| {
+                serde_json::json!({
+                    "name": html::escape(name.trim()),
+                    "value": kibou_api::render_profile_text(value.trim())
+                })
+            })
+            .collect();
+        actor.fields = serde_json::json!(fields);
+    }
+
+    actor::update(pooled_connection, actor.clone());
+
+    uncache_account(&actor.actor_uri);
+    uncache_statuses_by(&actor);
+
+    kibou_api::update_actor(&actor);
+    Ok(Json(Account::from_actor(pooled_connection, actor, true)))
+}
+
+// This function is used to return an empty array on endpoints which are not yet implemented, this
+// happens to prevent breaking Mastodon_API-compatible clients
+pub fn unsupported_endpoint() -> ApiResult<Vec<serde_json::Value>> {
+    Ok(Json(Vec::new()))
+}
+
+pub fn v1_filter(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<V1Filter> {
+    let (filter, keyword) = v1_filter_for_token(pooled_connection, token, id)?;
+    Ok(Json(V1Filter::from_keyword(&filter, keyword)))
+}
+
+/// Creates a filter with a single keyword, as filters of version 1 of the API only have a phrase
+pub fn v1_filter_create(
+    pooled_connection: &PooledConnection,
+    token: String,
+    form: V1FilterForm,
+) -> ApiResult<V1Filter> {
+    let actor = actor_for_token(pooled_connection, token)?;
+    let new_filter = NewFilter {
+        title: form.phrase.clone(),
+        context: form.context,
+        expires_in: form.expires_in,
+        irreversible: form.irreversible.unwrap_or(false),
+    };
+
+    let new_filter =
+        filter::create(pooled_connection, &actor, new_filter).map_err(ApiError::unprocessable)?;
+    match filter::add_keyword(
+        pooled_connection,
+        &new_filter,
+        &form.phrase,
+        form.whole_word.unwrap_or(true),
+    ) {
+        Ok(keyword) => Ok(Json(V1Filter::from_keyword(&new_filter, keyword))),
+        Err(e) => {
+            filter::delete(pooled_connection, &new_filter);
+            Err(ApiError::unprocessable(e))
+        }
+    }
+}
+
+/// Removes a keyword and its filter, once the filter doesn't match anything anymore
+pub fn v1_filter_delete(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    let (filter, keyword) = v1_filter_for_token(pooled_connection, token, id)?;
+    filter::remove_keyword(pooled_connection, &keyword);
+
+    let keywords =
+        filter::keywords_for_filter(pooled_connection, &filter).unwrap_or_else(|_| Vec::new());
+    let statuses =
+        filter::statuses_for_filter(pooled_connection, &filter).unwrap_or_else(|_| Vec::new());
+    if keywords.is_empty() && statuses.is_empty() {
+        filter::delete(pooled_connection, &filter);
+    }
+    Ok(Json(serde_json::json!({})))
+}
+
+pub fn v1_filter_update(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    form: V1FilterForm,
+) -> ApiResult<V1Filter> {
+    let (filter, keyword) = v1_filter_for_token(pooled_connection, token, id)?;
+    let new_filter = NewFilter {
+        title: filter.title.clone(),
+        context: form.context,
+        expires_in: form.expires_in,
+        irreversible: form.irreversible.unwrap_or(false),
+    };
+
+    let filter =
+        filter::update(pooled_connection, &filter, new_filter).map_err(ApiError::unprocessable)?;
+    match filter::update_keyword(
+        pooled_connection,
+        &keyword,
+        &form.phrase,
+        form.whole_word.unwrap_or(true),
+    ) {
+        Ok(keyword) => Ok(Json(V1Filter::from_keyword(&filter, keyword))),
+        Err(e) => Err(ApiError::unprocessable(e)),
+    }
+}
+
+pub fn v1_filters(pooled_connection: &PooledConnection, token: String) -> ApiResult<Vec<V1Filter>> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match filter::filters_for_actor(pooled_connection, &actor) {
+        Ok(filters) => {
+            let mut v1_filters: Vec<V1Filter> = Vec::new();
+            for filter in filters {
+                for keyword in filter::keywords_for_filter(pooled_connection, &filter)
+                    .unwrap_or_else(|_| Vec::new())
+                {
+                    v1_filters.push(V1Filter::from_keyword(&filter, keyword));
+                }
+            }
+            Ok(Json(v1_filters))
+        }
+        Err(_) => Err(ApiError::internal(
+            "An error occured while fetching filters",
+        )),
+    }
+}
+
+// Resolves the local actor an OAuth token belongs to
+fn actor_for_token(
+    pooled_connection: &PooledConnection,
+    token: String,
+) -> Result<actor::Actor, ApiError> {
+    match verify_token(pooled_connection, token) {
+        Ok(token) => {
+            match actor::get_local_actor_by_preferred_username(pooled_connection, &token.actor) {
+                Ok(actor) => Ok(actor),
+                Err(_) => Err(ApiError::unauthorized("User associated to token not found")),
+            }
+        }
+        Err(_) => Err(ApiError::unauthorized("Invalid oauth token")),
+    }
+}
+
+// Sets the properties of statuses which depend on the viewer, as cached statuses are shared
+// between all viewers
+fn apply_viewer_state(
+    pooled_connection: &PooledConnection,
+    mut statuses: Vec<Status>,
+    viewer: Option<&actor::Actor>,
+) -> Vec<Status> {
+    if let Some(viewer) = viewer {
+        let ids: Vec<i64> = statuses
+            .iter()
+            .filter_map(|status| status.id.parse::<i64>().ok())
+            .collect();
+        let bookmarked = bookmark::bookmarked_among(pooled_connection, viewer, ids.clone());
+        let pinned = pin::pinned_among(pooled_connection, viewer, ids);
+
+        for status in &mut statuses {
+            let id = status.id.parse::<i64>().unwrap_or_default();
+            status.bookmarked = Some(bookmarked.contains(&id));
+
+            // Statuses can only be pinned by their author
+            if status.account.id == viewer.id.to_string() {
+                status.pinned = Some(pinned.contains(&id));
+            }
+        }
+    }
+    statuses
+}
+
+// Applies the viewer's filters of a certain context to a set of statuses
+fn apply_filters(
+    pooled_connection: &PooledConnection,
+    statuses: Vec<Status>,
+    viewer: Option<&actor::Actor>,
+    context: &str,
+) -> Vec<Status> {
+    match viewer {
+        Some(viewer) => {
+            let active_filters = filter::active_filters(pooled_connection, viewer, context);
+            statuses
+                .into_iter()
+                .filter_map(|status| filtered_status(&active_filters, viewer, status))
+                .collect()
+        }
+        None => statuses,
+    }
+}
+
+// Resolves the actor an OAuth token belongs to and one of their filters
+fn filter_for_token(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> Result<(actor::Actor, filter::Filter), ApiError> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match filter::get_filter_for_actor(pooled_connection, &actor, id) {
+        Ok(filter) => Ok((actor, filter)),
+        Err(_) => Err(ApiError::not_found("Filter not found")),
+    }
+}
+
+// Returns `None` if a status is hidden by an irreversible filter, otherwise the status is
+// returned with the results of all filters matching it. Reblogs are matched by the status they
+// share and the viewer's own statuses are never filtered.
+fn filtered_status(
+    active_filters: &Vec<filter::ActiveFilter>,
+    viewer: &actor::Actor,
+    mut status: Status,
+) -> Option<Status> {
+    if active_filters.is_empty() || status.account.id == viewer.id.to_string() {
+        return Some(status);
+    }
+
+    let (status_id, content) = match &status.reblog {
+        Some(reblog) => (
+            reblog["id"].as_str().unwrap_or_default().to_string(),
+            format!(
+                "{} {}",
+                reblog["spoiler_text"].as_str().unwrap_or_default(),
+                reblog["content"].as_str().unwrap_or_default()
+            ),
+        ),
+        None => (
+            status.id.clone(),
+            format!("{} {}", status.spoiler_text, status.content),
+        ),
+    };
+    let mut filter_results: Vec<FilterResult> = Vec::new();
+
+    for active_filter in active_filters {
+        let keyword_matches = active_filter.keyword_matches(&content);
+        let status_matches = match status_id.parse::<i64>() {
+            Ok(activity_id) => active_filter.status_matches(activity_id),
+            Err(_) => false,
+        };
+
+        if keyword_matches.is_empty() && !status_matches {
+            continue;
+        }
+
+        if active_filter.filter.irreversible {
+            return None;
+        }
+
+        filter_results.push(FilterResult {
+            filter: Filter::from_filter(
+                active_filter.filter.clone(),
+                active_filter.keywords.clone(),
+                active_filter.statuses.clone(),
+            ),
+            keyword_matches: if keyword_matches.is_empty() {
+                None
+            } else {
+                Some(keyword_matches)
+            },
+            status_matches: if status_matches {
+                Some(vec![status_id.clone()])
+            } else {
+                None
+            },
+        });
+    }
+
+    if !filter_results.is_empty() {
+        status.filtered = Some(filter_results);
+    }
+    Some(status)
+}
+
+// Serializes a page of actors as accounts, linking to the neighbouring pages
+fn paginated_accounts(
+    pooled_connection: &PooledConnection,
+    actors: Result<Vec<actor::Actor>, diesel::result::Error>,
+    path: &str,
+) -> Result<PaginatedJson<Vec<Account>>, ApiError> {
+    match actors {
+        Ok(actors) => {
+            let ids: Vec<i64> = actors.iter().map(|actor| actor.id).collect();
+            let accounts: Vec<Account> = actors
+                .into_iter()
+                .map(|actor| Account::from_actor(pooled_connection, actor, false))
+                .collect();
+            Ok(PaginatedJson::new(accounts, path, ids))
+        }
+        Err(_) => Err(ApiError::internal(
+            "An error occured while fetching accounts",
+        )),
+    }
+}
+
+// Computes the relationship of a viewer to another actor
+fn relationship(
+    pooled_connection: &PooledConnection,
+    viewer: &actor::Actor,
+    target: &actor::Actor,
+) -> Relationship {
+    let following =
+        actor::is_actor_followed_by(pooled_connection, target, &viewer.actor_uri).unwrap_or(false);
+    let followed_by =
+        actor::is_actor_followed_by(pooled_connection, viewer, &target.actor_uri).unwrap_or(false);
+
+    // Follows of remote actors stay requested until they're accepted, which adds the viewer to
+    // the target's followers, local actors keep track of their pending requests
+    let requested = !following
+        && if target.local {
+            follow_request::is_requested(pooled_connection, target, viewer.id)
+        } else {
+            activity::follow_exists(pooled_connection, &viewer.actor_uri, &target.actor_uri)
+                .unwrap_or(false)
+        };
+
+    // Kibou has no blocks, mutes, endorsements or account notes (yet), so their fields are always
+    // unset
+    Relationship {
+        id: target.id.to_string(),
+        following: following,
+        followed_by: followed_by,
+        showing_reblogs: following,
+        blocking: false,
+        muting: false,
+        muting_notifications: false,
+        requested: requested,
+        domain_blocking: false,
+        endorsed: false,
+        note: String::new(),
+    }
+}
+
+// The accounts of a conversation are taken from the audience of its latest status, as only local
+// participants are being tracked in the database.
+fn serialize_conversation(
+    pooled_connection: &PooledConnection,
+    actor: &actor::Actor,
+    participant_conversation: ParticipantConversation,
+) -> Conversation {
+    let mut accounts: Vec<Account> = Vec::new();
+
+    match activity::get_activity_by_id(pooled_connection, participant_conversation.last_activity_id)
+    {
+        Ok(last_activity) => {
+            let mut participants: Vec<String> = vec![last_activity.actor.clone()];
+            for audience in &["to", "cc"] {
+                participants.extend(
+                    serde_json::from_value::<Vec<String>>(last_activity.data[audience].to_owned())
+                        .unwrap_or_else(|_| Vec::new()),
+                );
+            }
+            participants.sort();
+            participants.dedup();
+
+            for participant in participants {
+                if participant != actor.actor_uri {
+                    match cached_account(pooled_connection, &participant) {
+                        Ok(account) => accounts.push(account),
+                        Err(_) => (),
+                    }
+                }
+            }
+        }
+        Err(_) => (),
+    }
+
+    Conversation {
+        id: participant_conversation.id.to_string(),
+        accounts: accounts,
+        unread: participant_conversation.unread,
+        last_status: cached_status(pooled_connection, participant_conversation.last_activity_id)
+            .ok()
+            .map(Json::into_inner),
+    }
+}
+
+// Resolves the actor an OAuth token belongs to and one of their lists
+fn list_for_token(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> Result<(actor::Actor, list::List), ApiError> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match list::get_list_for_actor(pooled_connection, &actor, id) {
+        Ok(list) => Ok((actor, list)),
+        Err(_) => Err(ApiError::not_found("List not found")),
+    }
+}
+
+fn serialize_poll(
+    pooled_connection: &PooledConnection,
+    poll_activity: &activity::Activity,
+    viewer: Option<&actor::Actor>,
+) -> Option<Poll> {
+    let object = &poll_activity.data["object"];
+
+    match Poll::from_object(poll_activity.id, object) {
+        Some(mut poll) => {
+            match viewer {
+                Some(viewer) => {
+                    let options = poll::options(object);
+                    let own_votes: Vec<i64> =
+                        poll::votes_for_actor(pooled_connection, poll_activity.id, viewer.id)
+                            .unwrap_or_default()
+                            .iter()
+                            .filter_map(|vote| {
+                                options.iter().position(|option| option == &vote.choice)
+                            })
+                            .map(|index| index as i64)
+                            .collect();
+
+                    poll.voted =
+                        Some(!own_votes.is_empty() || poll_activity.actor == viewer.actor_uri);
+                    poll.own_votes = Some(own_votes);
+                }
+                None => (),
+            }
+            Some(poll)
+        }
+        None => None,
+    }
+}
+
+fn status_reaction_update(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    emoji: String,
+    react: bool,
+) -> ApiResult<Status> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match visible_activity(pooled_connection, id, Some(&actor)) {
+        Ok(activity) => {
+            let object_id = activity.data["object"]["id"].as_str().unwrap_or_default();
+            let result = if react {
+                kibou_api::emoji_react(&actor, object_id, &emoji)
+            } else {
+                kibou_api::emoji_unreact(&actor, object_id, &emoji)
+            };
+
+            match result {
+                Ok(_) => {
+                    uncache_status(id);
+                    status_for_viewer(pooled_connection, id, &actor)
+                }
+                Err(e) => Err(ApiError::unprocessable(e)),
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// Favourites or reblogs a status, depending on the type of the reaction
+fn status_react_with(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+    reaction: &str,
+) -> ApiResult<Status> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match visible_activity(pooled_connection, id, Some(&actor)) {
+        Ok(activity) => match activity.data["object"]["id"].as_str() {
+            Some(object_id) => {
+                kibou_api::react(&actor.id, reaction, object_id);
+                status_for_viewer(pooled_connection, id, &actor)
+            }
+            None => Err(ApiError::unprocessable("This status can't be reacted to")),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+// Lists the accounts which reacted to a status with a certain type of reaction, paginated by
+// their reactions
+fn status_reactors(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    token: Option<String>,
+    reaction: &str,
+    path: &str,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Account>>, ApiError> {
+    let viewer = viewer_for_token(pooled_connection, token);
+    let activity = visible_activity(pooled_connection, id, viewer.as_ref())?;
+
+    match activity::get_ap_object_reactions_by_id(
+        pooled_connection,
+        activity.data["object"]["id"].as_str().unwrap_or_default(),
+        reaction,
+        max_id,
+        since_id,
+        min_id,
+        page_limit(limit, 40, 80),
+    ) {
+        Ok(reactions) => {
+            let accounts: Vec<Account> = reactions
+                .iter()
+                .filter_map(|reaction| cached_account(pooled_connection, &reaction.actor).ok())
+                .collect();
+
+            Ok(PaginatedJson::new(
+                accounts,
+                path,
+                reactions.iter().map(|reaction| reaction.id).collect(),
+            ))
+        }
+        Err(_) => Err(ApiError::internal(
+            "An error occured while fetching reactions",
+        )),
+    }
+}
+
+fn status_children_for_id(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    resolve_children: bool,
+) -> Vec<Status> {
+    let mut statuses: Vec<Status> = vec![];
+
+    match cached_status(pooled_connection, id) {
+        Ok(status) => match get_ap_object_replies_by_id(pooled_connection, &status.uri) {
+            Ok(replies) => {
+                for reply in replies {
+                    if resolve_children {
+                        let mut child_statuses =
+                            status_children_for_id(pooled_connection, reply.id, true);
+                        statuses.append(&mut child_statuses);
+                    }
+
+                    if let Ok(reply) = Status::try_from(reply) {
+                        statuses.push(reply);
+                    }
+                }
+            }
+            Err(e) => eprintln!("{}", e),
+        },
+        Err(e) => eprintln!("{}", e.message()),
+    }
+    return statuses;
+}
+
+fn status_parents_for_id(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    is_head: bool,
+) -> Vec<Status> {
+    let mut statuses: Vec<Status> = vec![];
+
+    match cached_status(pooled_connection, id) {
+        Ok(status) => {
+            let status = status.into_inner();
+            if let Some(Ok(parent_id)) = status
+                .in_reply_to_id
+                .as_ref()
+                .map(|parent_id| parent_id.parse::<i64>())
+            {
+                statuses.append(&mut status_parents_for_id(
                     pooled_connection,
-                    status
-                        .in_reply_to_id
-                        .clone()
-                        .unwrap()
-                        .parse::<i64>()
-                        .unwrap(),
+                    parent_id,
                     false,
                 ));
             }
@@ -624,8 +2397,214 @@ fn status_parents_for_id(
                 statuses.push(status);
             }
         }
-        Err(e) => eprintln!("{}", e),
+        Err(e) => eprintln!("{}", e.message()),
     }
 
     return statuses;
 }
+
+// Accounts have to be serialized again once the actor changed outside of the Mastodon API, e.g.
+// after moving to another account
+pub fn uncache_account(actor_uri: &str) {
+    let mut account_cache = MASTODON_API_ACCOUNT_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    account_cache.pop(actor_uri);
+}
+
+// Cached statuses embed their poll and reactions, so they have to be serialized again once
+// these change
+fn uncache_status(id: i64) {
+    let mut status_cache = MASTODON_API_STATUS_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    status_cache.pop(&id);
+}
+
+// Cached statuses embed the account of their author, including the original status of reblogs, so
+// they have to be serialized again once the author updates their profile
+fn uncache_statuses_by(actor: &actor::Actor) {
+    let account_id = actor.id.to_string();
+    let mut status_cache = MASTODON_API_STATUS_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let stale_statuses: Vec<i64> = status_cache
+        .iter()
+        .filter(|(_, status)| {
+            status["account"]["id"].as_str() == Some(account_id.as_str())
+                || status["reblog"]["account"]["id"].as_str() == Some(account_id.as_str())
+        })
+        .map(|(id, _)| *id)
+        .collect();
+
+    for id in stale_statuses {
+        status_cache.pop(&id);
+    }
+}
+
+fn validate_profile(form: &UpdateCredentialsForm) -> Result<(), &'static str> {
+    if let Some(display_name) = &form.display_name {
+        if display_name.trim().chars().count() > actor::MAX_DISPLAY_NAME_CHARS {
+            return Err("Display names may not be longer than 30 characters");
+        }
+    }
+
+    if let Some(note) = &form.note {
+        if note.trim().chars().count() > actor::MAX_NOTE_CHARS {
+            return Err("Notes may not be longer than 500 characters");
+        }
+    }
+
+    if let Some(fields) = &form.fields_attributes {
+        if fields.len() > actor::MAX_PROFILE_FIELDS {
+            return Err("Profiles may not have more than 4 metadata fields");
+        }
+
+        if fields.iter().any(|(name, value)| {
+            name.trim().chars().count() > actor::MAX_PROFILE_FIELD_CHARS
+                || value.trim().chars().count() > actor::MAX_PROFILE_FIELD_CHARS
+        }) {
+            return Err("Metadata fields may not be longer than 255 characters");
+        }
+    }
+
+    for upload in vec![&form.avatar, &form.header] {
+        if let Some(upload) = upload {
+            if media::image_extension(&upload.content_type).is_none() {
+                return Err("Unsupported image format");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_poll(form: PollForm) -> Result<NewPoll, &'static str> {
+    let expires_in = form.expires_in.unwrap_or_else(|| 86400);
+
+    if form.options.len() < 2 || form.options.len() > poll::MAX_OPTIONS {
+        return Err("A poll needs between two and four options");
+    }
+
+    if form
+        .options
+        .iter()
+        .any(|option| option.chars().count() > poll::MAX_OPTION_CHARS)
+    {
+        return Err("Poll options can't be longer than 50 characters");
+    }
+
+    let mut unique_options = form.options.clone();
+    unique_options.sort();
+    unique_options.dedup();
+    if unique_options.len() != form.options.len() {
+        return Err("Poll options have to be unique");
+    }
+
+    if expires_in < poll::MIN_EXPIRATION || expires_in > poll::MAX_EXPIRATION {
+        return Err("Polls have to run between five minutes and one month");
+    }
+
+    Ok(NewPoll {
+        options: form.options,
+        expires_in: expires_in,
+        multiple: form.multiple.unwrap_or_else(|| false),
+    })
+}
+
+fn serialize_filter(pooled_connection: &PooledConnection, filter: filter::Filter) -> Filter {
+    let keywords =
+        filter::keywords_for_filter(pooled_connection, &filter).unwrap_or_else(|_| Vec::new());
+    let statuses =
+        filter::statuses_for_filter(pooled_connection, &filter).unwrap_or_else(|_| Vec::new());
+    Filter::from_filter(filter, keywords, statuses)
+}
+
+// Resolves the filter and keyword behind the id of a filter of version 1 of the API
+fn v1_filter_for_token(
+    pooled_connection: &PooledConnection,
+    token: String,
+    id: i64,
+) -> Result<(filter::Filter, filter::FilterKeyword), ApiError> {
+    let actor = actor_for_token(pooled_connection, token)?;
+
+    match filter::get_keyword_for_actor(pooled_connection, &actor, id) {
+        Ok(keyword) => {
+            match filter::get_filter_for_actor(pooled_connection, &actor, keyword.filter_id) {
+                Ok(filter) => Ok((filter, keyword)),
+                Err(_) => Err(ApiError::not_found("Filter not found")),
+            }
+        }
+        Err(_) => Err(ApiError::not_found("Filter not found")),
+    }
+}
+
+// Returns the local actor behind an optional OAuth token, invalid tokens are treated like
+// anonymous requests
+fn viewer_for_token(
+    pooled_connection: &PooledConnection,
+    token: Option<String>,
+) -> Option<actor::Actor> {
+    match token {
+        Some(token) => actor_for_token(pooled_connection, token).ok(),
+        None => None,
+    }
+}
+
+// Returns a single status as seen by anonymous viewers, without checking its audience
+fn cached_status(pooled_connection: &PooledConnection, id: i64) -> ApiResult<Status> {
+    match cached_statuses(pooled_connection, vec![id], None, "thread").pop() {
+        Some(status) => Ok(Json(status)),
+        None => Err(ApiError::not_found("Status not found")),
+    }
+}
+
+// Statuses outside of the viewer's audience are treated as if they didn't exist, so direct
+// messages can't be fetched, bookmarked or reacted to by anyone they weren't addressed to
+fn visible_activity(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    viewer: Option<&actor::Actor>,
+) -> Result<activity::Activity, ApiError> {
+    match activity::get_activity_by_id(pooled_connection, id) {
+        Ok(activity) => {
+            if conversation::is_visible_to(
+                &activity.data,
+                &conversation::followers_uri_of(pooled_connection, &activity.data),
+                viewer.map(|viewer| viewer.actor_uri.as_str()),
+            ) {
+                Ok(activity)
+            } else {
+                Err(ApiError::not_found("Status not found"))
+            }
+        }
+        Err(_) => Err(ApiError::not_found("Status not found")),
+    }
+}
+
+fn visible_statuses(
+    pooled_connection: &PooledConnection,
+    statuses: Vec<Status>,
+    viewer: Option<&actor::Actor>,
+) -> Vec<Status> {
+    statuses
+        .into_iter()
+        .filter(|status| match status.id.parse::<i64>() {
+            Ok(id) => visible_activity(pooled_connection, id, viewer).is_ok(),
+            Err(_) => false,
+        })
+        .collect()
+}
+
+// Returns a single status including the properties which depend on the viewer
+fn status_for_viewer(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    viewer: &actor::Actor,
+) -> ApiResult<Status> {
+    let statuses = cached_statuses(pooled_connection, vec![id], None, "thread");
+
+    match apply_viewer_state(pooled_connection, statuses, Some(viewer)).pop() {
+        Some(status) => Ok(Json(status)),
+        None => Err(ApiError::not_found("Status not found")),
+    }
+}
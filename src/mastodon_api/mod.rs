@@ -8,14 +8,28 @@ use activitypub;
 use actor::{count_followees, get_actor_by_uri, Actor};
 use database;
 use database::PooledConnection;
+use emoji;
 use env;
-use rocket::request;
-use rocket::request::FromRequest;
+use filter;
+use html;
+use list;
+use marker;
+use media::{self, Upload};
+use poll;
+use reaction::{self, Reaction};
+use regex::Regex;
+use rocket::data::{self, Data, FromDataSimple};
+use rocket::http::RawStr;
 use rocket::request::Request;
+use rocket::request::{FormItems, FromForm, FromFormValue};
+use rocket::response::{self, Responder};
 use rocket::Outcome;
+use rocket_contrib::json::Json;
 use serde::{Deserialize, Serialize};
 use serde_json;
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
+use web::multipart::{self, Part};
 
 #[derive(Serialize, Deserialize)]
 pub struct Account {
@@ -37,6 +51,11 @@ pub struct Account {
     pub header: String,
     pub header_static: String,
     pub emojis: Vec<Emoji>,
+    pub bot: bool,
+    pub discoverable: Option<bool>,
+    pub fields: Vec<Field>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moved: Option<Box<Account>>,
     pub source: Option<Source>,
 }
 
@@ -47,7 +66,7 @@ pub struct ApplicationForm {
     // - https://docs.joinmastodon.org/api/rest/apps/#post-api-v1-apps
     pub client_name: String,
     pub redirect_uris: String,
-    pub scopes: String,
+    pub scopes: Option<String>,
     pub website: Option<String>,
 }
 
@@ -64,15 +83,25 @@ pub struct Attachment {
     pub description: Option<String>,
 }
 
-#[derive(Debug)]
-pub struct AuthorizationHeader(pub String);
+#[derive(Serialize, Deserialize)]
+pub struct Conversation {
+    // Properties according to
+    // - https://docs.joinmastodon.org/api/entities/#conversation
+    pub id: String,
+    pub accounts: Vec<Account>,
+    pub unread: bool,
+    pub last_status: Option<Status>,
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct Emoji {
+    // Properties according to
+    // - https://docs.joinmastodon.org/api/entities/#emoji
     pub shortcode: String,
     pub static_url: String,
     pub url: String,
     pub visible_in_picker: bool,
+    pub category: Option<String>,
 }
 
 #[derive(FromForm)]
@@ -83,6 +112,93 @@ pub struct HomeTimeline {
     pub limit: Option<i64>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct EmojiReaction {
+    // Properties according to
+    // - https://docs.pleroma.social/backend/development/API/differences_in_mastoapi_responses/
+    pub name: String,
+    pub count: i64,
+    pub me: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accounts: Option<Vec<Account>>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct Field {
+    // Properties according to
+    // - https://docs.joinmastodon.org/entities/Field/
+    pub name: String,
+    pub value: String,
+    pub verified_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Filter {
+    // Properties according to
+    // - https://docs.joinmastodon.org/entities/Filter/
+    pub id: String,
+    pub title: String,
+    pub context: Vec<String>,
+    pub expires_at: Option<String>,
+    pub filter_action: String,
+    pub keywords: Vec<FilterKeyword>,
+    pub statuses: Vec<FilterStatus>,
+}
+
+pub struct FilterForm {
+    // Properties according to
+    // - https://docs.joinmastodon.org/methods/filters/#create
+    pub title: Option<String>,
+    pub context: Vec<String>,
+    pub filter_action: Option<String>,
+    pub expires_in: Option<i64>,
+    pub keywords_attributes: Vec<FilterKeywordForm>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FilterKeyword {
+    // Properties according to
+    // - https://docs.joinmastodon.org/entities/FilterKeyword/
+    pub id: String,
+    pub keyword: String,
+    pub whole_word: bool,
+}
+
+pub struct FilterKeywordForm {
+    // Properties according to
+    // - https://docs.joinmastodon.org/methods/filters/#keywords-create
+    pub id: Option<i64>,
+    pub keyword: Option<String>,
+    pub whole_word: Option<bool>,
+    pub _destroy: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FilterResult {
+    // Properties according to
+    // - https://docs.joinmastodon.org/entities/FilterResult/
+    pub filter: Filter,
+    pub keyword_matches: Option<Vec<String>>,
+    pub status_matches: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FilterStatus {
+    // Properties according to
+    // - https://docs.joinmastodon.org/entities/FilterStatus/
+    pub id: String,
+    pub status_id: String,
+}
+
+#[derive(FromForm)]
+pub struct FilterStatusForm {
+    // Properties according to
+    // - https://docs.joinmastodon.org/methods/filters/#statuses-add
+    pub status_id: i64,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Instance {
     // Properties according to
@@ -97,6 +213,48 @@ pub struct Instance {
     pub stats: serde_json::Value,
     pub languages: Vec<String>,
     pub contact_account: Option<Account>,
+    pub registrations: bool,
+    pub approval_required: bool,
+    pub invites_enabled: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct List {
+    // Properties according to
+    // - https://docs.joinmastodon.org/api/entities/#list
+    pub id: String,
+    pub title: String,
+}
+
+pub struct ListAccountsForm {
+    // Properties according to
+    // - https://docs.joinmastodon.org/api/rest/lists/#post-api-v1-lists-id-accounts
+    pub account_ids: Vec<i64>,
+}
+
+#[derive(FromForm)]
+pub struct ListForm {
+    // Properties according to
+    // - https://docs.joinmastodon.org/api/rest/lists/#post-api-v1-lists
+    pub title: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Marker {
+    // Properties according to
+    // - https://docs.joinmastodon.org/entities/marker/
+    pub last_read_id: String,
+    pub version: i32,
+    pub updated_at: String,
+}
+
+pub struct MarkersForm {
+    pub timelines: Vec<String>,
+}
+
+pub struct MarkersUpdateForm {
+    pub home: Option<i64>,
+    pub notifications: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -116,6 +274,59 @@ pub struct Notification {
     pub account: Account,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<Status>,
+    // Only set for notifications of the type `pleroma:emoji_reaction`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<String>,
+}
+
+pub struct NotificationsForm {
+    pub types: Vec<String>,
+    pub exclude_types: Vec<String>,
+    pub max_id: Option<i64>,
+    pub since_id: Option<i64>,
+    pub min_id: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+// A JSON response with an optional `Link` header, which points Mastodon-API clients to the
+// neighbouring pages of a paginated list
+pub struct PaginatedJson<T> {
+    pub body: Json<T>,
+    pub link: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Poll {
+    // Properties according to
+    // - https://docs.joinmastodon.org/api/entities/#poll
+    pub id: String,
+    pub expires_at: Option<String>,
+    pub expired: bool,
+    pub multiple: bool,
+    pub votes_count: i64,
+    pub voters_count: Option<i64>,
+    pub voted: Option<bool>,
+    pub own_votes: Option<Vec<i64>>,
+    pub options: Vec<PollOption>,
+    pub emojis: Vec<Emoji>,
+}
+
+pub struct PollForm {
+    // Properties according to
+    // - https://docs.joinmastodon.org/api/rest/statuses/#post-api-v1-statuses
+    pub options: Vec<String>,
+    pub expires_in: Option<i64>,
+    pub multiple: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PollOption {
+    pub title: String,
+    pub votes_count: Option<i64>,
+}
+
+pub struct PollVoteForm {
+    pub choices: Vec<usize>,
 }
 
 #[derive(FromForm)]
@@ -135,6 +346,10 @@ pub struct RegistrationForm {
     pub username: String,
     pub email: String,
     pub password: String,
+    // Required if new accounts are approved by admins, see `registration`
+    pub reason: Option<String>,
+    pub invite_code: Option<String>,
+    pub captcha_answer: Option<String>,
     // Optional values in Kibou, as they're not used by the backend (yet?)
     pub agreement: Option<String>,
     pub locale: Option<String>,
@@ -147,10 +362,18 @@ pub struct Relationship {
     pub id: String,
     pub following: bool,
     pub followed_by: bool,
+    pub showing_reblogs: bool,
     pub blocking: bool,
     pub muting: bool,
     pub muting_notifications: bool,
     pub requested: bool,
+    pub domain_blocking: bool,
+    pub endorsed: bool,
+    pub note: String,
+}
+
+pub struct RelationshipsForm {
+    pub ids: Vec<i64>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq)]
@@ -159,7 +382,7 @@ pub struct Source {
     pub sensitive: Option<bool>,
     pub language: Option<String>,
     pub note: String,
-    pub fields: Option<Vec<String>>,
+    pub fields: Option<Vec<Field>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -182,6 +405,7 @@ pub struct Status {
     pub reblogged: Option<bool>,
     pub favourited: Option<bool>,
     pub muted: Option<bool>,
+    pub bookmarked: Option<bool>,
     pub sensitive: bool,
     pub spoiler_text: String,
     pub visibility: String,
@@ -191,9 +415,21 @@ pub struct Status {
     pub application: serde_json::Value,
     pub language: Option<String>,
     pub pinned: Option<bool>,
+    pub poll: Option<Poll>,
+    #[serde(default)]
+    pub pleroma: StatusPleroma,
+    // Only set for statuses matched by a filter of the viewing account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filtered: Option<Vec<FilterResult>>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct StatusPleroma {
+    // Properties according to
+    // - https://docs.pleroma.social/backend/development/API/differences_in_mastoapi_responses/
+    pub emoji_reactions: Vec<EmojiReaction>,
 }
 
-#[derive(FromForm)]
 pub struct StatusForm {
     pub status: Option<String>,
     pub in_reply_to_id: Option<String>,
@@ -201,6 +437,7 @@ pub struct StatusForm {
     pub sensitive: Option<bool>,
     pub spoiler_text: Option<String>,
     pub visibility: Option<String>,
+    pub poll: Option<PollForm>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -210,12 +447,71 @@ pub struct Tag {
     pub history: Option<serde_json::Value>,
 }
 
+pub struct UpdateCredentialsForm {
+    // Properties according to
+    // - https://docs.joinmastodon.org/methods/accounts/#update_credentials
+    pub display_name: Option<String>,
+    pub note: Option<String>,
+    pub avatar: Option<Upload>,
+    pub header: Option<Upload>,
+    pub locked: Option<bool>,
+    pub bot: Option<bool>,
+    pub discoverable: Option<bool>,
+    pub fields_attributes: Option<Vec<(String, String)>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct V1Filter {
+    // Properties according to
+    // - https://docs.joinmastodon.org/entities/V1_Filter/
+    pub id: String,
+    pub phrase: String,
+    pub context: Vec<String>,
+    pub expires_at: Option<String>,
+    pub irreversible: bool,
+    pub whole_word: bool,
+}
+
+pub struct V1FilterForm {
+    // Properties according to
+    // - https://docs.joinmastodon.org/methods/filters/#create-v1
+    pub phrase: String,
+    pub context: Vec<String>,
+    pub irreversible: Option<bool>,
+    pub whole_word: Option<bool>,
+    pub expires_in: Option<i64>,
+}
+
 // Entities throw an empty error if they fail serializing. Entities should only fail silently, as
 // these errors do not show up on the API. Furthermore should serializing a Mastodon-API entity
 // never panic.
 
 impl Account {
     pub fn from_actor(
+        pooled_connection: &PooledConnection,
+        actor: Actor,
+        include_source: bool,
+    ) -> Account {
+        let moved = match &actor.moved_to {
+            Some(moved_to) => match get_actor_by_uri(pooled_connection, moved_to) {
+                // The new account isn't resolved any further, as accounts might move back and forth
+                Ok(moved_actor) => Some(Box::new(Account::from_actor_without_moved(
+                    pooled_connection,
+                    moved_actor,
+                    false,
+                ))),
+                Err(_) => None,
+            },
+            None => None,
+        };
+
+        let mut account =
+            Account::from_actor_without_moved(pooled_connection, actor, include_source);
+        account.moved = moved;
+        return account;
+    }
+
+    fn from_actor_without_moved(
         pooled_connection: &PooledConnection,
         mut actor: Actor,
         include_source: bool,
@@ -228,12 +524,43 @@ impl Account {
         let statuses = count_ap_notes_for_actor(&pooled_connection, &actor.actor_uri)
             .unwrap_or_else(|_| 0) as i64;
 
+        // Only emojis of local actors are known, as the tags of remote actors aren't stored
+        let emojis = if actor.local {
+            Emoji::from_tags(&serde_json::json!(emoji::tags_for_text(
+                &pooled_connection,
+                &format!(
+                    "{} {}",
+                    actor.username.clone().unwrap_or_default(),
+                    actor.summary.clone().unwrap_or_default()
+                )
+            )))
+        } else {
+            vec![]
+        };
+
+        let fields: Vec<Field> = serde_json::from_value(actor.fields.clone())
+            .unwrap_or_else(|_| Vec::<serde_json::Value>::new())
+            .iter()
+            .map(|field| Field {
+                name: html::to_raw_text(field["name"].as_str().unwrap_or_default()),
+                value: field["value"].as_str().unwrap_or_default().to_string(),
+                verified_at: None,
+            })
+            .collect();
+        let header = actor.header.clone().unwrap_or_else(|| {
+            format!(
+                "{}://{}/static/assets/default_banner.png",
+                env::get_value(String::from("endpoint.base_scheme")),
+                env::get_value(String::from("endpoint.base_domain"))
+            )
+        });
+
         let mut new_account = Account {
             id: actor.id.to_string(),
             username: actor.preferred_username.clone(),
             acct: actor.get_acct(),
             display_name: actor.username.unwrap_or_else(|| String::from("")),
-            locked: false,
+            locked: actor.locked,
             created_at: actor.created.to_string(),
             followers_count: followers.len() as i64,
             following_count: followees,
@@ -254,17 +581,13 @@ impl Account {
                     env::get_value(String::from("endpoint.base_domain"))
                 )
             }),
-            header: format!(
-                "{}://{}/static/assets/default_banner.png",
-                env::get_value(String::from("endpoint.base_scheme")),
-                env::get_value(String::from("endpoint.base_domain"))
-            ),
-            header_static: format!(
-                "{}://{}/static/assets/default_banner.png",
-                env::get_value(String::from("endpoint.base_scheme")),
-                env::get_value(String::from("endpoint.base_domain"))
-            ),
-            emojis: vec![],
+            header: header.clone(),
+            header_static: header,
+            emojis: emojis,
+            bot: actor.bot,
+            discoverable: Some(actor.discoverable),
+            fields: fields,
+            moved: None,
             source: None,
         };
 
@@ -273,8 +596,18 @@ impl Account {
                 privacy: None,
                 sensitive: None,
                 language: None,
-                note: new_account.note.clone(),
-                fields: None,
+                note: html::to_raw_text(&new_account.note),
+                fields: Some(
+                    new_account
+                        .fields
+                        .iter()
+                        .map(|field| Field {
+                            name: field.name.clone(),
+                            value: html::to_raw_text(&field.value),
+                            verified_at: None,
+                        })
+                        .collect(),
+                ),
             });
         }
 
@@ -282,6 +615,171 @@ impl Account {
     }
 }
 
+impl Emoji {
+    pub fn from_emoji(emoji: emoji::Emoji) -> Emoji {
+        Emoji {
+            shortcode: emoji.shortcode.clone(),
+            static_url: emoji.url(),
+            url: emoji.url(),
+            visible_in_picker: emoji.visible_in_picker,
+            category: emoji.category,
+        }
+    }
+
+    pub fn from_tags(tags: &serde_json::Value) -> Vec<Emoji> {
+        emoji::parse_tags(tags)
+            .into_iter()
+            .map(|(shortcode, url)| Emoji {
+                shortcode: shortcode,
+                static_url: url.clone(),
+                url: url,
+                visible_in_picker: false,
+                category: None,
+            })
+            .collect()
+    }
+}
+
+impl EmojiReaction {
+    /// Serializes grouped reactions, the `me` property depends on the viewer
+    ///
+    /// # Parameters
+    ///
+    /// * `pooled_connection` - &PooledConnection | Reference to a database connection
+    /// * `reaction`          -          Reaction | All reactions with the same emoji on a status
+    /// * `viewer`            -      Option<&str> | The actor URI of the viewer, if there's one
+    /// * `with_accounts`     -              bool | Whether the reacting accounts are included
+    pub fn from_reaction(
+        pooled_connection: &PooledConnection,
+        reaction: Reaction,
+        viewer: Option<&str>,
+        with_accounts: bool,
+    ) -> EmojiReaction {
+        let accounts: Option<Vec<Account>> = if with_accounts {
+            Some(
+                reaction
+                    .actors
+                    .iter()
+                    .filter_map(|actor| controller::cached_account(pooled_connection, actor).ok())
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        EmojiReaction {
+            count: reaction.actors.len() as i64,
+            me: match viewer {
+                Some(viewer) => reaction.actors.iter().any(|actor| actor == viewer),
+                None => false,
+            },
+            name: reaction.name,
+            url: reaction.url,
+            accounts: accounts,
+        }
+    }
+}
+
+impl Filter {
+    pub fn from_filter(
+        filter: filter::Filter,
+        keywords: Vec<filter::FilterKeyword>,
+        statuses: Vec<filter::FilterStatus>,
+    ) -> Filter {
+        Filter {
+            id: filter.id.to_string(),
+            title: filter.title,
+            context: filter.context,
+            expires_at: filter.expires_at.map(|date| {
+                chrono::DateTime::<chrono::Utc>::from_utc(date, chrono::Utc).to_rfc3339()
+            }),
+            filter_action: if filter.irreversible {
+                String::from("hide")
+            } else {
+                String::from("warn")
+            },
+            keywords: keywords
+                .into_iter()
+                .map(FilterKeyword::from_keyword)
+                .collect(),
+            statuses: statuses
+                .into_iter()
+                .map(FilterStatus::from_status)
+                .collect(),
+        }
+    }
+}
+
+impl FilterKeyword {
+    pub fn from_keyword(keyword: filter::FilterKeyword) -> FilterKeyword {
+        FilterKeyword {
+            id: keyword.id.to_string(),
+            keyword: keyword.keyword,
+            whole_word: keyword.whole_word,
+        }
+    }
+}
+
+impl FilterKeywordForm {
+    fn new() -> FilterKeywordForm {
+        FilterKeywordForm {
+            id: None,
+            keyword: None,
+            whole_word: None,
+            _destroy: None,
+        }
+    }
+
+    fn has(&self, field: &str) -> bool {
+        match field {
+            "id" => self.id.is_some(),
+            "keyword" => self.keyword.is_some(),
+            "whole_word" => self.whole_word.is_some(),
+            "_destroy" => self._destroy.is_some(),
+            _ => false,
+        }
+    }
+
+    fn set(&mut self, field: &str, value: String) {
+        match field {
+            "id" => self.id = value.parse::<i64>().ok(),
+            "keyword" => self.keyword = Some(value),
+            "whole_word" => self.whole_word = parse_form_bool(&value),
+            "_destroy" => self._destroy = parse_form_bool(&value),
+            _ => (),
+        }
+    }
+}
+
+impl FilterStatus {
+    pub fn from_status(status: filter::FilterStatus) -> FilterStatus {
+        FilterStatus {
+            id: status.id.to_string(),
+            status_id: status.activity_id.to_string(),
+        }
+    }
+}
+
+impl List {
+    pub fn from_list(list: list::List) -> List {
+        List {
+            id: list.id.to_string(),
+            title: list.title,
+        }
+    }
+}
+
+impl Marker {
+    pub fn from_marker(marker: marker::Marker) -> Marker {
+        Marker {
+            last_read_id: marker.last_read_id.to_string(),
+            version: marker.version,
+            updated_at: chrono::DateTime::<chrono::Utc>::from_utc(marker.modified, chrono::Utc)
+                .to_rfc3339(),
+        }
+    }
+}
+
 impl Notification {
     pub fn try_from(activity: Activity) -> Result<Self, ()> {
         let activitypub_activity: Result<activitypub::activity::Activity, serde_json::Error> =
@@ -305,13 +803,7 @@ impl Notification {
         activity_id: i64,
         activity: activitypub::activity::Activity,
     ) -> Result<Self, ()> {
-        let account_result: Result<Account, serde_json::Error> = serde_json::from_value(
-            controller::cached_account(
-                pooled_connection,
-                Box::leak(activity.actor.to_owned().into_boxed_str()),
-            )
-            .into(),
-        );
+        let account_result = controller::cached_account(pooled_connection, &activity.actor);
 
         // An activity without an actor should never occur, but if it does,
         // this function needs to fail.
@@ -322,30 +814,30 @@ impl Notification {
                     "Create" => String::from("mention"),
                     "Announce" => String::from("reblog"),
                     "Like" => String::from("favourite"),
+                    "EmojiReact" => String::from("pleroma:emoji_reaction"),
                     _ => String::from(""),
                 };
                 let mut status: Option<Status> = None;
 
                 if !notification_type.is_empty() {
-                    if &notification_type == "reblog" || &notification_type == "favourite" {
-                        status = serde_json::from_value(
-                            controller::status_by_id(
-                                pooled_connection,
-                                get_ap_object_by_id(
-                                    pooled_connection,
-                                    activity.object.as_str().unwrap(),
-                                )
-                                .unwrap()
-                                .id,
-                            )
-                            .into(),
-                        )
-                        .unwrap_or_else(|_| None);
+                    if &notification_type == "reblog"
+                        || &notification_type == "favourite"
+                        || &notification_type == "pleroma:emoji_reaction"
+                    {
+                        status = activity
+                            .object
+                            .as_str()
+                            .and_then(|object_id| {
+                                get_ap_object_by_id(pooled_connection, object_id).ok()
+                            })
+                            .and_then(|object| {
+                                controller::status_by_id(pooled_connection, object.id).ok()
+                            })
+                            .map(Json::into_inner);
                     } else if &notification_type == "mention" {
-                        status = serde_json::from_value(
-                            controller::status_by_id(pooled_connection, activity_id).into(),
-                        )
-                        .unwrap_or_else(|_| None);
+                        status = controller::status_by_id(pooled_connection, activity_id)
+                            .ok()
+                            .map(Json::into_inner);
                     }
 
                     return Ok(Notification {
@@ -354,6 +846,7 @@ impl Notification {
                         created_at: activity.published,
                         account: account,
                         status: status,
+                        emoji: activity.content,
                     });
                 } else {
                     return Err(());
@@ -364,6 +857,39 @@ impl Notification {
     }
 }
 
+impl Poll {
+    /// Serializes the poll of a status, the `voted` and `own_votes` properties depend on the
+    /// viewer and have to be set separately
+    pub fn from_object(activity_id: i64, object: &serde_json::Value) -> Option<Self> {
+        if !poll::is_question(object) {
+            return None;
+        }
+
+        let option_votes = poll::option_votes(object);
+        let options: Vec<PollOption> = poll::options(object)
+            .into_iter()
+            .enumerate()
+            .map(|(index, title)| PollOption {
+                title: title,
+                votes_count: option_votes.get(index).cloned(),
+            })
+            .collect();
+
+        Some(Poll {
+            id: activity_id.to_string(),
+            expires_at: poll::end_time(object).map(|date| date.to_rfc3339()),
+            expired: poll::is_expired(object),
+            multiple: poll::is_multiple(object),
+            votes_count: option_votes.iter().sum(),
+            voters_count: object["votersCount"].as_i64(),
+            voted: None,
+            own_votes: None,
+            options: options,
+            emojis: vec![],
+        })
+    }
+}
+
 impl Status {
     pub fn try_from(activity: Activity) -> Result<Self, ()> {
         let activitypub_activity: Result<activitypub::activity::Activity, serde_json::Error> =
@@ -382,9 +908,7 @@ impl Status {
         activity_id: i64,
         activity: activitypub::activity::Activity,
     ) -> Result<Self, ()> {
-        let account_result: Result<Account, serde_json::Error> = serde_json::from_value(
-            controller::cached_account(pooled_connection, &activity.actor).into(),
-        );
+        let account_result = controller::cached_account(pooled_connection, &activity.actor);
 
         // An activity without an actor should never occur, but if it does,
         // this function needs to fail.
@@ -392,10 +916,7 @@ impl Status {
             Ok(account) => {
                 let mut mentions: Vec<Mention> = Vec::new();
                 for actor in &activity.to {
-                    let mention_account: Result<Account, serde_json::Error> =
-                        serde_json::from_value(
-                            controller::cached_account(pooled_connection, &actor).into(),
-                        );
+                    let mention_account = controller::cached_account(pooled_connection, &actor);
                     // Just unwrapping every account would mean that an entire status fails serializing,
                     // because of one invalid account.
                     match mention_account {
@@ -510,6 +1031,25 @@ impl Status {
                                 )
                                 .unwrap_or_else(|_| 0)
                                     as i64;
+                                let emoji_reactions: Vec<EmojiReaction> =
+                                    reaction::reactions_for_object(
+                                        pooled_connection,
+                                        &inner_object.id,
+                                    )
+                                    .map(|reactions| reaction::group(&reactions))
+                                    .unwrap_or_else(|_| Vec::new())
+                                    .into_iter()
+                                    .map(|reaction| {
+                                        EmojiReaction::from_reaction(
+                                            pooled_connection,
+                                            reaction,
+                                            None,
+                                            false,
+                                        )
+                                    })
+                                    .collect();
+                                let spoiler_text: String =
+                                    inner_object.summary.clone().unwrap_or_default();
                                 return Ok(Status {
                                     id: activity_id.to_string(),
                                     uri: inner_object.id.clone(),
@@ -520,15 +1060,17 @@ impl Status {
                                     reblog: None,
                                     content: inner_object.content,
                                     created_at: inner_object.published,
-                                    emojis: vec![],
+                                    emojis: Emoji::from_tags(&activity.object["tag"]),
                                     replies_count: replies,
                                     reblogs_count: reblogs,
                                     favourites_count: favourites,
                                     reblogged: Some(false),
                                     favourited: Some(false),
                                     muted: None,
-                                    sensitive: inner_object.sensitive.unwrap_or_else(|| false),
-                                    spoiler_text: "".to_string(),
+                                    bookmarked: None,
+                                    sensitive: inner_object.sensitive.unwrap_or_else(|| false)
+                                        || !spoiler_text.is_empty(),
+                                    spoiler_text: spoiler_text,
                                     visibility: visibility,
                                     media_attachments: media_attachments,
                                     mentions: mentions,
@@ -536,6 +1078,11 @@ impl Status {
                                     application: serde_json::json!({"name": "Web", "website": null}),
                                     language: None,
                                     pinned: None,
+                                    poll: Poll::from_object(activity_id, &activity.object),
+                                    pleroma: StatusPleroma {
+                                        emoji_reactions: emoji_reactions,
+                                    },
+                                    filtered: None,
                                 });
                             }
                             Err(_) => Err(()),
@@ -565,6 +1112,7 @@ impl Status {
                                             reblogged: Some(false),
                                             favourited: Some(false),
                                             muted: Some(false),
+                                            bookmarked: None,
                                             sensitive: false,
                                             spoiler_text: String::new(),
                                             visibility: visibility,
@@ -574,6 +1122,11 @@ impl Status {
                                             application: serde_json::json!({"name": "Web", "website": null}),
                                             language: None,
                                             pinned: None,
+                                            poll: None,
+                                            pleroma: StatusPleroma {
+                                                emoji_reactions: vec![],
+                                            },
+                                            filtered: None,
                                         })
                                     }
                                     Err(_) => Err(()),
@@ -591,22 +1144,425 @@ impl Status {
     }
 }
 
-impl ToString for AuthorizationHeader {
-    fn to_string(&self) -> String {
-        format!("{:?}", &self)
+impl UpdateCredentialsForm {
+    fn from_parts(parts: Vec<Part>) -> UpdateCredentialsForm {
+        let attribute_regex = Regex::new(r"^fields_attributes\[(\d+)\]\[(name|value)\]$").unwrap();
+        let mut form = UpdateCredentialsForm {
+            display_name: None,
+            note: None,
+            avatar: None,
+            header: None,
+            locked: None,
+            bot: None,
+            discoverable: None,
+            fields_attributes: None,
+        };
+        let mut fields: BTreeMap<usize, (String, String)> = BTreeMap::new();
+
+        for part in parts {
+            if part.name == "avatar" || part.name == "header" {
+                // Browsers submit empty file inputs as well
+                if !part.data.is_empty() {
+                    let upload = Upload {
+                        content_type: part.content_type.unwrap_or_default(),
+                        data: part.data,
+                    };
+
+                    if part.name == "avatar" {
+                        form.avatar = Some(upload);
+                    } else {
+                        form.header = Some(upload);
+                    }
+                }
+                continue;
+            }
+
+            let value = String::from_utf8_lossy(&part.data).to_string();
+            match part.name.as_str() {
+                "display_name" => form.display_name = Some(value),
+                "note" => form.note = Some(value),
+                "locked" => form.locked = parse_form_bool(&value),
+                "bot" => form.bot = parse_form_bool(&value),
+                "discoverable" => form.discoverable = parse_form_bool(&value),
+                key => {
+                    if let Some(captures) = attribute_regex.captures(key) {
+                        if let Ok(index) = captures[1].parse::<usize>() {
+                            let field = fields.entry(index).or_default();
+                            if &captures[2] == "name" {
+                                field.0 = value;
+                            } else {
+                                field.1 = value;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if !fields.is_empty() {
+            form.fields_attributes = Some(fields.into_iter().map(|(_, field)| field).collect());
+        }
+        form
     }
 }
 
-impl<'a, 'r> FromRequest<'a, 'r> for AuthorizationHeader {
+impl V1Filter {
+    // Version 1 of the filter API knows one keyword per filter, which is why each keyword is
+    // represented as its own filter
+    pub fn from_keyword(filter: &filter::Filter, keyword: filter::FilterKeyword) -> V1Filter {
+        V1Filter {
+            id: keyword.id.to_string(),
+            phrase: keyword.keyword,
+            context: filter.context.clone(),
+            expires_at: filter.expires_at.map(|date| {
+                chrono::DateTime::<chrono::Utc>::from_utc(date, chrono::Utc).to_rfc3339()
+            }),
+            irreversible: filter.irreversible,
+            whole_word: keyword.whole_word,
+        }
+    }
+}
+
+impl<T> PaginatedJson<T> {
+    /// Builds a paginated response for the endpoint at `path` which returned the given IDs
+    ///
+    /// # Parameters
+    ///
+    /// * `body` -        T | The response body
+    /// * `path` -     &str | Path of the endpoint, e.g. `/api/v1/favourites`, including any
+    ///                       query parameters besides the pagination
+    /// * `ids`  - Vec<i64> | The IDs used for paginating the listed entities
+    ///
+    pub fn new(body: T, path: &str, ids: Vec<i64>) -> PaginatedJson<T> {
+        PaginatedJson {
+            body: Json(body),
+            link: link_header(
+                &format!(
+                    "{base_scheme}://{base_domain}{path}",
+                    base_scheme = env::get_value(String::from("endpoint.base_scheme")),
+                    base_domain = env::get_value(String::from("endpoint.base_domain")),
+                    path = path
+                ),
+                &ids,
+            ),
+        }
+    }
+}
+
+impl<'r, T: Serialize> Responder<'r> for PaginatedJson<T> {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+        let mut response = self.body.respond_to(request)?;
+        if let Some(link) = self.link {
+            response.set_raw_header("Link", link);
+        }
+        Ok(response)
+    }
+}
+
+// Mastodon-API clients submit poll options, choices, filter contexts, account IDs, timelines and
+// notification types as repeated or nested fields (e.g. `choices[]`), which Rocket's derived forms
+// don't support.
+impl<'f> FromForm<'f> for FilterForm {
     type Error = ();
 
-    fn from_request(request: &'a Request<'r>) -> request::Outcome<AuthorizationHeader, ()> {
-        let headers: Vec<_> = request.headers().get("Authorization").collect();
-        if headers.is_empty() {
-            return Outcome::Failure((rocket::http::Status::BadRequest, ()));
-        } else {
-            return Outcome::Success(AuthorizationHeader(headers[0].to_string()));
+    fn from_form(items: &mut FormItems<'f>, _strict: bool) -> Result<FilterForm, ()> {
+        let attribute_regex = Regex::new(r"^keywords_attributes\[(\d*)\]\[(\w+)\]$").unwrap();
+        let mut form = FilterForm {
+            title: None,
+            context: Vec::new(),
+            filter_action: None,
+            expires_in: None,
+            keywords_attributes: Vec::new(),
+        };
+        let mut keyword_forms: Vec<(String, FilterKeywordForm)> = Vec::new();
+
+        for item in items {
+            let (key, value) = item.key_value_decoded();
+            match key.as_str() {
+                "title" => form.title = Some(value),
+                "context[]" | "context" => form.context.push(value),
+                "filter_action" => form.filter_action = Some(value),
+                "expires_in" => form.expires_in = value.parse::<i64>().ok(),
+                _ => {
+                    if let Some(captures) = attribute_regex.captures(&key) {
+                        let index = captures[1].to_string();
+                        let field = &captures[2];
+
+                        // Keywords are either indexed (`keywords_attributes[0][keyword]`) or
+                        // start over once a field repeats (`keywords_attributes[][keyword]`)
+                        let position = if index.is_empty() {
+                            match keyword_forms.last() {
+                                Some((last_index, keyword_form))
+                                    if last_index.is_empty() && !keyword_form.has(field) =>
+                                {
+                                    Some(keyword_forms.len() - 1)
+                                }
+                                _ => None,
+                            }
+                        } else {
+                            keyword_forms
+                                .iter()
+                                .position(|(form_index, _)| form_index == &index)
+                        };
+                        let position = position.unwrap_or_else(|| {
+                            keyword_forms.push((index.clone(), FilterKeywordForm::new()));
+                            keyword_forms.len() - 1
+                        });
+
+                        keyword_forms[position].1.set(field, value);
+                    }
+                }
+            }
+        }
+
+        form.keywords_attributes = keyword_forms
+            .into_iter()
+            .map(|(_, keyword_form)| keyword_form)
+            .collect();
+        Ok(form)
+    }
+}
+
+impl<'f> FromForm<'f> for FilterKeywordForm {
+    type Error = ();
+
+    fn from_form(items: &mut FormItems<'f>, _strict: bool) -> Result<FilterKeywordForm, ()> {
+        let mut form = FilterKeywordForm::new();
+
+        for item in items {
+            let (key, value) = item.key_value_decoded();
+            form.set(key.as_str(), value);
         }
+        Ok(form)
+    }
+}
+
+impl<'f> FromForm<'f> for ListAccountsForm {
+    type Error = ();
+
+    fn from_form(items: &mut FormItems<'f>, _strict: bool) -> Result<ListAccountsForm, ()> {
+        let mut account_ids: Vec<i64> = Vec::new();
+
+        for item in items {
+            let (key, value) = item.key_value_decoded();
+            match key.as_str() {
+                "account_ids[]" | "account_ids" => match value.parse::<i64>() {
+                    Ok(account_id) => account_ids.push(account_id),
+                    Err(_) => return Err(()),
+                },
+                _ => (),
+            }
+        }
+
+        Ok(ListAccountsForm {
+            account_ids: account_ids,
+        })
+    }
+}
+
+impl<'f> FromForm<'f> for MarkersForm {
+    type Error = ();
+
+    fn from_form(items: &mut FormItems<'f>, _strict: bool) -> Result<MarkersForm, ()> {
+        let mut timelines: Vec<String> = Vec::new();
+
+        for item in items {
+            let (key, value) = item.key_value_decoded();
+            match key.as_str() {
+                "timeline[]" | "timeline" => timelines.push(value),
+                _ => (),
+            }
+        }
+
+        Ok(MarkersForm {
+            timelines: timelines,
+        })
+    }
+}
+
+impl<'f> FromForm<'f> for MarkersUpdateForm {
+    type Error = ();
+
+    fn from_form(items: &mut FormItems<'f>, _strict: bool) -> Result<MarkersUpdateForm, ()> {
+        let mut form = MarkersUpdateForm {
+            home: None,
+            notifications: None,
+        };
+
+        for item in items {
+            let (key, value) = item.key_value_decoded();
+            match key.as_str() {
+                "home[last_read_id]" => match value.parse::<i64>() {
+                    Ok(id) => form.home = Some(id),
+                    Err(_) => return Err(()),
+                },
+                "notifications[last_read_id]" => match value.parse::<i64>() {
+                    Ok(id) => form.notifications = Some(id),
+                    Err(_) => return Err(()),
+                },
+                _ => (),
+            }
+        }
+
+        Ok(form)
+    }
+}
+
+impl<'f> FromForm<'f> for NotificationsForm {
+    type Error = ();
+
+    fn from_form(items: &mut FormItems<'f>, _strict: bool) -> Result<NotificationsForm, ()> {
+        let mut form = NotificationsForm {
+            types: Vec::new(),
+            exclude_types: Vec::new(),
+            max_id: None,
+            since_id: None,
+            min_id: None,
+            limit: None,
+        };
+
+        for item in items {
+            let (key, value) = item.key_value_decoded();
+            match key.as_str() {
+                "types[]" | "types" => form.types.push(value),
+                "exclude_types[]" | "exclude_types" => form.exclude_types.push(value),
+                "max_id" => form.max_id = value.parse::<i64>().ok(),
+                "since_id" => form.since_id = value.parse::<i64>().ok(),
+                "min_id" => form.min_id = value.parse::<i64>().ok(),
+                "limit" => form.limit = value.parse::<i64>().ok(),
+                _ => (),
+            }
+        }
+
+        Ok(form)
+    }
+}
+
+impl<'f> FromForm<'f> for PollVoteForm {
+    type Error = ();
+
+    fn from_form(items: &mut FormItems<'f>, _strict: bool) -> Result<PollVoteForm, ()> {
+        let mut choices: Vec<usize> = Vec::new();
+
+        for item in items {
+            let (key, value) = item.key_value_decoded();
+            match key.as_str() {
+                "choices[]" | "choices" => match value.parse::<usize>() {
+                    Ok(choice) => choices.push(choice),
+                    Err(_) => return Err(()),
+                },
+                _ => (),
+            }
+        }
+
+        Ok(PollVoteForm { choices: choices })
+    }
+}
+
+impl<'f> FromForm<'f> for RelationshipsForm {
+    type Error = ();
+
+    fn from_form(items: &mut FormItems<'f>, _strict: bool) -> Result<RelationshipsForm, ()> {
+        let mut ids: Vec<i64> = Vec::new();
+
+        for item in items {
+            let (key, value) = item.key_value_decoded();
+            match key.as_str() {
+                "id[]" | "id" => match value.parse::<i64>() {
+                    Ok(id) => ids.push(id),
+                    Err(_) => return Err(()),
+                },
+                _ => (),
+            }
+        }
+
+        Ok(RelationshipsForm { ids: ids })
+    }
+}
+
+impl<'f> FromForm<'f> for StatusForm {
+    type Error = ();
+
+    fn from_form(items: &mut FormItems<'f>, _strict: bool) -> Result<StatusForm, ()> {
+        let mut form = StatusForm {
+            status: None,
+            in_reply_to_id: None,
+            media_ids: None,
+            sensitive: None,
+            spoiler_text: None,
+            visibility: None,
+            poll: None,
+        };
+        let mut poll_form = PollForm {
+            options: Vec::new(),
+            expires_in: None,
+            multiple: None,
+        };
+
+        for item in items {
+            let (key, value) = item.key_value_decoded();
+            match key.as_str() {
+                "status" => form.status = Some(value),
+                "in_reply_to_id" => form.in_reply_to_id = Some(value),
+                "media_ids" | "media_ids[]" => form.media_ids = Some(value),
+                "sensitive" => form.sensitive = parse_form_bool(&value),
+                "spoiler_text" => form.spoiler_text = Some(value),
+                "visibility" => form.visibility = Some(value),
+                "poll[options][]" | "poll[options]" => {
+                    if !value.trim().is_empty() {
+                        poll_form.options.push(value)
+                    }
+                }
+                "poll[expires_in]" => poll_form.expires_in = value.parse::<i64>().ok(),
+                "poll[multiple]" => poll_form.multiple = parse_form_bool(&value),
+                _ => (),
+            }
+        }
+
+        if !poll_form.options.is_empty() {
+            form.poll = Some(poll_form);
+        }
+        Ok(form)
+    }
+}
+
+// Profile updates may contain files, which are submitted as `multipart/form-data`
+impl FromDataSimple for UpdateCredentialsForm {
+    type Error = String;
+
+    fn from_data(request: &Request, data: Data) -> data::Outcome<Self, String> {
+        // Both an avatar and a header may be uploaded at once
+        match multipart::from_data(request, data, media::MAX_UPLOAD_SIZE * 2 + 65536) {
+            Ok(parts) => Outcome::Success(UpdateCredentialsForm::from_parts(parts)),
+            Err(e) => Outcome::Failure(e),
+        }
+    }
+}
+
+impl<'f> FromForm<'f> for V1FilterForm {
+    type Error = ();
+
+    fn from_form(items: &mut FormItems<'f>, _strict: bool) -> Result<V1FilterForm, ()> {
+        let mut form = V1FilterForm {
+            phrase: String::new(),
+            context: Vec::new(),
+            irreversible: None,
+            whole_word: None,
+            expires_in: None,
+        };
+
+        for item in items {
+            let (key, value) = item.key_value_decoded();
+            match key.as_str() {
+                "phrase" => form.phrase = value,
+                "context[]" | "context" => form.context.push(value),
+                "irreversible" => form.irreversible = parse_form_bool(&value),
+                "whole_word" => form.whole_word = parse_form_bool(&value),
+                "expires_in" => form.expires_in = value.parse::<i64>().ok(),
+                _ => (),
+            }
+        }
+        Ok(form)
     }
 }
 
@@ -619,8 +1575,51 @@ lazy_static! {
         Arc::new(Mutex::new(lru::LruCache::new(400)));
 }
 
-pub fn parse_authorization_header(header: &str) -> String {
-    let header_vec: Vec<&str> = header.split(" ").collect();
+/// Builds the value of a `Link` header, pointing to the pages before and after a set of IDs
+///
+/// # Parameters
+///
+/// * `url` -   &str | Full URL of the paginated endpoint, which may already have a query
+/// * `ids` - &[i64] | The IDs of the current page
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/mastodon_api.rs`
+/// - link_header_for_ids()
+/// - link_header_for_empty_page()
+/// - link_header_for_url_with_query()
+pub fn link_header(url: &str, ids: &[i64]) -> Option<String> {
+    let separator = if url.contains('?') { '&' } else { '?' };
+
+    match (ids.iter().min(), ids.iter().max()) {
+        (Some(min), Some(max)) => Some(format!(
+            "<{url}{separator}max_id={min}>; rel=\"next\", \
+             <{url}{separator}min_id={max}>; rel=\"prev\"",
+            url = url,
+            separator = separator,
+            min = min,
+            max = max
+        )),
+        _ => None,
+    }
+}
+
+/// Clamps the `limit` parameter of a paginated endpoint, falling back to a default
+///
+/// # Parameters
+///
+/// * `limit`   - Option<i64> | The limit requested by the client
+/// * `default` -         i64 | The limit if none was requested
+/// * `max`     -         i64 | The highest allowed limit
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/mastodon_api.rs`
+/// - page_limit()
+pub fn page_limit(limit: Option<i64>, default: i64, max: i64) -> i64 {
+    limit.unwrap_or(default).max(1).min(max)
+}
 
-    return header_vec[1].replace("\")", "").to_string();
+fn parse_form_bool(value: &str) -> Option<bool> {
+    bool::from_form_value(RawStr::from_str(value)).ok()
 }
@@ -1,28 +1,72 @@
+use api_error::{ApiError, ApiResult};
 use database::PooledConnection;
 
 use mastodon_api::controller;
 use mastodon_api::{
-    parse_authorization_header, ApplicationForm, AuthorizationHeader, HomeTimeline, PublicTimeline,
-    StatusForm,
+    Account, ApplicationForm, Conversation, Emoji, EmojiReaction, Filter, FilterForm,
+    FilterKeyword, FilterKeywordForm, FilterStatus, FilterStatusForm, HomeTimeline, Instance, List,
+    ListAccountsForm, ListForm, Marker, MarkersForm, MarkersUpdateForm, Notification,
+    NotificationsForm, PaginatedJson, Poll, PollVoteForm, PublicTimeline, RegistrationForm,
+    Relationship, RelationshipsForm, Status, StatusForm, UpdateCredentialsForm, V1Filter,
+    V1FilterForm,
 };
 use oauth::application::Application;
+use oauth::scope::{
+    Authorized, ReadAccounts, ReadBookmarks, ReadFavourites, ReadFilters, ReadFollows, ReadLists,
+    ReadNotifications, ReadStatuses, WriteAccounts, WriteBookmarks, WriteConversations,
+    WriteFavourites, WriteFilters, WriteFollows, WriteLists, WriteNotifications, WriteStatuses,
+};
 use rocket::request::LenientForm;
-
-use rocket_contrib::json::JsonValue;
+use serde_json;
+use std::collections::BTreeMap;
 
 #[get("/api/v1/accounts/<id>")]
-pub fn account(pooled_connection: PooledConnection, id: i64) -> JsonValue {
+pub fn account(pooled_connection: PooledConnection, id: i64) -> ApiResult<Account> {
     return controller::account(&pooled_connection, id);
 }
 
 #[options("/api/v1/accounts/<id>")]
-pub fn options_account(pooled_connection: PooledConnection, id: i64) -> JsonValue {
+pub fn options_account(pooled_connection: PooledConnection, id: i64) -> ApiResult<Account> {
     return account(pooled_connection, id);
 }
 
+// Applications register accounts with a token of the `client_credentials` grant
+#[post("/api/v1/accounts", data = "<form>")]
+pub fn account_create(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    form: LenientForm<RegistrationForm>,
+) -> ApiResult<serde_json::Value> {
+    return controller::account_register(&pooled_connection, _token.access_token(), &form);
+}
+
 #[post("/api/v1/accounts/<id>/follow")]
-pub fn account_follow(_token: AuthorizationHeader, id: i64) -> JsonValue {
-    return controller::follow(parse_authorization_header(&_token.to_string()), id);
+pub fn account_follow(_token: Authorized<WriteFollows>, id: i64) -> ApiResult<Relationship> {
+    return controller::follow(_token.access_token(), id);
+}
+
+#[get("/api/v1/accounts/<id>/followers?<max_id>&<since_id>&<min_id>&<limit>")]
+pub fn account_followers(
+    pooled_connection: PooledConnection,
+    id: i64,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Account>>, ApiError> {
+    return controller::followers(&pooled_connection, id, max_id, since_id, min_id, limit);
+}
+
+#[get("/api/v1/accounts/<id>/following?<max_id>&<since_id>&<min_id>&<limit>")]
+pub fn account_following(
+    pooled_connection: PooledConnection,
+    id: i64,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Account>>, ApiError> {
+    return controller::following(&pooled_connection, id, max_id, since_id, min_id, limit);
 }
 
 #[get("/api/v1/accounts/<id>/statuses?<only_media>&<pinned>&<exclude_replies>&<max_id>&<since_id>&<min_id>&<limit>&<exclude_reblogs>")]
@@ -37,14 +81,17 @@ pub fn account_statuses(
     min_id: Option<i64>,
     limit: Option<i64>,
     exclude_reblogs: Option<bool>,
-) -> JsonValue {
+    _token: Option<Authorized<ReadStatuses>>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
     return controller::account_statuses_by_id(
         &pooled_connection,
         id,
+        pinned.unwrap_or(false),
         max_id,
         since_id,
         min_id,
         limit,
+        _token.map(|token| token.access_token()),
     );
 }
 
@@ -60,7 +107,8 @@ pub fn options_account_statuses(
     min_id: Option<i64>,
     limit: Option<i64>,
     exclude_reblogs: Option<bool>,
-) -> JsonValue {
+    _token: Option<Authorized<ReadStatuses>>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
     return account_statuses(
         pooled_connection,
         id,
@@ -72,30 +120,59 @@ pub fn options_account_statuses(
         min_id,
         limit,
         exclude_reblogs,
+        _token,
     );
 }
 
+#[get("/api/v1/accounts/<id>/lists")]
+pub fn account_lists(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadLists>,
+    id: i64,
+) -> ApiResult<Vec<List>> {
+    return controller::lists(&pooled_connection, _token.access_token(), Some(id));
+}
+
 #[post("/api/v1/accounts/<id>/unfollow")]
-pub fn account_unfollow(_token: AuthorizationHeader, id: i64) -> JsonValue {
-    return controller::unfollow(parse_authorization_header(&_token.to_string()), id);
+pub fn account_unfollow(_token: Authorized<WriteFollows>, id: i64) -> ApiResult<Relationship> {
+    return controller::unfollow(_token.access_token(), id);
 }
 
-#[get("/api/v1/accounts/verify_credentials")]
-pub fn account_verify_credentials(
+#[get("/api/v1/accounts/relationships?<form..>")]
+pub fn account_relationships(
     pooled_connection: PooledConnection,
-    _token: AuthorizationHeader,
-) -> JsonValue {
-    return controller::account_by_oauth_token(
+    _token: Authorized<ReadFollows>,
+    form: LenientForm<RelationshipsForm>,
+) -> ApiResult<Vec<Relationship>> {
+    return controller::relationships(
         &pooled_connection,
-        parse_authorization_header(&_token.to_string()),
+        _token.access_token(),
+        form.into_inner().ids,
     );
 }
 
+#[patch("/api/v1/accounts/update_credentials", data = "<form>")]
+pub fn account_update_credentials(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    form: UpdateCredentialsForm,
+) -> ApiResult<Account> {
+    return controller::update_credentials(&pooled_connection, _token.access_token(), form);
+}
+
+#[get("/api/v1/accounts/verify_credentials")]
+pub fn account_verify_credentials(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadAccounts>,
+) -> ApiResult<Account> {
+    return controller::account_by_oauth_token(&pooled_connection, _token.access_token());
+}
+
 #[options("/api/v1/accounts/verify_credentials")]
 pub fn options_account_verify_credentials(
     pooled_connection: PooledConnection,
-    _token: AuthorizationHeader,
-) -> JsonValue {
+    _token: Authorized<ReadAccounts>,
+) -> ApiResult<Account> {
     return account_verify_credentials(pooled_connection, _token);
 }
 
@@ -103,7 +180,7 @@ pub fn options_account_verify_credentials(
 pub fn application(
     pooled_connection: PooledConnection,
     form: LenientForm<ApplicationForm>,
-) -> JsonValue {
+) -> ApiResult<serde_json::Value> {
     let form_data: ApplicationForm = form.into_inner();
     return controller::application_create(
         &pooled_connection,
@@ -113,104 +190,721 @@ pub fn application(
             client_id: String::new(),
             client_secret: String::new(),
             redirect_uris: form_data.redirect_uris,
-            scopes: form_data.scopes,
+            scopes: form_data.scopes.unwrap_or_default(),
             website: form_data.website,
         },
     );
 }
 
+#[post("/api/v1/conversations/<id>/read")]
+pub fn conversation_read(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteConversations>,
+    id: i64,
+) -> ApiResult<Conversation> {
+    return controller::conversation_read(&pooled_connection, _token.access_token(), id);
+}
+
+#[get("/api/v1/conversations?<max_id>&<since_id>&<min_id>&<limit>")]
+pub fn conversations(
+    pooled_connection: PooledConnection,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+    _token: Authorized<ReadStatuses>,
+) -> Result<PaginatedJson<Vec<Conversation>>, ApiError> {
+    return controller::conversations(
+        &pooled_connection,
+        HomeTimeline {
+            max_id,
+            since_id,
+            min_id,
+            limit,
+        },
+        _token.access_token(),
+    );
+}
+
+#[get("/api/v1/bookmarks?<max_id>&<since_id>&<min_id>&<limit>")]
+pub fn bookmarks(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadBookmarks>,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
+    return controller::bookmarks(
+        &pooled_connection,
+        _token.access_token(),
+        max_id,
+        since_id,
+        min_id,
+        limit,
+    );
+}
+
+#[get("/api/v1/favourites?<max_id>&<since_id>&<min_id>&<limit>")]
+pub fn favourites(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadFavourites>,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
+    return controller::favourites(
+        &pooled_connection,
+        _token.access_token(),
+        max_id,
+        since_id,
+        min_id,
+        limit,
+    );
+}
+
+#[get("/api/v1/follow_requests?<max_id>&<since_id>&<min_id>&<limit>")]
+pub fn follow_requests(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadFollows>,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Account>>, ApiError> {
+    return controller::follow_requests(
+        &pooled_connection,
+        _token.access_token(),
+        max_id,
+        since_id,
+        min_id,
+        limit,
+    );
+}
+
+#[post("/api/v1/follow_requests/<id>/authorize")]
+pub fn follow_request_authorize(
+    _token: Authorized<WriteFollows>,
+    id: i64,
+) -> ApiResult<Relationship> {
+    return controller::follow_request_authorize(_token.access_token(), id);
+}
+
+#[post("/api/v1/follow_requests/<id>/reject")]
+pub fn follow_request_reject(_token: Authorized<WriteFollows>, id: i64) -> ApiResult<Relationship> {
+    return controller::follow_request_reject(_token.access_token(), id);
+}
+
 #[get("/api/v1/custom_emojis")]
-pub fn custom_emojis() -> JsonValue {
-    return controller::unsupported_endpoint();
+pub fn custom_emojis(pooled_connection: PooledConnection) -> ApiResult<Vec<Emoji>> {
+    return controller::custom_emojis(&pooled_connection);
 }
 
 #[get("/api/v1/filters")]
-pub fn filters() -> JsonValue {
-    return controller::unsupported_endpoint();
+pub fn v1_filters(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadFilters>,
+) -> ApiResult<Vec<V1Filter>> {
+    return controller::v1_filters(&pooled_connection, _token.access_token());
+}
+
+#[post("/api/v1/filters", data = "<form>")]
+pub fn v1_filter_create(
+    pooled_connection: PooledConnection,
+    form: LenientForm<V1FilterForm>,
+    _token: Authorized<WriteFilters>,
+) -> ApiResult<V1Filter> {
+    return controller::v1_filter_create(
+        &pooled_connection,
+        _token.access_token(),
+        form.into_inner(),
+    );
+}
+
+#[get("/api/v1/filters/<id>")]
+pub fn v1_filter(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadFilters>,
+    id: i64,
+) -> ApiResult<V1Filter> {
+    return controller::v1_filter(&pooled_connection, _token.access_token(), id);
+}
+
+#[put("/api/v1/filters/<id>", data = "<form>")]
+pub fn v1_filter_update(
+    pooled_connection: PooledConnection,
+    form: LenientForm<V1FilterForm>,
+    _token: Authorized<WriteFilters>,
+    id: i64,
+) -> ApiResult<V1Filter> {
+    return controller::v1_filter_update(
+        &pooled_connection,
+        _token.access_token(),
+        id,
+        form.into_inner(),
+    );
+}
+
+#[delete("/api/v1/filters/<id>")]
+pub fn v1_filter_delete(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteFilters>,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    return controller::v1_filter_delete(&pooled_connection, _token.access_token(), id);
+}
+
+#[get("/api/v2/filters")]
+pub fn filters(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadFilters>,
+) -> ApiResult<Vec<Filter>> {
+    return controller::filters(&pooled_connection, _token.access_token());
+}
+
+#[post("/api/v2/filters", data = "<form>")]
+pub fn filter_create(
+    pooled_connection: PooledConnection,
+    form: LenientForm<FilterForm>,
+    _token: Authorized<WriteFilters>,
+) -> ApiResult<Filter> {
+    return controller::filter_create(&pooled_connection, _token.access_token(), form.into_inner());
+}
+
+#[get("/api/v2/filters/<id>")]
+pub fn filter(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadFilters>,
+    id: i64,
+) -> ApiResult<Filter> {
+    return controller::filter(&pooled_connection, _token.access_token(), id);
+}
+
+#[put("/api/v2/filters/<id>", data = "<form>")]
+pub fn filter_update(
+    pooled_connection: PooledConnection,
+    form: LenientForm<FilterForm>,
+    _token: Authorized<WriteFilters>,
+    id: i64,
+) -> ApiResult<Filter> {
+    return controller::filter_update(
+        &pooled_connection,
+        _token.access_token(),
+        id,
+        form.into_inner(),
+    );
+}
+
+#[delete("/api/v2/filters/<id>")]
+pub fn filter_delete(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteFilters>,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    return controller::filter_delete(&pooled_connection, _token.access_token(), id);
+}
+
+// Ranked lower than `/api/v2/filters/keywords/<id>`, as both paths could match each other
+#[get("/api/v2/filters/<id>/keywords", rank = 2)]
+pub fn filter_keywords(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadFilters>,
+    id: i64,
+) -> ApiResult<Vec<FilterKeyword>> {
+    return controller::filter_keywords(&pooled_connection, _token.access_token(), id);
+}
+
+#[post("/api/v2/filters/<id>/keywords", data = "<form>")]
+pub fn filter_keyword_add(
+    pooled_connection: PooledConnection,
+    form: LenientForm<FilterKeywordForm>,
+    _token: Authorized<WriteFilters>,
+    id: i64,
+) -> ApiResult<FilterKeyword> {
+    return controller::filter_keyword_add(
+        &pooled_connection,
+        _token.access_token(),
+        id,
+        form.into_inner(),
+    );
+}
+
+#[get("/api/v2/filters/keywords/<id>")]
+pub fn filter_keyword(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadFilters>,
+    id: i64,
+) -> ApiResult<FilterKeyword> {
+    return controller::filter_keyword(&pooled_connection, _token.access_token(), id);
+}
+
+#[put("/api/v2/filters/keywords/<id>", data = "<form>")]
+pub fn filter_keyword_update(
+    pooled_connection: PooledConnection,
+    form: LenientForm<FilterKeywordForm>,
+    _token: Authorized<WriteFilters>,
+    id: i64,
+) -> ApiResult<FilterKeyword> {
+    return controller::filter_keyword_update(
+        &pooled_connection,
+        _token.access_token(),
+        id,
+        form.into_inner(),
+    );
+}
+
+#[delete("/api/v2/filters/keywords/<id>")]
+pub fn filter_keyword_delete(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteFilters>,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    return controller::filter_keyword_delete(&pooled_connection, _token.access_token(), id);
+}
+
+// Ranked lower than `/api/v2/filters/statuses/<id>`, as both paths could match each other
+#[get("/api/v2/filters/<id>/statuses", rank = 2)]
+pub fn filter_statuses(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadFilters>,
+    id: i64,
+) -> ApiResult<Vec<FilterStatus>> {
+    return controller::filter_statuses(&pooled_connection, _token.access_token(), id);
+}
+
+#[post("/api/v2/filters/<id>/statuses", data = "<form>")]
+pub fn filter_status_add(
+    pooled_connection: PooledConnection,
+    form: LenientForm<FilterStatusForm>,
+    _token: Authorized<WriteFilters>,
+    id: i64,
+) -> ApiResult<FilterStatus> {
+    return controller::filter_status_add(
+        &pooled_connection,
+        _token.access_token(),
+        id,
+        form.into_inner(),
+    );
+}
+
+#[get("/api/v2/filters/statuses/<id>")]
+pub fn filter_status(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadFilters>,
+    id: i64,
+) -> ApiResult<FilterStatus> {
+    return controller::filter_status(&pooled_connection, _token.access_token(), id);
+}
+
+#[delete("/api/v2/filters/statuses/<id>")]
+pub fn filter_status_delete(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteFilters>,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    return controller::filter_status_delete(&pooled_connection, _token.access_token(), id);
 }
 
 #[options("/api/v1/apps", data = "<form>")]
 pub fn options_application(
     pooled_connection: PooledConnection,
     form: LenientForm<ApplicationForm>,
-) -> JsonValue {
+) -> ApiResult<serde_json::Value> {
     return application(pooled_connection, form);
 }
 
 #[get("/api/v1/instance")]
-pub fn instance() -> JsonValue {
+pub fn instance() -> ApiResult<Instance> {
     return controller::instance_info();
 }
 
-#[get("/api/v1/notifications?<limit>")]
+#[get("/api/v1/lists")]
+pub fn lists(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadLists>,
+) -> ApiResult<Vec<List>> {
+    return controller::lists(&pooled_connection, _token.access_token(), None);
+}
+
+#[post("/api/v1/lists", data = "<form>")]
+pub fn list_create(
+    pooled_connection: PooledConnection,
+    form: LenientForm<ListForm>,
+    _token: Authorized<WriteLists>,
+) -> ApiResult<List> {
+    return controller::list_create(&pooled_connection, _token.access_token(), form.into_inner());
+}
+
+#[get("/api/v1/lists/<id>")]
+pub fn list(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadLists>,
+    id: i64,
+) -> ApiResult<List> {
+    return controller::list(&pooled_connection, _token.access_token(), id);
+}
+
+#[put("/api/v1/lists/<id>", data = "<form>")]
+pub fn list_update(
+    pooled_connection: PooledConnection,
+    form: LenientForm<ListForm>,
+    _token: Authorized<WriteLists>,
+    id: i64,
+) -> ApiResult<List> {
+    return controller::list_update(
+        &pooled_connection,
+        _token.access_token(),
+        id,
+        form.into_inner(),
+    );
+}
+
+#[delete("/api/v1/lists/<id>")]
+pub fn list_delete(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteLists>,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    return controller::list_delete(&pooled_connection, _token.access_token(), id);
+}
+
+#[get("/api/v1/lists/<id>/accounts")]
+pub fn list_accounts(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadLists>,
+    id: i64,
+) -> ApiResult<Vec<Account>> {
+    return controller::list_accounts(&pooled_connection, _token.access_token(), id);
+}
+
+#[post("/api/v1/lists/<id>/accounts", data = "<form>")]
+pub fn list_accounts_add(
+    pooled_connection: PooledConnection,
+    form: LenientForm<ListAccountsForm>,
+    _token: Authorized<WriteLists>,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    return controller::list_accounts_add(
+        &pooled_connection,
+        _token.access_token(),
+        id,
+        form.into_inner(),
+    );
+}
+
+#[delete("/api/v1/lists/<id>/accounts", data = "<form>")]
+pub fn list_accounts_remove(
+    pooled_connection: PooledConnection,
+    form: LenientForm<ListAccountsForm>,
+    _token: Authorized<WriteLists>,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    return controller::list_accounts_remove(
+        &pooled_connection,
+        _token.access_token(),
+        id,
+        form.into_inner(),
+    );
+}
+
+#[get("/api/v1/markers?<form..>")]
+pub fn markers(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadStatuses>,
+    form: LenientForm<MarkersForm>,
+) -> ApiResult<BTreeMap<String, Marker>> {
+    return controller::markers(&pooled_connection, _token.access_token(), form.into_inner());
+}
+
+#[post("/api/v1/markers", data = "<form>")]
+pub fn markers_update(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteStatuses>,
+    form: LenientForm<MarkersUpdateForm>,
+) -> ApiResult<BTreeMap<String, Marker>> {
+    return controller::markers_update(
+        &pooled_connection,
+        _token.access_token(),
+        form.into_inner(),
+    );
+}
+
+#[get("/api/v1/notifications/<id>")]
+pub fn notification(
+    pooled_connection: PooledConnection,
+    _token: Authorized<ReadNotifications>,
+    id: i64,
+) -> ApiResult<Notification> {
+    return controller::notification(&pooled_connection, _token.access_token(), id);
+}
+
+#[post("/api/v1/notifications/<id>/dismiss")]
+pub fn notification_dismiss(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteNotifications>,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    return controller::notification_dismiss(&pooled_connection, _token.access_token(), id);
+}
+
+#[get("/api/v1/notifications?<form..>")]
 pub fn notifications(
     pooled_connection: PooledConnection,
-    _token: AuthorizationHeader,
-    limit: Option<i64>,
-) -> JsonValue {
-    return controller::notifications(
+    _token: Authorized<ReadNotifications>,
+    form: LenientForm<NotificationsForm>,
+) -> Result<PaginatedJson<Vec<Notification>>, ApiError> {
+    return controller::notifications(&pooled_connection, _token.access_token(), form.into_inner());
+}
+
+#[post("/api/v1/notifications/clear")]
+pub fn notifications_clear(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteNotifications>,
+) -> ApiResult<serde_json::Value> {
+    return controller::notifications_clear(&pooled_connection, _token.access_token());
+}
+
+#[get("/api/v1/polls/<id>")]
+pub fn poll(
+    pooled_connection: PooledConnection,
+    _token: Option<Authorized<ReadStatuses>>,
+    id: i64,
+) -> ApiResult<Poll> {
+    return controller::poll(
         &pooled_connection,
-        parse_authorization_header(&_token.to_string()),
-        limit,
+        id,
+        _token.map(|token| token.access_token()),
+    );
+}
+
+#[post("/api/v1/polls/<id>/votes", data = "<form>")]
+pub fn poll_vote(
+    pooled_connection: PooledConnection,
+    form: LenientForm<PollVoteForm>,
+    _token: Authorized<WriteStatuses>,
+    id: i64,
+) -> ApiResult<Poll> {
+    return controller::poll_vote(
+        &pooled_connection,
+        _token.access_token(),
+        id,
+        form.into_inner(),
     );
 }
 
 #[options("/api/v1/instance")]
-pub fn options_instance() -> JsonValue {
+pub fn options_instance() -> ApiResult<Instance> {
     return controller::instance_info();
 }
 
 #[get("/api/v1/statuses/<id>")]
-pub fn status(pooled_connection: PooledConnection, id: i64) -> JsonValue {
-    return controller::status_by_id(&pooled_connection, id);
+pub fn status(
+    pooled_connection: PooledConnection,
+    _token: Option<Authorized<ReadStatuses>>,
+    id: i64,
+) -> ApiResult<Status> {
+    return controller::status_by_id(
+        &pooled_connection,
+        id,
+        _token.map(|token| token.access_token()),
+    );
 }
 
 #[options("/api/v1/statuses/<id>")]
-pub fn options_status(pooled_connection: PooledConnection, id: i64) -> JsonValue {
-    return status(pooled_connection, id);
+pub fn options_status(
+    pooled_connection: PooledConnection,
+    _token: Option<Authorized<ReadStatuses>>,
+    id: i64,
+) -> ApiResult<Status> {
+    return status(pooled_connection, _token, id);
+}
+
+#[post("/api/v1/statuses/<id>/bookmark")]
+pub fn status_bookmark(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteBookmarks>,
+    id: i64,
+) -> ApiResult<Status> {
+    return controller::status_bookmark(&pooled_connection, _token.access_token(), id);
 }
 
 #[get("/api/v1/statuses/<id>/context")]
-pub fn status_context(pooled_connection: PooledConnection, id: i64) -> JsonValue {
-    return controller::context_json_for_id(&pooled_connection, id);
+pub fn status_context(
+    pooled_connection: PooledConnection,
+    _token: Option<Authorized<ReadStatuses>>,
+    id: i64,
+) -> ApiResult<serde_json::Value> {
+    return controller::context_json_for_id(
+        &pooled_connection,
+        id,
+        _token.map(|token| token.access_token()),
+    );
 }
 
 #[post("/api/v1/statuses/<id>/favourite")]
 pub fn status_favourite(
     pooled_connection: PooledConnection,
-    _token: AuthorizationHeader,
+    _token: Authorized<WriteFavourites>,
+    id: i64,
+) -> ApiResult<Status> {
+    return controller::favourite(&pooled_connection, _token.access_token(), id);
+}
+
+#[get("/api/v1/statuses/<id>/favourited_by?<max_id>&<since_id>&<min_id>&<limit>")]
+pub fn status_favourited_by(
+    pooled_connection: PooledConnection,
+    _token: Option<Authorized<ReadStatuses>>,
     id: i64,
-) -> JsonValue {
-    return controller::favourite(
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Account>>, ApiError> {
+    return controller::favourited_by(
         &pooled_connection,
-        parse_authorization_header(&_token.to_string()),
         id,
+        _token.map(|token| token.access_token()),
+        max_id,
+        since_id,
+        min_id,
+        limit,
     );
 }
 
+#[post("/api/v1/statuses/<id>/pin")]
+pub fn status_pin(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    id: i64,
+) -> ApiResult<Status> {
+    return controller::status_pin(&pooled_connection, _token.access_token(), id);
+}
+
+#[post("/api/v1/statuses/<id>/unbookmark")]
+pub fn status_unbookmark(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteBookmarks>,
+    id: i64,
+) -> ApiResult<Status> {
+    return controller::status_unbookmark(&pooled_connection, _token.access_token(), id);
+}
+
+#[post("/api/v1/statuses/<id>/unpin")]
+pub fn status_unpin(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteAccounts>,
+    id: i64,
+) -> ApiResult<Status> {
+    return controller::status_unpin(&pooled_connection, _token.access_token(), id);
+}
+
+#[get("/api/v1/pleroma/statuses/<id>/reactions")]
+pub fn status_reactions(
+    pooled_connection: PooledConnection,
+    _token: Option<Authorized<ReadStatuses>>,
+    id: i64,
+) -> ApiResult<Vec<EmojiReaction>> {
+    return controller::status_reactions(
+        &pooled_connection,
+        id,
+        None,
+        _token.map(|token| token.access_token()),
+    );
+}
+
+#[get("/api/v1/pleroma/statuses/<id>/reactions/<emoji>")]
+pub fn status_reactions_by_emoji(
+    pooled_connection: PooledConnection,
+    _token: Option<Authorized<ReadStatuses>>,
+    id: i64,
+    emoji: String,
+) -> ApiResult<Vec<EmojiReaction>> {
+    return controller::status_reactions(
+        &pooled_connection,
+        id,
+        Some(emoji),
+        _token.map(|token| token.access_token()),
+    );
+}
+
+#[put("/api/v1/pleroma/statuses/<id>/reactions/<emoji>")]
+pub fn status_react(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteStatuses>,
+    id: i64,
+    emoji: String,
+) -> ApiResult<Status> {
+    return controller::status_react(&pooled_connection, _token.access_token(), id, emoji);
+}
+
+#[delete("/api/v1/pleroma/statuses/<id>/reactions/<emoji>")]
+pub fn status_unreact(
+    pooled_connection: PooledConnection,
+    _token: Authorized<WriteStatuses>,
+    id: i64,
+    emoji: String,
+) -> ApiResult<Status> {
+    return controller::status_unreact(&pooled_connection, _token.access_token(), id, emoji);
+}
+
 #[post("/api/v1/statuses", data = "<form>")]
 pub fn status_post(
     pooled_connection: PooledConnection,
     form: LenientForm<StatusForm>,
-    _token: AuthorizationHeader,
-) -> JsonValue {
-    return controller::status_post(
-        &pooled_connection,
-        form.into_inner(),
-        parse_authorization_header(&_token.to_string()),
-    );
+    _token: Authorized<WriteStatuses>,
+) -> ApiResult<Status> {
+    return controller::status_post(&pooled_connection, form.into_inner(), _token.access_token());
 }
 
 #[post("/api/v1/statuses/<id>/reblog")]
 pub fn status_reblog(
     pooled_connection: PooledConnection,
-    _token: AuthorizationHeader,
+    _token: Authorized<WriteStatuses>,
+    id: i64,
+) -> ApiResult<Status> {
+    return controller::reblog(&pooled_connection, _token.access_token(), id);
+}
+
+#[get("/api/v1/statuses/<id>/reblogged_by?<max_id>&<since_id>&<min_id>&<limit>")]
+pub fn status_reblogged_by(
+    pooled_connection: PooledConnection,
+    _token: Option<Authorized<ReadStatuses>>,
     id: i64,
-) -> JsonValue {
-    return controller::reblog(
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<PaginatedJson<Vec<Account>>, ApiError> {
+    return controller::reblogged_by(
         &pooled_connection,
-        parse_authorization_header(&_token.to_string()),
         id,
+        _token.map(|token| token.access_token()),
+        max_id,
+        since_id,
+        min_id,
+        limit,
+    );
+}
+
+#[get("/api/v1/timelines/direct?<max_id>&<since_id>&<min_id>&<limit>")]
+pub fn direct_timeline(
+    pooled_connection: PooledConnection,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+    _token: Authorized<ReadStatuses>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
+    return controller::direct_timeline(
+        &pooled_connection,
+        HomeTimeline {
+            max_id,
+            since_id,
+            min_id,
+            limit,
+        },
+        _token.access_token(),
     );
 }
 
@@ -221,8 +915,8 @@ pub fn home_timeline(
     since_id: Option<i64>,
     min_id: Option<i64>,
     limit: Option<i64>,
-    _token: AuthorizationHeader,
-) -> JsonValue {
+    _token: Authorized<ReadStatuses>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
     return controller::home_timeline(
         &pooled_connection,
         HomeTimeline {
@@ -231,7 +925,7 @@ pub fn home_timeline(
             min_id,
             limit,
         },
-        parse_authorization_header(&_token.to_string()),
+        _token.access_token(),
     );
 }
 
@@ -242,11 +936,34 @@ pub fn options_home_timeline(
     since_id: Option<i64>,
     min_id: Option<i64>,
     limit: Option<i64>,
-    _token: AuthorizationHeader,
-) -> JsonValue {
+    _token: Authorized<ReadStatuses>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
     return home_timeline(pooled_connection, max_id, since_id, min_id, limit, _token);
 }
 
+#[get("/api/v1/timelines/list/<id>?<max_id>&<since_id>&<min_id>&<limit>")]
+pub fn list_timeline(
+    pooled_connection: PooledConnection,
+    id: i64,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+    _token: Authorized<ReadLists>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
+    return controller::list_timeline(
+        &pooled_connection,
+        HomeTimeline {
+            max_id,
+            since_id,
+            min_id,
+            limit,
+        },
+        _token.access_token(),
+        id,
+    );
+}
+
 #[get("/api/v1/timelines/public?<local>&<only_media>&<max_id>&<since_id>&<min_id>&<limit>")]
 pub fn public_timeline(
     pooled_connection: PooledConnection,
@@ -256,7 +973,8 @@ pub fn public_timeline(
     since_id: Option<i64>,
     min_id: Option<i64>,
     limit: Option<i64>,
-) -> JsonValue {
+    _token: Option<Authorized<ReadStatuses>>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
     return controller::public_timeline(
         &pooled_connection,
         PublicTimeline {
@@ -267,6 +985,7 @@ pub fn public_timeline(
             min_id,
             limit,
         },
+        _token.map(|token| token.access_token()),
     );
 }
 
@@ -279,7 +998,8 @@ pub fn options_public_timeline(
     since_id: Option<i64>,
     min_id: Option<i64>,
     limit: Option<i64>,
-) -> JsonValue {
+    _token: Option<Authorized<ReadStatuses>>,
+) -> Result<PaginatedJson<Vec<Status>>, ApiError> {
     return public_timeline(
         pooled_connection,
         local,
@@ -288,5 +1008,6 @@ pub fn options_public_timeline(
         since_id,
         min_id,
         limit,
+        _token,
     );
 }
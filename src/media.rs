@@ -0,0 +1,62 @@
+use env;
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+// Media files are served as static files, see `rocket_app` in lib.rs
+pub const MEDIA_DIRECTORY: &str = "static/media";
+
+// Uploads larger than 8MB are rejected
+pub const MAX_UPLOAD_SIZE: u64 = 8388608;
+
+/// A file uploaded by a local actor, such as an avatar or a header
+pub struct Upload {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Returns the file extension of a supported image type
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/media.rs`
+/// - image_extension()
+pub fn image_extension(content_type: &str) -> Option<&'static str> {
+    match content_type {
+        "image/gif" => Some("gif"),
+        "image/jpeg" => Some("jpg"),
+        "image/png" => Some("png"),
+        "image/webp" => Some("webp"),
+        _ => None,
+    }
+}
+
+/// Stores an uploaded image under a random name and returns its URL
+///
+/// # Parameters
+///
+/// * `upload` - &Upload | The uploaded image
+pub fn store_image(upload: &Upload) -> Result<String, &'static str> {
+    let extension = match image_extension(&upload.content_type) {
+        Some(extension) => extension,
+        None => return Err("Unsupported image format"),
+    };
+
+    if upload.data.is_empty() || upload.data.len() as u64 > MAX_UPLOAD_SIZE {
+        return Err("Images may not be empty or larger than 8MB");
+    }
+
+    let file = format!("{}.{}", Uuid::new_v4(), extension);
+    if fs::create_dir_all(MEDIA_DIRECTORY).is_err()
+        || fs::write(Path::new(MEDIA_DIRECTORY).join(&file), &upload.data).is_err()
+    {
+        return Err("Unable to store image");
+    }
+
+    Ok(format!(
+        "{}://{}/static/media/{}",
+        env::get_value(String::from("endpoint.base_scheme")),
+        env::get_value(String::from("endpoint.base_domain")),
+        file
+    ))
+}
@@ -2,7 +2,19 @@ use actor::Actor;
 use chrono::{NaiveDateTime, Utc};
 use database::models::InsertNotification;
 use database::schema::notifications;
-use diesel::{sql_query, PgConnection, RunQueryDsl};
+use diesel::{ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl};
+use std::collections::HashMap;
+
+// The notification types of the Mastodon API, emoji reactions are an extension by Pleroma
+pub const TYPES: [&str; 7] = [
+    "mention",
+    "reblog",
+    "favourite",
+    "follow",
+    "follow_request",
+    "poll",
+    "pleroma:emoji_reaction",
+];
 
 #[derive(Queryable, PartialEq, QueryableByName, Clone)]
 #[table_name = "notifications"]
@@ -12,59 +24,163 @@ pub struct Notification {
     actor_id: i64,
     created: NaiveDateTime,
     modified: NaiveDateTime,
+    notification_type: String,
 }
 
 impl Notification {
-    pub fn new(activity_id: i64, actor_id: i64) -> Notification {
+    pub fn new(activity_id: i64, actor_id: i64, notification_type: &str) -> Notification {
         Notification {
             id: 0,
             activity_id: activity_id,
             actor_id: actor_id,
             created: Utc::now().naive_utc(),
             modified: Utc::now().naive_utc(),
+            notification_type: notification_type.to_string(),
         }
     }
 }
 
+/// Returns the notification types which are left after applying the `types[]` and
+/// `exclude_types[]` parameters of the Mastodon API
+///
+/// # Description
+///
+/// All types are included if no `types[]` are given, unknown types are ignored.
+///
+/// # Parameters
+///
+/// * `types`         - &[String] | The types to include
+/// * `exclude_types` - &[String] | The types to exclude
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/notification.rs`
+/// - requested_types()
+pub fn requested_types(types: &[String], exclude_types: &[String]) -> Vec<&'static str> {
+    TYPES
+        .iter()
+        .cloned()
+        .filter(|notification_type| {
+            types.is_empty() || types.iter().any(|_type| _type == notification_type)
+        })
+        .filter(|notification_type| !exclude_types.iter().any(|_type| _type == notification_type))
+        .collect()
+}
+
+/// Returns the ids of the activities an actor was notified about
+///
+/// # Description
+///
+/// Notifications are exposed by the ids of their activities, which is why they're paginated by
+/// them instead of their own ids.
+///
+/// # Parameters
+///
+/// * `db_connection` -     &PgConnection | Reference to a database connection
+/// * `actor`         -            &Actor | The local actor who was notified
+/// * `types`         - Vec<&'static str> | The notification types to include
+/// * `max_id`        -       Option<i64> | Only notifications older than this activity
+/// * `since_id`      -       Option<i64> | Only notifications newer than this activity
+/// * `min_id`        -       Option<i64> | The notifications directly following this activity
+/// * `limit`         -       Option<i64> | The maximum amount of notifications, 20 by default
 pub fn notifications_for_actor(
     db_connection: &PgConnection,
     actor: &Actor,
+    types: Vec<&'static str>,
     max_id: Option<i64>,
     since_id: Option<i64>,
     min_id: Option<i64>,
     limit: Option<i64>,
 ) -> Result<Vec<i64>, diesel::result::Error> {
-    let id = if max_id.is_some() {
-        format!("AND id < {} ORDER BY id DESC", &max_id.unwrap().to_string())
+    let mut query = notifications::table
+        .filter(notifications::actor_id.eq(actor.id))
+        .filter(notifications::notification_type.eq_any(types))
+        .select(notifications::activity_id)
+        .into_boxed();
+
+    query = if max_id.is_some() {
+        query
+            .filter(notifications::activity_id.lt(max_id.unwrap()))
+            .order(notifications::activity_id.desc())
     } else if since_id.is_some() {
-        format!(
-            "AND id > {} ORDER BY id DESC",
-            &since_id.unwrap().to_string()
-        )
+        query
+            .filter(notifications::activity_id.gt(since_id.unwrap()))
+            .order(notifications::activity_id.desc())
     } else if min_id.is_some() {
-        format!("AND id > {} ORDER BY id ASC", &min_id.unwrap().to_string())
+        query
+            .filter(notifications::activity_id.gt(min_id.unwrap()))
+            .order(notifications::activity_id.asc())
     } else {
-        String::from("ORDER BY id DESC")
+        query.order(notifications::activity_id.desc())
     };
 
-    match sql_query(format!(
-        "SELECT * \
-         FROM notifications \
-         WHERE \
-         actor_id = '{actor_id}' \
-         {order} \
-         LIMIT {limit};",
-        actor_id = actor.id,
-        order = id,
-        limit = limit.unwrap_or_else(|| 20).to_string()
-    ))
-    .load::<Notification>(db_connection)
+    query
+        .limit(limit.unwrap_or_else(|| 20))
+        .load::<i64>(db_connection)
+}
+
+/// Determines whether an actor was notified about an activity
+pub fn is_notified(db_connection: &PgConnection, actor: &Actor, activity_id: i64) -> bool {
+    match notifications::table
+        .filter(notifications::actor_id.eq(actor.id))
+        .filter(notifications::activity_id.eq(activity_id))
+        .count()
+        .get_result::<i64>(db_connection)
     {
-        Ok(notifications) => Ok(notifications
-            .iter()
-            .map(|notification| notification.activity_id)
-            .collect()),
-        Err(e) => Err(e),
+        Ok(count) => count > 0,
+        Err(_) => false,
+    }
+}
+
+/// Returns the types of an actor's notifications about the given activities, by their activities
+pub fn types_among(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    activity_ids: Vec<i64>,
+) -> HashMap<i64, String> {
+    notifications::table
+        .filter(notifications::actor_id.eq(actor.id))
+        .filter(notifications::activity_id.eq_any(activity_ids))
+        .select((notifications::activity_id, notifications::notification_type))
+        .load::<(i64, String)>(db_connection)
+        .map(|types| types.into_iter().collect())
+        .unwrap_or_else(|_| HashMap::new())
+}
+
+/// Removes the notification about an activity, returns how many notifications were removed
+pub fn dismiss(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    activity_id: i64,
+) -> Result<usize, diesel::result::Error> {
+    diesel::delete(
+        notifications::table
+            .filter(notifications::actor_id.eq(actor.id))
+            .filter(notifications::activity_id.eq(activity_id)),
+    )
+    .execute(db_connection)
+}
+
+/// Removes all notifications of an actor
+pub fn clear(db_connection: &PgConnection, actor: &Actor) -> Result<usize, diesel::result::Error> {
+    diesel::delete(notifications::table.filter(notifications::actor_id.eq(actor.id)))
+        .execute(db_connection)
+}
+
+/// Notifies an actor about an activity, or changes the type of the notification if the actor was
+/// already notified about it, as notifications are identified by their activities
+pub fn upsert(db_connection: &PgConnection, notification: Notification) {
+    let updated = diesel::update(
+        notifications::table
+            .filter(notifications::actor_id.eq(notification.actor_id))
+            .filter(notifications::activity_id.eq(notification.activity_id)),
+    )
+    .set(notifications::notification_type.eq(&notification.notification_type))
+    .execute(db_connection)
+    .unwrap_or(0);
+
+    if updated == 0 {
+        insert(db_connection, notification);
     }
 }
 
@@ -75,6 +191,7 @@ pub fn insert(db_connection: &PgConnection, notification: Notification) {
             actor_id: notification.actor_id,
             created: notification.created,
             modified: notification.modified,
+            notification_type: notification.notification_type,
         })
         .execute(db_connection)
         .expect("Error creating notification");
@@ -10,6 +10,9 @@ use openssl::bn::MsbOption;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+// Redirect URI of applications which can't receive redirects, e.g. CLI applications
+pub const OOB_REDIRECT_URI: &str = "urn:ietf:wg:oauth:2.0:oob";
+
 #[derive(Serialize, Deserialize)]
 pub struct Application {
     pub id: i64,
@@ -51,6 +54,37 @@ pub fn get_application_by_client_id(
     }
 }
 
+pub fn get_application_by_id(
+    db_connection: &PgConnection,
+    application_id: i64,
+) -> Result<Application, diesel::result::Error> {
+    match oauth_applications
+        .filter(id.eq(application_id))
+        .limit(1)
+        .first::<QueryOAuthApplication>(db_connection)
+    {
+        Ok(application) => Ok(serialize_application(application)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Determines whether a redirect URI is one of those registered by an application
+///
+/// # Description
+///
+/// Applications may register several redirect URIs, separated by whitespace.
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/oauth_application.rs`
+/// - is_redirect_uri()
+pub fn is_redirect_uri(application: &Application, redirect_uri: &str) -> bool {
+    application
+        .redirect_uris
+        .split_whitespace()
+        .any(|registered_uri| registered_uri == redirect_uri)
+}
+
 pub fn create(db_connection: &PgConnection, mut app: Application) -> Application {
     let mut big_num: BigNum = BigNum::new().unwrap();
     big_num
@@ -1,4 +1,5 @@
 use actor;
+use base64;
 use chrono::prelude::*;
 use chrono::Duration;
 use chrono::NaiveDateTime;
@@ -10,24 +11,59 @@ use diesel::pg::PgConnection;
 use diesel::ExpressionMethods;
 use diesel::QueryDsl;
 use diesel::RunQueryDsl;
-use oauth::application::get_application_by_client_id;
+use email;
+use oauth::application::{
+    get_application_by_client_id, is_redirect_uri, Application, OOB_REDIRECT_URI,
+};
+use oauth::scope;
+use oauth::token::verify_token;
 use openssl::bn::BigNum;
 use openssl::bn::MsbOption;
+use openssl::sha::sha256;
+use registration;
+use rocket::http::{Cookie, Cookies};
 use rocket::response::Redirect;
 use rocket_contrib::templates::Template;
+use serde_json;
 use std::collections::HashMap;
+use two_factor;
+use url::{form_urlencoded, Url};
+
+// Private cookie holding the token of the last rendered consent form, see `csrf_token`
+const CSRF_COOKIE: &str = "oauth_csrf";
 
 pub struct Authorization {
     pub application: i64,
     pub actor: String,
     pub code: String,
     pub valid_until: String,
+    pub scopes: String,
+    pub code_challenge: Option<String>,
+}
+
+#[derive(FromForm)]
+pub struct AuthorizationRequest {
+    pub client_id: Option<String>,
+    pub response_type: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+    pub styling: Option<bool>,
 }
 
 #[derive(FromForm)]
 pub struct UserForm {
-    pub username: String,
-    pub password: String,
+    // Both are left empty by actors who are signed in to Raito-FE
+    pub username: Option<String>,
+    pub password: Option<String>,
+    // Either `allow` or `deny`
+    pub consent: String,
+    // Has to match the token of the rendered consent form, see `csrf_token`
+    pub csrf_token: Option<String>,
+    // Submitted in a second step by actors who enabled two-factor authentication
+    pub two_factor_code: Option<String>,
 }
 
 fn serialize_authorization(sql_authorization: QueryOAuthAuthorization) -> Authorization {
@@ -36,73 +72,312 @@ fn serialize_authorization(sql_authorization: QueryOAuthAuthorization) -> Author
         actor: sql_authorization.actor,
         code: sql_authorization.code,
         valid_until: sql_authorization.valid_until.to_string(),
+        scopes: sql_authorization.scopes,
+        code_challenge: sql_authorization.code_challenge,
     }
 }
 
-pub fn get_authorization_by_code(
+/// Deletes an authorization which hasn't expired yet and returns it, so that its code can only
+/// be redeemed once
+///
+/// # Description
+///
+/// Only the application the code was issued to can redeem it, other applications can't burn it.
+pub fn consume_authorization_by_code(
     db_connection: &PgConnection,
+    application_id: i64,
     _code: String,
 ) -> Result<Authorization, diesel::result::Error> {
-    match oauth_authorizations
-        .filter(code.eq(_code))
-        .limit(1)
-        .first::<QueryOAuthAuthorization>(db_connection)
+    match diesel::delete(
+        oauth_authorizations
+            .filter(application.eq(application_id))
+            .filter(code.eq(_code))
+            .filter(valid_until.gt(Utc::now().naive_utc())),
+    )
+    .get_result::<QueryOAuthAuthorization>(db_connection)
     {
         Ok(authorization) => Ok(serialize_authorization(authorization)),
         Err(e) => Err(e),
     }
 }
 
+/// Deletes authorization codes which expired without being redeemed
+pub fn delete_expired(db_connection: &PgConnection) -> Result<usize, diesel::result::Error> {
+    diesel::delete(oauth_authorizations.filter(valid_until.le(Utc::now().naive_utc())))
+        .execute(db_connection)
+}
+
+/// Renders the consent page of an authorization request
+///
+/// # Parameters
+///
+/// * `request`       - AuthorizationRequest | The parameters of the authorization request
+/// * `session_token` -       Option<String> | The token of a Raito-FE session, if there is one
+/// * `cookies`       -         &mut Cookies | The cookies which store the token of the form
+///
+pub fn authorization_page(
+    request: AuthorizationRequest,
+    session_token: Option<String>,
+    cookies: &mut Cookies,
+) -> Template {
+    let db_connection = database::establish_connection();
+
+    match validate_request(&db_connection, &request) {
+        Ok((serialized_application, granted_scopes)) => consent_page(
+            cookies,
+            &request,
+            &serialized_application,
+            &granted_scopes,
+            session_actor(&db_connection, session_token),
+            false,
+            String::new(),
+        ),
+        Err(e) => user_authorization_error(e.to_string(), request.styling),
+    }
+}
+
 pub fn handle_user_authorization(
     user_form: UserForm,
-    client_id: Option<String>,
-    _response_type: Option<String>,
-    redirect_uri: Option<String>,
-    state: Option<String>,
-    styling: Option<bool>,
+    request: AuthorizationRequest,
+    session_token: Option<String>,
+    cookies: &mut Cookies,
 ) -> Result<Redirect, Template> {
     let db_connection = database::establish_connection();
+    let (serialized_application, granted_scopes) = match validate_request(&db_connection, &request)
+    {
+        Ok(validated_request) => validated_request,
+        Err(e) => return Err(user_authorization_error(e.to_string(), request.styling)),
+    };
+    let redirect_uri = request.redirect_uri.clone().unwrap_or_default();
+    let session_actor = session_actor(&db_connection, session_token);
 
-    if client_id.is_some() && redirect_uri.is_some() {
-        match actor::authorize(&db_connection, &user_form.username, user_form.password) {
-            Ok(true) => match get_application_by_client_id(&db_connection, client_id.unwrap()) {
-                Ok(serialized_application) => {
-                    let redirect_uri = redirect_uri.unwrap();
-                    let auth_code =
-                        authorize_application(user_form.username, serialized_application.id);
-                    let state = match state {
-                        Some(value) => format!("&state={}", value),
-                        None => String::from(""),
-                    };
-                    let symbol = if redirect_uri.contains("?") {
-                        "&".to_string()
-                    } else {
-                        "?".to_string()
-                    };
-
-                    Ok(Redirect::found(format!(
-                        "{uri}{symbol}code={code}{state}",
-                        uri = redirect_uri,
-                        symbol = symbol,
-                        code = auth_code,
-                        state = state
-                    )))
+    // Signed in actors authorize without their password, so the form must not be forged by others
+    if !verify_csrf_token(cookies, &user_form.csrf_token) {
+        return Err(consent_page(
+            cookies,
+            &request,
+            &serialized_application,
+            &granted_scopes,
+            session_actor,
+            false,
+            String::from("The form has expired, please try again!"),
+        ));
+    }
+
+    if user_form.consent != "allow" {
+        if redirect_uri == OOB_REDIRECT_URI {
+            return Err(user_authorization_error(
+                String::from("The authorization was denied"),
+                request.styling,
+            ));
+        }
+
+        return Ok(Redirect::found(redirect_location(
+            &redirect_uri,
+            "error=access_denied",
+            &request.state,
+        )));
+    }
+
+    // Actors who are signed in to Raito-FE don't have to enter their password again
+    let authorized_actor = match (
+        user_form.username,
+        user_form.password,
+        user_form.two_factor_code,
+    ) {
+        (_, _, Some(two_factor_code)) => {
+            two_factor::verify_pending(&db_connection, cookies, &two_factor_code)
+        }
+        (Some(username), Some(password), None) if !username.is_empty() => {
+            match actor::authorize(&db_connection, &username, password) {
+                Ok(true) => {
+                    if !registration::is_approved(&db_connection, &username) {
+                        return Err(consent_page(
+                            cookies,
+                            &request,
+                            &serialized_application,
+                            &granted_scopes,
+                            session_actor,
+                            false,
+                            String::from("Your account is waiting for approval!"),
+                        ));
+                    }
+
+                    if !email::is_confirmed(&db_connection, &username) {
+                        return Err(consent_page(
+                            cookies,
+                            &request,
+                            &serialized_application,
+                            &granted_scopes,
+                            session_actor,
+                            false,
+                            String::from("Please confirm your e-mail address first!"),
+                        ));
+                    }
+
+                    if two_factor::is_required(&db_connection, &username) {
+                        two_factor::set_pending(cookies, &username);
+                        return Err(consent_page(
+                            cookies,
+                            &request,
+                            &serialized_application,
+                            &granted_scopes,
+                            session_actor,
+                            true,
+                            String::new(),
+                        ));
+                    }
+                    Some(username)
                 }
-                Err(_) => Err(user_authorization_error(
-                    String::from("Invalid application credentials"),
-                    styling,
-                )),
-            },
-            _ => Err(user_authorization_error(
+                _ => None,
+            }
+        }
+        _ => session_actor.clone(),
+    };
+
+    let authorized_actor = match authorized_actor {
+        Some(username) => username,
+        None => {
+            // A wrong code doesn't require the password again, as long as the login is pending
+            let pending = two_factor::pending_actor(cookies).is_some();
+            return Err(consent_page(
+                cookies,
+                &request,
+                &serialized_application,
+                &granted_scopes,
+                session_actor,
+                pending,
                 String::from("Invalid login data!"),
-                styling,
-            )),
+            ));
         }
-    } else {
-        Err(user_authorization_error(
-            String::from("Invalid OAuth parameters"),
-            styling,
-        ))
+    };
+
+    let auth_code = authorize_application(
+        authorized_actor,
+        serialized_application.id,
+        granted_scopes,
+        request.code_challenge.clone(),
+    );
+
+    // Applications without a redirect URI have the code copied by the user
+    if redirect_uri == OOB_REDIRECT_URI {
+        return Err(out_of_band_code(auth_code, request.styling));
+    }
+
+    Ok(Redirect::found(redirect_location(
+        &redirect_uri,
+        &format!("code={}", auth_code),
+        &request.state,
+    )))
+}
+
+/// Determines whether a URL may be linked on the consent page, which is only the case for `http`
+/// and `https` URLs
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/oauth_authorization.rs`
+/// - is_web_url()
+pub fn is_web_url(url: &str) -> bool {
+    match Url::parse(url) {
+        Ok(url) => url.scheme() == "http" || url.scheme() == "https",
+        Err(_) => false,
+    }
+}
+
+/// Verifies a PKCE code verifier against the challenge of an authorization, only the `S256`
+/// method is supported
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/oauth_authorization.rs`
+/// - verify_code_challenge()
+pub fn verify_code_challenge(code_verifier: &str, _code_challenge: &str) -> bool {
+    base64::encode_config(&sha256(code_verifier.as_bytes()), base64::URL_SAFE_NO_PAD)
+        == _code_challenge
+}
+
+fn consent_page(
+    cookies: &mut Cookies,
+    request: &AuthorizationRequest,
+    serialized_application: &Application,
+    granted_scopes: &str,
+    session_actor: Option<String>,
+    two_factor_step: bool,
+    error_context: String,
+) -> Template {
+    Template::render(
+        "oauth_authorization",
+        serde_json::json!({
+            "error_context": error_context,
+            "styling": request.styling.unwrap_or(true).to_string(),
+            "application_name": serialized_application.client_name.clone().unwrap_or_default(),
+            "application_website": serialized_application
+                .website
+                .clone()
+                .filter(|website| is_web_url(website))
+                .unwrap_or_default(),
+            "scopes": scope::parse(granted_scopes),
+            "session_account": session_actor.unwrap_or_default(),
+            "two_factor": two_factor_step,
+            "csrf_token": csrf_token(cookies)
+        }),
+    )
+}
+
+// Consent forms carry a random token which is also stored in a private cookie, so that other sites
+// can't submit the form on behalf of actors who are signed in to Raito-FE
+fn csrf_token(cookies: &mut Cookies) -> String {
+    let mut token_num: BigNum = BigNum::new().unwrap();
+    token_num
+        .rand(128, MsbOption::MAYBE_ZERO, true)
+        .expect("Error generating CSRF token");
+    let token = token_num.to_hex_str().unwrap().to_string();
+
+    cookies.add_private(Cookie::new(CSRF_COOKIE, token.clone()));
+    token
+}
+
+fn out_of_band_code(auth_code: String, styling: Option<bool>) -> Template {
+    let mut parameters = HashMap::<String, String>::new();
+    parameters.insert(String::from("error_context"), String::new());
+    parameters.insert(String::from("code"), auth_code);
+    parameters.insert(
+        String::from("styling"),
+        styling.unwrap_or_else(|| true).to_string(),
+    );
+
+    Template::render("oauth_authorization", parameters)
+}
+
+fn redirect_location(redirect_uri: &str, parameter: &str, state: &Option<String>) -> String {
+    let symbol = if redirect_uri.contains("?") { "&" } else { "?" };
+    let state = match state {
+        Some(value) => format!(
+            "&state={}",
+            form_urlencoded::byte_serialize(value.as_bytes()).collect::<String>()
+        ),
+        None => String::new(),
+    };
+
+    format!("{}{}{}{}", redirect_uri, symbol, parameter, state)
+}
+
+fn verify_csrf_token(cookies: &mut Cookies, form_token: &Option<String>) -> bool {
+    match (cookies.get_private(CSRF_COOKIE), form_token) {
+        (Some(cookie), Some(form_token)) => !form_token.is_empty() && cookie.value() == form_token,
+        _ => false,
+    }
+}
+
+// Returns the username of the actor a Raito-FE session belongs to
+fn session_actor(db_connection: &PgConnection, session_token: Option<String>) -> Option<String> {
+    match session_token {
+        Some(session_token) => match verify_token(db_connection, session_token) {
+            Ok(token) => Some(token.actor).filter(|username| !username.is_empty()),
+            Err(_) => None,
+        },
+        None => None,
     }
 }
 
@@ -117,11 +392,57 @@ fn user_authorization_error(error_context: String, styling: Option<bool>) -> Tem
     Template::render("oauth_authorization", parameters)
 }
 
-pub fn authorize_application(_actor: String, application_id: i64) -> String {
+// Returns the application and the scopes granted by a valid authorization request
+fn validate_request(
+    db_connection: &PgConnection,
+    request: &AuthorizationRequest,
+) -> Result<(Application, String), &'static str> {
+    let serialized_application = match &request.client_id {
+        Some(application_id) => {
+            match get_application_by_client_id(db_connection, application_id.to_string()) {
+                Ok(serialized_application) => serialized_application,
+                Err(_) => return Err("Invalid application credentials"),
+            }
+        }
+        None => return Err("Invalid OAuth parameters"),
+    };
+
+    match &request.redirect_uri {
+        Some(redirect_uri) => {
+            if !is_redirect_uri(&serialized_application, redirect_uri) {
+                return Err("The redirect URI is not registered by the application");
+            }
+        }
+        None => return Err("Invalid OAuth parameters"),
+    }
+
+    if request.response_type.as_ref().map(String::as_str) != Some("code") {
+        return Err("Unsupported response type");
+    }
+
+    if request.code_challenge.is_some()
+        && request.code_challenge_method.as_ref().map(String::as_str) != Some("S256")
+    {
+        return Err("Unsupported code challenge method");
+    }
+
+    let granted_scopes = scope::validate(
+        request.scope.as_ref().map(String::as_str),
+        &serialized_application.scopes,
+    )?;
+    Ok((serialized_application, granted_scopes))
+}
+
+pub fn authorize_application(
+    _actor: String,
+    application_id: i64,
+    _scopes: String,
+    _code_challenge: Option<String>,
+) -> String {
     let db_connection = database::establish_connection();
     let mut hex_num: BigNum = BigNum::new().unwrap();
     let utc_time: chrono::DateTime<Utc> = Utc::now();
-    let expiration_date: chrono::DateTime<Utc> = utc_time + Duration::days(30);
+    let expiration_date: chrono::DateTime<Utc> = utc_time + Duration::minutes(10);
     hex_num
         .rand(256, MsbOption::MAYBE_ZERO, true)
         .expect("Error generating authorization code");
@@ -131,6 +452,8 @@ pub fn authorize_application(_actor: String, application_id: i64) -> String {
         actor: _actor,
         code: hex_num.to_string(),
         valid_until: expiration_date.timestamp().to_string(),
+        scopes: _scopes,
+        code_challenge: _code_challenge,
     };
 
     insert(&db_connection, &new_authorization);
@@ -146,6 +469,8 @@ fn insert(db_connection: &PgConnection, authorization: &Authorization) {
         actor.eq(&authorization.actor),
         code.eq(&authorization.code),
         valid_until.eq(&parsed_expiration_date),
+        scopes.eq(&authorization.scopes),
+        code_challenge.eq(&authorization.code_challenge),
     );
 
     diesel::insert_into(oauth_authorizations::table)
@@ -0,0 +1,103 @@
+//! Applications which hold tokens for a local actor
+//!
+//! An application is listed once, no matter how many tokens it holds. Tokens without an
+//! application, like those of Raito-FE sessions, aren't listed.
+//!
+
+use chrono::{DateTime, Utc};
+use database::models::QueryOauthToken;
+use diesel::pg::PgConnection;
+use oauth::application::get_application_by_id;
+use oauth::scope;
+use oauth::token::get_tokens_by_actor;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+pub struct AuthorizedApplication {
+    pub id: i64,
+    pub name: Option<String>,
+    pub website: Option<String>,
+    pub scopes: Vec<String>,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
+
+/// Returns the applications which hold valid tokens for a local actor
+///
+/// # Parameters
+///
+/// * `db_connection`  - &PgConnection | Reference to a database connection
+/// * `actor_username` -          &str | The username of a local actor
+///
+pub fn authorized_applications(
+    db_connection: &PgConnection,
+    actor_username: &str,
+) -> Result<Vec<AuthorizedApplication>, diesel::result::Error> {
+    let tokens = get_tokens_by_actor(db_connection, actor_username)?;
+
+    Ok(group_tokens(&tokens)
+        .into_iter()
+        .filter_map(|mut authorized_application| {
+            match get_application_by_id(db_connection, authorized_application.id) {
+                Ok(application) => {
+                    authorized_application.name = application.client_name;
+                    authorized_application.website = application.website;
+                    Some(authorized_application)
+                }
+                Err(_) => None,
+            }
+        })
+        .collect())
+}
+
+/// Groups tokens by their application
+///
+/// # Description
+///
+/// The scopes of all tokens are merged, the application was authorized when its first token was
+/// issued and last used when any of its tokens was. Names and websites are left empty.
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/oauth_authorized_application.rs`
+/// - group_tokens()
+pub fn group_tokens(tokens: &[QueryOauthToken]) -> Vec<AuthorizedApplication> {
+    let mut applications: Vec<AuthorizedApplication> = Vec::new();
+
+    for token in tokens.iter().filter(|token| token.application != 0) {
+        let created_at = DateTime::<Utc>::from_utc(token.created, Utc).to_rfc3339();
+        let last_used_at = token
+            .last_used
+            .map(|timestamp| DateTime::<Utc>::from_utc(timestamp, Utc).to_rfc3339());
+
+        match applications
+            .iter_mut()
+            .find(|application| application.id == token.application)
+        {
+            Some(application) => {
+                for token_scope in scope::parse(&token.scopes) {
+                    if !application.scopes.contains(&token_scope) {
+                        application.scopes.push(token_scope);
+                    }
+                }
+
+                // RFC 3339 timestamps in UTC are ordered lexicographically
+                if created_at < application.created_at {
+                    application.created_at = created_at;
+                }
+                if last_used_at > application.last_used_at {
+                    application.last_used_at = last_used_at;
+                }
+            }
+            None => applications.push(AuthorizedApplication {
+                id: token.application,
+                name: None,
+                website: None,
+                scopes: scope::parse(&token.scopes),
+                created_at: created_at,
+                last_used_at: last_used_at,
+            }),
+        }
+    }
+    applications
+}
@@ -1,4 +1,7 @@
 pub mod application;
 pub mod authorization;
+pub mod authorized_application;
 pub mod routes;
+pub mod scope;
+pub mod sweeper;
 pub mod token;
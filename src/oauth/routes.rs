@@ -1,46 +1,59 @@
-use oauth::authorization::{handle_user_authorization, UserForm};
-use oauth::token::{get_token, TokenForm};
+use oauth::authorization::{
+    authorization_page, handle_user_authorization, AuthorizationRequest, UserForm,
+};
+use oauth::token::{get_token, revoke, OAuthError, RevokeForm, TokenForm};
+use rocket::http::Cookies;
 use rocket::request::LenientForm;
 use rocket::response::Redirect;
+use rocket_contrib::json;
 use rocket_contrib::json::JsonValue;
 use rocket_contrib::templates::Template;
-use std::collections::HashMap;
 
-#[get("/oauth/authorize?<styling>")]
-pub fn authorize(styling: Option<bool>) -> Template {
-    let mut parameters = HashMap::<String, String>::new();
-    parameters.insert(String::from("error_context"), String::new());
-    parameters.insert(
-        String::from("styling"),
-        styling.unwrap_or_else(|| true).to_string(),
-    );
-    Template::render("oauth_authorization", parameters)
+#[get("/oauth/authorize?<request..>")]
+pub fn authorize(request: LenientForm<AuthorizationRequest>, mut cookies: Cookies) -> Template {
+    let session_token = session_token(&mut cookies);
+    authorization_page(request.into_inner(), session_token, &mut cookies)
 }
 
-#[post(
-    "/oauth/authorize?<client_id>&<response_type>&<redirect_uri>&<scope>&<state>&<styling>",
-    data = "<form>"
-)]
+#[post("/oauth/authorize?<request..>", data = "<form>")]
 pub fn authorize_result(
-    client_id: Option<String>,
-    response_type: Option<String>,
-    redirect_uri: Option<String>,
-    scope: Option<String>,
-    state: Option<String>,
-    styling: Option<bool>,
+    request: LenientForm<AuthorizationRequest>,
+    mut cookies: Cookies,
     form: LenientForm<UserForm>,
 ) -> Result<Redirect, Template> {
+    let session_token = session_token(&mut cookies);
     handle_user_authorization(
         form.into_inner(),
-        client_id,
-        response_type,
-        redirect_uri,
-        state,
-        styling,
+        request.into_inner(),
+        session_token,
+        &mut cookies,
     )
 }
 
+#[post("/oauth/revoke", data = "<form>")]
+pub fn token_revoke(form: LenientForm<RevokeForm>) -> Result<JsonValue, OAuthError> {
+    revoke(form.into_inner())
+}
+
 #[post("/oauth/token", data = "<form>")]
-pub fn token(form: LenientForm<TokenForm>) -> JsonValue {
+pub fn token(form: LenientForm<TokenForm>) -> Result<JsonValue, OAuthError> {
     get_token(form.into_inner())
 }
+
+// Requests rejected by `scope::Authorized` end up here, as Mastodon-API clients expect JSON errors
+#[catch(401)]
+pub fn unauthorized() -> JsonValue {
+    json!({"error": "The access token is invalid"})
+}
+
+#[catch(403)]
+pub fn forbidden() -> JsonValue {
+    json!({"error": "This action is outside the authorized scopes"})
+}
+
+// Actors who are signed in to Raito-FE can authorize applications without their password
+fn session_token(cookies: &mut Cookies) -> Option<String> {
+    cookies
+        .get_private("oauth_token")
+        .map(|cookie| cookie.value().to_string())
+}
@@ -0,0 +1,262 @@
+//! OAuth scopes as used by the Mastodon API
+//!
+//! Top-level scopes like `read` grant all granular scopes below them, e.g. `read:statuses`. The
+//! legacy `follow` scope grants the scopes needed for managing relationships.
+//!
+
+use database::PooledConnection;
+use oauth::token::{verify_token, Token};
+use rocket::http::Status;
+use rocket::request::{self, FromRequest, Request};
+use rocket::Outcome;
+use std::marker::PhantomData;
+
+// Applications which don't register any scopes are granted this one
+pub const DEFAULT_SCOPES: &str = "read";
+
+// Raito-FE doesn't go through the OAuth flow, its tokens are granted what every token used to be
+pub const RAITO_SCOPES: &str = "read write follow";
+
+pub const SCOPES: [&str; 34] = [
+    "read",
+    "read:accounts",
+    "read:blocks",
+    "read:bookmarks",
+    "read:favourites",
+    "read:filters",
+    "read:follows",
+    "read:lists",
+    "read:mutes",
+    "read:notifications",
+    "read:search",
+    "read:statuses",
+    "write",
+    "write:accounts",
+    "write:blocks",
+    "write:bookmarks",
+    "write:conversations",
+    "write:favourites",
+    "write:filters",
+    "write:follows",
+    "write:lists",
+    "write:media",
+    "write:mutes",
+    "write:notifications",
+    "write:reports",
+    "write:statuses",
+    "follow",
+    "push",
+    "admin:read",
+    "admin:read:accounts",
+    "admin:read:reports",
+    "admin:write",
+    "admin:write:accounts",
+    "admin:write:reports",
+];
+
+const FOLLOW_SCOPES: [&str; 6] = [
+    "read:blocks",
+    "read:follows",
+    "read:mutes",
+    "write:blocks",
+    "write:follows",
+    "write:mutes",
+];
+
+/// A scope which can be required by request guards
+pub trait Scope {
+    const NAME: &'static str;
+}
+
+macro_rules! scopes {
+    ($($marker:ident => $name:expr),*) => {
+        $(
+            pub struct $marker;
+
+            impl Scope for $marker {
+                const NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+scopes! {
+    Read => "read",
+    ReadAccounts => "read:accounts",
+    ReadBookmarks => "read:bookmarks",
+    ReadFavourites => "read:favourites",
+    ReadFilters => "read:filters",
+    ReadFollows => "read:follows",
+    ReadLists => "read:lists",
+    ReadNotifications => "read:notifications",
+    ReadStatuses => "read:statuses",
+    Write => "write",
+    WriteAccounts => "write:accounts",
+    WriteBookmarks => "write:bookmarks",
+    WriteConversations => "write:conversations",
+    WriteFavourites => "write:favourites",
+    WriteFilters => "write:filters",
+    WriteFollows => "write:follows",
+    WriteLists => "write:lists",
+    WriteNotifications => "write:notifications",
+    WriteStatuses => "write:statuses",
+    AdminRead => "admin:read",
+    AdminWrite => "admin:write"
+}
+
+/// Request guard for a valid OAuth token which was granted the scope `S`
+///
+/// # Description
+///
+/// Requests without a bearer token or with an unknown one fail with `401 Unauthorized`, tokens
+/// lacking the scope fail with `403 Forbidden`.
+pub struct Authorized<S: Scope>(pub Token, PhantomData<S>);
+
+impl<S: Scope> Authorized<S> {
+    pub fn access_token(&self) -> String {
+        self.0.access_token.clone()
+    }
+}
+
+impl<'a, 'r, S: Scope> FromRequest<'a, 'r> for Authorized<S> {
+    type Error = &'static str;
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Authorized<S>, &'static str> {
+        let access_token = match request
+            .headers()
+            .get_one("Authorization")
+            .and_then(bearer_token)
+        {
+            Some(access_token) => access_token,
+            None => return Outcome::Failure((Status::Unauthorized, "The access token is missing")),
+        };
+
+        let pooled_connection = match request.guard::<PooledConnection>() {
+            Outcome::Success(pooled_connection) => pooled_connection,
+            _ => {
+                return Outcome::Failure((
+                    Status::ServiceUnavailable,
+                    "The database is unavailable",
+                ))
+            }
+        };
+
+        match verify_token(&pooled_connection, access_token) {
+            Ok(token) => {
+                if grants(&token.scope, S::NAME) {
+                    Outcome::Success(Authorized(token, PhantomData))
+                } else {
+                    Outcome::Failure((
+                        Status::Forbidden,
+                        "This action is outside the authorized scopes",
+                    ))
+                }
+            }
+            Err(_) => Outcome::Failure((Status::Unauthorized, "The access token is invalid")),
+        }
+    }
+}
+
+/// Returns the token of an `Authorization` header using the bearer scheme
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/oauth_scope.rs`
+/// - bearer_token()
+pub fn bearer_token(header: &str) -> Option<String> {
+    let mut parts = header.split_whitespace();
+
+    match (parts.next(), parts.next()) {
+        (Some(scheme), Some(token)) => {
+            if scheme.trim_end_matches(':').eq_ignore_ascii_case("Bearer") {
+                Some(token.to_string())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Determines whether a set of granted scopes includes a certain scope
+///
+/// # Parameters
+///
+/// * `granted`  - &str | Space separated scopes, as stored on tokens
+/// * `required` - &str | A single scope, e.g. `read:statuses`
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/oauth_scope.rs`
+/// - grants()
+pub fn grants(granted: &str, required: &str) -> bool {
+    parse(granted).iter().any(|scope| {
+        scope == required
+            || required.starts_with(&format!("{}:", scope))
+            || (scope == "follow" && FOLLOW_SCOPES.contains(&required))
+    })
+}
+
+/// Splits scopes, which are separated by spaces or by `+` in some query strings
+pub fn parse(scopes: &str) -> Vec<String> {
+    scopes
+        .split(|character: char| character.is_whitespace() || character == '+')
+        .filter(|scope| !scope.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Normalizes the scopes an application registers, unknown scopes are rejected
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/oauth_scope.rs`
+/// - normalize()
+pub fn normalize(scopes: &str) -> Result<String, &'static str> {
+    let mut parsed_scopes: Vec<String> = Vec::new();
+    for scope in parse(scopes) {
+        if !parsed_scopes.contains(&scope) {
+            parsed_scopes.push(scope);
+        }
+    }
+
+    if parsed_scopes.is_empty() {
+        return Ok(String::from(DEFAULT_SCOPES));
+    }
+
+    if parsed_scopes
+        .iter()
+        .all(|scope| SCOPES.contains(&scope.as_str()))
+    {
+        Ok(parsed_scopes.join(" "))
+    } else {
+        Err("Unknown OAuth scope")
+    }
+}
+
+/// Validates the scopes requested during an authorization against those of the application
+///
+/// # Parameters
+///
+/// * `requested`  - Option<&str> | The requested scopes, the application's scopes if none
+/// * `registered` -         &str | The scopes registered by the application
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/oauth_scope.rs`
+/// - validate()
+pub fn validate(requested: Option<&str>, registered: &str) -> Result<String, &'static str> {
+    let requested_scopes = match requested {
+        Some(requested) => normalize(requested)?,
+        None => return normalize(registered),
+    };
+
+    if parse(&requested_scopes)
+        .iter()
+        .all(|scope| grants(registered, scope))
+    {
+        Ok(requested_scopes)
+    } else {
+        Err("The requested scopes exceed those of the application")
+    }
+}
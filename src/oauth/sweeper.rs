@@ -0,0 +1,34 @@
+//! Background sweeper for expired OAuth tokens and authorization codes
+//!
+
+use database;
+use oauth::{authorization, token};
+use std::thread;
+use std::time::Duration;
+
+// Interval between two sweeps in seconds
+const SWEEP_INTERVAL: u64 = 3600;
+
+/// Starts a thread which deletes expired tokens and authorization codes every hour
+pub fn start() {
+    thread::spawn(|| loop {
+        sweep();
+        thread::sleep(Duration::from_secs(SWEEP_INTERVAL));
+    });
+}
+
+fn sweep() {
+    let db_connection = database::establish_connection();
+
+    match authorization::delete_expired(&db_connection) {
+        Ok(count) if count > 0 => println!("Deleted {} expired OAuth authorizations", count),
+        Ok(_) => (),
+        Err(e) => eprintln!("Error deleting expired OAuth authorizations: {}", e),
+    }
+
+    match token::delete_expired(&db_connection) {
+        Ok(count) if count > 0 => println!("Deleted {} expired OAuth tokens", count),
+        Ok(_) => (),
+        Err(e) => eprintln!("Error deleting expired OAuth tokens: {}", e),
+    }
+}
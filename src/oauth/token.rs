@@ -1,3 +1,4 @@
+use actor::authorize;
 use chrono::prelude::*;
 use chrono::Duration;
 use chrono::NaiveDateTime;
@@ -7,15 +8,61 @@ use database::schema::oauth_tokens;
 use database::schema::oauth_tokens::dsl::*;
 use diesel::pg::PgConnection;
 use diesel::query_dsl::RunQueryDsl;
+use diesel::BoolExpressionMethods;
 use diesel::ExpressionMethods;
 use diesel::QueryDsl;
-use oauth::application::verify_credentials;
-use oauth::authorization::get_authorization_by_code;
+use email;
+use oauth::application::{get_application_by_client_id, is_redirect_uri, verify_credentials};
+use oauth::authorization::{consume_authorization_by_code, verify_code_challenge};
+use oauth::scope;
 use openssl::bn::BigNum;
 use openssl::bn::MsbOption;
+use registration;
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
 use rocket_contrib::json;
 use rocket_contrib::json::JsonValue;
 use serde::{Deserialize, Serialize};
+use two_factor;
+
+// Precision of the `last_used` timestamp of tokens in minutes
+const LAST_USED_PRECISION: i64 = 5;
+
+/// An error response of the token and revocation endpoints according to RFC 6749
+#[derive(Debug, PartialEq)]
+pub struct OAuthError {
+    pub error: String,
+    pub description: String,
+}
+
+impl OAuthError {
+    pub fn new(error: &str, description: &str) -> OAuthError {
+        OAuthError {
+            error: error.to_string(),
+            description: description.to_string(),
+        }
+    }
+
+    // Failed client authentication is answered with `401 Unauthorized`, errors of the server
+    // itself with `500 Internal Server Error` and every other error with `400 Bad Request`
+    pub fn status(&self) -> Status {
+        match self.error.as_str() {
+            "invalid_client" => Status::Unauthorized,
+            "server_error" => Status::InternalServerError,
+            _ => Status::BadRequest,
+        }
+    }
+}
+
+impl<'r> Responder<'r> for OAuthError {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+        let status = self.status();
+        let body = json!({"error": self.error, "error_description": self.description})
+            .respond_to(request)?;
+        Response::build_from(body).status(status).ok()
+    }
+}
 
 #[derive(Deserialize, Serialize)]
 pub struct Token {
@@ -31,9 +78,23 @@ pub struct Token {
 pub struct TokenForm {
     pub client_id: String,
     pub client_secret: String,
+    // One of `authorization_code`, `refresh_token`, `password` or `client_credentials`
     pub grant_type: String,
-    pub code: String,
-    pub redirect_uri: String,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub refresh_token: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub scope: Option<String>,
+    // Required if the authorization code was requested with a PKCE challenge
+    pub code_verifier: Option<String>,
+}
+
+#[derive(FromForm)]
+pub struct RevokeForm {
+    pub client_id: String,
+    pub client_secret: String,
+    pub token: String,
 }
 
 fn serialize_token(sql_token: QueryOauthToken) -> Token {
@@ -43,7 +104,7 @@ fn serialize_token(sql_token: QueryOauthToken) -> Token {
         token_type: String::from("Bearer"),
         actor: sql_token.actor,
         valid_until: sql_token.valid_until.to_string(),
-        scope: String::from("read+write+follow"),
+        scope: sql_token.scopes,
     }
 }
 
@@ -51,30 +112,258 @@ pub fn verify_token(
     db_connection: &PgConnection,
     _token: String,
 ) -> Result<Token, diesel::result::Error> {
+    let now = Utc::now().naive_utc();
+
     match oauth_tokens
         .filter(access_token.eq(_token))
+        .filter(valid_until.gt(now))
         .limit(1)
         .first::<QueryOauthToken>(db_connection)
     {
-        Ok(sql_token) => Ok(serialize_token(sql_token)),
+        Ok(sql_token) => {
+            // Tokens are verified on every request, the timestamp doesn't need to be more precise
+            let outdated = match sql_token.last_used {
+                Some(timestamp) => timestamp < now - Duration::minutes(LAST_USED_PRECISION),
+                None => true,
+            };
+
+            if outdated {
+                diesel::update(oauth_tokens.filter(id.eq(sql_token.id)))
+                    .set(last_used.eq(now))
+                    .execute(db_connection)
+                    .expect("Error updating oauth token");
+            }
+            Ok(serialize_token(sql_token))
+        }
         Err(e) => Err(e),
     }
 }
 
-pub fn get_token(form: TokenForm) -> JsonValue {
+/// Returns the ID of the application a valid token was issued to
+pub fn get_application_id(
+    db_connection: &PgConnection,
+    _token: &str,
+) -> Result<i64, diesel::result::Error> {
+    oauth_tokens
+        .filter(access_token.eq(_token))
+        .filter(valid_until.gt(Utc::now().naive_utc()))
+        .select(application)
+        .first::<i64>(db_connection)
+}
+
+/// Returns all valid tokens which were issued to a local actor
+pub fn get_tokens_by_actor(
+    db_connection: &PgConnection,
+    actor_username: &str,
+) -> Result<Vec<QueryOauthToken>, diesel::result::Error> {
+    oauth_tokens
+        .filter(actor.eq(actor_username))
+        .filter(valid_until.gt(Utc::now().naive_utc()))
+        .order(id.asc())
+        .load::<QueryOauthToken>(db_connection)
+}
+
+/// Deletes every token of a local actor which was issued to a certain application
+///
+/// # Parameters
+///
+/// * `db_connection`  - &PgConnection | Reference to a database connection
+/// * `actor_username` -          &str | The username of a local actor
+/// * `application_id` -           i64 | The ID of the OAuth application
+///
+pub fn revoke_application(
+    db_connection: &PgConnection,
+    actor_username: &str,
+    application_id: i64,
+) -> Result<usize, diesel::result::Error> {
+    diesel::delete(
+        oauth_tokens
+            .filter(actor.eq(actor_username))
+            .filter(application.eq(application_id)),
+    )
+    .execute(db_connection)
+}
+
+/// Deletes every token which was issued to a local actor
+pub fn revoke_actor(
+    db_connection: &PgConnection,
+    actor_username: &str,
+) -> Result<usize, diesel::result::Error> {
+    diesel::delete(oauth_tokens.filter(actor.eq(actor_username))).execute(db_connection)
+}
+
+/// Deletes expired tokens, their refresh tokens can't be redeemed afterwards
+pub fn delete_expired(db_connection: &PgConnection) -> Result<usize, diesel::result::Error> {
+    diesel::delete(oauth_tokens.filter(valid_until.le(Utc::now().naive_utc())))
+        .execute(db_connection)
+}
+
+/// Issues a token according to the `grant_type` of a token request
+///
+/// # Description
+///
+/// Authorization codes are consumed when they're redeemed, refresh tokens replace the token they
+/// belong to. Tokens of the `client_credentials` grant aren't bound to any actor.
+pub fn get_token(form: TokenForm) -> Result<JsonValue, OAuthError> {
+    let db_connection = database::establish_connection();
+
+    if !verify_credentials(
+        &db_connection,
+        form.client_id.clone(),
+        form.client_secret.clone(),
+    ) {
+        return oauth_error(
+            "invalid_client",
+            "OAuth application credentials are invalid.",
+        );
+    }
+    let oauth_application =
+        get_application_by_client_id(&db_connection, form.client_id.clone()).unwrap();
+
+    match form.grant_type.as_str() {
+        "authorization_code" => {
+            match &form.redirect_uri {
+                Some(uri) if is_redirect_uri(&oauth_application, uri) => (),
+                _ => return oauth_error("invalid_grant", "OAuth redirect URI is invalid."),
+            }
+
+            match consume_authorization_by_code(
+                &db_connection,
+                oauth_application.id,
+                form.code.unwrap_or_default(),
+            ) {
+                Ok(authorization) => {
+                    let valid_verifier = match &authorization.code_challenge {
+                        Some(challenge) => match &form.code_verifier {
+                            Some(verifier) => verify_code_challenge(verifier, challenge),
+                            None => false,
+                        },
+                        None => true,
+                    };
+
+                    if valid_verifier {
+                        Ok(json!(create(
+                            &authorization.actor,
+                            oauth_application.id,
+                            &authorization.scopes
+                        )))
+                    } else {
+                        oauth_error("invalid_grant", "OAuth authorization code is invalid.")
+                    }
+                }
+                Err(_) => oauth_error("invalid_grant", "OAuth authorization code is invalid."),
+            }
+        }
+        // Expired tokens can't be refreshed, even if they weren't deleted by the sweeper yet
+        "refresh_token" => match diesel::delete(
+            oauth_tokens
+                .filter(refresh_token.eq(form.refresh_token.unwrap_or_default()))
+                .filter(application.eq(oauth_application.id))
+                .filter(valid_until.gt(Utc::now().naive_utc())),
+        )
+        .get_result::<QueryOauthToken>(&*db_connection)
+        {
+            Ok(sql_token) => Ok(json!(create(
+                &sql_token.actor,
+                oauth_application.id,
+                &sql_token.scopes
+            ))),
+            Err(_) => oauth_error("invalid_grant", "OAuth refresh token is invalid."),
+        },
+        "password" => {
+            let username = form.username.unwrap_or_default();
+
+            match authorize(&db_connection, &username, form.password.unwrap_or_default()) {
+                Ok(true) if !registration::is_approved(&db_connection, &username) => {
+                    oauth_error("invalid_grant", "This account is waiting for approval.")
+                }
+                Ok(true) if !email::is_confirmed(&db_connection, &username) => oauth_error(
+                    "invalid_grant",
+                    "The e-mail address of this account isn't confirmed yet.",
+                ),
+                // The grant has no second step, these actors have to use the authorization flow
+                Ok(true) if two_factor::is_required(&db_connection, &username) => oauth_error(
+                    "invalid_grant",
+                    "Two-factor authentication is enabled for this account.",
+                ),
+                Ok(true) => match scope::validate(
+                    form.scope.as_ref().map(String::as_str),
+                    &oauth_application.scopes,
+                ) {
+                    Ok(granted_scopes) => Ok(json!(create(
+                        &username,
+                        oauth_application.id,
+                        &granted_scopes
+                    ))),
+                    Err(e) => oauth_error("invalid_scope", e),
+                },
+                _ => oauth_error("invalid_grant", "Invalid login data."),
+            }
+        }
+        "client_credentials" => match scope::validate(
+            form.scope.as_ref().map(String::as_str),
+            &oauth_application.scopes,
+        ) {
+            Ok(granted_scopes) => Ok(json!(create("", oauth_application.id, &granted_scopes))),
+            Err(e) => oauth_error("invalid_scope", e),
+        },
+        _ => oauth_error(
+            "unsupported_grant_type",
+            "OAuth grant type is not supported.",
+        ),
+    }
+}
+
+/// Revokes a token issued to an application
+///
+/// # Description
+///
+/// According to RFC 7009, either the access or the refresh token of a token may be revoked and
+/// unknown tokens are not considered an error.
+pub fn revoke(form: RevokeForm) -> Result<JsonValue, OAuthError> {
     let db_connection = database::establish_connection();
 
-    if verify_credentials(&db_connection, form.client_id, form.client_secret) {
-        match get_authorization_by_code(&db_connection, form.code) {
-            Ok(authorization) => json!(create(&authorization.actor)),
-            Err(_) => json!({"Error": "OAuth authorization code is invalid."}),
+    if !verify_credentials(&db_connection, form.client_id.clone(), form.client_secret) {
+        return oauth_error(
+            "invalid_client",
+            "OAuth application credentials are invalid.",
+        );
+    }
+    let oauth_application = match get_application_by_client_id(&db_connection, form.client_id) {
+        Ok(oauth_application) => oauth_application,
+        Err(_) => {
+            return oauth_error(
+                "invalid_client",
+                "OAuth application credentials are invalid.",
+            )
         }
-    } else {
-        json!({"Error": "OAuth application credentials are invalid."})
+    };
+
+    match diesel::delete(
+        oauth_tokens
+            .filter(
+                access_token
+                    .eq(&form.token)
+                    .or(refresh_token.eq(&form.token)),
+            )
+            .filter(application.eq(oauth_application.id)),
+    )
+    .execute(&*db_connection)
+    {
+        Ok(_) => Ok(json!({})),
+        Err(_) => oauth_error("server_error", "OAuth token could not be revoked."),
     }
 }
 
-pub fn create(actor_username: &str) -> Token {
+/// Creates a new token for an actor and inserts it into the database
+///
+/// # Parameters
+///
+/// * `actor_username` - &str | The username of a local actor
+/// * `application_id` -  i64 | The ID of the OAuth application, `0` for Raito-FE
+/// * `scopes`         - &str | Space separated scopes granted to the token
+///
+pub fn create(actor_username: &str, application_id: i64, scopes: &str) -> Token {
     let db_connection = database::establish_connection();
     let mut access_token_num: BigNum = BigNum::new().unwrap();
     let mut refresh_token_num: BigNum = BigNum::new().unwrap();
@@ -94,22 +383,24 @@ pub fn create(actor_username: &str) -> Token {
         actor: actor_username.to_string(),
         token_type: String::from("Bearer"),
         valid_until: expiration_date.timestamp().to_string(),
-        scope: String::from("read write follow"),
+        scope: scopes.to_string(),
     };
 
-    insert(&db_connection, &new_token);
+    insert(&db_connection, &new_token, application_id);
     return new_token;
 }
 
-fn insert(db_connection: &PgConnection, mut _token: &Token) {
+fn insert(db_connection: &PgConnection, mut _token: &Token, application_id: i64) {
     let parsed_expiration_date: NaiveDateTime =
         chrono::NaiveDateTime::from_timestamp(_token.valid_until.parse::<i64>().unwrap(), 0);
 
     let new_token = (
+        application.eq(application_id),
         access_token.eq(&_token.access_token),
         actor.eq(&_token.actor),
         refresh_token.eq(&_token.refresh_token),
         valid_until.eq(&parsed_expiration_date),
+        scopes.eq(&_token.scope),
     );
 
     diesel::insert_into(oauth_tokens::table)
@@ -117,3 +408,8 @@ fn insert(db_connection: &PgConnection, mut _token: &Token) {
         .execute(db_connection)
         .expect("Error creating oauth application");
 }
+
+// Errors of token requests according to RFC 6749
+fn oauth_error(error: &str, description: &str) -> Result<JsonValue, OAuthError> {
+    Err(OAuthError::new(error, description))
+}
@@ -0,0 +1,130 @@
+use activity::Activity;
+use actor::Actor;
+use database::models::InsertPin;
+use database::schema::pins;
+use diesel::pg::PgConnection;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+// The same limit as Mastodon, as pinned statuses are meant to highlight a few statuses only
+pub const MAX_PINS: usize = 5;
+
+/// Determines whether an actor is allowed to pin a status
+///
+/// # Description
+///
+/// Only public or unlisted statuses can be pinned and only by their own author.
+///
+/// # Parameters
+///
+/// * `actor_uri` -              &str | The URI of the actor who wants to pin the status
+/// * `activity`  - &serde_json::Value | The `Create` activity of the status
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/pin.rs`
+/// - is_pinnable()
+pub fn is_pinnable(actor_uri: &str, activity: &serde_json::Value) -> bool {
+    let public = "https://www.w3.org/ns/activitystreams#Public";
+
+    activity["type"].as_str() == Some("Create")
+        && activity["actor"].as_str() == Some(actor_uri)
+        && [&activity["to"], &activity["cc"]].iter().any(|audience| {
+            audience
+                .as_array()
+                .map(|receipients| receipients.iter().any(|receipient| receipient == public))
+                .unwrap_or(false)
+        })
+}
+
+pub fn pin(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    activity: &Activity,
+) -> Result<(), &'static str> {
+    if !is_pinnable(&actor.actor_uri, &activity.data) {
+        return Err("Only your own public statuses can be pinned");
+    }
+
+    let pinned_activities = pinned_activities(db_connection, actor).unwrap_or_else(|_| Vec::new());
+    if pinned_activities.contains(&activity.id) {
+        return Ok(());
+    } else if pinned_activities.len() >= MAX_PINS {
+        return Err("You have already pinned the maximum number of statuses");
+    }
+
+    match diesel::insert_into(pins::table)
+        .values(InsertPin {
+            actor_id: actor.id,
+            activity_id: activity.id,
+        })
+        .on_conflict_do_nothing()
+        .execute(db_connection)
+    {
+        Ok(_) => Ok(()),
+        Err(_) => Err("Unable to pin status"),
+    }
+}
+
+pub fn unpin(db_connection: &PgConnection, actor: &Actor, activity_id: i64) {
+    diesel::delete(
+        pins::table
+            .filter(pins::actor_id.eq(actor.id))
+            .filter(pins::activity_id.eq(activity_id)),
+    )
+    .execute(db_connection)
+    .expect("Error removing pin");
+}
+
+/// Returns the ids of an actor's pinned activities, starting with the latest pin
+pub fn pinned_activities(
+    db_connection: &PgConnection,
+    actor: &Actor,
+) -> Result<Vec<i64>, diesel::result::Error> {
+    pins::table
+        .filter(pins::actor_id.eq(actor.id))
+        .select(pins::activity_id)
+        .order(pins::id.desc())
+        .load::<i64>(db_connection)
+}
+
+/// Returns which of the given activities an actor has pinned
+pub fn pinned_among(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    activity_ids: Vec<i64>,
+) -> Vec<i64> {
+    pins::table
+        .filter(pins::actor_id.eq(actor.id))
+        .filter(pins::activity_id.eq_any(activity_ids))
+        .select(pins::activity_id)
+        .load::<i64>(db_connection)
+        .unwrap_or_else(|_| Vec::new())
+}
+
+/// Replaces the pins of a remote actor with the contents of their `featured` collection, of which
+/// only the first `MAX_PINS` objects are pinned
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -       &Actor | A remote actor
+/// * `activity_ids`  -     Vec<i64> | The activities of the objects in the collection
+pub fn replace_pins(db_connection: &PgConnection, actor: &Actor, mut activity_ids: Vec<i64>) {
+    activity_ids.truncate(MAX_PINS);
+
+    diesel::delete(pins::table.filter(pins::actor_id.eq(actor.id)))
+        .execute(db_connection)
+        .expect("Error removing pins");
+
+    // The featured collection lists the latest pin first, but pins are ordered by their id
+    for activity_id in activity_ids.into_iter().rev() {
+        diesel::insert_into(pins::table)
+            .values(InsertPin {
+                actor_id: actor.id,
+                activity_id: activity_id,
+            })
+            .on_conflict_do_nothing()
+            .execute(db_connection)
+            .expect("Error adding pin");
+    }
+}
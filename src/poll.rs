@@ -0,0 +1,506 @@
+use activity::{get_ap_object_by_id, get_unannounced_ended_polls, update_activity_data, Activity};
+use actor::{get_actor_by_uri, Actor};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use database;
+use database::models::InsertPollVote;
+use database::schema::{activities, actors, ended_polls, poll_refreshes, poll_votes};
+use diesel::pg::PgConnection;
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl};
+use notification::{self, Notification};
+use std::thread;
+use std::time::Duration;
+use web;
+
+// Limits for polls created by local actors, these are the same as Mastodon's defaults so that
+// polls don't get rejected by other servers
+pub const MAX_OPTIONS: usize = 4;
+pub const MAX_OPTION_CHARS: usize = 50;
+pub const MIN_EXPIRATION: i64 = 300;
+pub const MAX_EXPIRATION: i64 = 2629746;
+
+// Remote polls are fetched again at most this often, however often their results are requested
+const REFRESH_SECONDS: i64 = 60;
+
+// Interval between two checks for ended polls in seconds
+const ENDED_CHECK_INTERVAL: u64 = 60;
+
+// Polls which ended longer ago aren't announced anymore, e.g. after the server was down
+const ENDED_ANNOUNCE_HOURS: i64 = 24;
+
+/// A poll which is about to be created by a local actor
+pub struct NewPoll {
+    pub options: Vec<String>,
+    pub expires_in: i64,
+    pub multiple: bool,
+}
+
+#[derive(Queryable, PartialEq, Clone)]
+pub struct PollVote {
+    pub id: i64,
+    pub activity_id: i64,
+    pub actor_id: i64,
+    pub choice: String,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+/// Determines whether an ActivityStreams object is a poll
+///
+/// # Parameters
+///
+/// * `object` - &serde_json::Value | An ActivityStreams object serialized in JSON
+pub fn is_question(object: &serde_json::Value) -> bool {
+    object["type"].as_str() == Some("Question")
+}
+
+/// Determines whether an ActivityStreams object is a vote on a poll
+///
+/// # Description
+///
+/// Votes are federated as objects which reply to the poll and only carry the chosen option as
+/// their `name`. Pleroma uses the type `Answer` for them, Mastodon uses plain notes.
+///
+/// # Parameters
+///
+/// * `object` - &serde_json::Value | An ActivityStreams object serialized in JSON
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/poll.rs`
+/// - is_vote()
+pub fn is_vote(object: &serde_json::Value) -> bool {
+    match object["type"].as_str() {
+        Some("Note") | Some("Answer") => {
+            object["name"].is_string()
+                && object["inReplyTo"].is_string()
+                && object["content"].as_str().unwrap_or_default().is_empty()
+        }
+        _ => false,
+    }
+}
+
+/// Determines whether more than one option of a poll may be chosen
+pub fn is_multiple(object: &serde_json::Value) -> bool {
+    object["anyOf"].is_array()
+}
+
+/// Returns the point in time a poll ends or has ended, if there is one
+pub fn end_time(object: &serde_json::Value) -> Option<DateTime<Utc>> {
+    let end_time = match object["closed"].as_str() {
+        Some(closed) => closed,
+        None => object["endTime"].as_str()?,
+    };
+
+    match DateTime::parse_from_rfc3339(end_time) {
+        Ok(date) => Some(date.with_timezone(&Utc)),
+        Err(_) => None,
+    }
+}
+
+/// Determines whether a poll doesn't accept any votes anymore
+///
+/// # Parameters
+///
+/// * `object` - &serde_json::Value | An ActivityStreams `Question` serialized in JSON
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/poll.rs`
+/// - is_expired()
+pub fn is_expired(object: &serde_json::Value) -> bool {
+    if object["closed"].is_string() {
+        return true;
+    }
+
+    match end_time(object) {
+        Some(date) => date < Utc::now(),
+        None => false,
+    }
+}
+
+/// Returns the names of all options of a poll
+///
+/// # Parameters
+///
+/// * `object` - &serde_json::Value | An ActivityStreams `Question` serialized in JSON
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/poll.rs`
+/// - options()
+pub fn options(object: &serde_json::Value) -> Vec<String> {
+    let key = if is_multiple(object) {
+        "anyOf"
+    } else {
+        "oneOf"
+    };
+    let mut options: Vec<String> = Vec::new();
+
+    match object[key].as_array() {
+        Some(option_vec) => {
+            for option in option_vec {
+                match option["name"].as_str() {
+                    Some(name) => options.push(name.to_string()),
+                    None => (),
+                }
+            }
+        }
+        None => (),
+    }
+    options
+}
+
+/// Returns the amount of votes per option of a poll, in the order of `options`
+pub fn option_votes(object: &serde_json::Value) -> Vec<i64> {
+    let key = if is_multiple(object) {
+        "anyOf"
+    } else {
+        "oneOf"
+    };
+
+    match object[key].as_array() {
+        Some(option_vec) => option_vec
+            .iter()
+            .map(|option| {
+                option["replies"]["totalItems"]
+                    .as_i64()
+                    .unwrap_or_else(|| 0)
+            })
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Handles an incoming vote on a local poll and updates the poll's results
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection      | Reference to a database connection
+/// * `activity`      - &serde_json::Value | A `Create` activity wrapping a vote
+pub fn handle_vote(db_connection: &PgConnection, activity: &serde_json::Value) {
+    let poll_id = activity["object"]["inReplyTo"].as_str().unwrap_or_default();
+    let choice = activity["object"]["name"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+
+    match get_ap_object_by_id(db_connection, poll_id) {
+        Ok(poll) => {
+            let local_poll = match get_actor_by_uri(db_connection, &poll.actor) {
+                Ok(author) => author.local,
+                Err(_) => false,
+            };
+
+            if local_poll {
+                match get_actor_by_uri(db_connection, activity["actor"].as_str().unwrap()) {
+                    Ok(voter) => match vote(db_connection, &poll, &voter, &vec![choice]) {
+                        Ok(_) => {
+                            tally(db_connection, &poll);
+                        }
+                        Err(e) => eprintln!("Rejected vote on poll '{}': {}", poll_id, e),
+                    },
+                    Err(_) => (),
+                }
+            }
+        }
+        Err(_) => eprintln!("Received vote on unknown poll '{}'", poll_id),
+    }
+}
+
+/// Notifies local authors and voters about polls which have ended
+///
+/// # Description
+///
+/// Every poll is only announced once. Remote polls are refreshed beforehand, so that the
+/// notification shows their final results.
+pub fn notify_ended(db_connection: &PgConnection) {
+    let polls = match get_unannounced_ended_polls(db_connection, ENDED_ANNOUNCE_HOURS) {
+        Ok(polls) => polls,
+        Err(e) => {
+            eprintln!("Unable to fetch ended polls: {}", e);
+            return;
+        }
+    };
+
+    for poll in polls {
+        let claimed = diesel::insert_into(ended_polls::table)
+            .values(ended_polls::activity_id.eq(poll.id))
+            .on_conflict_do_nothing()
+            .execute(db_connection)
+            .unwrap_or(0);
+        if claimed == 0 {
+            continue;
+        }
+
+        let mut receipients: Vec<i64> = poll_votes::table
+            .inner_join(actors::table)
+            .filter(poll_votes::activity_id.eq(poll.id))
+            .filter(actors::local.eq(true))
+            .select(poll_votes::actor_id)
+            .load::<i64>(db_connection)
+            .unwrap_or_else(|_| Vec::new());
+
+        match get_actor_by_uri(db_connection, &poll.actor) {
+            Ok(author) => {
+                if author.local {
+                    receipients.push(author.id);
+                } else {
+                    refresh(db_connection, &poll);
+                }
+            }
+            Err(_) => (),
+        }
+        receipients.sort();
+        receipients.dedup();
+
+        for actor_id in receipients {
+            notification::upsert(db_connection, Notification::new(poll.id, actor_id, "poll"));
+        }
+    }
+}
+
+/// Starts a thread which notifies about ended polls every minute, see `notify_ended`
+pub fn start_notifier() {
+    thread::spawn(|| loop {
+        notify_ended(&database::establish_connection());
+        thread::sleep(Duration::from_secs(ENDED_CHECK_INTERVAL));
+    });
+}
+
+/// Determines whether the results of a remote poll should be fetched again
+///
+/// # Description
+///
+/// Polls are refreshed at most every `REFRESH_SECONDS`. Once a poll has ended it's only refreshed
+/// a last time to get its final results.
+///
+/// # Parameters
+///
+/// * `last_refresh` - Option<NaiveDateTime> | The last time the poll was refreshed, if ever
+/// * `end`          - Option<DateTime<Utc>> | The point in time the poll ends, see `end_time`
+/// * `now`          -         NaiveDateTime | The current point in time
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/poll.rs`
+/// - refresh_due()
+pub fn refresh_due(
+    last_refresh: Option<NaiveDateTime>,
+    end: Option<DateTime<Utc>>,
+    now: NaiveDateTime,
+) -> bool {
+    match last_refresh {
+        Some(last_refresh) => {
+            let ended_before_refresh = match end {
+                Some(end) => end.naive_utc() <= last_refresh,
+                None => false,
+            };
+
+            !ended_before_refresh
+                && now - last_refresh >= chrono::Duration::seconds(REFRESH_SECONDS)
+        }
+        None => true,
+    }
+}
+
+/// Claims the refresh of a remote poll if it's due, see `refresh_due`
+///
+/// # Description
+///
+/// Polls which were closed by their author are never refreshed. The claim is stored right away,
+/// so that the refresh itself can happen in the background.
+pub fn claim_refresh(db_connection: &PgConnection, poll: &Activity) -> bool {
+    let object = &poll.data["object"];
+    if object["closed"].is_string() {
+        return false;
+    }
+
+    let now = Utc::now().naive_utc();
+    let last_refresh = poll_refreshes::table
+        .filter(poll_refreshes::activity_id.eq(poll.id))
+        .select(poll_refreshes::refreshed)
+        .first::<NaiveDateTime>(db_connection)
+        .ok();
+
+    if !refresh_due(last_refresh, end_time(object), now) {
+        return false;
+    }
+
+    diesel::insert_into(poll_refreshes::table)
+        .values((
+            poll_refreshes::activity_id.eq(poll.id),
+            poll_refreshes::refreshed.eq(now),
+        ))
+        .on_conflict(poll_refreshes::activity_id)
+        .do_update()
+        .set(poll_refreshes::refreshed.eq(now))
+        .execute(db_connection)
+        .is_ok()
+}
+
+/// Fetches the current state of a remote poll and stores its results
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `poll`          -     &Activity | The internal `Create` activity of a remote poll
+pub fn refresh(db_connection: &PgConnection, poll: &Activity) -> Activity {
+    let object_id = poll.data["object"]["id"].as_str().unwrap_or_default();
+
+    match web::fetch_remote_object(object_id) {
+        Ok(remote_object) => {
+            let remote_poll: serde_json::Value =
+                serde_json::from_str(&remote_object).unwrap_or_default();
+
+            if is_question(&remote_poll) && remote_poll["id"].as_str() == Some(object_id) {
+                let mut new_data = poll.data.clone();
+                for key in &["oneOf", "anyOf", "endTime", "closed", "votersCount"] {
+                    if remote_poll.get(key).is_some() {
+                        new_data["object"][key] = remote_poll[key].clone();
+                    }
+                }
+
+                match update_activity_data(db_connection, poll.id, &new_data) {
+                    Ok(updated_poll) => return updated_poll,
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+        }
+        Err(_) => eprintln!("Unable to refresh poll: {}", object_id),
+    }
+    poll.clone()
+}
+
+/// Counts the votes of a local poll and stores the results in the poll itself
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `poll`          -     &Activity | The internal `Create` activity of a local poll
+pub fn tally(db_connection: &PgConnection, poll: &Activity) -> Activity {
+    let key = if is_multiple(&poll.data["object"]) {
+        "anyOf"
+    } else {
+        "oneOf"
+    };
+    let votes: Vec<(i64, String)> = poll_votes::table
+        .filter(poll_votes::activity_id.eq(poll.id))
+        .select((poll_votes::actor_id, poll_votes::choice))
+        .load::<(i64, String)>(db_connection)
+        .unwrap_or_else(|_| Vec::new());
+
+    let mut voters: Vec<i64> = votes.iter().map(|vote| vote.0).collect();
+    voters.sort();
+    voters.dedup();
+
+    let mut new_data = poll.data.clone();
+    match new_data["object"][key].as_array_mut() {
+        Some(option_vec) => {
+            for option in option_vec {
+                let name = option["name"].as_str().unwrap_or_default().to_string();
+                let total_items = votes.iter().filter(|vote| vote.1 == name).count();
+
+                option["replies"] = serde_json::json!({
+                    "type": "Collection",
+                    "totalItems": total_items
+                });
+            }
+        }
+        None => (),
+    }
+    new_data["object"]["votersCount"] = serde_json::json!(voters.len());
+
+    match update_activity_data(db_connection, poll.id, &new_data) {
+        Ok(updated_poll) => updated_poll,
+        Err(_) => poll.clone(),
+    }
+}
+
+/// Records the votes of an actor on a poll
+///
+/// # Description
+///
+/// Votes are only recorded if the poll is still open, the actor isn't its author and every
+/// choice is one of the poll's options. Single choice polls only accept one vote per actor.
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `poll`          -     &Activity | The internal `Create` activity of a poll
+/// * `actor`         -        &Actor | The actor who's voting
+/// * `choices`       -  &Vec<String> | The names of the chosen options
+pub fn vote(
+    db_connection: &PgConnection,
+    poll: &Activity,
+    actor: &Actor,
+    choices: &Vec<String>,
+) -> Result<(), &'static str> {
+    let object = &poll.data["object"];
+
+    if !is_question(object) {
+        return Err("Status is not a poll");
+    }
+
+    if is_expired(object) {
+        return Err("The poll has already ended");
+    }
+
+    if poll.actor == actor.actor_uri {
+        return Err("Polls can't be voted on by their author");
+    }
+
+    let poll_options = options(object);
+    if choices.is_empty() || choices.iter().any(|choice| !poll_options.contains(choice)) {
+        return Err("Invalid choice");
+    }
+
+    // The poll is locked until the votes are recorded, so that concurrent requests of an actor
+    // can't choose several options of a single choice poll
+    let recorded = db_connection.transaction::<_, diesel::result::Error, _>(|| {
+        activities::table
+            .find(poll.id)
+            .select(activities::id)
+            .for_update()
+            .first::<i64>(db_connection)?;
+
+        let previous_choices: Vec<String> = votes_for_actor(db_connection, poll.id, actor.id)?
+            .into_iter()
+            .map(|vote| vote.choice)
+            .collect();
+
+        if !is_multiple(object) && (!previous_choices.is_empty() || choices.len() > 1) {
+            return Ok(Err("Only one option can be chosen"));
+        }
+
+        for choice in choices {
+            if !previous_choices.contains(choice) {
+                diesel::insert_into(poll_votes::table)
+                    .values(InsertPollVote {
+                        activity_id: poll.id,
+                        actor_id: actor.id,
+                        choice: choice,
+                    })
+                    .on_conflict_do_nothing()
+                    .execute(db_connection)?;
+            }
+        }
+        Ok(Ok(()))
+    });
+
+    match recorded {
+        Ok(result) => result,
+        Err(_) => Err("Unable to record vote"),
+    }
+}
+
+pub fn votes_for_actor(
+    db_connection: &PgConnection,
+    activity_id: i64,
+    actor_id: i64,
+) -> Result<Vec<PollVote>, diesel::result::Error> {
+    poll_votes::table
+        .filter(poll_votes::activity_id.eq(activity_id))
+        .filter(poll_votes::actor_id.eq(actor_id))
+        .order(poll_votes::id.asc())
+        .load::<PollVote>(db_connection)
+}
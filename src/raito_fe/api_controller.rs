@@ -1,26 +1,289 @@
 use actor;
 use database::PooledConnection;
+use email;
 
+use kibou_api::{self, ImportForm, InviteForm};
 use mastodon_api::{
-    controller, routes, Account, AuthorizationHeader, HomeTimeline, Notification, PublicTimeline,
-    RegistrationForm, Relationship, Status, StatusForm,
+    controller, Account, Conversation, HomeTimeline, List, ListAccountsForm, ListForm,
+    Notification, NotificationsForm, PollVoteForm, PublicTimeline, RegistrationForm, Relationship,
+    Status, StatusForm, UpdateCredentialsForm,
 };
 use oauth;
-use raito_fe::{LoginForm, BYPASS_API, MASTODON_API_BASE_URI};
+use oauth::authorized_application::AuthorizedApplication;
+use raito_fe::{
+    Login, LoginForm, PasswordForm, PasswordResetForm, Registration, TwoFactorForm, BYPASS_API,
+    MASTODON_API_BASE_URI,
+};
+use registration;
 use reqwest::header::{HeaderValue, ACCEPT};
+use rocket::http::Cookies;
 use rocket::request::LenientForm;
+use rocket_contrib::json::Json;
+use two_factor;
 
 pub fn follow(token: &str, id: i64) -> Result<Relationship, ()> {
     if unsafe { BYPASS_API } == &true {
-        match serde_json::from_str(
-            &routes::account_follow(AuthorizationHeader(format!("Bearer: {}", token)), id)
-                .to_string(),
+        controller::follow(token.to_string(), id)
+            .map(Json::into_inner)
+            .map_err(|_| ())
+    } else {
+        return Err(());
+    }
+}
+
+pub fn conversations(
+    pooled_connection: &PooledConnection,
+    token: &str,
+) -> Result<Vec<Conversation>, ()> {
+    if unsafe { BYPASS_API } == &true {
+        controller::conversations(
+            pooled_connection,
+            HomeTimeline {
+                max_id: None,
+                since_id: None,
+                min_id: None,
+                limit: Some(40),
+            },
+            token.to_string(),
+        )
+        .map(|page| page.body.into_inner())
+        .map_err(|_| ())
+    } else {
+        Err(())
+    }
+}
+
+pub fn conversation_read(pooled_connection: &PooledConnection, token: &str, id: i64) {
+    if unsafe { BYPASS_API } == &true {
+        let _ = controller::conversation_read(pooled_connection, token.to_string(), id);
+    }
+}
+
+pub fn authorized_applications(
+    pooled_connection: &PooledConnection,
+    token: &str,
+) -> Result<Vec<AuthorizedApplication>, ()> {
+    if unsafe { BYPASS_API } == &true {
+        kibou_api::authorized_applications(pooled_connection, token.to_string())
+            .map(Json::into_inner)
+            .map_err(|_| ())
+    } else {
+        Err(())
+    }
+}
+
+pub fn authorized_application_revoke(pooled_connection: &PooledConnection, token: &str, id: i64) {
+    if unsafe { BYPASS_API } == &true {
+        let _ = kibou_api::authorized_application_revoke(pooled_connection, token.to_string(), id);
+    }
+}
+
+pub fn email_confirmation(
+    pooled_connection: &PooledConnection,
+    token: &str,
+) -> Result<(), &'static str> {
+    if unsafe { BYPASS_API } == &true {
+        email::confirm(pooled_connection, token)
+    } else {
+        Err("E-mail addresses can't be confirmed in standalone mode")
+    }
+}
+
+pub fn email_preferences(
+    pooled_connection: &PooledConnection,
+    token: &str,
+) -> Result<(bool, bool), ()> {
+    if unsafe { BYPASS_API } == &true {
+        let response = kibou_api::email_preferences(pooled_connection, token.to_string())
+            .map(Json::into_inner)
+            .map_err(|_| ())?;
+        match (
+            response["mentions"].as_bool(),
+            response["follows"].as_bool(),
         ) {
-            Ok(relationship) => Ok(relationship),
-            Err(_) => Err(()),
+            (Some(mentions), Some(follows)) => Ok((mentions, follows)),
+            _ => Err(()),
         }
     } else {
-        return Err(());
+        Err(())
+    }
+}
+
+pub fn email_preferences_update(
+    pooled_connection: &PooledConnection,
+    token: &str,
+    mentions: bool,
+    follows: bool,
+) {
+    if unsafe { BYPASS_API } == &true {
+        let _ = kibou_api::email_preferences_update(
+            pooled_connection,
+            token.to_string(),
+            mentions,
+            follows,
+        );
+    }
+}
+
+pub fn export(
+    pooled_connection: &PooledConnection,
+    token: &str,
+    file: &str,
+) -> Result<Vec<u8>, ()> {
+    if unsafe { BYPASS_API } == &true {
+        kibou_api::export(pooled_connection, token.to_string(), file).map_err(|_| ())
+    } else {
+        Err(())
+    }
+}
+
+pub fn import(pooled_connection: &PooledConnection, token: &str, form: ImportForm) {
+    if unsafe { BYPASS_API } == &true {
+        let _ = kibou_api::import(pooled_connection, token.to_string(), form);
+    }
+}
+
+pub fn invites(
+    pooled_connection: &PooledConnection,
+    token: &str,
+) -> Result<Vec<registration::SerializedInvite>, ()> {
+    if unsafe { BYPASS_API } == &true {
+        kibou_api::invites(pooled_connection, token.to_string())
+            .map(Json::into_inner)
+            .map_err(|_| ())
+    } else {
+        Err(())
+    }
+}
+
+pub fn invite_create(
+    pooled_connection: &PooledConnection,
+    token: &str,
+    form: LenientForm<InviteForm>,
+) {
+    if unsafe { BYPASS_API } == &true {
+        let _ = kibou_api::invite_create(
+            pooled_connection,
+            token.to_string(),
+            form.max_uses,
+            form.expires_in,
+        );
+    }
+}
+
+pub fn invite_delete(pooled_connection: &PooledConnection, token: &str, id: i64) {
+    if unsafe { BYPASS_API } == &true {
+        let _ = kibou_api::invite_delete(pooled_connection, token.to_string(), id);
+    }
+}
+
+pub fn list(pooled_connection: &PooledConnection, token: &str, id: i64) -> Result<List, ()> {
+    if unsafe { BYPASS_API } == &true {
+        controller::list(pooled_connection, token.to_string(), id)
+            .map(Json::into_inner)
+            .map_err(|_| ())
+    } else {
+        Err(())
+    }
+}
+
+pub fn list_accounts(
+    pooled_connection: &PooledConnection,
+    token: &str,
+    id: i64,
+) -> Result<Vec<Account>, ()> {
+    if unsafe { BYPASS_API } == &true {
+        controller::list_accounts(pooled_connection, token.to_string(), id)
+            .map(Json::into_inner)
+            .map_err(|_| ())
+    } else {
+        Err(())
+    }
+}
+
+pub fn list_accounts_add(
+    pooled_connection: &PooledConnection,
+    token: &str,
+    id: i64,
+    account_id: i64,
+) {
+    if unsafe { BYPASS_API } == &true {
+        let _ = controller::list_accounts_add(
+            pooled_connection,
+            token.to_string(),
+            id,
+            ListAccountsForm {
+                account_ids: vec![account_id],
+            },
+        );
+    }
+}
+
+pub fn list_accounts_remove(
+    pooled_connection: &PooledConnection,
+    token: &str,
+    id: i64,
+    account_id: i64,
+) {
+    if unsafe { BYPASS_API } == &true {
+        let _ = controller::list_accounts_remove(
+            pooled_connection,
+            token.to_string(),
+            id,
+            ListAccountsForm {
+                account_ids: vec![account_id],
+            },
+        );
+    }
+}
+
+pub fn list_create(pooled_connection: &PooledConnection, token: &str, form: LenientForm<ListForm>) {
+    if unsafe { BYPASS_API } == &true {
+        let _ = controller::list_create(pooled_connection, token.to_string(), form.into_inner());
+    }
+}
+
+pub fn list_delete(pooled_connection: &PooledConnection, token: &str, id: i64) {
+    if unsafe { BYPASS_API } == &true {
+        let _ = controller::list_delete(pooled_connection, token.to_string(), id);
+    }
+}
+
+pub fn list_timeline(
+    pooled_connection: &PooledConnection,
+    token: &str,
+    id: i64,
+) -> Result<Vec<Status>, ()> {
+    if unsafe { BYPASS_API } == &true {
+        controller::list_timeline(
+            pooled_connection,
+            HomeTimeline {
+                max_id: None,
+                since_id: None,
+                min_id: None,
+                limit: Some(40),
+            },
+            token.to_string(),
+            id,
+        )
+        .map(|page| page.body.into_inner())
+        .map_err(|_| ())
+    } else {
+        Err(())
+    }
+}
+
+pub fn lists(
+    pooled_connection: &PooledConnection,
+    token: &str,
+    account_id: Option<i64>,
+) -> Result<Vec<List>, ()> {
+    if unsafe { BYPASS_API } == &true {
+        controller::lists(pooled_connection, token.to_string(), account_id)
+            .map(Json::into_inner)
+            .map_err(|_| ())
+    } else {
+        Err(())
     }
 }
 
@@ -29,11 +292,72 @@ pub fn notifications(
     token: &str,
 ) -> Result<Vec<Notification>, ()> {
     if unsafe { BYPASS_API } == &true {
-        match serde_json::from_str(
-            &controller::notifications(pooled_connection, token.to_string(), None).to_string(),
+        controller::notifications(
+            pooled_connection,
+            token.to_string(),
+            NotificationsForm {
+                types: vec![],
+                exclude_types: vec![],
+                max_id: None,
+                since_id: None,
+                min_id: None,
+                limit: None,
+            },
+        )
+        .map(|page| page.body.into_inner())
+        .map_err(|_| ())
+    } else {
+        Err(())
+    }
+}
+
+pub fn two_factor(pooled_connection: &PooledConnection, token: &str) -> Result<bool, ()> {
+    if unsafe { BYPASS_API } == &true {
+        let response = kibou_api::two_factor(pooled_connection, token.to_string())
+            .map(Json::into_inner)
+            .map_err(|_| ())?;
+        response["enabled"].as_bool().ok_or(())
+    } else {
+        Err(())
+    }
+}
+
+pub fn two_factor_confirm(
+    pooled_connection: &PooledConnection,
+    token: &str,
+    code: &str,
+) -> Result<Vec<String>, ()> {
+    if unsafe { BYPASS_API } == &true {
+        let response = kibou_api::two_factor_confirm(pooled_connection, token.to_string(), code)
+            .map(Json::into_inner)
+            .map_err(|_| ())?;
+        serde_json::from_value(response["recovery_codes"].clone()).map_err(|_| ())
+    } else {
+        Err(())
+    }
+}
+
+pub fn two_factor_disable(pooled_connection: &PooledConnection, token: &str, code: &str) {
+    if unsafe { BYPASS_API } == &true {
+        let _ = kibou_api::two_factor_disable(pooled_connection, token.to_string(), code);
+    }
+}
+
+// Returns the secret and the otpauth URI of a new enrollment
+pub fn two_factor_enroll(
+    pooled_connection: &PooledConnection,
+    token: &str,
+) -> Result<(String, String), ()> {
+    if unsafe { BYPASS_API } == &true {
+        let response = kibou_api::two_factor_enroll(pooled_connection, token.to_string())
+            .map(Json::into_inner)
+            .map_err(|_| ())?;
+        match (
+            response["secret"].as_str(),
+            response["otpauth_uri"].as_str(),
         ) {
-            Ok(timeline) => Ok(timeline),
-            Err(_) => Err(()),
+            (Some(secret), Some(otpauth_uri)) => Ok((secret.to_string(), otpauth_uri.to_string())),
+            _ => Err(()),
         }
     } else {
         Err(())
@@ -42,24 +366,34 @@ pub fn notifications(
 
 pub fn unfollow(token: &str, id: i64) -> Result<Relationship, ()> {
     if unsafe { BYPASS_API } == &true {
-        match serde_json::from_str(
-            &routes::account_unfollow(AuthorizationHeader(format!("Bearer: {}", token)), id)
-                .to_string(),
-        ) {
-            Ok(relationship) => Ok(relationship),
-            Err(_) => Err(()),
-        }
+        controller::unfollow(token.to_string(), id)
+            .map(Json::into_inner)
+            .map_err(|_| ())
     } else {
         return Err(());
     }
 }
 
+pub fn update_credentials(
+    pooled_connection: &PooledConnection,
+    token: &str,
+    form: UpdateCredentialsForm,
+) -> Result<Account, ()> {
+    if unsafe { BYPASS_API } == &true {
+        controller::update_credentials(pooled_connection, token.to_string(), form)
+            .map(Json::into_inner)
+            .map_err(|_| ())
+    } else {
+        Err(())
+    }
+}
+
 pub fn get_account(pooled_connection: &PooledConnection, id: &str) -> Result<Account, ()> {
     if unsafe { BYPASS_API } == &true {
-        match serde_json::from_str(
-            &controller::account(pooled_connection, id.parse::<i64>().unwrap()).to_string(),
-        ) {
-            Ok(account) => Ok(account),
+        match id.parse::<i64>() {
+            Ok(id) => controller::account(pooled_connection, id)
+                .map(Json::into_inner)
+                .map_err(|_| ()),
             Err(_) => Err(()),
         }
     } else {
@@ -77,12 +411,16 @@ pub fn get_account(pooled_connection: &PooledConnection, id: &str) -> Result<Acc
     }
 }
 
-pub fn get_status(pooled_connection: &PooledConnection, id: String) -> Result<Status, ()> {
+pub fn get_status(
+    pooled_connection: &PooledConnection,
+    id: String,
+    token: Option<&str>,
+) -> Result<Status, ()> {
     if unsafe { BYPASS_API } == &true {
-        match serde_json::from_str(
-            &controller::status_by_id(pooled_connection, id.parse::<i64>().unwrap()).to_string(),
-        ) {
-            Ok(status) => Ok(status),
+        match id.parse::<i64>() {
+            Ok(id) => controller::status_by_id(pooled_connection, id, token.map(String::from))
+                .map(Json::into_inner)
+                .map_err(|_| ()),
             Err(_) => Err(()),
         }
     } else {
@@ -103,13 +441,15 @@ pub fn get_status(pooled_connection: &PooledConnection, id: String) -> Result<St
 pub fn get_status_context(
     pooled_connection: &PooledConnection,
     id: String,
+    token: Option<&str>,
 ) -> Result<serde_json::Value, ()> {
     if unsafe { BYPASS_API } == &true {
-        match serde_json::from_str(
-            &controller::context_json_for_id(pooled_connection, id.parse::<i64>().unwrap())
-                .to_string(),
-        ) {
-            Ok(context) => Ok(context),
+        match id.parse::<i64>() {
+            Ok(id) => {
+                controller::context_json_for_id(pooled_connection, id, token.map(String::from))
+                    .map(Json::into_inner)
+                    .map_err(|_| ())
+            }
             Err(_) => Err(()),
         }
     } else {
@@ -127,24 +467,53 @@ pub fn get_status_context(
     }
 }
 
-pub fn home_timeline(pooled_connection: &PooledConnection, token: &str) -> Result<Vec<Status>, ()> {
+pub fn get_status_favourited_by(
+    pooled_connection: &PooledConnection,
+    id: i64,
+    token: Option<&str>,
+) -> Result<Vec<Account>, ()> {
     if unsafe { BYPASS_API } == &true {
-        match serde_json::from_str(
-            &controller::home_timeline(
-                &pooled_connection,
-                HomeTimeline {
-                    max_id: None,
-                    since_id: None,
-                    min_id: None,
-                    limit: Some(40),
-                },
-                token.to_string(),
-            )
-            .to_string(),
+        match controller::favourited_by(
+            pooled_connection,
+            id,
+            token.map(String::from),
+            None,
+            None,
+            None,
+            None,
         ) {
-            Ok(timeline) => Ok(timeline),
+            Ok(accounts) => Ok(accounts.body.into_inner()),
+            Err(_) => Err(()),
+        }
+    } else {
+        match fetch_object(&format!(
+            "{base}/api/v1/statuses/{id}/favourited_by",
+            base = unsafe { MASTODON_API_BASE_URI },
+            id = id
+        )) {
+            Ok(accounts) => match serde_json::from_str(&accounts) {
+                Ok(serialized_accounts) => Ok(serialized_accounts),
+                Err(_) => Err(()),
+            },
             Err(_) => Err(()),
         }
+    }
+}
+
+pub fn home_timeline(pooled_connection: &PooledConnection, token: &str) -> Result<Vec<Status>, ()> {
+    if unsafe { BYPASS_API } == &true {
+        controller::home_timeline(
+            &pooled_connection,
+            HomeTimeline {
+                max_id: None,
+                since_id: None,
+                min_id: None,
+                limit: Some(40),
+            },
+            token.to_string(),
+        )
+        .map(|page| page.body.into_inner())
+        .map_err(|_| ())
     } else {
         Err(())
     }
@@ -153,25 +522,23 @@ pub fn home_timeline(pooled_connection: &PooledConnection, token: &str) -> Resul
 pub fn get_public_timeline(
     pooled_connection: &PooledConnection,
     local: bool,
+    token: Option<&str>,
 ) -> Result<Vec<Status>, ()> {
     if unsafe { BYPASS_API } == &true {
-        match serde_json::from_str(
-            &controller::public_timeline(
-                &pooled_connection,
-                PublicTimeline {
-                    local: Some(local),
-                    only_media: None,
-                    max_id: None,
-                    since_id: None,
-                    min_id: None,
-                    limit: Some(40),
-                },
-            )
-            .to_string(),
-        ) {
-            Ok(timeline) => Ok(timeline),
-            Err(_) => Err(()),
-        }
+        controller::public_timeline(
+            &pooled_connection,
+            PublicTimeline {
+                local: Some(local),
+                only_media: None,
+                max_id: None,
+                since_id: None,
+                min_id: None,
+                limit: Some(40),
+            },
+            token.map(String::from),
+        )
+        .map(|page| page.body.into_inner())
+        .map_err(|_| ())
     } else {
         match fetch_object(&format!(
             "{base}/api/v1/timelines/public?local={local}&limit=40",
@@ -190,20 +557,22 @@ pub fn get_public_timeline(
 pub fn get_user_timeline(
     pooled_connection: &PooledConnection,
     id: String,
+    token: Option<&str>,
 ) -> Result<Vec<Status>, ()> {
     if unsafe { BYPASS_API } == &true {
-        match serde_json::from_str(
-            &controller::account_statuses_by_id(
+        match id.parse::<i64>() {
+            Ok(id) => controller::account_statuses_by_id(
                 pooled_connection,
-                id.parse::<i64>().unwrap(),
+                id,
+                false,
                 None,
                 None,
                 None,
                 Some(40),
+                token.map(String::from),
             )
-            .to_string(),
-        ) {
-            Ok(statuses) => Ok(statuses),
+            .map(|page| page.body.into_inner())
+            .map_err(|_| ()),
             Err(_) => Err(()),
         }
     } else {
@@ -227,53 +596,129 @@ pub fn get_user_timeline(
 // is run in standalone.
 //
 // TODO: Rework
-pub fn login(pooled_connection: &PooledConnection, form: LenientForm<LoginForm>) -> Option<String> {
+pub fn login(pooled_connection: &PooledConnection, form: LenientForm<LoginForm>) -> Login {
     if unsafe { BYPASS_API } == &true {
         let form = form.into_inner();
         match actor::authorize(pooled_connection, &form.username, form.password) {
-            Ok(true) => Some(oauth::token::create(&form.username).access_token),
-            Ok(false) => None,
-            Err(_) => None,
+            Ok(true) => {
+                if !registration::is_approved(pooled_connection, &form.username) {
+                    Login::Pending
+                } else if !email::is_confirmed(pooled_connection, &form.username) {
+                    Login::Unconfirmed
+                } else if two_factor::is_required(pooled_connection, &form.username) {
+                    Login::TwoFactorRequired(form.username)
+                } else {
+                    Login::Authenticated(
+                        oauth::token::create(&form.username, 0, oauth::scope::RAITO_SCOPES)
+                            .access_token,
+                    )
+                }
+            }
+            Ok(false) => Login::Failed,
+            Err(_) => Login::Failed,
         }
+    } else {
+        Login::Failed
+    }
+}
+
+pub fn login_two_factor(
+    pooled_connection: &PooledConnection,
+    cookies: &mut Cookies,
+    form: LenientForm<TwoFactorForm>,
+) -> Option<String> {
+    if unsafe { BYPASS_API } == &true {
+        two_factor::verify_pending(pooled_connection, cookies, &form.code).map(|username| {
+            oauth::token::create(&username, 0, oauth::scope::RAITO_SCOPES).access_token
+        })
     } else {
         None
     }
 }
 
+pub fn password_reset(
+    pooled_connection: &PooledConnection,
+    form: LenientForm<PasswordForm>,
+) -> Result<(), &'static str> {
+    if unsafe { BYPASS_API } == &true {
+        email::reset_password(pooled_connection, &form.token, &form.password)
+    } else {
+        Err("Passwords can't be reset in standalone mode")
+    }
+}
+
+pub fn password_reset_request(
+    pooled_connection: &PooledConnection,
+    form: LenientForm<PasswordResetForm>,
+) {
+    if unsafe { BYPASS_API } == &true {
+        email::request_password_reset(pooled_connection, &form.email);
+    }
+}
+
+pub fn poll_vote(
+    pooled_connection: &PooledConnection,
+    token: &str,
+    id: i64,
+    form: LenientForm<PollVoteForm>,
+) {
+    if unsafe { BYPASS_API } == &true {
+        let _ = controller::poll_vote(pooled_connection, token.to_string(), id, form.into_inner());
+    }
+}
+
 pub fn post_status(
     pooled_connection: &PooledConnection,
     form: LenientForm<StatusForm>,
     token: &str,
 ) {
     if unsafe { BYPASS_API } == &true {
-        controller::status_post(pooled_connection, form.into_inner(), token.to_string());
+        let _ = controller::status_post(pooled_connection, form.into_inner(), token.to_string());
     }
 }
 
 // TODO: Rework
 // (same as in line 129)
-pub fn register(form: LenientForm<RegistrationForm>) -> Option<String> {
+pub fn register(form: LenientForm<RegistrationForm>) -> Registration {
     if unsafe { BYPASS_API } == &true {
+        let response =
+            match controller::account_create(&form.into_inner(), 0, oauth::scope::RAITO_SCOPES) {
+                Ok(response) => response.into_inner(),
+                Err(e) => return Registration::Failed(e.message().to_string()),
+            };
+
+        let approval_required = response["approval_required"].as_bool() == Some(true);
+        let confirmation_required = response["confirmation_required"].as_bool() == Some(true);
+        if approval_required || confirmation_required {
+            return Registration::Pending {
+                approval_required: approval_required,
+                confirmation_required: confirmation_required,
+            };
+        }
+
         let token: Result<oauth::token::Token, serde_json::Error> =
-            serde_json::from_value(controller::account_create(&form.into_inner()).into());
+            serde_json::from_value(response);
         match token {
-            Ok(token) => Some(token.access_token),
-            Err(_) => None,
+            Ok(token) => Registration::Authenticated(token.access_token),
+            Err(_) => Registration::Failed(String::from("Account could not be created")),
         }
     } else {
-        None
+        Registration::Failed(String::from(
+            "Registrations aren't available in standalone mode",
+        ))
     }
 }
 
-pub fn relationships_by_token(token: &str, ids: Vec<i64>) -> Option<Vec<Relationship>> {
+pub fn relationships_by_token(
+    pooled_connection: &PooledConnection,
+    token: &str,
+    ids: Vec<i64>,
+) -> Option<Vec<Relationship>> {
     if unsafe { BYPASS_API } == &true {
-        match serde_json::from_value(controller::relationships(token, ids).into()) {
-            Ok(relationships) => Some(relationships),
-            Err(_) => None,
-        }
+        controller::relationships(pooled_connection, token.to_string(), ids)
+            .map(Json::into_inner)
+            .ok()
     } else {
-        // As of now there is no Mastodon-API endpoint for relationships due to limitations of
-        // Rocket
         None
     }
 }
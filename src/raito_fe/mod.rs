@@ -19,12 +19,52 @@ pub struct Configuration {
     pub token: Option<String>,
 }
 
+#[derive(FromForm)]
+pub struct ListMemberForm {
+    pub list_id: i64,
+}
+
 #[derive(FromForm)]
 pub struct LoginForm {
     pub username: String,
     pub password: String,
 }
 
+// Actors who enabled two-factor authentication enter their code after their password
+pub enum Login {
+    Authenticated(String),
+    TwoFactorRequired(String),
+    // The account wasn't approved by an admin yet
+    Pending,
+    Unconfirmed,
+    Failed,
+}
+
+#[derive(FromForm)]
+pub struct PasswordForm {
+    pub token: String,
+    pub password: String,
+}
+
+#[derive(FromForm)]
+pub struct PasswordResetForm {
+    pub email: String,
+}
+
+pub enum Registration {
+    Authenticated(String),
+    Pending {
+        approval_required: bool,
+        confirmation_required: bool,
+    },
+    Failed(String),
+}
+
+#[derive(FromForm)]
+pub struct TwoFactorForm {
+    pub code: String,
+}
+
 impl<'a, 'r> FromRequest<'a, 'r> for Configuration {
     type Error = ();
 
@@ -50,17 +90,12 @@ impl<'a, 'r> FromRequest<'a, 'r> for Configuration {
 
         match request.cookies().get_private("oauth_token") {
             Some(oauth_token) => {
-                let json_account: Result<mastodon_api::Account, serde_json::Error> =
-                    serde_json::from_value(
-                        mastodon_api::controller::account_by_oauth_token(
-                            &PooledConnection(database::POOL.get().unwrap()),
-                            oauth_token.value().to_string(),
-                        )
-                        .into(),
-                    );
-                match json_account {
+                match mastodon_api::controller::account_by_oauth_token(
+                    &PooledConnection(database::POOL.get().unwrap()),
+                    oauth_token.value().to_string(),
+                ) {
                     Ok(mastodon_api_account) => {
-                        account = Some(mastodon_api_account);
+                        account = Some(mastodon_api_account.into_inner());
                         token = Some(oauth_token.value().to_string());
                     }
                     Err(_) => (),
@@ -117,19 +152,50 @@ pub fn get_routes() -> Vec<rocket::Route> {
         routes::about,
         routes::account,
         routes::account_follow,
+        routes::account_list_add,
         routes::account_unfollow,
         routes::actor,
+        routes::direct_timeline,
+        routes::direct_timeline_read,
+        routes::email_confirmation,
         routes::global_timeline,
         routes::home_timeline,
         routes::index,
+        routes::list_create,
+        routes::list_delete,
+        routes::list_member_remove,
+        routes::list_timeline,
+        routes::lists,
         routes::login,
         routes::login_post,
+        routes::login_two_factor,
         routes::object,
+        routes::password,
+        routes::password_edit,
+        routes::password_post,
+        routes::password_update,
+        routes::poll_vote,
         routes::public_timeline,
         routes::register,
+        routes::register_form,
         routes::settings,
+        routes::settings_application_revoke,
+        routes::settings_applications,
+        routes::settings_email,
+        routes::settings_email_post,
+        routes::settings_export,
+        routes::settings_import,
+        routes::settings_invite_create,
+        routes::settings_invite_delete,
+        routes::settings_invites,
+        routes::settings_post,
+        routes::settings_two_factor,
+        routes::settings_two_factor_confirm,
+        routes::settings_two_factor_disable,
+        routes::settings_two_factor_enroll,
         routes::status_compose,
         routes::status_draft,
+        routes::status_favourited_by,
         routes::view_status
     ]
 }
@@ -3,16 +3,28 @@ use actor;
 use chrono::prelude::*;
 use database;
 use database::PooledConnection;
+use email::MIN_PASSWORD_CHARS;
 use env;
-use mastodon_api::{Notification, RegistrationForm, Status, StatusForm};
-use raito_fe::{self, Configuration, LoginForm};
-use rocket::http::{Cookie, Cookies};
+use html;
+use kibou_api::{EmailPreferencesForm, ImportForm, InviteForm};
+use mastodon_api::{
+    Emoji, EmojiReaction, ListForm, Notification, Poll, PollVoteForm, RegistrationForm, Status,
+    StatusForm, UpdateCredentialsForm,
+};
+use raito_fe::{
+    self, Configuration, ListMemberForm, Login, LoginForm, PasswordForm, PasswordResetForm,
+    Registration, TwoFactorForm,
+};
+use registration;
+use rocket::http::{ContentType, Cookie, Cookies};
 use rocket::request::LenientForm;
+use rocket::response::content::Content;
 use rocket::response::Redirect;
 use rocket::Rocket;
 use rocket_contrib::templates::Template;
 use std::collections::HashMap;
 use std::fs;
+use two_factor;
 
 pub fn about(configuration: &Configuration) -> Template {
     let mut context = HashMap::<String, String>::new();
@@ -46,13 +58,16 @@ pub fn account_by_local_id(
                         match &configuration.token {
                             Some(token) => {
                                 match raito_fe::api_controller::relationships_by_token(
+                                    pooled_connection,
                                     &token,
                                     vec![id.parse::<i64>().unwrap()],
-                                ) {
+                                )
+                                .and_then(|relationships| relationships.into_iter().next())
+                                {
                                     Some(relationship) => {
                                         context.insert(
                                             String::from("account_relationship_following"),
-                                            relationship[0].following.to_string(),
+                                            relationship.following.to_string(),
                                         );
                                     }
                                     None => {
@@ -85,6 +100,10 @@ pub fn account_by_local_id(
                 }
             }
 
+            context.insert(
+                String::from("account_lists"),
+                prepare_list_selection(pooled_connection, configuration, &account.id),
+            );
             context.insert(String::from("account_acct"), account.acct);
             context.insert(String::from("account_display_name"), account.display_name);
             context.insert(String::from("account_avatar"), account.avatar);
@@ -150,6 +169,26 @@ pub fn account_follow(
     }
 }
 
+pub fn account_list_add(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    id: i64,
+    form: LenientForm<ListMemberForm>,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            raito_fe::api_controller::list_accounts_add(
+                pooled_connection,
+                &token,
+                form.list_id,
+                id,
+            );
+            return Redirect::to(format!("/account/{}", id));
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
 pub fn compose(
     pooled_connection: &PooledConnection,
     configuration: &Configuration,
@@ -159,6 +198,7 @@ pub fn compose(
     context.extend(configuration.context.clone());
 
     if configuration.account.is_none() {
+        registration_context(&mut context);
         return Template::render("raito_fe/infoscreen", context);
     } else {
         match in_reply_to {
@@ -167,6 +207,7 @@ pub fn compose(
                 match raito_fe::api_controller::get_status(
                     pooled_connection,
                     head_status_id.to_string(),
+                    configuration.token.as_ref().map(String::as_str),
                 ) {
                     Ok(status) => context.insert(
                         String::from("head_status"),
@@ -271,14 +312,22 @@ pub fn conversation(
 
     context.extend(configuration.context.clone());
 
-    match raito_fe::api_controller::get_status(pooled_connection, id.clone()) {
+    match raito_fe::api_controller::get_status(
+        pooled_connection,
+        id.clone(),
+        configuration.token.as_ref().map(String::as_str),
+    ) {
         Ok(status) => {
             let mut renderered_statuses: Vec<String> = vec![];
             let mut parent_statuses: Vec<Status> = vec![];
             let mut child_statuses: Vec<Status> = vec![];
             let mut timeline_parameters = HashMap::<String, String>::new();
 
-            match raito_fe::api_controller::get_status_context(pooled_connection, id) {
+            match raito_fe::api_controller::get_status_context(
+                pooled_connection,
+                id,
+                configuration.token.as_ref().map(String::as_str),
+            ) {
                 Ok(context) => {
                     parent_statuses =
                         serde_json::from_value(context["ancestors"].to_owned()).unwrap();
@@ -342,6 +391,81 @@ pub fn conversation_by_uri(
     }
 }
 
+pub fn direct_messages(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    let rocket_renderer = rocket::ignite().attach(Template::fairing());
+    let mut timeline_parameters = HashMap::<String, String>::new();
+
+    context.extend(configuration.context.clone());
+
+    match &configuration.token {
+        Some(token) => match raito_fe::api_controller::conversations(pooled_connection, &token) {
+            Ok(conversations) => {
+                let mut renderered_statuses: Vec<String> = vec![];
+                for conversation in conversations {
+                    match conversation.last_status {
+                        Some(status) => {
+                            // Conversations are only marked as read on request, not by
+                            // merely loading this page
+                            if conversation.unread {
+                                renderered_statuses.push(format!(
+                                    "<div class=\"conversation-unread\">New\
+                                     <form method=\"post\" action=\"/timeline/direct/{id}/read\">\
+                                     <button type=\"submit\">Mark as read</button></form></div>",
+                                    id = html::escape(&conversation.id)
+                                ));
+                            }
+                            renderered_statuses.push(raw_status(
+                                configuration,
+                                status,
+                                &rocket_renderer,
+                            ));
+                        }
+                        None => (),
+                    }
+                }
+
+                context.insert(
+                    String::from("timeline_name"),
+                    String::from("Direct Messages"),
+                );
+                timeline_parameters.extend(configuration.context.clone());
+                timeline_parameters.insert(String::from("statuses"), renderered_statuses.join(""));
+                context.insert(
+                    String::from("timeline"),
+                    Template::show(
+                        &rocket_renderer,
+                        "raito_fe/components/timeline",
+                        timeline_parameters,
+                    )
+                    .unwrap(),
+                );
+
+                return Template::render("raito_fe/timeline_view", context);
+            }
+            Err(_) => Template::render("raito_fe/index", context),
+        },
+        None => return public_timeline(pooled_connection, configuration, false),
+    }
+}
+
+pub fn direct_messages_read(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    id: i64,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            raito_fe::api_controller::conversation_read(pooled_connection, &token, id);
+            return Redirect::to("/timeline/direct");
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
 pub fn home_timeline(
     pooled_connection: &PooledConnection,
     configuration: &Configuration,
@@ -416,11 +540,147 @@ pub fn index(pooled_connection: &PooledConnection, configuration: &Configuration
         Some(_account) => return home_timeline(pooled_connection, configuration),
         None => {
             context.extend(configuration.context.clone());
+            registration_context(&mut context);
             return Template::render("raito_fe/infoscreen", context);
         }
     }
 }
 
+pub fn list_create(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    form: LenientForm<ListForm>,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            raito_fe::api_controller::list_create(pooled_connection, &token, form);
+            return Redirect::to("/lists");
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
+pub fn list_delete(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    id: i64,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            raito_fe::api_controller::list_delete(pooled_connection, &token, id);
+            return Redirect::to("/lists");
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
+pub fn list_member_remove(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    id: i64,
+    account_id: i64,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            raito_fe::api_controller::list_accounts_remove(
+                pooled_connection,
+                &token,
+                id,
+                account_id,
+            );
+            return Redirect::to(format!("/lists/{}", id));
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
+pub fn list_timeline(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    id: i64,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    let rocket_renderer = rocket::ignite().attach(Template::fairing());
+    let mut timeline_parameters = HashMap::<String, String>::new();
+
+    context.extend(configuration.context.clone());
+
+    match &configuration.token {
+        Some(token) => match raito_fe::api_controller::list(pooled_connection, &token, id) {
+            Ok(list) => {
+                let mut rendered_members: Vec<String> = vec![];
+                for account in
+                    raito_fe::api_controller::list_accounts(pooled_connection, &token, id)
+                        .unwrap_or_else(|_| Vec::new())
+                {
+                    rendered_members.push(format!(
+                        "<li><a href=\"/account/{account_id}\">{acct}</a>\
+                         <form method=\"post\" action=\"/lists/{list_id}/remove/{account_id}\">\
+                         <button type=\"submit\">Remove</button></form></li>",
+                        account_id = account.id,
+                        acct = html::escape(&account.acct),
+                        list_id = list.id
+                    ));
+                }
+
+                let mut renderered_statuses: Vec<String> = vec![];
+                for status in raito_fe::api_controller::list_timeline(pooled_connection, &token, id)
+                    .unwrap_or_else(|_| Vec::new())
+                {
+                    renderered_statuses.push(raw_status(configuration, status, &rocket_renderer));
+                }
+
+                context.insert(String::from("list_id"), list.id);
+                context.insert(String::from("list_title"), list.title);
+                context.insert(String::from("list_members"), rendered_members.join(""));
+                context.insert(String::from("timeline_name"), String::from("Lists"));
+                timeline_parameters.extend(configuration.context.clone());
+                timeline_parameters.insert(String::from("statuses"), renderered_statuses.join(""));
+                context.insert(
+                    String::from("timeline"),
+                    Template::show(
+                        &rocket_renderer,
+                        "raito_fe/components/timeline",
+                        timeline_parameters,
+                    )
+                    .unwrap(),
+                );
+
+                return Template::render("raito_fe/list", context);
+            }
+            Err(_) => return lists(pooled_connection, configuration),
+        },
+        None => return public_timeline(pooled_connection, configuration, false),
+    }
+}
+
+pub fn lists(pooled_connection: &PooledConnection, configuration: &Configuration) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+
+    match &configuration.token {
+        Some(token) => {
+            let mut rendered_lists: Vec<String> = vec![];
+            for list in raito_fe::api_controller::lists(pooled_connection, &token, None)
+                .unwrap_or_else(|_| Vec::new())
+            {
+                rendered_lists.push(format!(
+                    "<li><a href=\"/lists/{id}\">{title}</a>\
+                     <form method=\"post\" action=\"/lists/{id}/delete\">\
+                     <button type=\"submit\">Delete</button></form></li>",
+                    id = list.id,
+                    title = html::escape(&list.title)
+                ));
+            }
+
+            context.insert(String::from("lists"), rendered_lists.join(""));
+            context.insert(String::from("timeline_name"), String::from("Lists"));
+            return Template::render("raito_fe/lists", context);
+        }
+        None => return public_timeline(pooled_connection, configuration, false),
+    }
+}
+
 pub fn login(pooled_connection: &PooledConnection, configuration: &Configuration) -> Template {
     let mut context = HashMap::<String, String>::new();
     context.extend(configuration.context.clone());
@@ -432,6 +692,24 @@ pub fn login(pooled_connection: &PooledConnection, configuration: &Configuration
     }
 }
 
+pub fn email_confirmation(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    token: &str,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+
+    match raito_fe::api_controller::email_confirmation(pooled_connection, token) {
+        Ok(()) => context.insert(
+            String::from("notice"),
+            String::from("Your e-mail address was confirmed, you can log in now."),
+        ),
+        Err(e) => context.insert(String::from("error"), e.to_string()),
+    };
+    return Template::render("raito_fe/login", context);
+}
+
 pub fn login_post(
     pooled_connection: &PooledConnection,
     configuration: &Configuration,
@@ -443,17 +721,128 @@ pub fn login_post(
 
     if configuration.account.is_none() {
         match raito_fe::api_controller::login(pooled_connection, form) {
+            Login::Authenticated(token) => {
+                cookies.add_private(Cookie::new("oauth_token", token));
+                return Ok(Redirect::to("/timeline/home"));
+            }
+            Login::TwoFactorRequired(username) => {
+                two_factor::set_pending(&mut cookies, &username);
+                context.insert(String::from("two_factor"), true.to_string());
+                Err(Template::render("raito_fe/login", context))
+            }
+            Login::Pending => {
+                context.insert(
+                    String::from("error"),
+                    String::from("Your account is waiting for approval by an admin."),
+                );
+                Err(Template::render("raito_fe/login", context))
+            }
+            Login::Unconfirmed => {
+                context.insert(
+                    String::from("error"),
+                    String::from("Please confirm your e-mail address first."),
+                );
+                Err(Template::render("raito_fe/login", context))
+            }
+            Login::Failed => Err(Template::render("raito_fe/login", context)),
+        }
+    } else {
+        return Ok(Redirect::to("/timeline/home"));
+    }
+}
+
+pub fn login_two_factor(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    mut cookies: Cookies,
+    form: LenientForm<TwoFactorForm>,
+) -> Result<Redirect, Template> {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+
+    if configuration.account.is_none() {
+        match raito_fe::api_controller::login_two_factor(pooled_connection, &mut cookies, form) {
             Some(token) => {
                 cookies.add_private(Cookie::new("oauth_token", token));
                 return Ok(Redirect::to("/timeline/home"));
             }
-            None => Err(Template::render("raito_fe/login", context)),
+            None => {
+                // A wrong code doesn't require the password again, as long as the login is pending
+                let pending = two_factor::pending_actor(&mut cookies).is_some();
+                context.insert(String::from("two_factor"), pending.to_string());
+                Err(Template::render("raito_fe/login", context))
+            }
         }
     } else {
         return Ok(Redirect::to("/timeline/home"));
     }
 }
 
+pub fn password(configuration: &Configuration) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+    context.insert(String::from("password_state"), String::from("request"));
+
+    return Template::render("raito_fe/password", context);
+}
+
+pub fn password_edit(configuration: &Configuration, token: String) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+    context.insert(String::from("password_state"), String::from("edit"));
+    context.insert(String::from("token"), token);
+    context.insert(
+        String::from("min_password_chars"),
+        MIN_PASSWORD_CHARS.to_string(),
+    );
+
+    return Template::render("raito_fe/password", context);
+}
+
+pub fn password_post(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    form: LenientForm<PasswordResetForm>,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+
+    // The same page is shown whether or not the address belongs to an account
+    raito_fe::api_controller::password_reset_request(pooled_connection, form);
+    context.insert(String::from("password_state"), String::from("requested"));
+    return Template::render("raito_fe/password", context);
+}
+
+pub fn password_update(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    form: LenientForm<PasswordForm>,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+    let token = form.token.clone();
+
+    match raito_fe::api_controller::password_reset(pooled_connection, form) {
+        Ok(()) => {
+            context.insert(
+                String::from("notice"),
+                String::from("Your password was changed, you can log in now."),
+            );
+            return Template::render("raito_fe/login", context);
+        }
+        Err(e) => {
+            context.insert(String::from("password_state"), String::from("edit"));
+            context.insert(String::from("token"), token);
+            context.insert(
+                String::from("min_password_chars"),
+                MIN_PASSWORD_CHARS.to_string(),
+            );
+            context.insert(String::from("error"), e.to_string());
+            return Template::render("raito_fe/password", context);
+        }
+    }
+}
+
 pub fn public_timeline(
     pooled_connection: &PooledConnection,
     configuration: &Configuration,
@@ -496,7 +885,11 @@ pub fn public_timeline(
         );
         return Template::render("raito_fe/timeline_view", context);
     } else {
-        match raito_fe::api_controller::get_public_timeline(pooled_connection, local) {
+        match raito_fe::api_controller::get_public_timeline(
+            pooled_connection,
+            local,
+            configuration.token.as_ref().map(String::as_str),
+        ) {
             Ok(statuses) => {
                 let mut renderered_statuses: Vec<String> = vec![];
                 for status in statuses {
@@ -520,6 +913,21 @@ pub fn public_timeline(
     }
 }
 
+pub fn poll_vote(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    id: i64,
+    form: LenientForm<PollVoteForm>,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            raito_fe::api_controller::poll_vote(pooled_connection, &token, id, form);
+            return Redirect::to(format!("/status/{}", id));
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
 pub fn raw_notification(rocket: &Rocket, notification: Notification) -> String {
     let mut context = HashMap::<String, String>::new();
     let mut notification_type = String::new();
@@ -528,8 +936,14 @@ pub fn raw_notification(rocket: &Rocket, notification: Notification) -> String {
     notification_type = match notification._type.as_str() {
         "mention" => String::from("has mentioned you"),
         "follow" => String::from("has followed you"),
+        "follow_request" => String::from("has requested to follow you"),
+        "poll" => String::from("ran a poll which has ended"),
         "favourite" => String::from("has favourited your status"),
         "reblog" => String::from("has shared your status"),
+        "pleroma:emoji_reaction" => format!(
+            "has reacted with {} to your status",
+            notification.emoji.clone().unwrap_or_default()
+        ),
         _ => String::from(""),
     };
     status_content = match notification.status {
@@ -553,6 +967,27 @@ pub fn raw_status(configuration: &Configuration, status: Status, rocket: &Rocket
     return Template::show(rocket, "raito_fe/components/status", context).unwrap();
 }
 
+// Invite links lead to the registration form with the code filled in
+pub fn register(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    invite: Option<String>,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+
+    match &configuration.account {
+        Some(_account) => return home_timeline(pooled_connection, configuration),
+        None => {
+            context.extend(configuration.context.clone());
+            registration_context(&mut context);
+            if let Some(invite_code) = invite {
+                context.insert(String::from("invite_code"), invite_code);
+            }
+            return Template::render("raito_fe/infoscreen", context);
+        }
+    }
+}
+
 pub fn register_post(
     configuration: &Configuration,
     mut cookies: Cookies,
@@ -563,11 +998,27 @@ pub fn register_post(
 
     if configuration.account.is_none() {
         match raito_fe::api_controller::register(form) {
-            Some(token) => {
+            Registration::Authenticated(token) => {
                 cookies.add_private(Cookie::new("oauth_token", token));
                 return Ok(Redirect::to("/timeline/home"));
             }
-            None => return Err(Template::render("raito_fe/infoscreen", context)),
+            Registration::Pending {
+                approval_required,
+                confirmation_required,
+            } => {
+                let notice = match (approval_required, confirmation_required) {
+                    (true, true) => "Your account will be reviewed by an admin. Meanwhile, please confirm your e-mail address with the link which was sent to it.",
+                    (true, false) => "Your account will be reviewed by an admin, you can log in once it's approved.",
+                    _ => "A confirmation link was sent to your e-mail address.",
+                };
+                context.insert(String::from("notice"), String::from(notice));
+                return Err(Template::render("raito_fe/login", context));
+            }
+            Registration::Failed(error) => {
+                registration_context(&mut context);
+                context.insert(String::from("error"), error);
+                return Err(Template::render("raito_fe/infoscreen", context));
+            }
         }
     } else {
         return Ok(Redirect::to("/timeline/home"));
@@ -577,9 +1028,376 @@ pub fn register_post(
 pub fn settings(configuration: &Configuration) -> Template {
     let mut context = HashMap::<String, String>::new();
     context.extend(configuration.context.clone());
+
+    if let Some(account) = &configuration.account {
+        context.insert("display_name".to_string(), account.display_name.clone());
+        context.insert("locked".to_string(), account.locked.to_string());
+        context.insert("bot".to_string(), account.bot.to_string());
+        context.insert(
+            "discoverable".to_string(),
+            account.discoverable.unwrap_or(false).to_string(),
+        );
+
+        if let Some(source) = &account.source {
+            let fields = source.fields.clone().unwrap_or_default();
+            context.insert("note".to_string(), source.note.clone());
+
+            for index in 0..actor::MAX_PROFILE_FIELDS {
+                let (name, value) = match fields.get(index) {
+                    Some(field) => (field.name.clone(), field.value.clone()),
+                    None => (String::new(), String::new()),
+                };
+                context.insert(format!("field_{}_name", index), name);
+                context.insert(format!("field_{}_value", index), value);
+            }
+        }
+    }
     return Template::render("raito_fe/settings", context);
 }
 
+pub fn settings_applications(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+
+    match &configuration.token {
+        Some(token) => {
+            let mut rendered_applications: Vec<String> = vec![];
+            for application in
+                raito_fe::api_controller::authorized_applications(pooled_connection, &token)
+                    .unwrap_or_else(|_| Vec::new())
+            {
+                rendered_applications.push(format!(
+                    "<li><b>{name}</b>\
+                     <p>Permissions: {scopes}</p>\
+                     <p>Authorized: {created_at}</p>\
+                     <p>Last used: {last_used_at}</p>\
+                     <form method=\"post\" action=\"/settings/applications/{id}/revoke\">\
+                     <button type=\"submit\">Revoke access</button></form></li>",
+                    id = application.id,
+                    name = html::escape(
+                        &application
+                            .name
+                            .unwrap_or_else(|| String::from("Unnamed application"))
+                    ),
+                    scopes = application.scopes.join(", "),
+                    created_at = application.created_at,
+                    last_used_at = application
+                        .last_used_at
+                        .unwrap_or_else(|| String::from("Never"))
+                ));
+            }
+
+            context.insert(String::from("applications"), rendered_applications.join(""));
+            return Template::render("raito_fe/authorized_applications", context);
+        }
+        None => return public_timeline(pooled_connection, configuration, false),
+    }
+}
+
+pub fn settings_application_revoke(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    id: i64,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            raito_fe::api_controller::authorized_application_revoke(pooled_connection, &token, id);
+            return Redirect::to("/settings/applications");
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
+pub fn settings_two_factor(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+
+    match &configuration.token {
+        Some(token) => {
+            let state = match raito_fe::api_controller::two_factor(pooled_connection, &token) {
+                Ok(true) => "enabled",
+                _ => "disabled",
+            };
+
+            context.insert(String::from("two_factor_state"), String::from(state));
+            return Template::render("raito_fe/two_factor", context);
+        }
+        None => return public_timeline(pooled_connection, configuration, false),
+    }
+}
+
+pub fn settings_two_factor_confirm(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    form: LenientForm<TwoFactorForm>,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+
+    match &configuration.token {
+        Some(token) => {
+            match raito_fe::api_controller::two_factor_confirm(
+                pooled_connection,
+                &token,
+                &form.code,
+            ) {
+                Ok(recovery_codes) => {
+                    let rendered_recovery_codes: Vec<String> = recovery_codes
+                        .iter()
+                        .map(|recovery_code| format!("<li><code>{}</code></li>", recovery_code))
+                        .collect();
+
+                    context.insert(String::from("two_factor_state"), String::from("confirmed"));
+                    context.insert(
+                        String::from("recovery_codes"),
+                        rendered_recovery_codes.join(""),
+                    );
+                }
+                // The secret isn't shown again, setting up starts over with a new one
+                Err(_) => {
+                    context.insert(String::from("two_factor_state"), String::from("disabled"));
+                    context.insert(
+                        String::from("error"),
+                        String::from("The code was invalid, please try again."),
+                    );
+                }
+            }
+            return Template::render("raito_fe/two_factor", context);
+        }
+        None => return public_timeline(pooled_connection, configuration, false),
+    }
+}
+
+pub fn settings_two_factor_disable(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    form: LenientForm<TwoFactorForm>,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            raito_fe::api_controller::two_factor_disable(pooled_connection, &token, &form.code);
+            return Redirect::to("/settings/two_factor");
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
+pub fn settings_two_factor_enroll(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+
+    match &configuration.token {
+        Some(token) => {
+            match raito_fe::api_controller::two_factor_enroll(pooled_connection, &token) {
+                Ok((secret, otpauth_uri)) => {
+                    context.insert(String::from("two_factor_state"), String::from("enrolling"));
+                    context.insert(String::from("secret"), secret);
+                    context.insert(String::from("otpauth_uri"), otpauth_uri);
+                }
+                Err(_) => {
+                    context.insert(String::from("two_factor_state"), String::from("enabled"));
+                }
+            }
+            return Template::render("raito_fe/two_factor", context);
+        }
+        None => return public_timeline(pooled_connection, configuration, false),
+    }
+}
+
+pub fn settings_email(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+
+    match &configuration.token {
+        Some(token) => {
+            let (mentions, follows) =
+                raito_fe::api_controller::email_preferences(pooled_connection, &token)
+                    .unwrap_or((false, false));
+
+            context.insert(String::from("mentions"), mentions.to_string());
+            context.insert(String::from("follows"), follows.to_string());
+            return Template::render("raito_fe/email_preferences", context);
+        }
+        None => return public_timeline(pooled_connection, configuration, false),
+    }
+}
+
+pub fn settings_email_post(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    form: LenientForm<EmailPreferencesForm>,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            raito_fe::api_controller::email_preferences_update(
+                pooled_connection,
+                &token,
+                form.mentions,
+                form.follows,
+            );
+            return Redirect::to("/settings/email");
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
+pub fn settings_export(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    file: &str,
+) -> Result<Content<Vec<u8>>, Redirect> {
+    match &configuration.token {
+        Some(token) => match raito_fe::api_controller::export(pooled_connection, &token, file) {
+            Ok(data) => {
+                if file.ends_with(".csv") {
+                    Ok(Content(ContentType::CSV, data))
+                } else {
+                    Ok(Content(ContentType::new("application", "x-tar"), data))
+                }
+            }
+            Err(_) => Err(Redirect::to("/settings")),
+        },
+        None => Err(Redirect::to("/login")),
+    }
+}
+
+pub fn settings_import(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    form: ImportForm,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            raito_fe::api_controller::import(pooled_connection, &token, form);
+            return Redirect::to("/settings");
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
+pub fn settings_invites(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+
+    match &configuration.token {
+        Some(token) => {
+            let invites = raito_fe::api_controller::invites(pooled_connection, &token)
+                .unwrap_or_else(|_| vec![]);
+            let rendered_invites: Vec<String> = invites
+                .iter()
+                .map(|invite| {
+                    let uses = match invite.max_uses {
+                        Some(max_uses) => format!("{}/{}", invite.uses, max_uses),
+                        None => invite.uses.to_string(),
+                    };
+                    format!(
+                        "<tr><td><a href=\"{url}\">{url}</a></td><td>{uses}</td><td>{expires_at}</td><td><form method=\"post\" action=\"/settings/invites/{id}/delete\"><button type=\"submit\">Delete</button></form></td></tr>",
+                        url = html::escape(&invite.url),
+                        uses = uses,
+                        expires_at = invite.expires_at.as_ref().map(String::as_str).unwrap_or("Never"),
+                        id = invite.id
+                    )
+                })
+                .collect();
+
+            context.insert(
+                String::from("invites_enabled"),
+                (registration::mode() != registration::Mode::Closed).to_string(),
+            );
+            context.insert(String::from("invites"), rendered_invites.join(""));
+            return Template::render("raito_fe/invites", context);
+        }
+        None => return public_timeline(pooled_connection, configuration, false),
+    }
+}
+
+pub fn settings_invite_create(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    form: LenientForm<InviteForm>,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            raito_fe::api_controller::invite_create(pooled_connection, &token, form);
+            return Redirect::to("/settings/invites");
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
+pub fn settings_invite_delete(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    id: i64,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            raito_fe::api_controller::invite_delete(pooled_connection, &token, id);
+            return Redirect::to("/settings/invites");
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
+pub fn settings_post(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    form: UpdateCredentialsForm,
+) -> Redirect {
+    match &configuration.token {
+        Some(token) => {
+            let _ = raito_fe::api_controller::update_credentials(pooled_connection, &token, form);
+            return Redirect::to("/settings");
+        }
+        None => return Redirect::to("/login"),
+    }
+}
+
+pub fn status_favourited_by(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    id: i64,
+) -> Template {
+    let mut context = HashMap::<String, String>::new();
+    context.extend(configuration.context.clone());
+
+    let mut rendered_accounts: Vec<String> = vec![];
+    for account in raito_fe::api_controller::get_status_favourited_by(
+        pooled_connection,
+        id,
+        configuration.token.as_ref().map(String::as_str),
+    )
+    .unwrap_or_else(|_| Vec::new())
+    {
+        rendered_accounts.push(format!(
+            "<li><a href=\"/account/{id}\">{display_name}</a> {acct}</li>",
+            id = account.id,
+            display_name = html::escape(&account.display_name),
+            acct = html::escape(&account.acct)
+        ));
+    }
+
+    context.insert(String::from("accounts"), rendered_accounts.join(""));
+    context.insert(String::from("status_url"), format!("/status/{}", id));
+    context.insert(String::from("timeline_name"), String::from("Favourited by"));
+    return Template::render("raito_fe/favourited_by", context);
+}
+
 pub fn user_timeline(
     pooled_connection: &PooledConnection,
     configuration: &Configuration,
@@ -587,7 +1405,11 @@ pub fn user_timeline(
 ) -> String {
     let mut context = HashMap::<String, String>::new();
 
-    match raito_fe::api_controller::get_user_timeline(pooled_connection, id) {
+    match raito_fe::api_controller::get_user_timeline(
+        pooled_connection,
+        id,
+        configuration.token.as_ref().map(String::as_str),
+    ) {
         Ok(statuses) => {
             let mut renderered_statuses: Vec<String> = vec![];
             let rocket_renderer = rocket::ignite().attach(Template::fairing());
@@ -606,6 +1428,41 @@ pub fn user_timeline(
     }
 }
 
+// Lets the viewer add an account to one of their lists
+fn prepare_list_selection(
+    pooled_connection: &PooledConnection,
+    configuration: &Configuration,
+    account_id: &str,
+) -> String {
+    let lists = match &configuration.token {
+        Some(token) => raito_fe::api_controller::lists(pooled_connection, &token, None)
+            .unwrap_or_else(|_| Vec::new()),
+        None => return String::new(),
+    };
+
+    if lists.is_empty() {
+        return String::new();
+    }
+
+    let options: Vec<String> = lists
+        .iter()
+        .map(|list| {
+            format!(
+                "<option value=\"{id}\">{title}</option>",
+                id = list.id,
+                title = html::escape(&list.title)
+            )
+        })
+        .collect();
+    format!(
+        "<form method=\"post\" action=\"/account/{account_id}/lists\">\
+         <select name=\"list_id\">{options}</select>\
+         <button type=\"submit\">Add to list</button></form>",
+        account_id = account_id,
+        options = options.join("")
+    )
+}
+
 fn prepare_status_context(status: Status) -> HashMap<String, String> {
     let mut context = HashMap::<String, String>::new();
 
@@ -647,7 +1504,26 @@ fn prepare_status_context(status: Status) -> HashMap<String, String> {
         String::from("status_account_url"),
         format!("/account/{}", status.account.id),
     );
-    context.insert(String::from("status_content"), status.content);
+    context.insert(
+        String::from("status_content"),
+        emojify(&status.content, &status.emojis),
+    );
+    context.insert(
+        String::from("status_sensitive"),
+        status.sensitive.to_string(),
+    );
+    context.insert(String::from("status_spoiler_text"), status.spoiler_text);
+    // Statuses matched by a filter which isn't irreversible stay collapsed behind its title
+    context.insert(
+        String::from("status_filtered"),
+        status
+            .filtered
+            .unwrap_or_else(Vec::new)
+            .iter()
+            .map(|result| result.filter.title.clone())
+            .collect::<Vec<String>>()
+            .join(", "),
+    );
     context.insert(String::from("status_created_at"), date);
     context.insert(String::from("status_favourites_count"), favourites_count);
     context.insert(String::from("status_id"), status.id.to_string());
@@ -665,6 +1541,17 @@ fn prepare_status_context(status: Status) -> HashMap<String, String> {
         String::from("status_media_attachments"),
         media_context.join(""),
     );
+    context.insert(
+        String::from("status_poll"),
+        match status.poll {
+            Some(poll) => prepare_poll(&status.id, poll),
+            None => String::new(),
+        },
+    );
+    context.insert(
+        String::from("status_reactions"),
+        prepare_reactions(status.pleroma.emoji_reactions),
+    );
 
     match status.reblog {
         Some(reblog_status) => {
@@ -676,12 +1563,14 @@ fn prepare_status_context(status: Status) -> HashMap<String, String> {
                 format!("/account/{}", reblog.id),
             );
             context.insert(String::from("reblog_content"), reblog.content);
+            context.insert(String::from("reblog_spoiler_text"), reblog.spoiler_text);
         }
         None => {
             context.insert(String::from("reblog_account_acct"), String::from(""));
             context.insert(String::from("reblog_account_avatar"), String::from(""));
             context.insert(String::from("reblog_account_url"), String::from(""));
             context.insert(String::from("reblog_content"), String::from(""));
+            context.insert(String::from("reblog_spoiler_text"), String::from(""));
         }
     }
     context.insert(String::from("status_reblogs_count"), shares_count);
@@ -691,3 +1580,123 @@ fn prepare_status_context(status: Status) -> HashMap<String, String> {
 
     return context;
 }
+
+// Replaces the shortcodes of custom emojis with their images
+fn emojify(text: &str, emojis: &Vec<Emoji>) -> String {
+    let mut emojified_text = text.to_string();
+
+    for emoji in emojis {
+        emojified_text = emojified_text.replace(
+            &format!(":{}:", emoji.shortcode),
+            &format!(
+                "<img class=\"emoji\" src=\"{url}\" alt=\":{shortcode}:\" title=\":{shortcode}:\">",
+                url = html::escape(&emoji.url),
+                shortcode = emoji.shortcode
+            ),
+        );
+    }
+    emojified_text
+}
+
+fn prepare_reactions(reactions: Vec<EmojiReaction>) -> String {
+    if reactions.is_empty() {
+        return String::new();
+    }
+
+    let mut reaction_context: Vec<String> = Vec::new();
+    for reaction in reactions {
+        let emoji = match reaction.url {
+            Some(url) => format!(
+                "<img class=\"emoji\" src=\"{url}\" alt=\"{name}\">",
+                url = html::escape(&url),
+                name = html::escape(&reaction.name)
+            ),
+            None => html::escape(&reaction.name),
+        };
+
+        reaction_context.push(format!(
+            "<li title=\"{name}\">{emoji} <span class=\"status-info-count\">{count}</span></li>",
+            name = html::escape(&reaction.name),
+            emoji = emoji,
+            count = reaction.count
+        ));
+    }
+    format!(
+        "<ul class=\"status-reactions\">{}</ul>",
+        reaction_context.join("")
+    )
+}
+
+fn prepare_poll(status_id: &str, poll: Poll) -> String {
+    let input_type = if poll.multiple { "checkbox" } else { "radio" };
+    let mut options_context: Vec<String> = Vec::new();
+
+    for (index, option) in poll.options.iter().enumerate() {
+        let votes = option.votes_count.unwrap_or_else(|| 0);
+        let percentage = if poll.votes_count > 0 {
+            votes * 100 / poll.votes_count
+        } else {
+            0
+        };
+
+        options_context.push(format!(
+            "<li><label>{input}{title}</label>\
+             <span class=\"status-poll-result\">{percentage}%</span></li>",
+            input = if poll.expired {
+                String::new()
+            } else {
+                format!(
+                    "<input type=\"{}\" name=\"choices\" value=\"{}\"> ",
+                    input_type, index
+                )
+            },
+            title = html::escape(&option.title),
+            percentage = percentage
+        ));
+    }
+
+    let expiration = match poll.expires_at {
+        Some(expires_at) => match DateTime::parse_from_rfc3339(&expires_at) {
+            Ok(date) => format!(
+                " &middot; {} {}",
+                if poll.expired { "Ended" } else { "Ends" },
+                date.format("%B %d, %Y, %H:%M")
+            ),
+            Err(_) => String::new(),
+        },
+        None => String::new(),
+    };
+
+    format!(
+        "<form class=\"status-poll\" method=\"post\" action=\"/status/{id}/vote\">\
+         <ul>{options}</ul>{submit}\
+         <span class=\"status-poll-meta\">{votes} votes{expiration}</span></form>",
+        id = status_id,
+        options = options_context.join(""),
+        submit = if poll.expired {
+            ""
+        } else {
+            "<input type=\"submit\" value=\"Vote\">"
+        },
+        votes = poll.votes_count,
+        expiration = expiration
+    )
+}
+
+// The registration form depends on the registration mode and the captcha
+fn registration_context(context: &mut HashMap<String, String>) {
+    let registrations_mode = match registration::mode() {
+        registration::Mode::Open => "open",
+        registration::Mode::Approval => "approval",
+        registration::Mode::Invite => "invite",
+        registration::Mode::Closed => "closed",
+    };
+    context.insert(
+        String::from("registrations_mode"),
+        String::from(registrations_mode),
+    );
+
+    if let registration::Captcha::Question(question, _) = registration::captcha() {
+        context.insert(String::from("captcha_question"), question);
+    }
+}
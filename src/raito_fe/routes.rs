@@ -1,9 +1,14 @@
 use database::PooledConnection;
+use kibou_api::{EmailPreferencesForm, ImportForm, InviteForm};
 
-use mastodon_api::{RegistrationForm, StatusForm};
-use raito_fe::{renderer, Configuration, LoginForm};
+use mastodon_api::{ListForm, PollVoteForm, RegistrationForm, StatusForm, UpdateCredentialsForm};
+use raito_fe::{
+    renderer, Configuration, ListMemberForm, LoginForm, PasswordForm, PasswordResetForm,
+    TwoFactorForm,
+};
 use rocket::http::Cookies;
 use rocket::request::LenientForm;
+use rocket::response::content::Content;
 use rocket::response::Redirect;
 
 use rocket_contrib::templates::Template;
@@ -36,6 +41,16 @@ pub fn account_follow(
     return renderer::account_follow(&pooled_connection, &configuration, id, false);
 }
 
+#[post("/account/<id>/lists", rank = 2, data = "<form>")]
+pub fn account_list_add(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    id: i64,
+    form: LenientForm<ListMemberForm>,
+) -> Redirect {
+    return renderer::account_list_add(&pooled_connection, &configuration, id, form);
+}
+
 #[get("/account/<id>/unfollow", rank = 2)]
 pub fn account_unfollow(
     pooled_connection: PooledConnection,
@@ -72,6 +87,48 @@ pub fn status_compose(
     return renderer::compose_post(&pooled_connection, &configuration, form);
 }
 
+#[get("/lists")]
+pub fn lists(pooled_connection: PooledConnection, configuration: Configuration) -> Template {
+    return renderer::lists(&pooled_connection, &configuration);
+}
+
+#[post("/lists", data = "<form>")]
+pub fn list_create(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    form: LenientForm<ListForm>,
+) -> Redirect {
+    return renderer::list_create(&pooled_connection, &configuration, form);
+}
+
+#[get("/lists/<id>")]
+pub fn list_timeline(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    id: i64,
+) -> Template {
+    return renderer::list_timeline(&pooled_connection, &configuration, id);
+}
+
+#[post("/lists/<id>/delete")]
+pub fn list_delete(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    id: i64,
+) -> Redirect {
+    return renderer::list_delete(&pooled_connection, &configuration, id);
+}
+
+#[post("/lists/<id>/remove/<account_id>")]
+pub fn list_member_remove(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    id: i64,
+    account_id: i64,
+) -> Redirect {
+    return renderer::list_member_remove(&pooled_connection, &configuration, id, account_id);
+}
+
 #[get("/login")]
 pub fn login(pooled_connection: PooledConnection, configuration: Configuration) -> Template {
     return renderer::login(&pooled_connection, &configuration);
@@ -87,6 +144,42 @@ pub fn login_post(
     return renderer::login_post(&pooled_connection, &configuration, cookies, form);
 }
 
+#[post("/login/two_factor", data = "<form>")]
+pub fn login_two_factor(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    cookies: Cookies,
+    form: LenientForm<TwoFactorForm>,
+) -> Result<Redirect, Template> {
+    return renderer::login_two_factor(&pooled_connection, &configuration, cookies, form);
+}
+
+#[get("/timeline/direct")]
+pub fn direct_timeline(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+) -> Template {
+    return renderer::direct_messages(&pooled_connection, &configuration);
+}
+
+#[post("/timeline/direct/<id>/read")]
+pub fn direct_timeline_read(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    id: i64,
+) -> Redirect {
+    return renderer::direct_messages_read(&pooled_connection, &configuration, id);
+}
+
+#[get("/auth/confirmation?<token>")]
+pub fn email_confirmation(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    token: String,
+) -> Template {
+    return renderer::email_confirmation(&pooled_connection, &configuration, &token);
+}
+
 #[get("/timeline/home")]
 pub fn home_timeline(
     pooled_connection: PooledConnection,
@@ -104,6 +197,43 @@ pub fn object(
     return renderer::conversation_by_uri(&pooled_connection, &configuration, id);
 }
 
+#[get("/auth/password")]
+pub fn password(configuration: Configuration) -> Template {
+    return renderer::password(&configuration);
+}
+
+#[post("/auth/password", data = "<form>")]
+pub fn password_post(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    form: LenientForm<PasswordResetForm>,
+) -> Template {
+    return renderer::password_post(&pooled_connection, &configuration, form);
+}
+
+#[get("/auth/password/edit?<token>")]
+pub fn password_edit(configuration: Configuration, token: String) -> Template {
+    return renderer::password_edit(&configuration, token);
+}
+
+#[post("/auth/password/edit", data = "<form>")]
+pub fn password_update(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    form: LenientForm<PasswordForm>,
+) -> Template {
+    return renderer::password_update(&pooled_connection, &configuration, form);
+}
+
+#[get("/register?<invite>")]
+pub fn register_form(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    invite: Option<String>,
+) -> Template {
+    return renderer::register(&pooled_connection, &configuration, invite);
+}
+
 #[post("/register", data = "<form>")]
 pub fn register(
     configuration: Configuration,
@@ -118,6 +248,127 @@ pub fn settings(configuration: Configuration) -> Template {
     return renderer::settings(&configuration);
 }
 
+#[get("/settings/applications")]
+pub fn settings_applications(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+) -> Template {
+    return renderer::settings_applications(&pooled_connection, &configuration);
+}
+
+#[post("/settings/applications/<id>/revoke")]
+pub fn settings_application_revoke(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    id: i64,
+) -> Redirect {
+    return renderer::settings_application_revoke(&pooled_connection, &configuration, id);
+}
+
+#[get("/settings/email")]
+pub fn settings_email(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+) -> Template {
+    return renderer::settings_email(&pooled_connection, &configuration);
+}
+
+#[post("/settings/email", data = "<form>")]
+pub fn settings_email_post(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    form: LenientForm<EmailPreferencesForm>,
+) -> Redirect {
+    return renderer::settings_email_post(&pooled_connection, &configuration, form);
+}
+
+#[get("/settings/export/<file>")]
+pub fn settings_export(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    file: String,
+) -> Result<Content<Vec<u8>>, Redirect> {
+    return renderer::settings_export(&pooled_connection, &configuration, &file);
+}
+
+#[post("/settings/import", data = "<form>")]
+pub fn settings_import(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    form: ImportForm,
+) -> Redirect {
+    return renderer::settings_import(&pooled_connection, &configuration, form);
+}
+
+#[get("/settings/invites")]
+pub fn settings_invites(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+) -> Template {
+    return renderer::settings_invites(&pooled_connection, &configuration);
+}
+
+#[post("/settings/invites", data = "<form>")]
+pub fn settings_invite_create(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    form: LenientForm<InviteForm>,
+) -> Redirect {
+    return renderer::settings_invite_create(&pooled_connection, &configuration, form);
+}
+
+#[post("/settings/invites/<id>/delete")]
+pub fn settings_invite_delete(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    id: i64,
+) -> Redirect {
+    return renderer::settings_invite_delete(&pooled_connection, &configuration, id);
+}
+
+#[get("/settings/two_factor")]
+pub fn settings_two_factor(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+) -> Template {
+    return renderer::settings_two_factor(&pooled_connection, &configuration);
+}
+
+#[post("/settings/two_factor")]
+pub fn settings_two_factor_enroll(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+) -> Template {
+    return renderer::settings_two_factor_enroll(&pooled_connection, &configuration);
+}
+
+#[post("/settings/two_factor/confirm", data = "<form>")]
+pub fn settings_two_factor_confirm(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    form: LenientForm<TwoFactorForm>,
+) -> Template {
+    return renderer::settings_two_factor_confirm(&pooled_connection, &configuration, form);
+}
+
+#[post("/settings/two_factor/disable", data = "<form>")]
+pub fn settings_two_factor_disable(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    form: LenientForm<TwoFactorForm>,
+) -> Redirect {
+    return renderer::settings_two_factor_disable(&pooled_connection, &configuration, form);
+}
+
+#[post("/settings", data = "<form>")]
+pub fn settings_post(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    form: UpdateCredentialsForm,
+) -> Redirect {
+    return renderer::settings_post(&pooled_connection, &configuration, form);
+}
+
 #[get("/status/<id>", rank = 2)]
 pub fn view_status(
     pooled_connection: PooledConnection,
@@ -127,6 +378,25 @@ pub fn view_status(
     return renderer::conversation(&pooled_connection, &configuration, id);
 }
 
+#[get("/status/<id>/favourited_by", rank = 2)]
+pub fn status_favourited_by(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    id: i64,
+) -> Template {
+    return renderer::status_favourited_by(&pooled_connection, &configuration, id);
+}
+
+#[post("/status/<id>/vote", rank = 2, data = "<form>")]
+pub fn poll_vote(
+    pooled_connection: PooledConnection,
+    configuration: Configuration,
+    id: i64,
+    form: LenientForm<PollVoteForm>,
+) -> Redirect {
+    return renderer::poll_vote(&pooled_connection, &configuration, id, form);
+}
+
 #[get("/timeline/global")]
 pub fn global_timeline(
     pooled_connection: PooledConnection,
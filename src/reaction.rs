@@ -0,0 +1,161 @@
+use activity::{serialize_activity, Activity};
+use database::models::QueryActivity;
+use database::runtime_escape;
+use diesel::pg::PgConnection;
+use diesel::{sql_query, RunQueryDsl};
+use emoji;
+
+// Unicode emojis consist of several code points at most, e.g. flags or families
+pub const MAX_REACTION_CHARS: usize = 16;
+
+/// All reactions with the same emoji on a status
+pub struct Reaction {
+    pub name: String,
+    pub url: Option<String>,
+    pub actors: Vec<String>,
+}
+
+/// Converts the `Like` activities Misskey uses for emoji reactions into `EmojiReact` activities
+///
+/// # Description
+///
+/// Misskey federates reactions as likes with an additional `_misskey_reaction` property, other
+/// activities are returned untouched.
+///
+/// # Parameters
+///
+/// * `activity` - serde_json::Value | An ActivityStreams activity serialized in JSON
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/reaction.rs`
+/// - normalize_misskey_like()
+pub fn normalize_misskey_like(mut activity: serde_json::Value) -> serde_json::Value {
+    if activity["type"].as_str() == Some("Like") && activity["_misskey_reaction"].is_string() {
+        activity["type"] = serde_json::json!("EmojiReact");
+        activity["content"] = activity["_misskey_reaction"].clone();
+    }
+    activity
+}
+
+/// Returns the reaction a text stands for, if it's either a single unicode emoji or the
+/// shortcode of a custom emoji
+///
+/// # Parameters
+///
+/// * `content` - &str | The content of an `EmojiReact` activity
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/reaction.rs`
+/// - parse_reaction()
+pub fn parse_reaction(content: &str) -> Option<String> {
+    let content = content.trim();
+
+    if content.starts_with(':') && content.ends_with(':') && content.len() > 2 {
+        let shortcodes = emoji::parse_shortcodes(content);
+
+        if shortcodes.len() == 1 && format!(":{}:", shortcodes[0]) == content {
+            Some(content.to_string())
+        } else {
+            None
+        }
+    } else if !content.is_empty()
+        && content.chars().count() <= MAX_REACTION_CHARS
+        && content.chars().any(|c| !c.is_ascii())
+        && !content.chars().any(|c| c.is_whitespace())
+    {
+        Some(content.to_string())
+    } else {
+        None
+    }
+}
+
+/// Groups the `EmojiReact` activities of a status by their emoji, in the order they were
+/// first used
+///
+/// # Parameters
+///
+/// * `reactions` - &Vec<Activity> | The internal `EmojiReact` activities of a status
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/reaction.rs`
+/// - group()
+pub fn group(reactions: &Vec<Activity>) -> Vec<Reaction> {
+    let mut grouped_reactions: Vec<Reaction> = Vec::new();
+
+    for reaction in reactions {
+        let name = match reaction.data["content"].as_str().and_then(parse_reaction) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        match grouped_reactions
+            .iter()
+            .position(|group| group.name == name)
+        {
+            Some(index) => {
+                if !grouped_reactions[index].actors.contains(&reaction.actor) {
+                    grouped_reactions[index].actors.push(reaction.actor.clone());
+                }
+            }
+            None => {
+                let shortcode = name.trim_matches(':').to_string();
+                let url = emoji::parse_tags(&reaction.data["tag"])
+                    .into_iter()
+                    .find(|(tag_shortcode, _)| tag_shortcode == &shortcode)
+                    .map(|(_, url)| url);
+
+                // Custom emojis can't be displayed without their image
+                if name.starts_with(':') && url.is_none() {
+                    continue;
+                }
+
+                grouped_reactions.push(Reaction {
+                    name: name,
+                    url: url,
+                    actors: vec![reaction.actor.clone()],
+                });
+            }
+        }
+    }
+    grouped_reactions
+}
+
+/// Returns the `EmojiReact` activity of an actor with a certain emoji on an object
+pub fn reaction_for_actor(
+    db_connection: &PgConnection,
+    actor: &str,
+    object_id: &str,
+    name: &str,
+) -> Option<Activity> {
+    reactions_for_object(db_connection, object_id)
+        .unwrap_or_else(|_| Vec::new())
+        .into_iter()
+        .find(|reaction| {
+            reaction.actor == actor
+                && reaction.data["content"].as_str().map(str::trim) == Some(name)
+        })
+}
+
+// Unicode emojis are filtered by `runtime_escape`, which is why the content of reactions is
+// compared in `reaction_for_actor` rather than in the query itself
+pub fn reactions_for_object(
+    db_connection: &PgConnection,
+    object_id: &str,
+) -> Result<Vec<Activity>, diesel::result::Error> {
+    match sql_query(format!(
+        "SELECT * FROM activities WHERE data @> '{{\"type\": \"EmojiReact\"}}' \
+         AND data @> '{{\"object\": \"{id}\"}}' ORDER BY id ASC;",
+        id = runtime_escape(object_id)
+    ))
+    .load::<QueryActivity>(db_connection)
+    {
+        Ok(activity_arr) => Ok(activity_arr
+            .iter()
+            .map(|activity| serialize_activity(activity.clone()))
+            .collect()),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,481 @@
+//! Registrations of new local actors
+//!
+//! `node.registrations_mode` is one of `open`, `approval` (new accounts are reviewed by admins
+//! before they can log in), `invite` (an invite code of an existing user is required) or
+//! `closed`. If it isn't set, the legacy `node.registrations_enabled` decides between `open` and
+//! `closed`.
+//!
+
+use actor::{self, get_local_actor_by_preferred_username, Actor};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use database::schema::invites;
+use database::schema::pending_registrations;
+use diesel::dsl::sql;
+use diesel::pg::PgConnection;
+use diesel::sql_types::Bool;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use env;
+use mailer;
+use openssl::bn::BigNum;
+use openssl::bn::MsbOption;
+use serde::{Deserialize, Serialize};
+use serde_json;
+
+// Invites can expire at most one year after they've been created
+pub const MAX_INVITE_EXPIRES_IN: i64 = 8760;
+
+// Usernames which could be mistaken for the staff of an instance, in addition to those in
+// `node.reserved_usernames`
+const RESERVED_USERNAMES: &[&str] = &[
+    "abuse",
+    "admin",
+    "administrator",
+    "help",
+    "hostmaster",
+    "mod",
+    "moderator",
+    "noreply",
+    "postmaster",
+    "root",
+    "security",
+    "staff",
+    "support",
+    "system",
+    "webmaster",
+];
+
+#[derive(PartialEq)]
+pub enum Mode {
+    Open,
+    Approval,
+    Invite,
+    Closed,
+}
+
+/// Captchas are solved before an account is created, further providers are added here
+pub enum Captcha {
+    Disabled,
+    // A question and its answer, which are configured by the admin
+    Question(String, String),
+}
+
+#[derive(Queryable, Clone)]
+pub struct Invite {
+    pub id: i64,
+    pub actor_id: i64,
+    pub code: String,
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+    pub valid_until: Option<NaiveDateTime>,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+#[derive(Queryable, Clone)]
+pub struct PendingRegistration {
+    pub id: i64,
+    pub actor_id: i64,
+    pub reason: String,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SerializedInvite {
+    pub id: i64,
+    pub code: String,
+    pub url: String,
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+    pub expires_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SerializedPendingRegistration {
+    pub username: String,
+    pub email: Option<String>,
+    pub reason: String,
+    pub created_at: String,
+}
+
+impl Invite {
+    /// Determines whether the invite may still be used
+    ///
+    /// # Tests
+    ///
+    /// Tests for this function are in `tests/registration.rs`
+    /// - invite_is_valid()
+    pub fn is_valid(&self, now: NaiveDateTime) -> bool {
+        self.max_uses.map_or(true, |max_uses| self.uses < max_uses)
+            && self
+                .valid_until
+                .map_or(true, |valid_until| valid_until > now)
+    }
+
+    pub fn serialize(&self) -> SerializedInvite {
+        SerializedInvite {
+            id: self.id,
+            code: self.code.clone(),
+            url: format!("{}/register?invite={}", mailer::base_url(), self.code),
+            max_uses: self.max_uses,
+            uses: self.uses,
+            expires_at: self
+                .valid_until
+                .map(|timestamp| DateTime::<Utc>::from_utc(timestamp, Utc).to_rfc3339()),
+            created_at: DateTime::<Utc>::from_utc(self.created, Utc).to_rfc3339(),
+        }
+    }
+}
+
+pub fn mode() -> Mode {
+    parse_mode(
+        &env::get_value(String::from("node.registrations_mode")),
+        &env::get_value(String::from("node.registrations_enabled")),
+    )
+}
+
+/// Parses the registration mode, falling back to `node.registrations_enabled`
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/registration.rs`
+/// - parse_mode()
+pub fn parse_mode(registrations_mode: &str, registrations_enabled: &str) -> Mode {
+    match registrations_mode {
+        "open" => Mode::Open,
+        "approval" => Mode::Approval,
+        "invite" => Mode::Invite,
+        "closed" => Mode::Closed,
+        _ if registrations_enabled == "true" => Mode::Open,
+        _ => Mode::Closed,
+    }
+}
+
+/// Determines whether anyone may sign up, which is also the case if accounts are reviewed
+pub fn is_open() -> bool {
+    match mode() {
+        Mode::Open | Mode::Approval => true,
+        Mode::Invite | Mode::Closed => false,
+    }
+}
+
+pub fn captcha() -> Captcha {
+    match env::get_value(String::from("captcha.provider")).as_str() {
+        "question" => Captcha::Question(
+            env::get_value(String::from("captcha.question")),
+            env::get_value(String::from("captcha.answer")),
+        ),
+        _ => Captcha::Disabled,
+    }
+}
+
+/// Verifies the answer to the captcha, answers to questions are compared case-insensitively
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/registration.rs`
+/// - verify_captcha()
+pub fn verify_captcha(captcha: &Captcha, answer: Option<&str>) -> bool {
+    match captcha {
+        Captcha::Disabled => true,
+        Captcha::Question(_, expected) => match answer {
+            Some(answer) => answer.trim().to_lowercase() == expected.trim().to_lowercase(),
+            None => false,
+        },
+    }
+}
+
+/// Determines whether a username is reserved, either by Kibou or by `node.reserved_usernames`
+///
+/// # Parameters
+///
+/// * `username` - &str | The requested username
+/// * `reserved` - &str | Comma separated usernames reserved by the admin
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/registration.rs`
+/// - is_reserved()
+pub fn is_reserved(username: &str, reserved: &str) -> bool {
+    let username = username.to_lowercase();
+
+    RESERVED_USERNAMES.contains(&username.as_str())
+        || reserved
+            .split(',')
+            .map(|reserved_username| reserved_username.trim().to_lowercase())
+            .any(|reserved_username| reserved_username == username)
+}
+
+/// Checks whether a new account may be created according to the registration mode
+///
+/// # Description
+///
+/// A valid invite is returned if one was given, it's redeemed once the account is created.
+/// Invited users don't have to be approved.
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `username`      -          &str | The requested username
+/// * `reason`        -  Option<&str> | Why the user wants to join, required for approvals
+/// * `invite_code`   -  Option<&str> | The code of an invite
+/// * `answer`        -  Option<&str> | The answer to the captcha
+///
+pub fn validate(
+    db_connection: &PgConnection,
+    username: &str,
+    reason: Option<&str>,
+    invite_code: Option<&str>,
+    answer: Option<&str>,
+) -> Result<Option<Invite>, &'static str> {
+    let registration_mode = mode();
+    if registration_mode == Mode::Closed {
+        return Err("Registrations are closed");
+    }
+
+    if is_reserved(
+        username,
+        &env::get_value(String::from("node.reserved_usernames")),
+    ) {
+        return Err("This username is reserved");
+    }
+
+    if get_local_actor_by_preferred_username(db_connection, username).is_ok() {
+        return Err("This username is already taken");
+    }
+
+    if !verify_captcha(&captcha(), answer) {
+        return Err("The answer to the captcha is wrong");
+    }
+
+    let invite = match invite_code.filter(|invite_code| !invite_code.is_empty()) {
+        Some(invite_code) => match get_invite(db_connection, invite_code) {
+            Some(invite) => Some(invite),
+            None => return Err("This invite is invalid or has expired"),
+        },
+        None => None,
+    };
+
+    match registration_mode {
+        Mode::Invite if invite.is_none() => Err("An invite is required to sign up"),
+        Mode::Approval if invite.is_none() && reason.unwrap_or_default().trim().is_empty() => {
+            Err("Please tell us why you want to join")
+        }
+        _ => Ok(invite),
+    }
+}
+
+/// Determines whether a new account has to be approved by an admin
+pub fn approval_required(invite: &Option<Invite>) -> bool {
+    mode() == Mode::Approval && invite.is_none()
+}
+
+/// Determines whether a local actor may log in, which isn't the case until their account is
+/// approved
+pub fn is_approved(db_connection: &PgConnection, username: &str) -> bool {
+    match get_local_actor_by_preferred_username(db_connection, username) {
+        Ok(actor) => !is_pending(db_connection, &actor),
+        Err(_) => true,
+    }
+}
+
+/// Determines whether a local actor is still waiting for approval, these actors aren't federated
+/// yet
+pub fn is_pending(db_connection: &PgConnection, actor: &Actor) -> bool {
+    pending_registrations::table
+        .filter(pending_registrations::actor_id.eq(actor.id))
+        .first::<PendingRegistration>(db_connection)
+        .is_ok()
+}
+
+pub fn request_approval(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    reason: &str,
+) -> Result<(), diesel::result::Error> {
+    diesel::insert_into(pending_registrations::table)
+        .values((
+            pending_registrations::actor_id.eq(actor.id),
+            pending_registrations::reason.eq(reason.trim()),
+        ))
+        .execute(db_connection)?;
+    Ok(())
+}
+
+/// Returns the accounts which are waiting for approval, oldest first
+pub fn pending(
+    db_connection: &PgConnection,
+) -> Result<Vec<SerializedPendingRegistration>, diesel::result::Error> {
+    let pending_registrations = pending_registrations::table
+        .order(pending_registrations::created.asc())
+        .load::<PendingRegistration>(db_connection)?;
+
+    Ok(pending_registrations
+        .iter()
+        .filter_map(|pending_registration| {
+            match actor::get_actor_by_id(db_connection, &pending_registration.actor_id) {
+                Ok(actor) => Some(SerializedPendingRegistration {
+                    username: actor.preferred_username,
+                    email: actor.email,
+                    reason: pending_registration.reason.clone(),
+                    created_at: DateTime::<Utc>::from_utc(pending_registration.created, Utc)
+                        .to_rfc3339(),
+                }),
+                Err(_) => None,
+            }
+        })
+        .collect())
+}
+
+/// Approves a pending account, the user is informed by e-mail if possible
+pub fn approve(db_connection: &PgConnection, username: &str) -> Result<(), &'static str> {
+    let actor = pending_actor(db_connection, username)?;
+
+    diesel::delete(
+        pending_registrations::table.filter(pending_registrations::actor_id.eq(actor.id)),
+    )
+    .execute(db_connection)
+    .expect("Error deleting pending registration");
+
+    if let Some(address) = &actor.email {
+        mailer::send(
+            "registration_approved",
+            address,
+            "Your account was approved",
+            serde_json::json!({ "username": actor.preferred_username }),
+        );
+    }
+    Ok(())
+}
+
+/// Rejects a pending account, which is deleted afterwards
+pub fn reject(db_connection: &PgConnection, username: &str) -> Result<(), &'static str> {
+    let actor = pending_actor(db_connection, username)?;
+
+    actor::delete(db_connection, actor);
+    Ok(())
+}
+
+/// Creates an invite of a local actor
+///
+/// # Parameters
+///
+/// * `db_connection` -         &PgConnection | Reference to a database connection
+/// * `actor`         -                &Actor | The local actor who invites someone
+/// * `max_uses`      -           Option<i32> | How many accounts may be created with the invite
+/// * `valid_until`   - Option<NaiveDateTime> | When the invite expires
+///
+pub fn create_invite(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    max_uses: Option<i32>,
+    valid_until: Option<NaiveDateTime>,
+) -> Result<Invite, diesel::result::Error> {
+    let mut code_num: BigNum = BigNum::new().unwrap();
+    code_num
+        .rand(64, MsbOption::MAYBE_ZERO, true)
+        .expect("Error generating invite code");
+
+    diesel::insert_into(invites::table)
+        .values((
+            invites::actor_id.eq(actor.id),
+            invites::code.eq(code_num.to_hex_str().unwrap().to_string()),
+            invites::max_uses.eq(max_uses.filter(|max_uses| *max_uses > 0)),
+            invites::valid_until.eq(valid_until),
+        ))
+        .get_result::<Invite>(db_connection)
+}
+
+/// Determines when an invite expires, invites without a positive `expires_in` never expire
+///
+/// # Parameters
+///
+/// * `expires_in` - Option<i64> | Hours after which the invite expires
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/registration.rs`
+/// - invite_valid_until()
+pub fn invite_valid_until(expires_in: Option<i64>) -> Result<Option<NaiveDateTime>, &'static str> {
+    match expires_in.filter(|expires_in| *expires_in > 0) {
+        Some(hours) if hours > MAX_INVITE_EXPIRES_IN => Err("Invites have to expire within a year"),
+        Some(hours) => match Utc::now()
+            .naive_utc()
+            .checked_add_signed(Duration::hours(hours))
+        {
+            Some(valid_until) => Ok(Some(valid_until)),
+            None => Err("Invites have to expire within a year"),
+        },
+        None => Ok(None),
+    }
+}
+
+pub fn delete_invite(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    invite_id: i64,
+) -> Result<usize, diesel::result::Error> {
+    diesel::delete(
+        invites::table
+            .filter(invites::id.eq(invite_id))
+            .filter(invites::actor_id.eq(actor.id)),
+    )
+    .execute(db_connection)
+}
+
+pub fn invites_by_actor(
+    db_connection: &PgConnection,
+    actor: &Actor,
+) -> Result<Vec<Invite>, diesel::result::Error> {
+    invites::table
+        .filter(invites::actor_id.eq(actor.id))
+        .order(invites::created.desc())
+        .load::<Invite>(db_connection)
+}
+
+/// Returns an invite if it may still be used
+pub fn get_invite(db_connection: &PgConnection, invite_code: &str) -> Option<Invite> {
+    invites::table
+        .filter(invites::code.eq(invite_code))
+        .first::<Invite>(db_connection)
+        .ok()
+        .filter(|invite| invite.is_valid(Utc::now().naive_utc()))
+}
+
+/// Counts a use of an invite, unless it was used up or expired in the meantime
+///
+/// # Description
+///
+/// The invite is checked and updated by the same query, so that concurrent sign-ups can't use it
+/// more often than allowed.
+pub fn redeem_invite(db_connection: &PgConnection, invite: &Invite) -> Result<(), &'static str> {
+    match diesel::update(
+        invites::table
+            .filter(invites::id.eq(invite.id))
+            .filter(sql::<Bool>("(max_uses IS NULL OR uses < max_uses)"))
+            .filter(sql::<Bool>(
+                "(valid_until IS NULL OR valid_until > (now() AT TIME ZONE 'utc'))",
+            )),
+    )
+    .set(invites::uses.eq(invites::uses + 1))
+    .execute(db_connection)
+    {
+        Ok(1) => Ok(()),
+        Ok(_) => Err("This invite is invalid or has expired"),
+        Err(_) => Err("Unable to redeem invite"),
+    }
+}
+
+fn pending_actor(db_connection: &PgConnection, username: &str) -> Result<Actor, &'static str> {
+    match get_local_actor_by_preferred_username(db_connection, username) {
+        Ok(actor) => {
+            if is_approved(db_connection, username) {
+                Err("This account isn't pending")
+            } else {
+                Ok(actor)
+            }
+        }
+        Err(_) => Err("User not found"),
+    }
+}
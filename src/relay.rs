@@ -0,0 +1,209 @@
+//! Relays distribute public activities between instances which don't know each other yet
+//!
+//! The instance subscribes to a relay by following it with its instance actor, relayed
+//! `Announce` activities are then ingested into the public timeline. In return, public statuses
+//! of local actors are delivered to the relays which accepted the subscription.
+//!
+
+use activity::get_ap_activity_by_id;
+use activitypub::actor::{refetch, remove_follow};
+use activitypub::controller as ap_controller;
+use actor::{self, get_actor_by_uri, get_local_actor_by_preferred_username, Actor};
+use chrono::{NaiveDateTime, Utc};
+use database::models::InsertRelay;
+use database::schema::relays;
+use diesel::pg::PgConnection;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use env;
+use serde_json;
+use uuid::Uuid;
+use web::federator;
+
+#[derive(Queryable, PartialEq, QueryableByName, Clone)]
+#[table_name = "relays"]
+pub struct Relay {
+    pub id: i64,
+    pub actor_uri: String,
+    pub follow_activity_id: String,
+    pub created: NaiveDateTime,
+    pub modified: NaiveDateTime,
+}
+
+/// Returns the instance actor, which is created on first use
+///
+/// # Description
+///
+/// Like Mastodon's instance actor, its username is the instance's domain, which can't be
+/// registered by anyone as it contains dots.
+pub fn instance_actor(db_connection: &PgConnection) -> Actor {
+    let domain = env::get_value(String::from("endpoint.base_domain"));
+
+    if let Ok(actor) = get_local_actor_by_preferred_username(db_connection, &domain) {
+        return actor;
+    }
+
+    let mut new_actor = Actor {
+        id: 0,
+        email: None,
+        // Nobody is supposed to log in as the instance actor
+        password: Some(Uuid::new_v4().to_string()),
+        actor_uri: format!(
+            "{base_scheme}://{base_domain}/actors/{base_domain}",
+            base_scheme = env::get_value(String::from("endpoint.base_scheme")),
+            base_domain = domain
+        ),
+        username: Some(domain.clone()),
+        preferred_username: domain.clone(),
+        summary: None,
+        followers: serde_json::json!({"activitypub": []}),
+        inbox: None,
+        icon: None,
+        header: None,
+        local: true,
+        keys: serde_json::json!({}),
+        created: Utc::now().naive_utc(),
+        modified: Utc::now().naive_utc(),
+        locked: false,
+        bot: true,
+        discoverable: false,
+        fields: serde_json::json!([]),
+        also_known_as: serde_json::json!([]),
+        moved_to: None,
+        followers_uri: None,
+    };
+    actor::create_actor(db_connection, &mut new_actor);
+
+    get_local_actor_by_preferred_username(db_connection, &domain)
+        .expect("Unable to create the instance actor")
+}
+
+/// Subscribes to a relay by following it with the instance actor
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `relay_uri`     -         &str | The URI of the relay's actor
+pub fn subscribe(db_connection: &PgConnection, relay_uri: &str) -> Result<Relay, &'static str> {
+    if get_relay(db_connection, relay_uri).is_ok() {
+        return Err("Already subscribed to this relay");
+    }
+
+    let relay_actor = refetch(relay_uri)?;
+    if relay_actor.local {
+        return Err("Relays have to be remote actors");
+    }
+
+    let instance_actor = instance_actor(db_connection);
+    let follow = ap_controller::follow(&instance_actor.actor_uri, &relay_actor.actor_uri);
+
+    match diesel::insert_into(relays::table)
+        .values(InsertRelay {
+            actor_uri: &relay_actor.actor_uri,
+            follow_activity_id: &follow.id,
+        })
+        .get_result::<Relay>(db_connection)
+    {
+        Ok(relay) => {
+            federator::enqueue(
+                instance_actor,
+                serde_json::json!(&follow),
+                vec![relay_actor.inbox.unwrap()],
+            );
+            Ok(relay)
+        }
+        Err(_) => Err("Unable to store relay"),
+    }
+}
+
+/// Unsubscribes from a relay by undoing the instance actor's follow
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `relay_uri`     -         &str | The URI of the relay's actor
+pub fn unsubscribe(db_connection: &PgConnection, relay_uri: &str) -> Result<(), &'static str> {
+    let relay = match get_relay(db_connection, relay_uri) {
+        Ok(relay) => relay,
+        Err(_) => return Err("Not subscribed to this relay"),
+    };
+    let instance_actor = instance_actor(db_connection);
+
+    // Pending subscriptions are undone as well
+    if let (Ok(follow), Ok(relay_actor)) = (
+        get_ap_activity_by_id(db_connection, &relay.follow_activity_id),
+        get_actor_by_uri(db_connection, &relay.actor_uri),
+    ) {
+        if is_accepted(db_connection, &relay) {
+            remove_follow(&relay.actor_uri, &instance_actor.actor_uri);
+        }
+
+        let undo = ap_controller::undo(
+            &instance_actor.actor_uri,
+            follow.data,
+            vec![relay_actor.actor_uri],
+            vec![],
+        );
+        federator::enqueue(
+            instance_actor,
+            serde_json::json!(&undo),
+            vec![relay_actor.inbox.unwrap()],
+        );
+    }
+
+    diesel::delete(relays::table.filter(relays::id.eq(relay.id)))
+        .execute(db_connection)
+        .expect("Could not delete relay");
+    Ok(())
+}
+
+pub fn get_relay(
+    db_connection: &PgConnection,
+    relay_uri: &str,
+) -> Result<Relay, diesel::result::Error> {
+    relays::table
+        .filter(relays::actor_uri.eq(relay_uri))
+        .first::<Relay>(db_connection)
+}
+
+pub fn get_relays(db_connection: &PgConnection) -> Result<Vec<Relay>, diesel::result::Error> {
+    relays::table
+        .order(relays::id.asc())
+        .load::<Relay>(db_connection)
+}
+
+/// Determines whether a relay accepted the instance actor's follow
+pub fn is_accepted(db_connection: &PgConnection, relay: &Relay) -> bool {
+    let instance_actor = instance_actor(db_connection);
+
+    match get_actor_by_uri(db_connection, &relay.actor_uri) {
+        Ok(relay_actor) => {
+            actor::is_actor_followed_by(db_connection, &relay_actor, &instance_actor.actor_uri)
+                .unwrap_or(false)
+        }
+        Err(_) => false,
+    }
+}
+
+pub fn is_relay(db_connection: &PgConnection, actor_uri: &str) -> bool {
+    get_relay(db_connection, actor_uri).is_ok()
+}
+
+/// Returns the inboxes of all relays which accepted the subscription
+pub fn relay_inboxes(db_connection: &PgConnection) -> Vec<String> {
+    get_relays(db_connection)
+        .unwrap_or_default()
+        .iter()
+        .filter(|relay| is_accepted(db_connection, relay))
+        .filter_map(|relay| get_actor_by_uri(db_connection, &relay.actor_uri).ok())
+        .filter_map(|relay_actor| relay_actor.inbox)
+        .collect()
+}
+
+/// Returns a relay serialized in JSON, as used by the admin API and the CLI
+pub fn to_json(db_connection: &PgConnection, relay: &Relay) -> serde_json::Value {
+    serde_json::json!({
+        "actor": relay.actor_uri,
+        "accepted": is_accepted(db_connection, relay),
+        "created_at": relay.created.to_string()
+    })
+}
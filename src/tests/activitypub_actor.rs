@@ -1,5 +1,6 @@
 use activitypub::actor;
 use actor as internal_actor;
+use api_error::ApiError;
 use database;
 use tests::utils::create_local_test_actor;
 use tests::utils::create_remote_test_actor;
@@ -7,10 +8,9 @@ use tests::utils::delete_test_actor;
 
 #[test]
 fn get_err_by_preferred_username() {
-    let json_object = actor::get_json_by_preferred_username("希");
     assert_eq!(
-        json_object["error"].to_string(),
-        format!("\"{}\"", "User not found.")
+        actor::get_json_by_preferred_username("希"),
+        Err(ApiError::NotFound(String::from("User not found.")))
     );
 }
 
@@ -32,7 +32,7 @@ fn get_json_by_preferred_username() {
         .unwrap_or_else(|| String::from(""));
     let local = test_actor.local.clone();
 
-    let json_object = actor::get_json_by_preferred_username(&preferred_username);
+    let json_object = actor::get_json_by_preferred_username(&preferred_username).unwrap();
 
     delete_test_actor(test_actor);
     assert_eq!(
@@ -59,6 +59,15 @@ fn get_json_by_preferred_username() {
         json_object["outbox"].to_string(),
         format!("\"{}/outbox\"", actor_uri.clone())
     );
+    assert_eq!(
+        json_object["featured"].to_string(),
+        format!("\"{}/collections/featured\"", actor_uri.clone())
+    );
+    assert_eq!(json_object["type"], "Person");
+    assert_eq!(json_object["manuallyApprovesFollowers"], false);
+    assert_eq!(json_object["attachment"], serde_json::json!([]));
+    assert_eq!(json_object["alsoKnownAs"], serde_json::json!([]));
+    assert!(json_object["movedTo"].is_null());
     assert_eq!(
         json_object["preferredUsername"].to_string(),
         format!("\"{}\"", preferred_username)
@@ -142,6 +151,14 @@ fn create_internal_actor_with_empty_icon_url() {
         url: String::from("https://example.tld/actors/277a152b-0575-437e-add5-18c2aa5585c9"),
         icon: Some(serde_json::json!({"type": "Image", "url": null})),
         endpoints: None,
+        tag: None,
+        featured: None,
+        image: None,
+        manuallyApprovesFollowers: None,
+        discoverable: None,
+        attachment: None,
+        alsoKnownAs: None,
+        movedTo: None,
     };
 
     internal_actor::create_actor(&database, &mut actor::create_internal_actor(actor));
@@ -52,3 +52,25 @@ fn object_exists() {
 
     assert_eq!(test_object_exists, true);
 }
+
+#[test]
+fn collection_item_ids() {
+    let collection_page = serde_json::json!({
+        "type": "CollectionPage",
+        "next": "https://remote.tld/objects/1/replies?page=2",
+        "items": [
+            "https://remote.tld/objects/2",
+            {"id": "https://remote.tld/objects/3", "type": "Note"},
+            {"type": "Note"}
+        ]
+    });
+
+    assert_eq!(
+        controller::collection_item_ids(&collection_page),
+        vec![
+            "https://remote.tld/objects/2",
+            "https://remote.tld/objects/3"
+        ]
+    );
+    assert!(controller::collection_item_ids(&serde_json::json!({})).is_empty());
+}
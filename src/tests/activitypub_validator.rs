@@ -42,6 +42,15 @@ fn valid_dummy_object(actor_uri: &str) -> Object {
         tag: None,
         attachment: None,
         sensitive: None,
+        name: None,
+        oneOf: None,
+        anyOf: None,
+        endTime: None,
+        closed: None,
+        votersCount: None,
+        conversation_context: None,
+        conversation: None,
+        replies: None,
     }
 }
 
@@ -66,5 +75,14 @@ fn invalid_dummy_object() -> Object {
         tag: None,
         attachment: None,
         sensitive: None,
+        name: None,
+        oneOf: None,
+        anyOf: None,
+        endTime: None,
+        closed: None,
+        votersCount: None,
+        conversation_context: None,
+        conversation: None,
+        replies: None,
     }
 }
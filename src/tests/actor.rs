@@ -58,6 +58,28 @@ fn update_local_keys() {
     assert_ne!(test_actor_pkey, test_actor_new_pkey);
 }
 
+#[test]
+fn followers_collection() {
+    let database = database::establish_connection();
+    let local_actor = create_local_test_actor("3c8e5f1a-6b2d-4f9e-a7c4-1d0b8e6f2a59");
+    let mut remote_actor = create_remote_test_actor("9a4d2e7c-1f5b-4c8a-b6e3-7f0c5d1a8b42");
+    remote_actor.followers_uri = Some(format!("{}/followers/all", remote_actor.actor_uri));
+    actor::update(&database, remote_actor.clone());
+
+    let stored_remote_actor = actor::get_actor_by_uri(&database, &remote_actor.actor_uri).unwrap();
+    delete_test_actor(local_actor.clone());
+    delete_test_actor(remote_actor.clone());
+
+    assert_eq!(
+        local_actor.followers_collection(),
+        format!("{}/followers", local_actor.actor_uri)
+    );
+    assert_eq!(
+        stored_remote_actor.followers_collection(),
+        format!("{}/followers/all", remote_actor.actor_uri)
+    );
+}
+
 #[test]
 fn is_actor_followed_by() {
     let database = database::establish_connection();
@@ -206,11 +228,19 @@ fn create_actor_with_optional_values() {
         summary: None,
         inbox: None,
         icon: None,
+        header: None,
         keys: serde_json::json!({}),
         local: false,
         followers: serde_json::json!({"activitypub": []}),
         created: Utc::now().naive_utc(),
         modified: Utc::now().naive_utc(),
+        locked: false,
+        bot: false,
+        discoverable: false,
+        fields: serde_json::json!([]),
+        also_known_as: serde_json::json!([]),
+        moved_to: None,
+        followers_uri: None,
     };
 
     let email = test_actor.email.clone();
@@ -0,0 +1,44 @@
+use api_error::ApiError;
+use diesel::result::Error as DieselError;
+use rocket::http::Status;
+
+#[test]
+fn from_diesel_error() {
+    assert_eq!(
+        ApiError::from(DieselError::NotFound),
+        ApiError::NotFound(String::from("Record not found"))
+    );
+    assert_eq!(
+        ApiError::from(DieselError::RollbackTransaction).status(),
+        Status::InternalServerError
+    );
+}
+
+#[test]
+fn message() {
+    assert_eq!(
+        ApiError::not_found("User not found.").message(),
+        "User not found."
+    );
+    assert_eq!(
+        ApiError::unprocessable("Statuses can't be empty").message(),
+        "Statuses can't be empty"
+    );
+}
+
+#[test]
+fn status() {
+    assert_eq!(ApiError::bad_request("").status(), Status::BadRequest);
+    assert_eq!(ApiError::unauthorized("").status(), Status::Unauthorized);
+    assert_eq!(ApiError::forbidden("").status(), Status::Forbidden);
+    assert_eq!(ApiError::not_found("").status(), Status::NotFound);
+    assert_eq!(
+        ApiError::unprocessable("").status(),
+        Status::UnprocessableEntity
+    );
+    assert_eq!(
+        ApiError::too_many_requests("").status(),
+        Status::TooManyRequests
+    );
+    assert_eq!(ApiError::internal("").status(), Status::InternalServerError);
+}
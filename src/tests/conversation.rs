@@ -0,0 +1,92 @@
+use conversation;
+
+#[test]
+fn is_direct() {
+    let direct_activity = serde_json::json!({
+        "type": "Create",
+        "actor": "https://example.tld/alyssa",
+        "to": ["https://remote.tld/ben"],
+        "cc": []
+    });
+    let followers_only_activity = serde_json::json!({
+        "type": "Create",
+        "actor": "https://example.tld/alyssa",
+        "to": ["https://example.tld/alyssa/followers", "https://remote.tld/ben"],
+        "cc": []
+    });
+    let public_activity = serde_json::json!({
+        "type": "Create",
+        "actor": "https://example.tld/alyssa",
+        "to": ["https://remote.tld/ben"],
+        "cc": ["https://www.w3.org/ns/activitystreams#Public"]
+    });
+    let remote_followers_only_activity = serde_json::json!({
+        "type": "Create",
+        "actor": "https://remote.tld/users/ben",
+        "to": ["https://remote.tld/users/ben/followers/all"],
+        "cc": []
+    });
+    let followers = "https://example.tld/alyssa/followers";
+
+    assert_eq!(conversation::is_direct(&direct_activity, followers), true);
+    assert_eq!(
+        conversation::is_direct(&followers_only_activity, followers),
+        false
+    );
+    assert_eq!(conversation::is_direct(&public_activity, followers), false);
+    assert_eq!(
+        conversation::is_direct(
+            &remote_followers_only_activity,
+            "https://remote.tld/users/ben/followers/all"
+        ),
+        false
+    );
+}
+
+#[test]
+fn is_visible_to() {
+    let direct_activity = serde_json::json!({
+        "type": "Create",
+        "actor": "https://example.tld/alyssa",
+        "to": ["https://remote.tld/ben"],
+        "cc": ["https://remote.tld/cyrus"]
+    });
+    let public_activity = serde_json::json!({
+        "type": "Create",
+        "actor": "https://example.tld/alyssa",
+        "to": ["https://www.w3.org/ns/activitystreams#Public"],
+        "cc": []
+    });
+    let followers = "https://example.tld/alyssa/followers";
+
+    assert!(conversation::is_visible_to(
+        &direct_activity,
+        followers,
+        Some("https://example.tld/alyssa")
+    ));
+    assert!(conversation::is_visible_to(
+        &direct_activity,
+        followers,
+        Some("https://remote.tld/ben")
+    ));
+    assert!(conversation::is_visible_to(
+        &direct_activity,
+        followers,
+        Some("https://remote.tld/cyrus")
+    ));
+    assert!(!conversation::is_visible_to(
+        &direct_activity,
+        followers,
+        Some("https://remote.tld/eve")
+    ));
+    assert!(!conversation::is_visible_to(
+        &direct_activity,
+        followers,
+        None
+    ));
+    assert!(conversation::is_visible_to(
+        &public_activity,
+        followers,
+        None
+    ));
+}
@@ -0,0 +1,52 @@
+use database;
+use emoji;
+use std::path::Path;
+
+#[test]
+fn add_oversized_image() {
+    let database = database::establish_connection();
+    let image = vec![0; emoji::MAX_IMAGE_SIZE as usize + 1];
+
+    assert!(emoji::add(&database, "e7c1a5f0_oversized", "png", &image, None).is_err());
+    assert!(emoji::get_emoji_by_shortcode(&database, "e7c1a5f0_oversized").is_err());
+    assert!(!Path::new(emoji::EMOJI_DIRECTORY)
+        .join("e7c1a5f0_oversized.png")
+        .exists());
+}
+
+#[test]
+fn parse_shortcodes() {
+    let shortcodes = emoji::parse_shortcodes(":blobcat: meets :blobfox: and :blobcat: again");
+
+    assert_eq!(shortcodes, vec!["blobcat", "blobfox"]);
+}
+
+#[test]
+fn parse_tags() {
+    let tags = serde_json::json!([
+        {
+            "type": "Mention",
+            "href": "https://remote.tld/ben",
+            "name": "@ben@remote.tld"
+        },
+        {
+            "id": "https://remote.tld/emoji/blobcat",
+            "type": "Emoji",
+            "name": ":blobcat:",
+            "icon": {"type": "Image", "url": "https://remote.tld/emoji/blobcat.png"}
+        },
+        {
+            "type": "Emoji",
+            "name": ":<script>:",
+            "icon": {"type": "Image", "url": "https://remote.tld/emoji/script.png"}
+        }
+    ]);
+
+    assert_eq!(
+        emoji::parse_tags(&tags),
+        vec![(
+            String::from("blobcat"),
+            String::from("https://remote.tld/emoji/blobcat.png")
+        )]
+    );
+}
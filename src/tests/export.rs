@@ -0,0 +1,47 @@
+use export;
+
+#[test]
+fn csv_field() {
+    assert_eq!(export::csv_field("Friends"), "Friends");
+    assert_eq!(export::csv_field("Friends, family"), "\"Friends, family\"");
+    assert_eq!(export::csv_field("\"Friends\""), "\"\"\"Friends\"\"\"");
+}
+
+#[test]
+fn parse_csv() {
+    let rows = export::parse_csv(
+        "\"Friends, family\",alyssa@example.tld\r\n\n\"Say \"\"hi\"\"\",ben@example.tld",
+    );
+
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0], vec!["Friends, family", "alyssa@example.tld"]);
+    assert_eq!(rows[1], vec!["Say \"hi\"", "ben@example.tld"]);
+}
+
+#[test]
+fn parse_accounts() {
+    let accounts = export::parse_accounts(
+        "Account address,Show boosts\nalyssa@example.tld,true\n@ben@example.tld,false\n",
+    );
+
+    assert_eq!(accounts, vec!["alyssa@example.tld", "ben@example.tld"]);
+}
+
+#[test]
+fn tar() {
+    let archive = export::tar(vec![(String::from("actor.json"), b"{}".to_vec())]);
+    let header = &archive[..512];
+    let checksum: u32 = header[..148]
+        .iter()
+        .chain(b"        ".iter())
+        .chain(header[156..].iter())
+        .map(|byte| *byte as u32)
+        .sum();
+
+    // One header block, one data block and two empty blocks
+    assert_eq!(archive.len(), 512 * 4);
+    assert_eq!(&header[..10], b"actor.json");
+    assert_eq!(&header[124..135], b"00000000002");
+    assert_eq!(&header[148..154], format!("{:06o}", checksum).as_bytes());
+    assert_eq!(&archive[512..514], b"{}");
+}
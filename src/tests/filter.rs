@@ -0,0 +1,35 @@
+use filter;
+
+#[test]
+fn expires_at() {
+    assert_eq!(filter::expires_at(None), Ok(None));
+    assert!(filter::expires_at(Some(3600)).unwrap().is_some());
+    assert!(filter::expires_at(Some(filter::MAX_EXPIRES_IN)).is_ok());
+    assert!(filter::expires_at(Some(filter::MAX_EXPIRES_IN + 1)).is_err());
+    assert!(filter::expires_at(Some(i64::max_value())).is_err());
+    assert!(filter::expires_at(Some(0)).is_err());
+    assert!(filter::expires_at(Some(-60)).is_err());
+}
+
+#[test]
+fn matches_keyword() {
+    assert!(filter::matches_keyword("My Cat is asleep", "cat", true));
+    assert!(filter::matches_keyword("concatenate", "cat", false));
+    assert!(!filter::matches_keyword("concatenate", "cat", true));
+    assert!(filter::matches_keyword("I love #rust", "#rust", true));
+    assert!(!filter::matches_keyword("anything", "  ", false));
+}
+
+#[test]
+fn validate_context() {
+    assert_eq!(
+        filter::validate_context(vec![
+            String::from("home"),
+            String::from("public"),
+            String::from("home")
+        ]),
+        Ok(vec![String::from("home"), String::from("public")])
+    );
+    assert!(filter::validate_context(vec![String::from("timeline")]).is_err());
+    assert!(filter::validate_context(Vec::new()).is_err());
+}
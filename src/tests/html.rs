@@ -1,5 +1,26 @@
 use html;
 
+#[test]
+fn escape() {
+    assert_eq!(
+        "&lt;a href=&quot;#&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;",
+        &html::escape("<a href=\"#\">Tom & Jerry's</a>")
+    );
+}
+
+#[test]
+fn linkify() {
+    assert_eq!(
+        "Visit <a href=\"https://example.tld/about\" rel=\"nofollow noopener\">\
+         https://example.tld/about</a>!",
+        &html::linkify("Visit https://example.tld/about!")
+    );
+    assert_eq!(
+        "<a href=\"https://example.tld\">Example</a>",
+        &html::linkify("<a href=\"https://example.tld\">Example</a>")
+    );
+}
+
 #[test]
 fn strip_tags() {
     let test_bad_tag = html::strip_tags("<script>Test</script>");
@@ -7,3 +28,13 @@ fn strip_tags() {
     assert_eq!("Test", &test_bad_tag);
     assert_eq!("<a >Test</a>", &test_bad_attribute);
 }
+
+#[test]
+fn to_raw_text() {
+    assert_eq!(
+        "Tom & Jerry\nhttps://example.tld",
+        &html::to_raw_text(
+            "Tom &amp; Jerry<br><a href=\"https://example.tld\">https://example.tld</a>"
+        )
+    );
+}
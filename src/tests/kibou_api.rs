@@ -1,6 +1,7 @@
 use actor;
 use database;
 use kibou_api;
+use list;
 use tests::utils::create_local_test_actor;
 use tests::utils::delete_test_actor;
 
@@ -14,7 +15,7 @@ fn follow() {
     let test_follower_1 = create_local_test_actor("d530d627-ad50-4780-bae4-7e475d924970");
     let test_follower_1_uri = test_follower_1.actor_uri.clone();
 
-    kibou_api::follow(&test_follower_1_uri, &test_actor_uri);
+    kibou_api::follow(&test_follower_1_uri, &test_actor_uri).unwrap();
 
     let serialized_test_actor = actor::get_actor_by_uri(&database, &test_actor.actor_uri).unwrap();
     let activitypub_followers: serde_json::Value =
@@ -27,3 +28,57 @@ fn follow() {
 
     assert_eq!(test_actor_follow_data[0]["href"], test_follower_1_uri);
 }
+
+#[test]
+fn import_list_member_with_pending_follow() {
+    let database = database::establish_connection();
+
+    let test_actor = create_local_test_actor("6f0c2a8e-3b1d-4e7a-9c5f-8d2b1a0e4c73");
+    let mut test_member = create_local_test_actor("c94e7b1a-2d5f-4a8c-b3e6-0f1d9a7c5e28");
+    test_member.locked = true;
+    actor::update(&database, test_member.clone());
+
+    kibou_api::import_list_member(&database, &test_actor, "Friends", test_member.clone()).unwrap();
+
+    let member_followed =
+        actor::is_actor_followed_by(&database, &test_member, &test_actor.actor_uri).unwrap();
+    let member_ids: Vec<i64> = list::lists_for_actor(&database, &test_actor)
+        .unwrap()
+        .iter()
+        .filter(|list| list.title == "Friends")
+        .flat_map(|list| list::members(&database, list))
+        .map(|member| member.id)
+        .collect();
+
+    delete_test_actor(test_actor);
+    delete_test_actor(test_member.clone());
+
+    assert_eq!(member_followed, false);
+    assert_eq!(member_ids, vec![test_member.id]);
+}
+
+#[test]
+fn move_followers() {
+    let database = database::establish_connection();
+
+    let old_actor = create_local_test_actor("0b8ba7c5-0d3f-4f3a-a34c-6e5c2e64a8c1");
+    let new_actor = create_local_test_actor("5d7e0c2f-9a6c-4c5e-8c1b-2e3f0a9b7d64");
+    let test_follower = create_local_test_actor("a3f1e9b2-7c4d-4e8a-9b6f-1d2c3e4f5a6b");
+
+    kibou_api::follow(&test_follower.actor_uri, &old_actor.actor_uri).unwrap();
+    let serialized_old_actor = actor::get_actor_by_uri(&database, &old_actor.actor_uri).unwrap();
+
+    kibou_api::move_followers(&serialized_old_actor, &new_actor);
+
+    let old_actor_followed =
+        actor::is_actor_followed_by(&database, &old_actor, &test_follower.actor_uri).unwrap();
+    let new_actor_followed =
+        actor::is_actor_followed_by(&database, &new_actor, &test_follower.actor_uri).unwrap();
+
+    delete_test_actor(old_actor);
+    delete_test_actor(new_actor);
+    delete_test_actor(test_follower);
+
+    assert_eq!(old_actor_followed, false);
+    assert_eq!(new_actor_followed, true);
+}
@@ -0,0 +1,11 @@
+use list;
+
+#[test]
+fn validate_title() {
+    assert_eq!(
+        list::validate_title("  Friends "),
+        Ok(String::from("Friends"))
+    );
+    assert!(list::validate_title("   ").is_err());
+    assert!(list::validate_title(&"a".repeat(list::MAX_TITLE_CHARS + 1)).is_err());
+}
@@ -0,0 +1,44 @@
+use mailer::{self, smtp, Email};
+
+#[test]
+fn format_message() {
+    let email = Email {
+        to: String::from("alyssa@example.com\r\nBcc: eve@example.com"),
+        subject: String::from("Confirm your e-mail address"),
+        body: String::from("Hello alyssa,\n\nplease confirm.\n"),
+    };
+    let message = mailer::format_message(
+        "noreply@example.com",
+        &email,
+        "Thu, 19 Sep 2019 15:30:21 +0000",
+        "1234@example.com",
+    );
+
+    assert!(message.starts_with("From: noreply@example.com\r\n"));
+    assert!(message.contains("\r\nTo: alyssa@example.com\r\n"));
+    assert!(!message.contains("Bcc"));
+    assert!(message.contains("\r\nSubject: Confirm your e-mail address\r\n"));
+    assert!(message.contains("\r\nMessage-ID: <1234@example.com>\r\n"));
+    assert!(message.ends_with("\r\n\r\nHello alyssa,\r\n\r\nplease confirm."));
+}
+
+#[test]
+fn format_message_encodes_subject() {
+    let email = Email {
+        to: String::from("alyssa@example.com"),
+        subject: String::from("Kibō"),
+        body: String::new(),
+    };
+    let message = mailer::format_message("noreply@example.com", &email, "", "1234@example.com");
+
+    assert!(message.contains("\r\nSubject: =?UTF-8?B?S2lixY0=?=\r\n"));
+}
+
+#[test]
+fn dot_stuff() {
+    assert_eq!(
+        smtp::dot_stuff("Hello\r\n.\r\n..world"),
+        "Hello\r\n..\r\n...world"
+    );
+    assert_eq!(smtp::dot_stuff("No dots"), "No dots");
+}
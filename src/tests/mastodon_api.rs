@@ -0,0 +1,86 @@
+use mastodon_api;
+use mastodon_api::{MarkersUpdateForm, NotificationsForm, RelationshipsForm};
+use rocket::request::{FormItems, FromForm};
+
+#[test]
+fn link_header_for_ids() {
+    let url = "https://example.tld/api/v1/favourites";
+
+    assert_eq!(
+        mastodon_api::link_header(url, &vec![42, 7, 13]),
+        Some(String::from(
+            "<https://example.tld/api/v1/favourites?max_id=7>; rel=\"next\", \
+             <https://example.tld/api/v1/favourites?min_id=42>; rel=\"prev\""
+        ))
+    );
+}
+
+#[test]
+fn link_header_for_empty_page() {
+    assert_eq!(
+        mastodon_api::link_header("https://example.tld/api/v1/favourites", &vec![]),
+        None
+    );
+}
+
+#[test]
+fn link_header_for_url_with_query() {
+    assert_eq!(
+        mastodon_api::link_header(
+            "https://example.tld/api/v1/timelines/public?local=true",
+            &vec![3]
+        ),
+        Some(String::from(
+            "<https://example.tld/api/v1/timelines/public?local=true&max_id=3>; rel=\"next\", \
+             <https://example.tld/api/v1/timelines/public?local=true&min_id=3>; rel=\"prev\""
+        ))
+    );
+}
+
+#[test]
+fn markers_update_form() {
+    let form = MarkersUpdateForm::from_form(
+        &mut FormItems::from("notifications%5Blast_read_id%5D=42"),
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(form.home, None);
+    assert_eq!(form.notifications, Some(42));
+    assert!(
+        MarkersUpdateForm::from_form(&mut FormItems::from("home[last_read_id]=alyssa"), false)
+            .is_err()
+    );
+}
+
+#[test]
+fn notifications_form() {
+    let form = NotificationsForm::from_form(
+        &mut FormItems::from("types[]=mention&exclude_types%5B%5D=follow&max_id=7&limit=5"),
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(form.types, vec![String::from("mention")]);
+    assert_eq!(form.exclude_types, vec![String::from("follow")]);
+    assert_eq!(form.max_id, Some(7));
+    assert_eq!(form.limit, Some(5));
+}
+
+#[test]
+fn page_limit() {
+    assert_eq!(mastodon_api::page_limit(None, 20, 40), 20);
+    assert_eq!(mastodon_api::page_limit(Some(30), 20, 40), 30);
+    assert_eq!(mastodon_api::page_limit(Some(500), 20, 40), 40);
+    assert_eq!(mastodon_api::page_limit(Some(-5), 20, 40), 1);
+}
+
+#[test]
+fn relationships_form() {
+    let form =
+        RelationshipsForm::from_form(&mut FormItems::from("id[]=1&id%5B%5D=42&limit=5"), false)
+            .unwrap();
+
+    assert_eq!(form.ids, vec![1, 42]);
+    assert!(RelationshipsForm::from_form(&mut FormItems::from("id[]=alyssa"), false).is_err());
+}
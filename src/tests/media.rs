@@ -0,0 +1,9 @@
+use media;
+
+#[test]
+fn image_extension() {
+    assert_eq!(media::image_extension("image/jpeg"), Some("jpg"));
+    assert_eq!(media::image_extension("image/png"), Some("png"));
+    assert_eq!(media::image_extension("image/svg+xml"), None);
+    assert_eq!(media::image_extension("text/html"), None);
+}
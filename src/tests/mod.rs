@@ -9,7 +9,53 @@ mod activitypub_validator;
 #[cfg(test)]
 mod actor;
 #[cfg(test)]
+mod api_error;
+#[cfg(test)]
+mod conversation;
+#[cfg(test)]
+mod emoji;
+#[cfg(test)]
+mod export;
+#[cfg(test)]
+mod filter;
+#[cfg(test)]
 mod html;
 #[cfg(test)]
 mod kibou_api;
+#[cfg(test)]
+mod list;
+#[cfg(test)]
+mod mailer;
+#[cfg(test)]
+mod mastodon_api;
+#[cfg(test)]
+mod media;
+#[cfg(test)]
+mod notification;
+#[cfg(test)]
+mod oauth_application;
+#[cfg(test)]
+mod oauth_authorization;
+#[cfg(test)]
+mod oauth_authorized_application;
+#[cfg(test)]
+mod oauth_scope;
+#[cfg(test)]
+mod oauth_token;
+#[cfg(test)]
+mod pin;
+#[cfg(test)]
+mod poll;
+#[cfg(test)]
+mod reaction;
+#[cfg(test)]
+mod registration;
+#[cfg(test)]
+mod relay;
+#[cfg(test)]
+mod two_factor;
 mod utils;
+#[cfg(test)]
+mod web_multipart;
+#[cfg(test)]
+mod well_known_webfinger;
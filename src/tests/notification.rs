@@ -0,0 +1,20 @@
+use notification;
+
+#[test]
+fn requested_types() {
+    assert_eq!(
+        notification::requested_types(&[], &[]),
+        notification::TYPES.to_vec()
+    );
+    assert_eq!(
+        notification::requested_types(
+            &[String::from("mention"), String::from("follow")],
+            &[String::from("follow")]
+        ),
+        vec!["mention"]
+    );
+    assert_eq!(
+        notification::requested_types(&[String::from("unknown")], &[]),
+        Vec::<&str>::new()
+    );
+}
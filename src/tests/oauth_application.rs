@@ -0,0 +1,27 @@
+use oauth::application::{self, Application};
+
+#[test]
+fn is_redirect_uri() {
+    let test_application = Application {
+        id: 0,
+        client_name: Some(String::from("Test application")),
+        client_id: String::new(),
+        client_secret: String::new(),
+        redirect_uris: String::from("https://app.example.tld/callback\nurn:ietf:wg:oauth:2.0:oob"),
+        scopes: String::from("read"),
+        website: None,
+    };
+
+    assert!(application::is_redirect_uri(
+        &test_application,
+        "https://app.example.tld/callback"
+    ));
+    assert!(application::is_redirect_uri(
+        &test_application,
+        "urn:ietf:wg:oauth:2.0:oob"
+    ));
+    assert!(!application::is_redirect_uri(
+        &test_application,
+        "https://evil.example.tld/callback"
+    ));
+}
@@ -0,0 +1,27 @@
+use oauth::authorization;
+
+#[test]
+fn is_web_url() {
+    assert!(authorization::is_web_url("https://example.com"));
+    assert!(authorization::is_web_url("http://example.com/app"));
+    assert!(!authorization::is_web_url("javascript:alert(1)"));
+    assert!(!authorization::is_web_url(
+        "data:text/html,<script></script>"
+    ));
+    assert!(!authorization::is_web_url("example.com"));
+}
+
+#[test]
+fn verify_code_challenge() {
+    // Example from RFC 7636, appendix B
+    let code_verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+
+    assert!(authorization::verify_code_challenge(
+        code_verifier,
+        "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+    ));
+    assert!(!authorization::verify_code_challenge(
+        "not-the-right-verifier",
+        "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+    ));
+}
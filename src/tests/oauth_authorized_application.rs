@@ -0,0 +1,42 @@
+use chrono::NaiveDateTime;
+use database::models::QueryOauthToken;
+use oauth::authorized_application;
+
+fn test_token(id: i64, application: i64, scopes: &str, timestamp: i64) -> QueryOauthToken {
+    QueryOauthToken {
+        id: id,
+        application: application,
+        actor: String::from("alyssa"),
+        access_token: String::new(),
+        refresh_token: String::new(),
+        created: NaiveDateTime::from_timestamp(timestamp, 0),
+        modified: NaiveDateTime::from_timestamp(timestamp, 0),
+        valid_until: NaiveDateTime::from_timestamp(timestamp + 2592000, 0),
+        scopes: String::from(scopes),
+        last_used: Some(NaiveDateTime::from_timestamp(timestamp, 0)),
+    }
+}
+
+#[test]
+fn group_tokens() {
+    let tokens = vec![
+        test_token(1, 1, "read", 1568000000),
+        test_token(2, 1, "read write:statuses", 1568100000),
+        test_token(3, 2, "read:accounts", 1568200000),
+        test_token(4, 0, "read write follow", 1568300000),
+    ];
+    let applications = authorized_application::group_tokens(&tokens);
+
+    assert_eq!(applications.len(), 2);
+    assert_eq!(applications[0].id, 1);
+    assert_eq!(
+        applications[0].scopes,
+        vec![String::from("read"), String::from("write:statuses")]
+    );
+    assert_eq!(applications[0].created_at, "2019-09-09T03:33:20+00:00");
+    assert_eq!(
+        applications[0].last_used_at,
+        Some(String::from("2019-09-10T07:20:00+00:00"))
+    );
+    assert_eq!(applications[1].id, 2);
+}
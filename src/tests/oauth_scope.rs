@@ -0,0 +1,50 @@
+use oauth::scope;
+
+#[test]
+fn bearer_token() {
+    assert_eq!(
+        scope::bearer_token("Bearer 4c6f6f6b"),
+        Some(String::from("4c6f6f6b"))
+    );
+    assert_eq!(
+        scope::bearer_token("bearer: 4c6f6f6b"),
+        Some(String::from("4c6f6f6b"))
+    );
+    assert_eq!(scope::bearer_token("Basic YWx5c3NhOmhhY2tlcg=="), None);
+    assert_eq!(scope::bearer_token("Bearer"), None);
+}
+
+#[test]
+fn grants() {
+    assert!(scope::grants("read write follow", "read:statuses"));
+    assert!(scope::grants("read:statuses", "read:statuses"));
+    assert!(scope::grants("follow", "write:follows"));
+    assert!(scope::grants("admin:read", "admin:read:accounts"));
+    assert!(!scope::grants("read", "write:statuses"));
+    assert!(!scope::grants("read:accounts", "read:statuses"));
+    assert!(!scope::grants("follow", "read:statuses"));
+    assert!(!scope::grants("read write", "admin:read"));
+}
+
+#[test]
+fn normalize() {
+    assert_eq!(
+        scope::normalize("read+write read"),
+        Ok(String::from("read write"))
+    );
+    assert_eq!(scope::normalize(""), Ok(String::from("read")));
+    assert!(scope::normalize("read delete:everything").is_err());
+}
+
+#[test]
+fn validate() {
+    assert_eq!(
+        scope::validate(Some("read:statuses write:media"), "read write"),
+        Ok(String::from("read:statuses write:media"))
+    );
+    assert_eq!(
+        scope::validate(None, "read write"),
+        Ok(String::from("read write"))
+    );
+    assert!(scope::validate(Some("read admin:read"), "read write follow").is_err());
+}
@@ -0,0 +1,30 @@
+use oauth::token::OAuthError;
+use rocket::http::Status;
+
+#[test]
+fn oauth_error_status() {
+    assert_eq!(
+        OAuthError::new(
+            "invalid_client",
+            "OAuth application credentials are invalid."
+        )
+        .status(),
+        Status::Unauthorized
+    );
+    assert_eq!(
+        OAuthError::new("invalid_grant", "OAuth refresh token is invalid.").status(),
+        Status::BadRequest
+    );
+    assert_eq!(
+        OAuthError::new(
+            "unsupported_grant_type",
+            "OAuth grant type is not supported."
+        )
+        .status(),
+        Status::BadRequest
+    );
+    assert_eq!(
+        OAuthError::new("server_error", "OAuth token could not be revoked.").status(),
+        Status::InternalServerError
+    );
+}
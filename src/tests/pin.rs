@@ -0,0 +1,32 @@
+use pin;
+
+#[test]
+fn is_pinnable() {
+    let actor_uri = "https://example.tld/actors/alyssa";
+    let public_status = serde_json::json!({
+        "type": "Create",
+        "actor": actor_uri,
+        "to": ["https://www.w3.org/ns/activitystreams#Public"],
+        "cc": ["https://example.tld/actors/alyssa/followers"]
+    });
+    let unlisted_status = serde_json::json!({
+        "type": "Create",
+        "actor": actor_uri,
+        "to": ["https://example.tld/actors/alyssa/followers"],
+        "cc": ["https://www.w3.org/ns/activitystreams#Public"]
+    });
+    let private_status = serde_json::json!({
+        "type": "Create",
+        "actor": actor_uri,
+        "to": ["https://example.tld/actors/alyssa/followers"],
+        "cc": []
+    });
+
+    assert!(pin::is_pinnable(actor_uri, &public_status));
+    assert!(pin::is_pinnable(actor_uri, &unlisted_status));
+    assert!(!pin::is_pinnable(actor_uri, &private_status));
+    assert!(!pin::is_pinnable(
+        "https://example.tld/actors/ben",
+        &public_status
+    ));
+}
@@ -0,0 +1,84 @@
+use chrono::{Duration, Utc};
+use poll;
+
+#[test]
+fn is_vote() {
+    let vote = serde_json::json!({
+        "type": "Note",
+        "name": "Yes",
+        "attributedTo": "https://remote.tld/ben",
+        "inReplyTo": "https://example.tld/objects/7c1d6a5e-a4d6-4d44-9e6a-d9d3a5e1c2f1",
+        "to": ["https://example.tld/alyssa"]
+    });
+    let reply = serde_json::json!({
+        "type": "Note",
+        "content": "I'd say yes",
+        "attributedTo": "https://remote.tld/ben",
+        "inReplyTo": "https://example.tld/objects/7c1d6a5e-a4d6-4d44-9e6a-d9d3a5e1c2f1",
+        "to": ["https://example.tld/alyssa"]
+    });
+
+    assert_eq!(poll::is_vote(&vote), true);
+    assert_eq!(poll::is_vote(&reply), false);
+}
+
+#[test]
+fn is_expired() {
+    let open_poll = serde_json::json!({
+        "type": "Question",
+        "endTime": "2999-01-01T00:00:00Z"
+    });
+    let ended_poll = serde_json::json!({
+        "type": "Question",
+        "endTime": "2019-01-01T00:00:00Z"
+    });
+    let closed_poll = serde_json::json!({
+        "type": "Question",
+        "endTime": "2999-01-01T00:00:00Z",
+        "closed": "2019-01-01T00:00:00Z"
+    });
+
+    assert_eq!(poll::is_expired(&open_poll), false);
+    assert_eq!(poll::is_expired(&ended_poll), true);
+    assert_eq!(poll::is_expired(&closed_poll), true);
+}
+
+#[test]
+fn options() {
+    let single_choice_poll = serde_json::json!({
+        "type": "Question",
+        "oneOf": [
+            {"type": "Note", "name": "Yes", "replies": {"type": "Collection", "totalItems": 3}},
+            {"type": "Note", "name": "No", "replies": {"type": "Collection", "totalItems": 1}}
+        ]
+    });
+    let multiple_choice_poll = serde_json::json!({
+        "type": "Question",
+        "anyOf": [
+            {"type": "Note", "name": "Tea"},
+            {"type": "Note", "name": "Coffee"}
+        ]
+    });
+
+    assert_eq!(poll::options(&single_choice_poll), vec!["Yes", "No"]);
+    assert_eq!(poll::option_votes(&single_choice_poll), vec![3, 1]);
+    assert_eq!(poll::options(&multiple_choice_poll), vec!["Tea", "Coffee"]);
+    assert_eq!(poll::is_multiple(&multiple_choice_poll), true);
+}
+
+#[test]
+fn refresh_due() {
+    let now = Utc::now();
+    let end = Some(now + Duration::hours(1));
+    let ended = Some(now - Duration::hours(1));
+    let recently = Some((now - Duration::seconds(10)).naive_utc());
+    let long_ago = Some((now - Duration::hours(2)).naive_utc());
+    let after_end = Some((now - Duration::minutes(30)).naive_utc());
+    let now = now.naive_utc();
+
+    assert_eq!(poll::refresh_due(None, end, now), true);
+    assert_eq!(poll::refresh_due(recently, end, now), false);
+    assert_eq!(poll::refresh_due(long_ago, end, now), true);
+    assert_eq!(poll::refresh_due(long_ago, ended, now), true);
+    assert_eq!(poll::refresh_due(after_end, ended, now), false);
+}
@@ -0,0 +1,94 @@
+use activity::Activity;
+use reaction;
+
+#[test]
+fn normalize_misskey_like() {
+    let misskey_like = serde_json::json!({
+        "type": "Like",
+        "id": "https://misskey.tld/likes/8b5e1d2a",
+        "actor": "https://misskey.tld/users/8b5e1d29",
+        "object": "https://example.tld/objects/0c6fbc52-d8d4-4ec2-b8dd-5bd1b5ba4c6e",
+        "_misskey_reaction": "🍮"
+    });
+    let like = serde_json::json!({
+        "type": "Like",
+        "id": "https://remote.tld/likes/1",
+        "actor": "https://remote.tld/users/ben",
+        "object": "https://example.tld/objects/0c6fbc52-d8d4-4ec2-b8dd-5bd1b5ba4c6e"
+    });
+
+    let normalized_like = reaction::normalize_misskey_like(misskey_like);
+    assert_eq!(normalized_like["type"], "EmojiReact");
+    assert_eq!(normalized_like["content"], "🍮");
+    assert_eq!(reaction::normalize_misskey_like(like.clone()), like);
+}
+
+#[test]
+fn parse_reaction() {
+    assert_eq!(reaction::parse_reaction("👍"), Some(String::from("👍")));
+    assert_eq!(reaction::parse_reaction(" 👍🏽 "), Some(String::from("👍🏽")));
+    assert_eq!(
+        reaction::parse_reaction(":blobcat:"),
+        Some(String::from(":blobcat:"))
+    );
+    assert_eq!(reaction::parse_reaction(":blob:cat:"), None);
+    assert_eq!(reaction::parse_reaction("like"), None);
+    assert_eq!(reaction::parse_reaction("👍 👎"), None);
+    assert_eq!(reaction::parse_reaction(""), None);
+}
+
+#[test]
+fn group() {
+    let reactions = vec![
+        dummy_reaction(1, "https://remote.tld/users/ben", "👍", None),
+        dummy_reaction(
+            2,
+            "https://remote.tld/users/ben",
+            ":blobcat:",
+            Some("https://remote.tld/emoji/blobcat.png"),
+        ),
+        dummy_reaction(3, "https://example.tld/users/alyssa", "👍", None),
+        dummy_reaction(4, "https://example.tld/users/alyssa", ":blobfox:", None),
+    ];
+    let grouped_reactions = reaction::group(&reactions);
+
+    assert_eq!(grouped_reactions.len(), 2);
+    assert_eq!(grouped_reactions[0].name, "👍");
+    assert_eq!(grouped_reactions[0].url, None);
+    assert_eq!(
+        grouped_reactions[0].actors,
+        vec![
+            "https://remote.tld/users/ben",
+            "https://example.tld/users/alyssa"
+        ]
+    );
+    assert_eq!(grouped_reactions[1].name, ":blobcat:");
+    assert_eq!(
+        grouped_reactions[1].url,
+        Some(String::from("https://remote.tld/emoji/blobcat.png"))
+    );
+}
+
+fn dummy_reaction(id: i64, actor: &str, content: &str, emoji_url: Option<&str>) -> Activity {
+    let tag = match emoji_url {
+        Some(url) => serde_json::json!([{
+            "type": "Emoji",
+            "name": content,
+            "icon": {"type": "Image", "url": url}
+        }]),
+        None => serde_json::json!([]),
+    };
+
+    Activity {
+        id: id,
+        data: serde_json::json!({
+            "type": "EmojiReact",
+            "id": format!("https://example.tld/activities/{}", id),
+            "actor": actor,
+            "object": "https://example.tld/objects/0c6fbc52-d8d4-4ec2-b8dd-5bd1b5ba4c6e",
+            "content": content,
+            "tag": tag
+        }),
+        actor: actor.to_string(),
+    }
+}
@@ -0,0 +1,75 @@
+use chrono::{Duration, Utc};
+use registration::{self, Captcha, Invite, Mode};
+
+#[test]
+fn parse_mode() {
+    assert!(registration::parse_mode("approval", "true") == Mode::Approval);
+    assert!(registration::parse_mode("invite", "true") == Mode::Invite);
+    assert!(registration::parse_mode("closed", "true") == Mode::Closed);
+    assert!(registration::parse_mode("open", "false") == Mode::Open);
+    assert!(registration::parse_mode("", "true") == Mode::Open);
+    assert!(registration::parse_mode("", "false") == Mode::Closed);
+}
+
+#[test]
+fn is_reserved() {
+    assert!(registration::is_reserved("admin", ""));
+    assert!(registration::is_reserved("Admin", ""));
+    assert!(registration::is_reserved("kibou", "foo, Kibou"));
+    assert!(!registration::is_reserved("alyssatest", "foo, kibou"));
+    assert!(!registration::is_reserved("alyssatest", ""));
+}
+
+#[test]
+fn verify_captcha() {
+    let question = Captcha::Question(
+        String::from("What's the name of this software?"),
+        String::from("Kibou"),
+    );
+
+    assert!(registration::verify_captcha(&Captcha::Disabled, None));
+    assert!(registration::verify_captcha(&question, Some(" kibou ")));
+    assert!(!registration::verify_captcha(&question, Some("Mastodon")));
+    assert!(!registration::verify_captcha(&question, None));
+}
+
+#[test]
+fn invite_is_valid() {
+    let now = Utc::now().naive_utc();
+    let invite = Invite {
+        id: 1,
+        actor_id: 1,
+        code: String::from("deadbeef"),
+        max_uses: Some(2),
+        uses: 1,
+        valid_until: Some(now + Duration::hours(1)),
+        created: now,
+        modified: now,
+    };
+
+    assert!(invite.is_valid(now));
+    assert!(!Invite {
+        uses: 2,
+        ..invite.clone()
+    }
+    .is_valid(now));
+    assert!(!invite.is_valid(now + Duration::hours(2)));
+    assert!(Invite {
+        max_uses: None,
+        valid_until: None,
+        uses: 100,
+        ..invite.clone()
+    }
+    .is_valid(now));
+}
+
+#[test]
+fn invite_valid_until() {
+    assert_eq!(registration::invite_valid_until(None), Ok(None));
+    assert_eq!(registration::invite_valid_until(Some(0)), Ok(None));
+    assert!(registration::invite_valid_until(Some(24))
+        .unwrap()
+        .is_some());
+    assert!(registration::invite_valid_until(Some(registration::MAX_INVITE_EXPIRES_IN)).is_ok());
+    assert!(registration::invite_valid_until(Some(i64::max_value())).is_err());
+}
@@ -0,0 +1,25 @@
+use database;
+use relay;
+
+#[test]
+fn instance_actor() {
+    let database = database::establish_connection();
+    let instance_actor = relay::instance_actor(&database);
+
+    assert!(instance_actor.local);
+    assert!(instance_actor.bot);
+    assert_eq!(
+        relay::instance_actor(&database).actor_uri,
+        instance_actor.actor_uri
+    );
+}
+
+#[test]
+fn is_relay() {
+    let database = database::establish_connection();
+
+    assert!(!relay::is_relay(
+        &database,
+        "https://relay.example.tld/actor"
+    ));
+}
@@ -0,0 +1,44 @@
+use two_factor;
+
+#[test]
+fn base32() {
+    assert_eq!(two_factor::base32_encode(b"foobar"), "MZXW6YTBOI");
+    assert_eq!(two_factor::base32_encode(b"f"), "MY");
+    assert_eq!(
+        two_factor::base32_decode("MZXW6YTBOI"),
+        Some(b"foobar".to_vec())
+    );
+    assert_eq!(
+        two_factor::base32_decode("mzxw6ytboi======"),
+        Some(b"foobar".to_vec())
+    );
+    assert_eq!(two_factor::base32_decode("MZXW6YTB0I"), None);
+}
+
+#[test]
+fn otpauth_uri() {
+    let uri = two_factor::otpauth_uri("alyssa", "MZXW6YTBOI");
+
+    assert!(uri.starts_with("otpauth://totp/"));
+    assert!(uri.contains(":alyssa?secret=MZXW6YTBOI&"));
+}
+
+#[test]
+fn totp() {
+    // Test vectors of RFC 6238 for SHA1, truncated to six digits
+    let secret = b"12345678901234567890";
+
+    assert_eq!(two_factor::totp(secret, 59 / 30), "287082");
+    assert_eq!(two_factor::totp(secret, 1111111109 / 30), "081804");
+    assert_eq!(two_factor::totp(secret, 1234567890 / 30), "005924");
+    assert_eq!(two_factor::totp(secret, 20000000000 / 30), "353130");
+}
+
+#[test]
+fn is_recovery_code() {
+    assert!(two_factor::is_recovery_code("0a1b2c3d4e"));
+    assert!(!two_factor::is_recovery_code("0A1B2C3D4E"));
+    assert!(!two_factor::is_recovery_code("287082"));
+    assert!(!two_factor::is_recovery_code("0a1b2c3d4e5f"));
+    assert!(!two_factor::is_recovery_code("0a1b2c3d4g"));
+}
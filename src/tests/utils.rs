@@ -14,11 +14,19 @@ pub fn create_local_test_actor(username: &str) -> actor::Actor {
         summary: Some(String::from("Hey it's me, Alyssa!")),
         inbox: None,
         icon: Some(String::from("https://i.imgur.com/NXOJzr3.png")),
+        header: None,
         keys: serde_json::json!({}),
         local: true,
         followers: serde_json::json!({"activitypub": []}),
         created: Utc::now().naive_utc(),
         modified: Utc::now().naive_utc(),
+        locked: false,
+        bot: false,
+        discoverable: false,
+        fields: serde_json::json!([]),
+        also_known_as: serde_json::json!([]),
+        moved_to: None,
+        followers_uri: None,
     };
 
     actor::create_actor(&database, &mut test_actor);
@@ -45,11 +53,19 @@ pub fn create_remote_test_actor(username: &str) -> actor::Actor {
         summary: Some(String::from("A hardware expert")),
         inbox: Some(String::from("https://remote.tld/inbox")),
         icon: Some(String::from("https://i.imgur.com/NXOJzr3.png")),
+        header: None,
         keys: serde_json::json!({}),
         local: false,
         followers: serde_json::json!({"activitypub": []}),
         created: Utc::now().naive_utc(),
         modified: Utc::now().naive_utc(),
+        locked: false,
+        bot: false,
+        discoverable: false,
+        fields: serde_json::json!([]),
+        also_known_as: serde_json::json!([]),
+        moved_to: None,
+        followers_uri: None,
     };
 
     actor::create_actor(&database, &mut test_actor);
@@ -0,0 +1,38 @@
+use web::multipart;
+
+#[test]
+fn parse() {
+    let body = "preamble\r\n\
+                --boundary\r\n\
+                Content-Disposition: form-data; name=\"display_name\"\r\n\
+                \r\n\
+                Alyssa P. Hacker\r\n\
+                --boundary\r\n\
+                Content-Disposition: form-data; name=\"avatar\"; filename=\"avatar.png\"\r\n\
+                Content-Type: image/png\r\n\
+                \r\n\
+                \u{89}PNG\r\n\r\ndata\r\n\
+                --boundary--\r\n";
+    let parts = multipart::parse(body.as_bytes(), "boundary");
+
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0].name, "display_name");
+    assert_eq!(parts[0].data, b"Alyssa P. Hacker".to_vec());
+    assert_eq!(parts[0].filename, None);
+    assert_eq!(parts[1].name, "avatar");
+    assert_eq!(parts[1].filename, Some(String::from("avatar.png")));
+    assert_eq!(parts[1].content_type, Some(String::from("image/png")));
+    assert_eq!(parts[1].data, "\u{89}PNG\r\n\r\ndata".as_bytes().to_vec());
+}
+
+#[test]
+fn parse_without_closing_delimiter() {
+    let body = "--boundary\r\n\
+                Content-Disposition: form-data; name=\"note\"\r\n\
+                \r\n\
+                Hello";
+    let parts = multipart::parse(body.as_bytes(), "boundary");
+
+    assert_eq!(parts.len(), 1);
+    assert_eq!(parts[0].data, b"Hello".to_vec());
+}
@@ -0,0 +1,29 @@
+use well_known::webfinger;
+
+#[test]
+fn actor_uri_from_jrd() {
+    let jrd = serde_json::json!({
+        "subject": "acct:alyssa@example.tld",
+        "links": [
+            {
+                "rel": "http://webfinger.net/rel/profile-page",
+                "type": "text/html",
+                "href": "https://example.tld/@alyssa"
+            },
+            {
+                "rel": "self",
+                "type": "application/activity+json",
+                "href": "https://example.tld/users/alyssa"
+            }
+        ]
+    });
+
+    assert_eq!(
+        webfinger::actor_uri_from_jrd(&jrd),
+        Some(String::from("https://example.tld/users/alyssa"))
+    );
+    assert_eq!(
+        webfinger::actor_uri_from_jrd(&serde_json::json!({"links": []})),
+        None
+    );
+}
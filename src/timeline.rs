@@ -7,7 +7,13 @@ use diesel::query_dsl::RunQueryDsl;
 use diesel::sql_query;
 use env;
 
-pub fn home_timeline(
+// The followers collection of an activity's actor, remote actors which were stored before their
+// collection was known are assumed to follow Kibou's URL layout
+const FOLLOWERS_URI: &str = "COALESCE(\
+     (SELECT followers_uri FROM actors WHERE actors.actor_uri = activities.actor_uri), \
+     activities.actor_uri || '/followers')";
+
+pub fn direct_timeline(
     db_connection: &PgConnection,
     actor: Actor,
     max_id: Option<i64>,
@@ -20,24 +26,22 @@ pub fn home_timeline(
         None => 20,
     };
 
-    let followees: Vec<String> = actor::get_actor_followees(db_connection, &actor.actor_uri)
-        .unwrap()
-        .iter()
-        .map(|followee| followee.actor_uri.to_owned())
-        .collect();
-
     match sql_query(format!(
         "SELECT id \
          FROM activities \
          WHERE \
-         (data @> '{{\"type\": \"Create\"}}' OR \
-         data @> '{{\"type\": \"Announce\"}}') AND \
-         (actor_uri = ANY (ARRAY['{followees}']::varchar(255)[]) OR \
-         actor_uri = '{actor_uri}') \
+         data @> '{{\"type\": \"Create\"}}' AND \
+         (actor_uri = '{actor_uri}' OR \
+         data -> 'to' ? '{actor_uri}' OR \
+         data -> 'cc' ? '{actor_uri}') AND \
+         NOT (data -> 'to' ? 'https://www.w3.org/ns/activitystreams#Public' OR \
+         data -> 'cc' ? 'https://www.w3.org/ns/activitystreams#Public' OR \
+         data -> 'to' ? {followers_uri} OR \
+         data -> 'cc' ? {followers_uri}) \
          {id} \
          LIMIT {limit};",
-        followees = followees.join("','"),
-        actor_uri = actor.actor_uri,
+        actor_uri = runtime_escape(&actor.actor_uri),
+        followers_uri = FOLLOWERS_URI,
         id = prepare_order_query(max_id, since_id, min_id),
         limit = runtime_escape(&limit.to_string())
     ))
@@ -48,6 +52,71 @@ pub fn home_timeline(
     }
 }
 
+pub fn home_timeline(
+    db_connection: &PgConnection,
+    actor: Actor,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<i64>, diesel::result::Error> {
+    let mut followees: Vec<String> = actor::get_actor_followees(db_connection, &actor.actor_uri)
+        .unwrap()
+        .iter()
+        .map(|followee| followee.actor_uri.to_owned())
+        .collect();
+    followees.push(actor.actor_uri.clone());
+
+    activities_by_actors(
+        db_connection,
+        &actor,
+        followees,
+        max_id,
+        since_id,
+        min_id,
+        limit,
+    )
+}
+
+/// Returns the statuses and shares of a list's members, restricted to the members which are
+/// still followed by the list's owner
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `owner`         -        Actor | The local actor who owns the list
+/// * `members`       -   Vec<Actor> | The members of the list
+pub fn list_timeline(
+    db_connection: &PgConnection,
+    owner: Actor,
+    members: Vec<Actor>,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<i64>, diesel::result::Error> {
+    let followees: Vec<String> = actor::get_actor_followees(db_connection, &owner.actor_uri)
+        .unwrap_or_else(|_| Vec::new())
+        .iter()
+        .map(|followee| followee.actor_uri.to_owned())
+        .collect();
+    let followed_members: Vec<String> = members
+        .into_iter()
+        .map(|member| member.actor_uri)
+        .filter(|member| followees.contains(member))
+        .collect();
+
+    activities_by_actors(
+        db_connection,
+        &owner,
+        followed_members,
+        max_id,
+        since_id,
+        min_id,
+        limit,
+    )
+}
+
 pub fn public_activities(db_connection: &PgConnection) -> Result<Vec<i64>, diesel::result::Error> {
     match sql_query(format!(
         "SELECT id \
@@ -144,7 +213,56 @@ pub fn user_timeline(
     }
 }
 
-fn prepare_order_query(max_id: Option<i64>, since_id: Option<i64>, min_id: Option<i64>) -> String {
+// Direct messages are only included if the viewer is their author or one of their receipients,
+// so that the conversations of followees with others don't show up in timelines
+fn activities_by_actors(
+    db_connection: &PgConnection,
+    viewer: &Actor,
+    actor_uris: Vec<String>,
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<i64>, diesel::result::Error> {
+    let actor_uris: Vec<String> = actor_uris
+        .iter()
+        .map(|actor_uri| runtime_escape(actor_uri))
+        .collect();
+
+    match sql_query(format!(
+        "SELECT id \
+         FROM activities \
+         WHERE \
+         (data @> '{{\"type\": \"Create\"}}' OR \
+         data @> '{{\"type\": \"Announce\"}}') AND \
+         actor_uri = ANY (ARRAY['{actor_uris}']::varchar(255)[]) AND \
+         (actor_uri = '{viewer_uri}' OR \
+         data -> 'to' ? 'https://www.w3.org/ns/activitystreams#Public' OR \
+         data -> 'cc' ? 'https://www.w3.org/ns/activitystreams#Public' OR \
+         data -> 'to' ? {followers_uri} OR \
+         data -> 'cc' ? {followers_uri} OR \
+         data -> 'to' ? '{viewer_uri}' OR \
+         data -> 'cc' ? '{viewer_uri}') \
+         {id} \
+         LIMIT {limit};",
+        actor_uris = actor_uris.join("','"),
+        viewer_uri = runtime_escape(&viewer.actor_uri),
+        followers_uri = FOLLOWERS_URI,
+        id = prepare_order_query(max_id, since_id, min_id),
+        limit = runtime_escape(&limit.unwrap_or_else(|| 20).to_string())
+    ))
+    .load::<QueryActivityId>(db_connection)
+    {
+        Ok(activities) => Ok(activities.iter().map(|activity| activity.id).collect()),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn prepare_order_query(
+    max_id: Option<i64>,
+    since_id: Option<i64>,
+    min_id: Option<i64>,
+) -> String {
     if max_id.is_some() {
         format!(
             "AND id < {} ORDER BY id DESC",
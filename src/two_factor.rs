@@ -0,0 +1,444 @@
+//! Two-factor authentication of local actors with time-based one-time passwords (RFC 6238)
+//!
+//! Enrolling generates a secret, which only takes effect once a first code generated from it was
+//! confirmed. Recovery codes are shown once after the confirmation and stored as bcrypt hashes,
+//! each of them can be used a single time instead of a code.
+//!
+
+use actor::{get_local_actor_by_preferred_username, Actor};
+use bcrypt;
+use chrono::Utc;
+use database::schema::two_factor_credentials;
+use database::schema::two_factor_credentials::dsl::*;
+use diesel::pg::PgConnection;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use env;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::rand::rand_bytes;
+use openssl::sign::Signer;
+use rocket::http::{Cookie, Cookies};
+use serde_json;
+use url::form_urlencoded;
+
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+// Recovery codes consist of this many random bytes, encoded in lowercase hex
+const RECOVERY_CODE_BYTES: usize = 5;
+
+// Codes are short enough to be guessed, so actors are locked out for a while after too many
+// wrong ones in a row
+const MAX_FAILED_ATTEMPTS: i32 = 5;
+const LOCKOUT_SECONDS: i64 = 900;
+
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+// The parameters most authenticator apps expect, they're included in the otpauth URI anyway
+const DIGITS: usize = 6;
+const STEP_SECONDS: i64 = 30;
+
+// Codes of the adjacent steps are accepted as well, as clocks are rarely in sync
+const STEP_TOLERANCE: i64 = 1;
+
+// Actors who entered their password but no code yet are remembered by this private cookie, until
+// the code was entered or the cookie expired
+const PENDING_COOKIE: &str = "two_factor_pending";
+const PENDING_SECONDS: i64 = 300;
+
+#[derive(Queryable, Clone)]
+pub struct TwoFactorCredential {
+    pub id: i64,
+    pub actor_id: i64,
+    pub secret: String,
+    pub enabled: bool,
+    pub recovery_codes: serde_json::Value,
+    pub last_step: i64,
+    pub created: chrono::NaiveDateTime,
+    pub modified: chrono::NaiveDateTime,
+    pub failed_attempts: i32,
+    pub locked_until: Option<chrono::NaiveDateTime>,
+}
+
+/// Encodes data in base32 without padding, as used for TOTP secrets
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/two_factor.rs`
+/// - base32()
+pub fn base32_encode(data: &[u8]) -> String {
+    let mut encoded = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for byte in data {
+        buffer = (buffer << 8) | u32::from(*byte);
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    encoded
+}
+
+/// Decodes base32 with or without padding, returns `None` for invalid characters
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/two_factor.rs`
+/// - base32()
+pub fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut decoded: Vec<u8> = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for character in encoded.trim_end_matches('=').bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|alphabet_character| *alphabet_character == character.to_ascii_uppercase())?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(decoded)
+}
+
+/// Determines whether a code has the format of recovery codes, before it's compared with their
+/// hashes
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/two_factor.rs`
+/// - is_recovery_code()
+pub fn is_recovery_code(code: &str) -> bool {
+    code.len() == RECOVERY_CODE_BYTES * 2
+        && code
+            .chars()
+            .all(|character| character.is_ascii_digit() || ('a'..='f').contains(&character))
+}
+
+/// Generates the code of a time step according to RFC 4226 and RFC 6238
+///
+/// # Parameters
+///
+/// * `totp_secret` - &[u8] | The decoded secret shared with the authenticator app
+/// * `step`        -   i64 | The number of 30 second steps since the Unix epoch
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/two_factor.rs`
+/// - totp()
+pub fn totp(totp_secret: &[u8], step: i64) -> String {
+    let key = PKey::hmac(totp_secret).unwrap();
+    let mut signer = Signer::new(MessageDigest::sha1(), &key).unwrap();
+    signer.update(&step.to_be_bytes()).unwrap();
+    let hmac = signer.sign_to_vec().unwrap();
+
+    let offset = (hmac[hmac.len() - 1] & 0xf) as usize;
+    let binary = (u32::from(hmac[offset] & 0x7f) << 24)
+        | (u32::from(hmac[offset + 1]) << 16)
+        | (u32::from(hmac[offset + 2]) << 8)
+        | u32::from(hmac[offset + 3]);
+
+    format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS as u32),
+        width = DIGITS
+    )
+}
+
+/// Returns the `otpauth` URI of a secret, which authenticator apps usually scan as a QR code
+///
+/// # Parameters
+///
+/// * `account`     - &str | The name of the account, e.g. the actor's username
+/// * `totp_secret` - &str | The base32 encoded secret
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/two_factor.rs`
+/// - otpauth_uri()
+pub fn otpauth_uri(account: &str, totp_secret: &str) -> String {
+    let issuer = env::get_value(String::from("endpoint.base_domain"));
+    let encode =
+        |value: &str| form_urlencoded::byte_serialize(value.as_bytes()).collect::<String>();
+
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+        issuer = encode(&issuer),
+        account = encode(account),
+        secret = totp_secret,
+        digits = DIGITS,
+        period = STEP_SECONDS
+    )
+}
+
+pub fn get_credential(
+    db_connection: &PgConnection,
+    actor: &Actor,
+) -> Result<TwoFactorCredential, diesel::result::Error> {
+    two_factor_credentials
+        .filter(actor_id.eq(actor.id))
+        .first::<TwoFactorCredential>(db_connection)
+}
+
+/// Determines whether a local actor has to enter a code after their password
+pub fn is_enabled(db_connection: &PgConnection, actor: &Actor) -> bool {
+    match get_credential(db_connection, actor) {
+        Ok(credential) => credential.enabled,
+        Err(_) => false,
+    }
+}
+
+/// Generates a new secret for a local actor and returns it encoded in base32
+///
+/// # Description
+///
+/// A secret which wasn't confirmed yet is replaced, an enabled one has to be disabled first.
+pub fn enroll(db_connection: &PgConnection, actor: &Actor) -> Result<String, &'static str> {
+    if is_enabled(db_connection, actor) {
+        return Err("Two-factor authentication is already enabled");
+    }
+
+    let mut secret_bytes = [0u8; 20];
+    rand_bytes(&mut secret_bytes).expect("Error generating two-factor secret");
+    let new_secret = base32_encode(&secret_bytes);
+
+    reset(db_connection, actor);
+    match diesel::insert_into(two_factor_credentials::table)
+        .values((actor_id.eq(actor.id), secret.eq(&new_secret)))
+        .execute(db_connection)
+    {
+        Ok(_) => Ok(new_secret),
+        Err(_) => Err("An error occured while enrolling"),
+    }
+}
+
+/// Enables two-factor authentication with the first code of a new secret and returns the
+/// recovery codes in plain text
+pub fn confirm(
+    db_connection: &PgConnection,
+    actor: &Actor,
+    code: &str,
+) -> Result<Vec<String>, &'static str> {
+    let credential = match get_credential(db_connection, actor) {
+        Ok(ref credential) if !credential.enabled => credential.clone(),
+        Ok(_) => return Err("Two-factor authentication is already enabled"),
+        Err(_) => return Err("Two-factor authentication was not set up yet"),
+    };
+
+    let step = match verify_totp(&credential, code) {
+        Some(step) => step,
+        None => return Err("The code is invalid"),
+    };
+
+    let plain_recovery_codes = generate_recovery_codes();
+    let hashed_recovery_codes: Vec<String> = plain_recovery_codes
+        .iter()
+        .map(|recovery_code| {
+            bcrypt::hash(recovery_code, bcrypt::DEFAULT_COST).expect("Error hashing recovery code")
+        })
+        .collect();
+
+    diesel::update(two_factor_credentials.filter(id.eq(credential.id)))
+        .set((
+            enabled.eq(true),
+            recovery_codes.eq(serde_json::json!(hashed_recovery_codes)),
+            last_step.eq(step),
+        ))
+        .execute(db_connection)
+        .expect("Error enabling two-factor authentication");
+    Ok(plain_recovery_codes)
+}
+
+/// Verifies the code or a recovery code of an actor who enabled two-factor authentication
+///
+/// # Description
+///
+/// Codes of a step which was already used can't be used again, recovery codes are deleted once
+/// they were used. After `MAX_FAILED_ATTEMPTS` wrong codes in a row every code is rejected for
+/// `LOCKOUT_SECONDS`.
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `actor`         -        &Actor | A local actor
+/// * `code`          -          &str | A code of the authenticator app or a recovery code
+///
+pub fn verify(db_connection: &PgConnection, actor: &Actor, code: &str) -> bool {
+    let credential = match get_credential(db_connection, actor) {
+        Ok(credential) => credential,
+        Err(_) => return false,
+    };
+
+    if !credential.enabled {
+        return false;
+    }
+
+    match credential.locked_until {
+        Some(lock) if lock > Utc::now().naive_utc() => return false,
+        _ => (),
+    }
+
+    if let Some(step) = verify_totp(&credential, code) {
+        diesel::update(two_factor_credentials.filter(id.eq(credential.id)))
+            .set((last_step.eq(step), failed_attempts.eq(0)))
+            .execute(db_connection)
+            .expect("Error updating two-factor authentication");
+        return true;
+    }
+
+    // Anything else than a recovery code is rejected before checking each of the hashes
+    let recovery_code = code.trim().to_lowercase();
+    if !is_recovery_code(&recovery_code) {
+        register_failed_attempt(db_connection, &credential);
+        return false;
+    }
+
+    let mut hashed_recovery_codes: Vec<String> =
+        serde_json::from_value(credential.recovery_codes.clone()).unwrap_or_default();
+
+    match hashed_recovery_codes
+        .iter()
+        .position(|hash| bcrypt::verify(&recovery_code, hash).unwrap_or(false))
+    {
+        Some(index) => {
+            hashed_recovery_codes.remove(index);
+            diesel::update(two_factor_credentials.filter(id.eq(credential.id)))
+                .set((
+                    recovery_codes.eq(serde_json::json!(hashed_recovery_codes)),
+                    failed_attempts.eq(0),
+                ))
+                .execute(db_connection)
+                .expect("Error updating two-factor authentication");
+            true
+        }
+        None => {
+            register_failed_attempt(db_connection, &credential);
+            false
+        }
+    }
+}
+
+/// Disables two-factor authentication of a local actor, which is used by admins to reset it too
+pub fn reset(db_connection: &PgConnection, actor: &Actor) {
+    diesel::delete(two_factor_credentials.filter(actor_id.eq(actor.id)))
+        .execute(db_connection)
+        .expect("Error deleting two-factor authentication");
+}
+
+/// Determines whether an actor, whose password was verified, has to enter a code as well
+pub fn is_required(db_connection: &PgConnection, username: &str) -> bool {
+    match get_local_actor_by_preferred_username(db_connection, username) {
+        Ok(actor) => is_enabled(db_connection, &actor),
+        Err(_) => false,
+    }
+}
+
+/// Verifies the code of the pending actor and returns their username, which completes the login
+///
+/// # Parameters
+///
+/// * `db_connection` - &PgConnection | Reference to a database connection
+/// * `cookies`       -  &mut Cookies | The cookies containing the pending actor
+/// * `code`          -          &str | A code of the authenticator app or a recovery code
+///
+pub fn verify_pending(
+    db_connection: &PgConnection,
+    cookies: &mut Cookies,
+    code: &str,
+) -> Option<String> {
+    let username = pending_actor(cookies)?;
+    let actor = get_local_actor_by_preferred_username(db_connection, &username).ok()?;
+
+    if verify(db_connection, &actor, code) {
+        clear_pending(cookies);
+        Some(username)
+    } else {
+        None
+    }
+}
+
+/// Remembers an actor who entered their password, until they entered their code too
+pub fn set_pending(cookies: &mut Cookies, username: &str) {
+    cookies.add_private(Cookie::new(
+        PENDING_COOKIE,
+        format!("{}:{}", Utc::now().timestamp(), username),
+    ));
+}
+
+/// Returns the username of an actor who entered their password in the last five minutes
+pub fn pending_actor(cookies: &mut Cookies) -> Option<String> {
+    let value = cookies.get_private(PENDING_COOKIE)?.value().to_string();
+    let mut parts = value.splitn(2, ':');
+
+    match (parts.next().map(str::parse::<i64>), parts.next()) {
+        (Some(Ok(timestamp)), Some(username))
+            if Utc::now().timestamp() - timestamp < PENDING_SECONDS =>
+        {
+            Some(username.to_string())
+        }
+        _ => None,
+    }
+}
+
+pub fn clear_pending(cookies: &mut Cookies) {
+    cookies.remove_private(Cookie::named(PENDING_COOKIE));
+}
+
+fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut random_bytes = [0u8; RECOVERY_CODE_BYTES];
+            rand_bytes(&mut random_bytes).expect("Error generating recovery code");
+            random_bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        })
+        .collect()
+}
+
+// Counts a wrong code, the last one allowed locks the actor out and starts counting over
+fn register_failed_attempt(db_connection: &PgConnection, credential: &TwoFactorCredential) {
+    let attempts = credential.failed_attempts + 1;
+    let lock = if attempts >= MAX_FAILED_ATTEMPTS {
+        Some(Utc::now().naive_utc() + chrono::Duration::seconds(LOCKOUT_SECONDS))
+    } else {
+        None
+    };
+
+    diesel::update(two_factor_credentials.filter(id.eq(credential.id)))
+        .set((
+            failed_attempts.eq(if lock.is_some() { 0 } else { attempts }),
+            locked_until.eq(lock),
+        ))
+        .execute(db_connection)
+        .expect("Error updating two-factor authentication");
+}
+
+// Returns the step of a valid code, which has to be newer than the last used one
+fn verify_totp(credential: &TwoFactorCredential, code: &str) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != DIGITS || !code.chars().all(|character| character.is_ascii_digit()) {
+        return None;
+    }
+
+    let decoded_secret = base32_decode(&credential.secret)?;
+    let current_step = Utc::now().timestamp() / STEP_SECONDS;
+
+    ((current_step - STEP_TOLERANCE)..=(current_step + STEP_TOLERANCE))
+        .filter(|step| *step > credential.last_step)
+        .find(|step| totp(&decoded_secret, *step) == code)
+}
@@ -1,5 +1,6 @@
 pub mod federator;
 pub mod http_signatures;
+pub mod multipart;
 
 use reqwest::header::HeaderValue;
 use reqwest::header::ACCEPT;
@@ -0,0 +1,159 @@
+// Rocket doesn't support `multipart/form-data` request bodies, which Mastodon-API clients use to
+// upload files, so they are split into their parts here
+
+use rocket::data::Data;
+use rocket::http::Status;
+use rocket::request::{FormItems, Request};
+use std::io::Read;
+
+pub struct Part {
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// Splits a `multipart/form-data` body into its parts, parts without a name are skipped
+///
+/// # Parameters
+///
+/// * `body`     - &[u8] | The request body
+/// * `boundary` -  &str | The boundary of the parts, as given in the `Content-Type` header
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/web_multipart.rs`
+/// - parse()
+/// - parse_without_closing_delimiter()
+pub fn parse(body: &[u8], boundary: &str) -> Vec<Part> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut parts: Vec<Part> = Vec::new();
+
+    // Everything before the first delimiter is a preamble, which is ignored
+    for section in split(body, &delimiter).into_iter().skip(1) {
+        // The closing delimiter is followed by two dashes
+        if section.starts_with(b"--") {
+            break;
+        }
+
+        let section = if section.starts_with(b"\r\n") {
+            &section[2..]
+        } else {
+            section
+        };
+        let header_end = match find(section, b"\r\n\r\n") {
+            Some(index) => index,
+            None => continue,
+        };
+        let headers = String::from_utf8_lossy(&section[..header_end]).to_string();
+        let mut data = &section[header_end + 4..];
+        if data.ends_with(b"\r\n") {
+            data = &data[..data.len() - 2];
+        }
+
+        let mut name: Option<String> = None;
+        let mut filename: Option<String> = None;
+        let mut content_type: Option<String> = None;
+        for header in headers.split("\r\n") {
+            let mut header_parts = header.splitn(2, ':');
+            let header_name = header_parts
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_lowercase();
+            let header_value = header_parts.next().unwrap_or_default().trim();
+
+            match header_name.as_str() {
+                "content-disposition" => {
+                    for parameter in header_value.split(';').skip(1) {
+                        let mut parameter_parts = parameter.trim().splitn(2, '=');
+                        let key = parameter_parts.next().unwrap_or_default();
+                        let value = parameter_parts
+                            .next()
+                            .unwrap_or_default()
+                            .trim_matches('"')
+                            .to_string();
+
+                        match key {
+                            "name" => name = Some(value),
+                            "filename" => filename = Some(value),
+                            _ => (),
+                        }
+                    }
+                }
+                "content-type" => content_type = Some(header_value.to_string()),
+                _ => (),
+            }
+        }
+
+        if let Some(name) = name {
+            parts.push(Part {
+                name: name,
+                filename: filename,
+                content_type: content_type,
+                data: data.to_vec(),
+            });
+        }
+    }
+    parts
+}
+
+/// Reads a request body into parts, regardless of whether it's `multipart/form-data` or
+/// `application/x-www-form-urlencoded`
+///
+/// # Parameters
+///
+/// * `request` - &Request | The incoming request
+/// * `data`    -     Data | The request body
+/// * `limit`   -      u64 | Maximum number of bytes which are read
+pub fn from_data(request: &Request, data: Data, limit: u64) -> Result<Vec<Part>, (Status, String)> {
+    let mut body: Vec<u8> = Vec::new();
+
+    if let Err(e) = data.open().take(limit).read_to_end(&mut body) {
+        return Err((Status::InternalServerError, format!("{:?}", e)));
+    }
+
+    match request.content_type() {
+        Some(content_type) if content_type.is_form_data() => {
+            match content_type.params().find(|(name, _)| name == &"boundary") {
+                Some((_, boundary)) => Ok(parse(&body, boundary.trim_matches('"'))),
+                None => Err((
+                    Status::BadRequest,
+                    String::from("Missing multipart boundary"),
+                )),
+            }
+        }
+        _ => {
+            let text = String::from_utf8_lossy(&body).to_string();
+            Ok(FormItems::from(text.as_str())
+                .map(|item| {
+                    let (key, value) = item.key_value_decoded();
+                    Part {
+                        name: key,
+                        filename: None,
+                        content_type: None,
+                        data: value.into_bytes(),
+                    }
+                })
+                .collect())
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn split<'a>(haystack: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut sections: Vec<&[u8]> = Vec::new();
+    let mut rest = haystack;
+
+    while let Some(index) = find(rest, delimiter) {
+        sections.push(&rest[..index]);
+        rest = &rest[index + delimiter.len()..];
+    }
+    sections.push(rest);
+    sections
+}
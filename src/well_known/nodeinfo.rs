@@ -2,6 +2,7 @@ use activity::count_local_ap_notes;
 use actor::count_local_actors;
 use database;
 use env;
+use registration;
 use rocket_contrib::json;
 use rocket_contrib::json::JsonValue;
 
@@ -122,10 +123,9 @@ fn get_local_posts() -> usize {
     count_local_ap_notes(&database).unwrap_or_else(|_| 0)
 }
 
+// Registrations which require approval are open as well, invite-only ones aren't
 fn get_open_registrations() -> bool {
-    env::get_value(String::from("node.registrations_enabled"))
-        .parse::<bool>()
-        .unwrap_or_else(|_| false)
+    registration::is_open()
 }
 
 fn get_total_users() -> usize {
@@ -1,8 +1,11 @@
 use actor::get_actor_by_acct;
 use database;
+use registration;
 use rocket::http::RawStr;
 use rocket_contrib::json;
 use rocket_contrib::json::JsonValue;
+use url::Url;
+use web;
 
 #[get("/.well-known/webfinger?<resource>")]
 pub fn webfinger(resource: &RawStr) -> JsonValue {
@@ -12,7 +15,7 @@ pub fn webfinger(resource: &RawStr) -> JsonValue {
 
     match get_actor_by_acct(&database, &str::replace(parsed_resource, "acct:", "")) {
         Ok(actor) => {
-            if actor.local {
+            if actor.local && !registration::is_pending(&database, &actor) {
                 json!({
                     "subject": parsed_resource,
 
@@ -31,3 +34,53 @@ pub fn webfinger(resource: &RawStr) -> JsonValue {
         Err(_) => json!({"error": "User not found."}),
     }
 }
+
+/// Looks up the ActivityPub actor of a remote account via WebFinger and returns their URI
+///
+/// # Parameters
+///
+/// * `acct` - &str | The acct of a remote account, e.g. `alyssa@example.tld`
+pub fn resolve(acct: &str) -> Result<String, &'static str> {
+    let acct = acct.trim_start_matches('@');
+    let host = match acct.splitn(2, '@').nth(1) {
+        Some(host) if !host.is_empty() => host,
+        _ => return Err("Invalid acct"),
+    };
+
+    let url = match Url::parse(&format!(
+        "https://{}/.well-known/webfinger?resource=acct:{}",
+        host, acct
+    )) {
+        Ok(url) => url,
+        Err(_) => return Err("Invalid acct"),
+    };
+
+    match web::fetch_remote_object(url.as_str()) {
+        Ok(body) => match serde_json::from_str(&body) {
+            Ok(jrd) => actor_uri_from_jrd(&jrd).ok_or("Account not found"),
+            Err(_) => Err("Invalid WebFinger response"),
+        },
+        Err(_) => Err("Unable to reach WebFinger endpoint"),
+    }
+}
+
+/// Returns the ActivityPub actor linked in a JSON resource descriptor
+///
+/// # Tests
+///
+/// Tests for this function are in `tests/well_known_webfinger.rs`
+/// - actor_uri_from_jrd()
+pub fn actor_uri_from_jrd(jrd: &serde_json::Value) -> Option<String> {
+    jrd["links"]
+        .as_array()?
+        .iter()
+        .find(|link| {
+            link["rel"].as_str() == Some("self")
+                && link["type"].as_str().map_or(false, |media_type| {
+                    media_type == "application/activity+json"
+                        || media_type.starts_with("application/ld+json")
+                })
+        })
+        .and_then(|link| link["href"].as_str())
+        .map(String::from)
+}